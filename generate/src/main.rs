@@ -11,6 +11,7 @@ use std::fs;
 async fn main() {
     commonmark().await;
     punctuation().await;
+    capi();
 }
 
 async fn commonmark() {
@@ -159,3 +160,170 @@ pub const PUNCTUATION: [char; {}] = [
 
     fs::write(code_url, doc).unwrap();
 }
+
+fn capi() {
+    let code_url = "src/capi.rs";
+    let header_url = "include/markdown.h";
+    let value = fs::read_to_string(code_url).unwrap();
+    let item = Regex::new(r"^pub (struct|enum) (\w+) \{$").unwrap();
+    let field = Regex::new(r"^\s+pub (\w+): (.+),$").unwrap();
+    let variant = Regex::new(r"^\s+(\w+),$").unwrap();
+    let function =
+        Regex::new(r#"^pub (?:unsafe )?extern "C" fn (\w+)\(([^)]*)\)(?: -> ([^{]+))? \{$"#)
+            .unwrap();
+    let mut items = vec![];
+    let mut comments: Vec<String> = vec![];
+    let mut lines = value.lines();
+
+    while let Some(line) = lines.next() {
+        if let Some(comment) = line.strip_prefix("///") {
+            comments.push(format!("//{}", comment));
+            continue;
+        }
+
+        if line.starts_with("#[") {
+            continue;
+        }
+
+        let mut code = String::new();
+
+        if let Some(captures) = item.captures(line) {
+            let name = &captures[2];
+            let mut members = vec![];
+
+            for line in lines.by_ref() {
+                if line == "}" {
+                    break;
+                }
+
+                if let Some(captures) = field.captures(line) {
+                    members.push(format!("  {};", c_declaration(&captures[2], &captures[1])));
+                } else if let Some(captures) = variant.captures(line) {
+                    members.push(format!(
+                        "  {}_{},",
+                        screaming_snake_case(name),
+                        screaming_snake_case(&captures[1])
+                    ));
+                }
+            }
+
+            code = format!(
+                "typedef {} {} {{\n{}\n}} {};",
+                &captures[1],
+                name,
+                members.join("\n"),
+                name
+            );
+        } else if line.starts_with("pub ") && line.contains(" extern \"C\" fn ") {
+            // Signatures can be wrapped over several lines.
+            let mut signature = line.to_string();
+
+            while !signature.ends_with('{') {
+                signature.push_str(lines.next().unwrap().trim());
+            }
+
+            let signature = signature.replace(",)", ")");
+            let captures = function.captures(&signature).unwrap();
+            let parameters = captures[2]
+                .split(',')
+                .filter(|d| !d.trim().is_empty())
+                .map(|d| {
+                    let (name, kind) = d.split_once(':').unwrap();
+                    c_declaration(kind, name.trim())
+                })
+                .collect::<Vec<_>>();
+            let result = captures.get(3).map_or("void", |d| d.as_str().trim());
+
+            code = format!(
+                "{}({});",
+                c_declaration(result, &captures[1]),
+                if parameters.is_empty() {
+                    "void".into()
+                } else {
+                    parameters.join(", ")
+                }
+            );
+        }
+
+        if !code.is_empty() {
+            items.push(format!("{}\n{}", comments.join("\n"), code));
+        }
+
+        comments.clear();
+    }
+
+    let doc = format!(
+        "// C API of `markdown-rs`.
+//
+// > 👉 **Important**: this file is generated by `generate/src/main.rs`.
+// > It is generated from `src/capi.rs`, see there for more info.
+
+#ifndef MARKDOWN_H
+#define MARKDOWN_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern \"C\" {{
+#endif
+
+{}
+
+#ifdef __cplusplus
+}}
+#endif
+
+#endif
+",
+        items.join("\n\n")
+    );
+
+    fs::create_dir_all("include").unwrap();
+    fs::write(header_url, doc).unwrap();
+}
+
+fn c_declaration(kind: &str, name: &str) -> String {
+    let kind = c_type(kind);
+
+    if kind.ends_with('*') {
+        format!("{}{}", kind, name)
+    } else {
+        format!("{} {}", kind, name)
+    }
+}
+
+fn c_type(kind: &str) -> String {
+    let kind = kind.trim();
+
+    if let Some(rest) = kind.strip_prefix("*const ") {
+        c_pointer(&format!("const {}", c_type(rest)))
+    } else if let Some(rest) = kind.strip_prefix("*mut ") {
+        c_pointer(&c_type(rest))
+    } else if kind == "c_char" {
+        "char".into()
+    } else {
+        kind.into()
+    }
+}
+
+fn c_pointer(kind: &str) -> String {
+    if kind.ends_with('*') {
+        format!("{}*", kind)
+    } else {
+        format!("{} *", kind)
+    }
+}
+
+fn screaming_snake_case(name: &str) -> String {
+    let mut result = String::new();
+
+    for char in name.chars() {
+        if char.is_ascii_uppercase() && !result.is_empty() {
+            result.push('_');
+        }
+
+        result.push(char.to_ascii_uppercase());
+    }
+
+    result
+}
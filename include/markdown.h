@@ -0,0 +1,185 @@
+// C API of `markdown-rs`.
+//
+// > 👉 **Important**: this file is generated by `generate/src/main.rs`.
+// > It is generated from `src/capi.rs`, see there for more info.
+
+#ifndef MARKDOWN_H
+#define MARKDOWN_H
+
+#include <stdbool.h>
+
+#ifdef __cplusplus
+extern "C" {
+#endif
+
+// Which constructs to turn on and off.
+//
+// Mirrors `Constructs` in Rust, see its docs for what each field does.
+typedef struct MarkdownConstructs {
+  bool attention;
+  bool autolink;
+  bool block_quote;
+  bool character_escape;
+  bool character_reference;
+  bool code_indented;
+  bool code_fenced;
+  bool code_text;
+  bool definition;
+  bool frontmatter;
+  bool gfm_autolink_literal;
+  bool gfm_footnote_definition;
+  bool gfm_label_start_footnote;
+  bool gfm_strikethrough;
+  bool gfm_table;
+  bool gfm_task_list_item;
+  bool hard_break_escape;
+  bool hard_break_trailing;
+  bool heading_atx;
+  bool heading_setext;
+  bool html_flow;
+  bool html_text;
+  bool label_start_image;
+  bool label_start_link;
+  bool label_end;
+  bool list_item;
+  bool math_flow;
+  bool math_text;
+  bool mdx_esm;
+  bool mdx_expression_flow;
+  bool mdx_expression_text;
+  bool mdx_jsx_flow;
+  bool mdx_jsx_text;
+  bool thematic_break;
+} MarkdownConstructs;
+
+// Line ending to use when there are none in the document.
+//
+// Mirrors `LineEnding` in Rust.
+typedef enum MarkdownLineEnding {
+  MARKDOWN_LINE_ENDING_CARRIAGE_RETURN_LINE_FEED,
+  MARKDOWN_LINE_ENDING_CARRIAGE_RETURN,
+  MARKDOWN_LINE_ENDING_LINE_FEED,
+} MarkdownLineEnding;
+
+// Configuration that describes how to parse from markdown.
+//
+// Mirrors `ParseOptions` in Rust, without the MDX parse hooks.
+typedef struct MarkdownParseOptions {
+  MarkdownConstructs constructs;
+  bool gfm_strikethrough_single_tilde;
+  bool math_text_single_dollar;
+} MarkdownParseOptions;
+
+// Syntax of HTML to produce.
+//
+// Mirrors `HtmlSyntax` in Rust.
+typedef enum MarkdownHtmlSyntax {
+  MARKDOWN_HTML_SYNTAX_DEFAULT,
+  MARKDOWN_HTML_SYNTAX_HTML5,
+  MARKDOWN_HTML_SYNTAX_XHTML,
+} MarkdownHtmlSyntax;
+
+// Configuration that describes how to compile to HTML.
+//
+// Mirrors `CompileOptions` in Rust.
+// The strings are optional: `NULL` means the default.
+// The protocols and hosts are comma-separated lists, such as `"https,tel"`.
+// `sanitize_html` sanitizes raw HTML with the schema GitHub uses.
+// `diagrams` turns common diagrams into elements, like `Diagram::defaults()`.
+typedef struct MarkdownCompileOptions {
+  bool allow_dangerous_html;
+  bool allow_dangerous_protocol;
+  const char *protocol_href;
+  const char *protocol_src;
+  bool sanitize_html;
+  MarkdownLineEnding default_line_ending;
+  MarkdownHtmlSyntax html_syntax;
+  const char *gfm_footnote_label;
+  const char *gfm_footnote_label_tag_name;
+  const char *gfm_footnote_label_attributes;
+  const char *gfm_footnote_back_label;
+  const char *gfm_footnote_clobber_prefix;
+  bool gfm_tagfilter;
+  bool sourcepos;
+  const char *link_external_attributes;
+  const char *link_internal_hosts;
+  const char *image_attributes;
+  bool image_figure;
+  bool code_line_numbers;
+  bool code_highlight_lines;
+  bool diagrams;
+} MarkdownCompileOptions;
+
+// Configuration (optional).
+//
+// Mirrors `Options` in Rust.
+typedef struct MarkdownOptions {
+  MarkdownParseOptions parse;
+  MarkdownCompileOptions compile;
+} MarkdownOptions;
+
+// Get the default options (`CommonMark`).
+MarkdownOptions markdown_options_default(void);
+
+// Get options for GFM.
+MarkdownOptions markdown_options_gfm(void);
+
+// Get options for MDX.
+MarkdownOptions markdown_options_mdx(void);
+
+// Turn markdown into HTML, safely.
+//
+// Returns `NULL` if `value` is `NULL` or not UTF-8.
+// Free the result with `markdown_string_free`.
+//
+// # Safety
+//
+// `value` must be `NULL` or point to a string that ends in a NUL byte.
+char *markdown_to_html(const char *value);
+
+// Turn markdown into HTML, with configuration.
+//
+// `options` can be `NULL` for the defaults.
+// Returns `NULL` on errors, such as syntax errors in MDX, in which case
+// `*error` is set to a message (if `error` is not `NULL`).
+// Free the result and the error with `markdown_string_free`.
+//
+// # Safety
+//
+// `value` and the strings in `options` must be `NULL` or point to strings
+// that end in a NUL byte.
+// `options` must be `NULL` or point to options.
+// `error` must be `NULL` or point to where a string can be written.
+char *markdown_to_html_with_options(const char *value, const MarkdownOptions *options, char **error);
+
+// Turn markdown into a syntax tree, as JSON.
+//
+// The JSON is the same as `JSON.stringify(tree)` of what
+// `mdast-util-from-markdown` gives in JavaScript.
+// `options` can be `NULL` for the defaults.
+// Returns `NULL` on errors, such as syntax errors in MDX, in which case
+// `*error` is set to a message (if `error` is not `NULL`).
+// Free the result and the error with `markdown_string_free`.
+//
+// # Safety
+//
+// `value` must be `NULL` or point to a string that ends in a NUL byte.
+// `options` must be `NULL` or point to options.
+// `error` must be `NULL` or point to where a string can be written.
+char *markdown_to_mdast_json(const char *value, const MarkdownParseOptions *options, char **error);
+
+// Free a string (a result or an error) made by `markdown-rs`.
+//
+// Does nothing when given `NULL`.
+//
+// # Safety
+//
+// `value` must be `NULL` or a string made by `markdown-rs` that was not yet
+// freed.
+void markdown_string_free(char *value);
+
+#ifdef __cplusplus
+}
+#endif
+
+#endif
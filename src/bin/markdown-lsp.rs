@@ -0,0 +1,127 @@
+//! Language server (LSP) for `markdown-rs`.
+//!
+//! Speaks the language server protocol over stdin and stdout, for editors.
+//! Needs the `lsp` feature.
+//!
+//! Run `markdown-lsp --help` for how to use it.
+
+use markdown::{language_server, Constructs, ParseOptions};
+use std::{
+    env,
+    io::{self, BufReader},
+    process,
+};
+
+/// Exit code when everything went fine.
+const EXIT_OK: i32 = 0;
+/// Exit code when stdin or stdout failed.
+const EXIT_ERROR: i32 = 1;
+/// Exit code when the arguments are wrong.
+const EXIT_USAGE: i32 = 2;
+
+/// Help message.
+const HELP: &str = "Usage: markdown-lsp [options]
+
+Language server (LSP) for markdown, over stdin and stdout.
+
+Supports go to definition (from link, image, and footnote references),
+hover (for footnote references), document symbols (from headings),
+diagnostics (from syntax errors in MDX), and completion (of definition
+labels).
+
+Options:
+      --gfm          turn on GFM (autolink literals, footnotes,
+                     strikethrough, tables, tasklists)
+      --mdx          turn on MDX (ESM, expressions, JSX); turns off
+                     autolinks, code (indented), and HTML
+      --math         turn on math (flow) and math (text)
+      --frontmatter  turn on frontmatter
+  -h, --help         show this help
+  -V, --version      show the version
+
+Exit codes: 0 when fine, 1 when stdin or stdout failed, and 2 when the
+arguments are wrong.";
+
+fn main() {
+    process::exit(run(env::args().skip(1).collect()));
+}
+
+/// Run with `args`, returning an exit code.
+fn run(args: Vec<String>) -> i32 {
+    let options = match parse_args(args) {
+        Ok(Some(options)) => options,
+        Ok(None) => return EXIT_OK,
+        Err(message) => {
+            eprintln!(
+                "markdown-lsp: {}\nTry `markdown-lsp --help` for more info.",
+                message
+            );
+            return EXIT_USAGE;
+        }
+    };
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+
+    match language_server(
+        &mut BufReader::new(stdin.lock()),
+        &mut stdout.lock(),
+        &options,
+    ) {
+        Ok(()) => EXIT_OK,
+        Err(error) => {
+            eprintln!("markdown-lsp: {}", error);
+            EXIT_ERROR
+        }
+    }
+}
+
+/// Turn arguments into options.
+///
+/// Returns `None` when help or the version was printed.
+fn parse_args(args: Vec<String>) -> Result<Option<ParseOptions>, String> {
+    let mut options = ParseOptions::default();
+
+    for arg in args {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", HELP);
+                return Ok(None);
+            }
+            "-V" | "--version" => {
+                println!("markdown-lsp {}", env!("CARGO_PKG_VERSION"));
+                return Ok(None);
+            }
+            "--gfm" => {
+                let constructs = &mut options.constructs;
+                constructs.gfm_autolink_literal = true;
+                constructs.gfm_footnote_definition = true;
+                constructs.gfm_label_start_footnote = true;
+                constructs.gfm_strikethrough = true;
+                constructs.gfm_table = true;
+                constructs.gfm_task_list_item = true;
+            }
+            "--mdx" => {
+                let mdx = Constructs::mdx();
+                let constructs = &mut options.constructs;
+                constructs.autolink = mdx.autolink;
+                constructs.code_indented = mdx.code_indented;
+                constructs.html_flow = mdx.html_flow;
+                constructs.html_text = mdx.html_text;
+                constructs.mdx_esm = mdx.mdx_esm;
+                constructs.mdx_expression_flow = mdx.mdx_expression_flow;
+                constructs.mdx_expression_text = mdx.mdx_expression_text;
+                constructs.mdx_jsx_flow = mdx.mdx_jsx_flow;
+                constructs.mdx_jsx_text = mdx.mdx_jsx_text;
+            }
+            "--math" => {
+                options.constructs.math_flow = true;
+                options.constructs.math_text = true;
+            }
+            "--frontmatter" => options.constructs.frontmatter = true,
+            _ => return Err(format!("unknown option `{}`", arg)),
+        }
+    }
+
+    Ok(Some(options))
+}
@@ -0,0 +1,406 @@
+//! Command-line interface to `markdown-rs`.
+//!
+//! Reads markdown from files, directories, or stdin, and writes HTML, a
+//! syntax tree (mdast) as JSON, or formatted markdown.
+//! Needs the `cli` feature.
+//!
+//! Run `markdown --help` for how to use it.
+
+use markdown::{
+    to_html_write_io, to_mdast_json, Constructs, FormatOptions, LineEnding, Options, ParseOptions,
+};
+use std::{
+    env, fs,
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    process,
+};
+
+/// Exit code when everything went fine.
+const EXIT_OK: i32 = 0;
+/// Exit code when a document could not be turned into the output, such as
+/// for syntax errors in MDX or when a file could not be read or written.
+const EXIT_ERROR: i32 = 1;
+/// Exit code when the arguments are wrong.
+const EXIT_USAGE: i32 = 2;
+
+/// Extensions of files to process when given a directory.
+const EXTENSIONS: [&str; 3] = ["md", "markdown", "mdx"];
+
+/// Help message.
+const HELP: &str = "Usage: markdown [options] [path...]
+
+Turn markdown into HTML, into a syntax tree (mdast) as JSON, or format it.
+
+Reads from stdin when no paths (or `-`) are given, and writes to stdout.
+When several paths or a directory are given, each markdown file (`.md`,
+`.markdown`, `.mdx`) is written next to it (`a.md` -> `a.html`), or into
+the directory given with `--output`.
+With `--to markdown`, files are formatted in place, unless `--output` is
+given.
+
+Options:
+  -o, --output <path>           write to a file, or, for several paths,
+                                into a directory
+  -t, --to <format>             output format: `html` (default), `mdast`,
+                                or `markdown`
+      --gfm                     turn on GFM (autolink literals, footnotes,
+                                strikethrough, tables, tasklists, tagfilter)
+      --mdx                     turn on MDX (ESM, expressions, JSX); turns
+                                off autolinks, code (indented), and HTML
+      --math                    turn on math (flow) and math (text)
+      --frontmatter             turn on frontmatter
+      --allow-dangerous-html    keep HTML in markdown
+      --allow-dangerous-protocol
+                                keep dangerous protocols in links and images
+      --sourcepos               add `data-sourcepos` attributes to block
+                                elements, with where they are in markdown
+      --line-ending <kind>      line ending to use when there is none in the
+                                document: `lf` (default), `crlf`, or `cr`
+  -h, --help                    show this help
+  -V, --version                 show the version
+
+Exit codes: 0 when fine, 1 when a document could not be converted (such as
+for MDX syntax errors) or a file could not be read or written, and 2 when
+the arguments are wrong.";
+
+/// Output format.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+enum Format {
+    /// HTML.
+    Html,
+    /// Syntax tree (mdast) as JSON.
+    Mdast,
+    /// Formatted markdown.
+    Markdown,
+}
+
+impl Format {
+    /// Extension of output files.
+    ///
+    /// `None` keeps the extension of the input file.
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            Format::Html => Some("html"),
+            Format::Mdast => Some("json"),
+            Format::Markdown => None,
+        }
+    }
+}
+
+/// Configuration, from arguments.
+#[derive(Debug)]
+struct Config {
+    /// Options passed to `markdown-rs`.
+    options: Options,
+    /// Output format.
+    format: Format,
+    /// Input paths, `-` for stdin.
+    inputs: Vec<String>,
+    /// Output path.
+    output: Option<PathBuf>,
+}
+
+fn main() {
+    process::exit(run(env::args().skip(1).collect()));
+}
+
+/// Run with `args`, returning an exit code.
+fn run(args: Vec<String>) -> i32 {
+    let config = match parse_args(args) {
+        Ok(Some(config)) => config,
+        Ok(None) => return EXIT_OK,
+        Err(message) => {
+            eprintln!(
+                "markdown: {}\nTry `markdown --help` for more info.",
+                message
+            );
+            return EXIT_USAGE;
+        }
+    };
+
+    let batch = config.inputs.len() > 1
+        || config
+            .inputs
+            .iter()
+            .any(|input| input != "-" && Path::new(input).is_dir());
+
+    if batch {
+        run_batch(&config)
+    } else {
+        let input = config.inputs.first().map_or("-", String::as_str);
+        match process_one(&config, input, config.output.as_deref()) {
+            Ok(()) => EXIT_OK,
+            Err(message) => {
+                eprintln!("{}", message);
+                EXIT_ERROR
+            }
+        }
+    }
+}
+
+/// Turn arguments into configuration.
+///
+/// Returns `None` when help or the version was printed.
+fn parse_args(args: Vec<String>) -> Result<Option<Config>, String> {
+    let mut options = Options::default();
+    let mut format = Format::Html;
+    let mut inputs = vec![];
+    let mut output = None;
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => {
+                println!("{}", HELP);
+                return Ok(None);
+            }
+            "-V" | "--version" => {
+                println!("markdown {}", env!("CARGO_PKG_VERSION"));
+                return Ok(None);
+            }
+            "-o" | "--output" => output = Some(PathBuf::from(value(&arg, args.next())?)),
+            "-t" | "--to" => {
+                format = match value(&arg, args.next())?.as_str() {
+                    "html" => Format::Html,
+                    "mdast" => Format::Mdast,
+                    "markdown" => Format::Markdown,
+                    other => {
+                        return Err(format!(
+                            "unknown format `{}`, expected `html`, `mdast`, or `markdown`",
+                            other
+                        ))
+                    }
+                };
+            }
+            "--gfm" => {
+                let constructs = &mut options.parse.constructs;
+                constructs.gfm_autolink_literal = true;
+                constructs.gfm_footnote_definition = true;
+                constructs.gfm_label_start_footnote = true;
+                constructs.gfm_strikethrough = true;
+                constructs.gfm_table = true;
+                constructs.gfm_task_list_item = true;
+                options.compile.gfm_tagfilter = true;
+            }
+            "--mdx" => {
+                let mdx = Constructs::mdx();
+                let constructs = &mut options.parse.constructs;
+                constructs.autolink = mdx.autolink;
+                constructs.code_indented = mdx.code_indented;
+                constructs.html_flow = mdx.html_flow;
+                constructs.html_text = mdx.html_text;
+                constructs.mdx_esm = mdx.mdx_esm;
+                constructs.mdx_expression_flow = mdx.mdx_expression_flow;
+                constructs.mdx_expression_text = mdx.mdx_expression_text;
+                constructs.mdx_jsx_flow = mdx.mdx_jsx_flow;
+                constructs.mdx_jsx_text = mdx.mdx_jsx_text;
+            }
+            "--math" => {
+                options.parse.constructs.math_flow = true;
+                options.parse.constructs.math_text = true;
+            }
+            "--frontmatter" => options.parse.constructs.frontmatter = true,
+            "--allow-dangerous-html" => options.compile.allow_dangerous_html = true,
+            "--allow-dangerous-protocol" => options.compile.allow_dangerous_protocol = true,
+            "--sourcepos" => options.compile.sourcepos = true,
+            "--line-ending" => {
+                options.compile.default_line_ending = match value(&arg, args.next())?.as_str() {
+                    "lf" => LineEnding::LineFeed,
+                    "crlf" => LineEnding::CarriageReturnLineFeed,
+                    "cr" => LineEnding::CarriageReturn,
+                    other => {
+                        return Err(format!(
+                            "unknown line ending `{}`, expected `lf`, `crlf`, or `cr`",
+                            other
+                        ))
+                    }
+                };
+            }
+            "--" => inputs.extend(args.by_ref()),
+            _ if arg.starts_with('-') && arg != "-" => {
+                return Err(format!("unknown option `{}`", arg));
+            }
+            _ => inputs.push(arg),
+        }
+    }
+
+    Ok(Some(Config {
+        options,
+        format,
+        inputs,
+        output,
+    }))
+}
+
+/// Get the value of the option `name`.
+fn value(name: &str, value: Option<String>) -> Result<String, String> {
+    value.ok_or_else(|| format!("missing value for `{}`", name))
+}
+
+/// Process several inputs, writing each to a file.
+fn run_batch(config: &Config) -> i32 {
+    let mut code = EXIT_OK;
+
+    for input in &config.inputs {
+        if input == "-" {
+            eprintln!("markdown: cannot read stdin when processing several paths");
+            return EXIT_USAGE;
+        }
+
+        let root = Path::new(input);
+        let files = if root.is_dir() {
+            let mut files = vec![];
+
+            if let Err(error) = find(root, &mut files) {
+                eprintln!("{}: {}", input, error);
+                code = EXIT_ERROR;
+                continue;
+            }
+
+            files
+        } else {
+            vec![root.to_path_buf()]
+        };
+
+        for file in files {
+            let relative = if root.is_dir() {
+                file.strip_prefix(root).unwrap_or(&file)
+            } else {
+                Path::new(file.file_name().unwrap_or(file.as_os_str()))
+            };
+            let mut destination = config
+                .output
+                .as_ref()
+                .map_or_else(|| file.clone(), |output| output.join(relative));
+
+            if let Some(extension) = config.format.extension() {
+                destination.set_extension(extension);
+            }
+
+            if let Some(parent) = destination.parent() {
+                if let Err(error) = fs::create_dir_all(parent) {
+                    eprintln!("{}: {}", parent.display(), error);
+                    code = EXIT_ERROR;
+                    continue;
+                }
+            }
+
+            if let Err(message) = process_one(config, &file.to_string_lossy(), Some(&destination)) {
+                eprintln!("{}", message);
+                code = EXIT_ERROR;
+            }
+        }
+    }
+
+    code
+}
+
+/// Find markdown files in `directory`, recursively, in a stable order.
+fn find(directory: &Path, files: &mut Vec<PathBuf>) -> io::Result<()> {
+    let mut entries = fs::read_dir(directory)?
+        .map(|entry| entry.map(|entry| entry.path()))
+        .collect::<io::Result<Vec<_>>>()?;
+    entries.sort();
+
+    for path in entries {
+        if path.is_dir() {
+            find(&path, files)?;
+        } else if path
+            .extension()
+            .and_then(|extension| extension.to_str())
+            .map_or(false, |extension| EXTENSIONS.contains(&extension))
+        {
+            files.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Read `input` (`-` for stdin), and write the result to `output` (or
+/// stdout).
+///
+/// Errors are prefixed with the input path.
+fn process_one(config: &Config, input: &str, output: Option<&Path>) -> Result<(), String> {
+    let name = if input == "-" { "<stdin>" } else { input };
+    let mut value = String::new();
+
+    let read = if input == "-" {
+        io::stdin().read_to_string(&mut value).map(|_| ())
+    } else {
+        fs::File::open(input).and_then(|mut file| file.read_to_string(&mut value).map(|_| ()))
+    };
+
+    read.map_err(|error| format!("{}: {}", name, error))?;
+
+    let result = if let Some(path) = output {
+        // Convert first, so that errors do not touch the file, which can be
+        // the input (`--to markdown`) or a good result from an earlier run.
+        let mut buffer = vec![];
+        write(config, &value, &mut buffer).and_then(|()| {
+            replace(path, &buffer).map_err(|error| format!("{}: {}", path.display(), error))
+        })
+    } else {
+        let stdout = io::stdout();
+        let mut lock = stdout.lock();
+        write(config, &value, &mut lock)
+    };
+
+    // Positional messages (`1:2: …`) are joined to the name like a position.
+    result.map_err(|message| {
+        if message.starts_with(|d: char| d.is_ascii_digit()) {
+            format!("{}:{}", name, message)
+        } else {
+            format!("{}: {}", name, message)
+        }
+    })
+}
+
+/// Write `value` to a temporary file next to `path`, and rename it over
+/// `path`, so that `path` is never left empty or half-written.
+fn replace(path: &Path, value: &[u8]) -> io::Result<()> {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(path.file_name().unwrap_or_default());
+    name.push(".tmp");
+    let temporary = path.with_file_name(name);
+
+    let result = fs::write(&temporary, value).and_then(|()| fs::rename(&temporary, path));
+
+    if result.is_err() {
+        let _ = fs::remove_file(&temporary);
+    }
+
+    result
+}
+
+/// Turn `value` into the output format, writing it to `output`.
+fn write(config: &Config, value: &str, output: &mut impl Write) -> Result<(), String> {
+    match config.format {
+        Format::Html => {
+            let mut output = io::BufWriter::new(output);
+            to_html_write_io(value, &config.options, &mut output)?;
+            output
+                .flush()
+                .map_err(|error| format!("Cannot write HTML: {}", error))
+        }
+        Format::Mdast => {
+            let json = to_mdast_json(value, &config.options.parse)?;
+            writeln!(output, "{}", json).map_err(|error| format!("Cannot write mdast: {}", error))
+        }
+        Format::Markdown => {
+            // Only constructs can be configured with arguments.
+            let options = FormatOptions {
+                parse: ParseOptions {
+                    constructs: config.options.parse.constructs.clone(),
+                    ..ParseOptions::default()
+                },
+                ..FormatOptions::default()
+            };
+            let markdown = markdown::format(value, &options)?;
+            output
+                .write_all(markdown.as_bytes())
+                .map_err(|error| format!("Cannot write markdown: {}", error))
+        }
+    }
+}
@@ -0,0 +1,544 @@
+//! C API of `markdown-rs`.
+//!
+//! Needs the `capi` feature.
+//! Build a library that C (or anything that speaks the C ABI) can link
+//! with:
+//!
+//! ```sh
+//! cargo rustc --release --features capi --crate-type cdylib
+//! ```
+//!
+//! …and include `include/markdown.h`.
+//!
+//! > 👉 **Important**: `include/markdown.h` is generated from this module by
+//! > `generate/src/main.rs`.
+//!
+//! Strings going in must be UTF-8 and end in a NUL byte.
+//! Strings coming out (results and errors) are owned by the caller, and must
+//! be freed with [`markdown_string_free()`][].
+//!
+//! ## Examples
+//!
+//! ```c
+//! #include <stdio.h>
+//! #include "markdown.h"
+//!
+//! int main(void) {
+//!   MarkdownOptions options = markdown_options_gfm();
+//!   char *error = NULL;
+//!   char *html = markdown_to_html_with_options("~hi~", &options, &error);
+//!
+//!   if (html == NULL) {
+//!     fprintf(stderr, "%s\n", error);
+//!     markdown_string_free(error);
+//!     return 1;
+//!   }
+//!
+//!   printf("%s\n", html);
+//!   markdown_string_free(html);
+//!   return 0;
+//! }
+//! ```
+
+use crate::{
+    to_html, to_html_with_options, to_mdast_json, CompileOptions, Constructs, Diagram, HtmlSchema,
+    HtmlSyntax, LineEnding, Options, ParseOptions,
+};
+use alloc::{format, string::String, vec::Vec};
+use core::ptr;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    panic::{self, AssertUnwindSafe},
+};
+
+/// Which constructs to turn on and off.
+///
+/// Mirrors `Constructs` in Rust, see its docs for what each field does.
+#[repr(C)]
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MarkdownConstructs {
+    pub attention: bool,
+    pub autolink: bool,
+    pub block_quote: bool,
+    pub character_escape: bool,
+    pub character_reference: bool,
+    pub code_indented: bool,
+    pub code_fenced: bool,
+    pub code_text: bool,
+    pub definition: bool,
+    pub frontmatter: bool,
+    pub gfm_autolink_literal: bool,
+    pub gfm_footnote_definition: bool,
+    pub gfm_label_start_footnote: bool,
+    pub gfm_strikethrough: bool,
+    pub gfm_table: bool,
+    pub gfm_task_list_item: bool,
+    pub hard_break_escape: bool,
+    pub hard_break_trailing: bool,
+    pub heading_atx: bool,
+    pub heading_setext: bool,
+    pub html_flow: bool,
+    pub html_text: bool,
+    pub label_start_image: bool,
+    pub label_start_link: bool,
+    pub label_end: bool,
+    pub list_item: bool,
+    pub math_flow: bool,
+    pub math_text: bool,
+    pub mdx_esm: bool,
+    pub mdx_expression_flow: bool,
+    pub mdx_expression_text: bool,
+    pub mdx_jsx_flow: bool,
+    pub mdx_jsx_text: bool,
+    pub thematic_break: bool,
+}
+
+/// Line ending to use when there are none in the document.
+///
+/// Mirrors `LineEnding` in Rust.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MarkdownLineEnding {
+    CarriageReturnLineFeed,
+    CarriageReturn,
+    LineFeed,
+}
+
+/// Configuration that describes how to parse from markdown.
+///
+/// Mirrors `ParseOptions` in Rust, without the MDX parse hooks.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MarkdownParseOptions {
+    pub constructs: MarkdownConstructs,
+    pub gfm_strikethrough_single_tilde: bool,
+    pub math_text_single_dollar: bool,
+}
+
+/// Syntax of HTML to produce.
+///
+/// Mirrors `HtmlSyntax` in Rust.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum MarkdownHtmlSyntax {
+    Default,
+    Html5,
+    Xhtml,
+}
+
+/// Configuration that describes how to compile to HTML.
+///
+/// Mirrors `CompileOptions` in Rust.
+/// The strings are optional: `NULL` means the default.
+/// The protocols and hosts are comma-separated lists, such as `"https,tel"`.
+/// `sanitize_html` sanitizes raw HTML with the schema GitHub uses.
+/// `diagrams` turns common diagrams into elements, like `Diagram::defaults()`.
+#[repr(C)]
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MarkdownCompileOptions {
+    pub allow_dangerous_html: bool,
+    pub allow_dangerous_protocol: bool,
+    pub protocol_href: *const c_char,
+    pub protocol_src: *const c_char,
+    pub sanitize_html: bool,
+    pub default_line_ending: MarkdownLineEnding,
+    pub html_syntax: MarkdownHtmlSyntax,
+    pub gfm_footnote_label: *const c_char,
+    pub gfm_footnote_label_tag_name: *const c_char,
+    pub gfm_footnote_label_attributes: *const c_char,
+    pub gfm_footnote_back_label: *const c_char,
+    pub gfm_footnote_clobber_prefix: *const c_char,
+    pub gfm_tagfilter: bool,
+    pub sourcepos: bool,
+    pub link_external_attributes: *const c_char,
+    pub link_internal_hosts: *const c_char,
+    pub image_attributes: *const c_char,
+    pub image_figure: bool,
+    pub code_line_numbers: bool,
+    pub code_highlight_lines: bool,
+    pub diagrams: bool,
+}
+
+/// Configuration (optional).
+///
+/// Mirrors `Options` in Rust.
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct MarkdownOptions {
+    pub parse: MarkdownParseOptions,
+    pub compile: MarkdownCompileOptions,
+}
+
+/// Get the default options (`CommonMark`).
+#[no_mangle]
+pub extern "C" fn markdown_options_default() -> MarkdownOptions {
+    MarkdownOptions::from(&Options::default())
+}
+
+/// Get options for GFM.
+#[no_mangle]
+pub extern "C" fn markdown_options_gfm() -> MarkdownOptions {
+    MarkdownOptions::from(&Options::gfm())
+}
+
+/// Get options for MDX.
+#[no_mangle]
+pub extern "C" fn markdown_options_mdx() -> MarkdownOptions {
+    MarkdownOptions::from(&Options {
+        parse: ParseOptions::mdx(),
+        ..Options::default()
+    })
+}
+
+/// Turn markdown into HTML, safely.
+///
+/// Returns `NULL` if `value` is `NULL` or not UTF-8.
+/// Free the result with `markdown_string_free`.
+///
+/// # Safety
+///
+/// `value` must be `NULL` or point to a string that ends in a NUL byte.
+#[no_mangle]
+pub unsafe extern "C" fn markdown_to_html(value: *const c_char) -> *mut c_char {
+    run(ptr::null_mut(), || Ok(to_html(read(value, "value")?)))
+}
+
+/// Turn markdown into HTML, with configuration.
+///
+/// `options` can be `NULL` for the defaults.
+/// Returns `NULL` on errors, such as syntax errors in MDX, in which case
+/// `*error` is set to a message (if `error` is not `NULL`).
+/// Free the result and the error with `markdown_string_free`.
+///
+/// # Safety
+///
+/// `value` and the strings in `options` must be `NULL` or point to strings
+/// that end in a NUL byte.
+/// `options` must be `NULL` or point to options.
+/// `error` must be `NULL` or point to where a string can be written.
+#[no_mangle]
+pub unsafe extern "C" fn markdown_to_html_with_options(
+    value: *const c_char,
+    options: *const MarkdownOptions,
+    error: *mut *mut c_char,
+) -> *mut c_char {
+    run(error, || {
+        let value = read(value, "value")?;
+
+        match options.as_ref() {
+            Some(options) => to_html_with_options(value, &to_options(options)?),
+            None => to_html_with_options(value, &Options::default()),
+        }
+    })
+}
+
+/// Turn markdown into a syntax tree, as JSON.
+///
+/// The JSON is the same as `JSON.stringify(tree)` of what
+/// `mdast-util-from-markdown` gives in JavaScript.
+/// `options` can be `NULL` for the defaults.
+/// Returns `NULL` on errors, such as syntax errors in MDX, in which case
+/// `*error` is set to a message (if `error` is not `NULL`).
+/// Free the result and the error with `markdown_string_free`.
+///
+/// # Safety
+///
+/// `value` must be `NULL` or point to a string that ends in a NUL byte.
+/// `options` must be `NULL` or point to options.
+/// `error` must be `NULL` or point to where a string can be written.
+#[no_mangle]
+pub unsafe extern "C" fn markdown_to_mdast_json(
+    value: *const c_char,
+    options: *const MarkdownParseOptions,
+    error: *mut *mut c_char,
+) -> *mut c_char {
+    run(error, || {
+        let value = read(value, "value")?;
+
+        match options.as_ref() {
+            Some(options) => to_mdast_json(value, &to_parse_options(options)),
+            None => to_mdast_json(value, &ParseOptions::default()),
+        }
+    })
+}
+
+/// Free a string (a result or an error) made by `markdown-rs`.
+///
+/// Does nothing when given `NULL`.
+///
+/// # Safety
+///
+/// `value` must be `NULL` or a string made by `markdown-rs` that was not yet
+/// freed.
+#[no_mangle]
+pub unsafe extern "C" fn markdown_string_free(value: *mut c_char) {
+    if !value.is_null() {
+        drop(CString::from_raw(value));
+    }
+}
+
+/// Run `func`, turning its result into an owned string, and its error (or a
+/// panic) into `NULL` and a message in `error`.
+unsafe fn run<F>(error: *mut *mut c_char, func: F) -> *mut c_char
+where
+    F: FnOnce() -> Result<String, String>,
+{
+    let result = match panic::catch_unwind(AssertUnwindSafe(func)) {
+        Ok(result) => result,
+        Err(_) => Err("Unexpected panic in markdown-rs".into()),
+    };
+
+    let result = result
+        .and_then(|value| CString::new(value).map_err(|_| "Unexpected NUL byte in result".into()));
+
+    match result {
+        Ok(value) => value.into_raw(),
+        Err(message) => {
+            if !error.is_null() {
+                // A message cannot contain NUL bytes, as they are turned into
+                // replacement characters when parsing.
+                *error = CString::new(message).map_or(ptr::null_mut(), CString::into_raw);
+            }
+
+            ptr::null_mut()
+        }
+    }
+}
+
+/// Read a string from C, named `name` for errors.
+unsafe fn read<'a>(value: *const c_char, name: &str) -> Result<&'a str, String> {
+    if value.is_null() {
+        return Err(format!(
+            "Unexpected `NULL` for `{}`, expected a string",
+            name
+        ));
+    }
+
+    CStr::from_ptr(value)
+        .to_str()
+        .map_err(|_| format!("Unexpected invalid UTF-8 in `{}`", name))
+}
+
+/// Read an optional string from C, named `name` for errors.
+unsafe fn read_optional(value: *const c_char, name: &str) -> Result<Option<String>, String> {
+    if value.is_null() {
+        Ok(None)
+    } else {
+        Ok(Some(read(value, name)?.into()))
+    }
+}
+
+/// Read an optional comma-separated list from C, named `name` for errors.
+unsafe fn read_list(value: *const c_char, name: &str) -> Result<Option<Vec<String>>, String> {
+    Ok(read_optional(value, name)?.map(|value| {
+        value
+            .split(',')
+            .map(|d| d.trim().into())
+            .filter(|d: &String| !d.is_empty())
+            .collect()
+    }))
+}
+
+/// Turn C options into options.
+unsafe fn to_options(options: &MarkdownOptions) -> Result<Options, String> {
+    let compile = &options.compile;
+
+    Ok(Options {
+        parse: to_parse_options(&options.parse),
+        compile: CompileOptions {
+            allow_dangerous_html: compile.allow_dangerous_html,
+            allow_dangerous_protocol: compile.allow_dangerous_protocol,
+            protocol_href: read_list(compile.protocol_href, "protocol_href")?,
+            protocol_src: read_list(compile.protocol_src, "protocol_src")?,
+            sanitize_html: if compile.sanitize_html {
+                Some(HtmlSchema::github())
+            } else {
+                None
+            },
+            default_line_ending: match compile.default_line_ending {
+                MarkdownLineEnding::CarriageReturnLineFeed => LineEnding::CarriageReturnLineFeed,
+                MarkdownLineEnding::CarriageReturn => LineEnding::CarriageReturn,
+                MarkdownLineEnding::LineFeed => LineEnding::LineFeed,
+            },
+            html_syntax: match compile.html_syntax {
+                MarkdownHtmlSyntax::Default => HtmlSyntax::Default,
+                MarkdownHtmlSyntax::Html5 => HtmlSyntax::Html5,
+                MarkdownHtmlSyntax::Xhtml => HtmlSyntax::Xhtml,
+            },
+            gfm_footnote_label: read_optional(compile.gfm_footnote_label, "gfm_footnote_label")?,
+            gfm_footnote_label_tag_name: read_optional(
+                compile.gfm_footnote_label_tag_name,
+                "gfm_footnote_label_tag_name",
+            )?,
+            gfm_footnote_label_attributes: read_optional(
+                compile.gfm_footnote_label_attributes,
+                "gfm_footnote_label_attributes",
+            )?,
+            gfm_footnote_back_label: read_optional(
+                compile.gfm_footnote_back_label,
+                "gfm_footnote_back_label",
+            )?,
+            gfm_footnote_clobber_prefix: read_optional(
+                compile.gfm_footnote_clobber_prefix,
+                "gfm_footnote_clobber_prefix",
+            )?,
+            gfm_tagfilter: compile.gfm_tagfilter,
+            sourcepos: compile.sourcepos,
+            rewrite_url: None,
+            link_external_attributes: read_optional(
+                compile.link_external_attributes,
+                "link_external_attributes",
+            )?,
+            link_internal_hosts: read_list(compile.link_internal_hosts, "link_internal_hosts")?,
+            image_attributes: read_optional(compile.image_attributes, "image_attributes")?,
+            image_figure: compile.image_figure,
+            render: None,
+            highlight: None,
+            math: None,
+            diagrams: if compile.diagrams {
+                Diagram::defaults()
+            } else {
+                Vec::new()
+            },
+            render_diagram: None,
+            code_line_numbers: compile.code_line_numbers,
+            code_highlight_lines: compile.code_highlight_lines,
+        },
+    })
+}
+
+/// Turn C parse options into parse options.
+fn to_parse_options(options: &MarkdownParseOptions) -> ParseOptions {
+    let constructs = &options.constructs;
+
+    ParseOptions {
+        constructs: Constructs {
+            attention: constructs.attention,
+            autolink: constructs.autolink,
+            block_quote: constructs.block_quote,
+            character_escape: constructs.character_escape,
+            character_reference: constructs.character_reference,
+            code_indented: constructs.code_indented,
+            code_fenced: constructs.code_fenced,
+            code_text: constructs.code_text,
+            definition: constructs.definition,
+            frontmatter: constructs.frontmatter,
+            gfm_autolink_literal: constructs.gfm_autolink_literal,
+            gfm_footnote_definition: constructs.gfm_footnote_definition,
+            gfm_label_start_footnote: constructs.gfm_label_start_footnote,
+            gfm_strikethrough: constructs.gfm_strikethrough,
+            gfm_table: constructs.gfm_table,
+            gfm_task_list_item: constructs.gfm_task_list_item,
+            hard_break_escape: constructs.hard_break_escape,
+            hard_break_trailing: constructs.hard_break_trailing,
+            heading_atx: constructs.heading_atx,
+            heading_setext: constructs.heading_setext,
+            html_flow: constructs.html_flow,
+            html_text: constructs.html_text,
+            label_start_image: constructs.label_start_image,
+            label_start_link: constructs.label_start_link,
+            label_end: constructs.label_end,
+            list_item: constructs.list_item,
+            math_flow: constructs.math_flow,
+            math_text: constructs.math_text,
+            mdx_esm: constructs.mdx_esm,
+            mdx_expression_flow: constructs.mdx_expression_flow,
+            mdx_expression_text: constructs.mdx_expression_text,
+            mdx_jsx_flow: constructs.mdx_jsx_flow,
+            mdx_jsx_text: constructs.mdx_jsx_text,
+            thematic_break: constructs.thematic_break,
+        },
+        gfm_strikethrough_single_tilde: options.gfm_strikethrough_single_tilde,
+        math_text_single_dollar: options.math_text_single_dollar,
+        ..ParseOptions::default()
+    }
+}
+
+impl From<&Options> for MarkdownOptions {
+    /// Turn options into C options.
+    ///
+    /// The strings in the compile options are not kept: they are all `NULL`.
+    fn from(options: &Options) -> Self {
+        let constructs = &options.parse.constructs;
+        let compile = &options.compile;
+
+        MarkdownOptions {
+            parse: MarkdownParseOptions {
+                constructs: MarkdownConstructs {
+                    attention: constructs.attention,
+                    autolink: constructs.autolink,
+                    block_quote: constructs.block_quote,
+                    character_escape: constructs.character_escape,
+                    character_reference: constructs.character_reference,
+                    code_indented: constructs.code_indented,
+                    code_fenced: constructs.code_fenced,
+                    code_text: constructs.code_text,
+                    definition: constructs.definition,
+                    frontmatter: constructs.frontmatter,
+                    gfm_autolink_literal: constructs.gfm_autolink_literal,
+                    gfm_footnote_definition: constructs.gfm_footnote_definition,
+                    gfm_label_start_footnote: constructs.gfm_label_start_footnote,
+                    gfm_strikethrough: constructs.gfm_strikethrough,
+                    gfm_table: constructs.gfm_table,
+                    gfm_task_list_item: constructs.gfm_task_list_item,
+                    hard_break_escape: constructs.hard_break_escape,
+                    hard_break_trailing: constructs.hard_break_trailing,
+                    heading_atx: constructs.heading_atx,
+                    heading_setext: constructs.heading_setext,
+                    html_flow: constructs.html_flow,
+                    html_text: constructs.html_text,
+                    label_start_image: constructs.label_start_image,
+                    label_start_link: constructs.label_start_link,
+                    label_end: constructs.label_end,
+                    list_item: constructs.list_item,
+                    math_flow: constructs.math_flow,
+                    math_text: constructs.math_text,
+                    mdx_esm: constructs.mdx_esm,
+                    mdx_expression_flow: constructs.mdx_expression_flow,
+                    mdx_expression_text: constructs.mdx_expression_text,
+                    mdx_jsx_flow: constructs.mdx_jsx_flow,
+                    mdx_jsx_text: constructs.mdx_jsx_text,
+                    thematic_break: constructs.thematic_break,
+                },
+                gfm_strikethrough_single_tilde: options.parse.gfm_strikethrough_single_tilde,
+                math_text_single_dollar: options.parse.math_text_single_dollar,
+            },
+            compile: MarkdownCompileOptions {
+                allow_dangerous_html: compile.allow_dangerous_html,
+                allow_dangerous_protocol: compile.allow_dangerous_protocol,
+                protocol_href: ptr::null(),
+                protocol_src: ptr::null(),
+                sanitize_html: compile.sanitize_html.is_some(),
+                default_line_ending: match compile.default_line_ending {
+                    LineEnding::CarriageReturnLineFeed => {
+                        MarkdownLineEnding::CarriageReturnLineFeed
+                    }
+                    LineEnding::CarriageReturn => MarkdownLineEnding::CarriageReturn,
+                    LineEnding::LineFeed => MarkdownLineEnding::LineFeed,
+                },
+                html_syntax: match compile.html_syntax {
+                    HtmlSyntax::Default => MarkdownHtmlSyntax::Default,
+                    HtmlSyntax::Html5 => MarkdownHtmlSyntax::Html5,
+                    HtmlSyntax::Xhtml => MarkdownHtmlSyntax::Xhtml,
+                },
+                gfm_footnote_label: ptr::null(),
+                gfm_footnote_label_tag_name: ptr::null(),
+                gfm_footnote_label_attributes: ptr::null(),
+                gfm_footnote_back_label: ptr::null(),
+                gfm_footnote_clobber_prefix: ptr::null(),
+                gfm_tagfilter: compile.gfm_tagfilter,
+                sourcepos: compile.sourcepos,
+                link_external_attributes: ptr::null(),
+                link_internal_hosts: ptr::null(),
+                image_attributes: ptr::null(),
+                image_figure: compile.image_figure,
+                code_line_numbers: compile.code_line_numbers,
+                code_highlight_lines: compile.code_highlight_lines,
+                diagrams: !compile.diagrams.is_empty(),
+            },
+        }
+    }
+}
@@ -1,8 +1,10 @@
+use crate::render::Render;
+use crate::unist::Position;
 use crate::util::{
     line_ending::LineEnding,
     mdx::{EsmParse as MdxEsmParse, ExpressionParse as MdxExpressionParse},
 };
-use alloc::{boxed::Box, fmt, string::String};
+use alloc::{boxed::Box, fmt, string::String, sync::Arc, vec, vec::Vec};
 
 /// Control which constructs are enabled.
 ///
@@ -31,6 +33,7 @@ use alloc::{boxed::Box, fmt, string::String};
 /// ```
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Constructs {
     /// Attention.
     ///
@@ -434,6 +437,144 @@ impl Constructs {
     }
 }
 
+/// A kind of frontmatter.
+///
+/// Used in [`frontmatter_matters`][ParseOptions::frontmatter_matters] to
+/// define which fences form frontmatter.
+///
+/// Matters with the kinds `yaml`, `toml`, and `json` turn into
+/// [`Yaml`][crate::mdast::Yaml], [`Toml`][crate::mdast::Toml], and
+/// [`Json`][crate::mdast::Json] nodes in mdast; other kinds turn into
+/// [`Frontmatter`][crate::mdast::Frontmatter] nodes.
+///
+/// Fences cannot be empty and cannot contain whitespace.
+/// When the opening fence is `{` and the closing fence is `}`, the fences are
+/// part of the value.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::Matter;
+/// # fn main() {
+///
+/// // Metadata in a `~~~meta` block, anywhere in the document:
+/// let meta = Matter {
+///   kind: "meta".into(),
+///   open: "~~~meta".into(),
+///   close: "~~~".into(),
+///   anywhere: true,
+/// };
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Matter {
+    /// Name of the kind of frontmatter (such as `yaml`).
+    pub kind: String,
+    /// Opening fence (such as `---`).
+    pub open: String,
+    /// Closing fence (such as `---`).
+    pub close: String,
+    /// Whether this frontmatter can occur anywhere in the document (flow),
+    /// instead of only at its start.
+    #[cfg_attr(feature = "serde", serde(default))]
+    pub anywhere: bool,
+}
+
+impl Matter {
+    /// Create a matter, that can only occur at the start of the document,
+    /// with the same opening and closing `fence`.
+    pub fn new(kind: &str, fence: &str) -> Self {
+        Self {
+            kind: kind.into(),
+            open: fence.into(),
+            close: fence.into(),
+            anywhere: false,
+        }
+    }
+
+    /// Create the matters for JSON: fenced with `;;;`, or an object whose
+    /// braces (`{` and `}`) are on their own lines.
+    ///
+    /// These are not in the default
+    /// [`frontmatter_matters`][ParseOptions::frontmatter_matters].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, Constructs, Matter, Options, ParseOptions};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// let mut frontmatter_matters = ParseOptions::default().frontmatter_matters;
+    /// frontmatter_matters.extend(Matter::json());
+    ///
+    /// let options = Options {
+    ///   parse: ParseOptions {
+    ///     constructs: Constructs {
+    ///       frontmatter: true,
+    ///       ..Constructs::default()
+    ///     },
+    ///     frontmatter_matters,
+    ///     ..ParseOptions::default()
+    ///   },
+    ///   ..Options::default()
+    /// };
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options("{\n  \"title\": \"Venus\"\n}\n\n# Venus", &options)?,
+    ///     "<h1>Venus</h1>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn json() -> Vec<Self> {
+        vec![
+            Self::new("json", ";;;"),
+            Self {
+                kind: "json".into(),
+                open: "{".into(),
+                close: "}".into(),
+                anywhere: false,
+            },
+        ]
+    }
+}
+
+/// Unit of `column` and `offset` in positions.
+///
+/// Used in [`position_encoding`][ParseOptions::position_encoding].
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::PositionEncoding;
+/// # fn main() {
+///
+/// // Count in UTF-16 code units, like JavaScript and LSP do:
+/// let utf16 = PositionEncoding::Utf16;
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PositionEncoding {
+    /// Bytes (UTF-8 code units), as in Rust strings.
+    ///
+    /// `a😀b` has `b` at column 6.
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "bytes"))]
+    Bytes,
+    /// UTF-16 code units, as in JavaScript strings and LSP.
+    ///
+    /// `a😀b` has `b` at column 4.
+    #[cfg_attr(feature = "serde", serde(rename = "utf16"))]
+    Utf16,
+    /// Unicode scalar values (`char`s).
+    ///
+    /// `a😀b` has `b` at column 3.
+    #[cfg_attr(feature = "serde", serde(rename = "chars"))]
+    Chars,
+}
+
 /// Configuration that describes how to compile to HTML.
 ///
 /// You likely either want to turn on the dangerous options
@@ -466,7 +607,8 @@ impl Constructs {
 /// # }
 /// ```
 #[allow(clippy::struct_excessive_bools)]
-#[derive(Clone, Debug, Default)]
+#[derive(Clone, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CompileOptions {
     /// Whether to allow (dangerous) HTML.
     ///
@@ -550,6 +692,125 @@ pub struct CompileOptions {
     /// ```
     pub allow_dangerous_protocol: bool,
 
+    /// Protocols to allow in links (`a[href]`), when not allowing dangerous
+    /// protocols.
+    ///
+    /// The default is `None`, which allows `http`, `https`, `irc`, `ircs`,
+    /// `mailto`, and `xmpp`.
+    /// Pass a list to allow other protocols, such as `tel` or `app`, in links,
+    /// autolinks, and GFM autolink literals.
+    /// Protocols are compared case-insensitively.
+    /// An entry with a colon, such as `data:image/png`, instead allows URLs
+    /// that start with it.
+    ///
+    /// URLs that have no protocol are always allowed.
+    /// To apply the same to a syntax tree, use
+    /// [`sanitize_mdast()`][crate::sanitize_mdast].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html, to_html_with_options, CompileOptions, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// // `markdown-rs` drops unknown protocols by default:
+    /// assert_eq!(
+    ///     to_html("[a](tel:123)"),
+    ///     "<p><a href=\"\">a</a></p>"
+    /// );
+    ///
+    /// // Pass `protocol_href` to allow others:
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "[a](tel:123)",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               protocol_href: Some(vec!["https".into(), "tel".into()]),
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<p><a href=\"tel:123\">a</a></p>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub protocol_href: Option<Vec<String>>,
+
+    /// Protocols to allow in images (`img[src]`), when not allowing dangerous
+    /// protocols.
+    ///
+    /// The default is `None`, which allows `http` and `https`.
+    /// Works like [`protocol_href`][CompileOptions::protocol_href].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html, to_html_with_options, CompileOptions, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// // `markdown-rs` drops `data:` by default:
+    /// assert_eq!(
+    ///     to_html("![a](data:image/png;base64,b)"),
+    ///     "<p><img src=\"\" alt=\"a\" /></p>"
+    /// );
+    ///
+    /// // Pass `protocol_src` to allow certain ones:
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "![a](data:image/png;base64,b) ![c](data:text/html;base64,d)",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               protocol_src: Some(vec!["data:image/png".into()]),
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<p><img src=\"data:image/png;base64,b\" alt=\"a\" /> <img src=\"\" alt=\"c\" /></p>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub protocol_src: Option<Vec<String>>,
+
+    /// Schema to sanitize raw HTML with.
+    ///
+    /// The default is `None`, which escapes raw HTML, or passes it through
+    /// when [`allow_dangerous_html`][CompileOptions::allow_dangerous_html] is
+    /// on.
+    /// Pass a schema to instead keep the elements and attributes it allows
+    /// (such as `<details>`, `<kbd>`, and `<sup>` with
+    /// [`HtmlSchema::github()`][HtmlSchema::github]), drop other elements
+    /// (but keep their content), drop comments, and balance unclosed
+    /// elements.
+    /// This takes precedence over `allow_dangerous_html`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, HtmlSchema, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "<details>\n<summary>a</summary>\n\n*b*\n\n</details>\n\n<script>c</script>\n\n<kbd onclick=\"d\">e</kbd> <sup>f",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               sanitize_html: Some(HtmlSchema::github()),
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<details>\n<summary>a</summary>\n<p><em>b</em></p>\n</details>\n<p><kbd>e</kbd> <sup>f</sup></p>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub sanitize_html: Option<HtmlSchema>,
+
     /// Default line ending to use when compiling to HTML, for line endings not
     /// in `value`.
     ///
@@ -594,6 +855,54 @@ pub struct CompileOptions {
     /// ```
     pub default_line_ending: LineEnding,
 
+    /// Syntax to use for void elements and boolean attributes.
+    ///
+    /// The default is [`HtmlSyntax::Default`][], which closes void elements
+    /// with a slash (`<br />`) and gives boolean attributes empty values
+    /// (`disabled=""`), which works in both HTML and XHTML.
+    ///
+    /// Pass [`HtmlSyntax::Html5`][] to drop those slashes and values
+    /// (`<br>`, `disabled`), or [`HtmlSyntax::Xhtml`][] for strict XHTML:
+    /// boolean attributes repeat their name (`disabled="disabled"`), and
+    /// raw HTML (see [`allow_dangerous_html`][CompileOptions::allow_dangerous_html])
+    /// has its void elements closed and its named character references
+    /// turned into numeric ones (`&copy;` to `&#xA9;`), as XHTML only knows
+    /// a few named ones.
+    ///
+    /// Note that a custom [`render`][CompileOptions::render] produces its
+    /// own syntax.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html, to_html_with_options, CompileOptions, HtmlSyntax, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// // `markdown-rs` closes void elements by default:
+    /// assert_eq!(
+    ///     to_html("a  \nb\n\n***"),
+    ///     "<p>a<br />\nb</p>\n<hr />"
+    /// );
+    ///
+    /// // Pass `HtmlSyntax::Html5` to not close them:
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "a  \nb\n\n***",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               html_syntax: HtmlSyntax::Html5,
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<p>a<br>\nb</p>\n<hr>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub html_syntax: HtmlSyntax,
+
     /// Textual label to use for the footnotes section.
     ///
     /// The default value is `"Footnotes"`.
@@ -800,92 +1109,629 @@ pub struct CompileOptions {
     /// ## Examples
     ///
     /// ```
-    /// use markdown::{to_html_with_options, CompileOptions, Options, ParseOptions};
+    /// use markdown::{to_html_with_options, CompileOptions, Options, ParseOptions};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// // `"user-content-"` is used by default:
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "[^a]\n\n[^a]: b",
+    ///         &Options::gfm()
+    ///     )?,
+    ///     "<p><sup><a href=\"#user-content-fn-a\" id=\"user-content-fnref-a\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup></p>\n<section data-footnotes=\"\" class=\"footnotes\"><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"user-content-fn-a\">\n<p>b <a href=\"#user-content-fnref-a\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>\n</li>\n</ol>\n</section>\n"
+    /// );
+    ///
+    /// // Pass `gfm_footnote_clobber_prefix` to use something else:
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "[^a]\n\n[^a]: b",
+    ///         &Options {
+    ///             parse: ParseOptions::gfm(),
+    ///             compile: CompileOptions {
+    ///               gfm_footnote_clobber_prefix: Some("".into()),
+    ///               ..CompileOptions::gfm()
+    ///             }
+    ///         }
+    ///     )?,
+    ///     "<p><sup><a href=\"#fn-a\" id=\"fnref-a\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup></p>\n<section data-footnotes=\"\" class=\"footnotes\"><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"fn-a\">\n<p>b <a href=\"#fnref-a\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>\n</li>\n</ol>\n</section>\n"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub gfm_footnote_clobber_prefix: Option<String>,
+
+    /// Whether to support the GFM tagfilter.
+    ///
+    /// This option does nothing if `allow_dangerous_html` is not turned on.
+    /// The default is `false`, which does not apply the GFM tagfilter to HTML.
+    /// Pass `true` for output that is a bit closer to GitHub’s actual output.
+    ///
+    /// The tagfilter is kinda weird and kinda useless.
+    /// The tag filter is a naïve attempt at XSS protection.
+    /// You should use a proper HTML sanitizing algorithm instead.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Options, ParseOptions};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// // With `allow_dangerous_html`, `markdown-rs` passes HTML through untouched:
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "<iframe>",
+    ///         &Options {
+    ///             parse: ParseOptions::gfm(),
+    ///             compile: CompileOptions {
+    ///               allow_dangerous_html: true,
+    ///               ..CompileOptions::default()
+    ///             }
+    ///         }
+    ///     )?,
+    ///     "<iframe>"
+    /// );
+    ///
+    /// // Pass `gfm_tagfilter: true` to make some of that safe:
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "<iframe>",
+    ///         &Options {
+    ///             parse: ParseOptions::gfm(),
+    ///             compile: CompileOptions {
+    ///               allow_dangerous_html: true,
+    ///               gfm_tagfilter: true,
+    ///               ..CompileOptions::default()
+    ///             }
+    ///         }
+    ///     )?,
+    ///     "&lt;iframe>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    ///
+    /// ## References
+    ///
+    /// *   [*§ 6.1 Disallowed Raw HTML (extension)* in GFM](https://github.github.com/gfm/#disallowed-raw-html-extension-)
+    /// *   [`cmark-gfm#extensions/tagfilter.c`](https://github.com/github/cmark-gfm/blob/master/extensions/tagfilter.c)
+    pub gfm_tagfilter: bool,
+
+    /// Whether to add the place in the markdown that block elements come
+    /// from, as `data-sourcepos` attributes.
+    ///
+    /// The default is `false`.
+    /// Pass `true` to add attributes such as `data-sourcepos="1:1-2:5"`
+    /// (start line and column, end line and column, inclusive; columns are
+    /// in bytes), like `cmark --sourcepos` does, which is useful to sync
+    /// scrolling between markdown and HTML in editors.
+    /// They are added to paragraphs, headings, thematic breaks, block
+    /// quotes, lists, list items, code and math (flow), and GFM tables,
+    /// table rows, and table cells.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "# a\n\n> b\n> c",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               sourcepos: true,
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<h1 data-sourcepos=\"1:1-1:3\">a</h1>\n<blockquote data-sourcepos=\"3:1-4:3\">\n<p data-sourcepos=\"3:3-4:3\">b\nc</p>\n</blockquote>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub sourcepos: bool,
+
+    /// Function to rewrite URLs with.
+    ///
+    /// The default is `None`, which keeps URLs as they are.
+    ///
+    /// Pass a function to change URLs in links, images, autolinks, and GFM
+    /// autolink literals, such as to resolve relative URLs against a base URL
+    /// (see [`resolve_url()`][crate::resolve_url]).
+    /// The function gets the URL, its [kind][UrlKind], and the position
+    /// (with byte offsets) of the link, image, or autolink in the document.
+    /// It returns the URL to use instead, or `None` to drop it.
+    /// The URL of a definition is rewritten each time a reference uses it,
+    /// with the kind and position of that link or image reference.
+    /// Rewritten URLs are still checked for dangerous protocols afterwards.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{resolve_url, to_html_with_options, CompileOptions, Options, UrlKind};
+    /// use std::sync::Arc;
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "[a](../api.md#b) ![c](d.png) ![e][f]\n\n[f]: f.png",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               rewrite_url: Some(Arc::new(|url, kind, _position| {
+    ///                   match kind {
+    ///                       UrlKind::Image => Some(resolve_url("/assets/", url)),
+    ///                       _ => Some(resolve_url("/docs/guide/", &url.replace(".md", "/"))),
+    ///                   }
+    ///               })),
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<p><a href=\"/docs/api/#b\">a</a> <img src=\"/assets/d.png\" alt=\"c\" /> <img src=\"/assets/f.png\" alt=\"e\" /></p>\n"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub rewrite_url: Option<Arc<RewriteUrl>>,
+
+    /// Attributes to add to external links (`a[href]`), such as
+    /// `rel="nofollow noopener" target="_blank"`.
+    ///
+    /// The default is `None`, which adds nothing.
+    ///
+    /// Links are external when their URL has a host (such as
+    /// `https://example.com/a` or `//example.com/a`) that is not in
+    /// [`link_internal_hosts`][CompileOptions::link_internal_hosts].
+    /// Like browsers, URLs with an `http`, `https`, `ftp`, `ws`, or `wss`
+    /// scheme have a host even without two slashes (`https:example.com`).
+    /// This applies to links, autolinks, and GFM autolink literals.
+    ///
+    /// > 👉 **Note**: the value is not checked or encoded, make sure it is
+    /// > valid and safe HTML.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "[a](https://example.com) [b](https://a.b) [c](/d)",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               link_external_attributes: Some("rel=\"nofollow noopener\" target=\"_blank\"".into()),
+    ///               link_internal_hosts: Some(vec!["example.com".into()]),
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<p><a href=\"https://example.com\">a</a> <a href=\"https://a.b\" rel=\"nofollow noopener\" target=\"_blank\">b</a> <a href=\"/d\">c</a></p>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub link_external_attributes: Option<String>,
+
+    /// Hosts of links that are not external.
+    ///
+    /// The default is `None`, which means links to any host are external.
+    ///
+    /// Hosts are compared case-insensitively, and also match their
+    /// subdomains: `example.com` includes `www.example.com`.
+    /// See [`link_external_attributes`][CompileOptions::link_external_attributes]
+    /// for an example.
+    pub link_internal_hosts: Option<Vec<String>>,
+
+    /// Attributes to add to images (`img`), such as
+    /// `loading="lazy" decoding="async"`.
+    ///
+    /// The default is `None`, which adds nothing.
+    ///
+    /// > 👉 **Note**: the value is not checked or encoded, make sure it is
+    /// > valid and safe HTML.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "![a](b.png)",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               image_attributes: Some("loading=\"lazy\" decoding=\"async\"".into()),
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<p><img src=\"b.png\" alt=\"a\" loading=\"lazy\" decoding=\"async\" /></p>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub image_attributes: Option<String>,
+
+    /// Whether to turn paragraphs that contain only an image into figures.
+    ///
+    /// The default is `false`.
+    /// Pass `true` to use `<figure>` instead of `<p>` for them, with the
+    /// title of the image, if there is one, in a `<figcaption>`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "![a](b.png \"c\")\n\nd ![e](f.png)",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               image_figure: true,
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<figure><img src=\"b.png\" alt=\"a\" title=\"c\" /><figcaption>c</figcaption></figure>\n<p>d <img src=\"f.png\" alt=\"e\" /></p>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub image_figure: bool,
+
+    /// Renderer to use for certain constructs.
+    ///
+    /// The default is `None`, which uses [`DefaultRender`][crate::DefaultRender].
+    ///
+    /// Pass a [`Render`][] to change the HTML of headings, code, links,
+    /// images, GFM tables, list items, and the GFM footnote section, such as
+    /// to add anchors to headings.
+    /// It must be `Send` and `Sync`, like the functions in these options, so
+    /// that options can be shared between threads.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Options, Render};
+    /// use std::sync::Arc;
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// struct Anchors;
+    ///
+    /// impl Render for Anchors {
+    ///     fn heading(&self, rank: u8, attributes: &str, children: &str) -> String {
+    ///         let id = children.to_lowercase().replace(' ', "-");
+    ///         format!(
+    ///             "<h{0} id=\"{1}\"{2}><a href=\"#{1}\">#</a> {3}</h{0}>",
+    ///             rank, id, attributes, children
+    ///         )
+    ///     }
+    /// }
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "## Getting started",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               render: Some(Arc::new(Anchors)),
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<h2 id=\"getting-started\"><a href=\"#getting-started\">#</a> Getting started</h2>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub render: Option<Arc<dyn Render + Send + Sync>>,
+
+    /// Function to highlight code with.
+    ///
+    /// The default is `None`, which encodes code as it is.
+    ///
+    /// Pass a function to highlight code (fenced and indented), such as with
+    /// a syntax highlighter.
+    /// The function gets the first word of the info string of fenced code
+    /// (such as `rust`), the rest of it (the meta), if any, and the code.
+    /// It returns the HTML to use as the content of the `code` element, or
+    /// `None` to encode the code as it is, such as for unknown languages.
+    /// The returned HTML is used as it is, so it must be safe.
+    ///
+    /// With the `highlight` feature, a built-in highlighter is available
+    /// as [`highlight()`][crate::highlight].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Options};
+    /// use std::sync::Arc;
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "```sh\necho 1\n```",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               highlight: Some(Arc::new(|lang, _meta, code| {
+    ///                   if lang == Some("sh") {
+    ///                       Some(code.replace("echo", "<b>echo</b>"))
+    ///                   } else {
+    ///                       None
+    ///                   }
+    ///               })),
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<pre><code class=\"language-sh\"><b>echo</b> 1\n</code></pre>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub highlight: Option<Arc<Highlight>>,
+
+    /// Whether to number the lines of code.
+    ///
+    /// The default is `false`.
+    ///
+    /// Pass `true` to wrap each line of code (fenced and indented) in a
+    /// `<span class="line" data-line-number="1">` element, which can be used
+    /// with CSS counters to show line numbers.
+    /// When highlighting (see [`highlight`][CompileOptions::highlight]),
+    /// elements in the highlighted HTML must not span several lines.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "```js\na\nb\n```",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               code_line_numbers: true,
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<pre><code class=\"language-js\"><span class=\"line\" data-line-number=\"1\">a</span>\n<span class=\"line\" data-line-number=\"2\">b</span>\n</code></pre>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub code_line_numbers: bool,
+
+    /// Whether to highlight lines of code listed in the meta of fenced code.
+    ///
+    /// The default is `false`.
+    ///
+    /// Pass `true` to support line numbers and ranges of them in braces in
+    /// the meta (such as `{1,3-5}` in ` ```js {1,3-5} `).
+    /// Each line of fenced code is then wrapped in a `<span class="line">`
+    /// element, and the listed lines get a `highlighted` class too.
+    /// When highlighting (see [`highlight`][CompileOptions::highlight]),
+    /// elements in the highlighted HTML must not span several lines.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Options};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "```js {2}\na\nb\n```",
+    ///         &Options {
+    ///             compile: CompileOptions {
+    ///               code_highlight_lines: true,
+    ///               ..CompileOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<pre><code class=\"language-js\"><span class=\"line\">a</span>\n<span class=\"line highlighted\">b</span>\n</code></pre>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub code_highlight_lines: bool,
+
+    /// Function to turn math into HTML with.
+    ///
+    /// The default is `None`, which turns math into `code` elements (with
+    /// `language-math` and `math-inline` or `math-display` classes), to be
+    /// rendered by something like `KaTeX` in the browser.
+    ///
+    /// Pass a function to render math when compiling instead.
+    /// The function gets the LaTeX of math (text) or math (flow), and
+    /// whether it is math (flow), which is displayed as a block.
+    /// It returns the HTML to use instead of the `code` element (and the
+    /// `pre` element of math (flow)), or `None` to use the default.
+    /// The returned HTML is used as it is, so it must be safe.
+    ///
+    /// With the `mathml` feature, a built-in function that turns a subset of
+    /// LaTeX into `MathML` is available as [`to_mathml()`][crate::to_mathml].
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Constructs, Options, ParseOptions};
+    /// use std::sync::Arc;
     /// # fn main() -> Result<(), String> {
     ///
-    /// // `"user-content-"` is used by default:
-    /// assert_eq!(
-    ///     to_html_with_options(
-    ///         "[^a]\n\n[^a]: b",
-    ///         &Options::gfm()
-    ///     )?,
-    ///     "<p><sup><a href=\"#user-content-fn-a\" id=\"user-content-fnref-a\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup></p>\n<section data-footnotes=\"\" class=\"footnotes\"><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"user-content-fn-a\">\n<p>b <a href=\"#user-content-fnref-a\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>\n</li>\n</ol>\n</section>\n"
-    /// );
-    ///
-    /// // Pass `gfm_footnote_clobber_prefix` to use something else:
     /// assert_eq!(
     ///     to_html_with_options(
-    ///         "[^a]\n\n[^a]: b",
+    ///         "$x$",
     ///         &Options {
-    ///             parse: ParseOptions::gfm(),
+    ///             parse: ParseOptions {
+    ///               constructs: Constructs {
+    ///                 math_text: true,
+    ///                 ..Constructs::default()
+    ///               },
+    ///               ..ParseOptions::default()
+    ///             },
     ///             compile: CompileOptions {
-    ///               gfm_footnote_clobber_prefix: Some("".into()),
-    ///               ..CompileOptions::gfm()
+    ///               math: Some(Arc::new(|value, _display| {
+    ///                   Some(format!("<math><mi>{}</mi></math>", value))
+    ///               })),
+    ///               ..CompileOptions::default()
     ///             }
     ///         }
     ///     )?,
-    ///     "<p><sup><a href=\"#fn-a\" id=\"fnref-a\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup></p>\n<section data-footnotes=\"\" class=\"footnotes\"><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"fn-a\">\n<p>b <a href=\"#fnref-a\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>\n</li>\n</ol>\n</section>\n"
+    ///     "<p><math><mi>x</mi></math></p>"
     /// );
     /// # Ok(())
     /// # }
     /// ```
-    pub gfm_footnote_clobber_prefix: Option<String>,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub math: Option<Arc<Math>>,
 
-    /// Whether to support the GFM tagfilter.
+    /// Diagrams to recognize in fenced code.
     ///
-    /// This option does nothing if `allow_dangerous_html` is not turned on.
-    /// The default is `false`, which does not apply the GFM tagfilter to HTML.
-    /// Pass `true` for output that is a bit closer to GitHub’s actual output.
+    /// The default is an empty list, which turns all fenced code into `pre`
+    /// and `code` elements.
     ///
-    /// The tagfilter is kinda weird and kinda useless.
-    /// The tag filter is a naïve attempt at XSS protection.
-    /// You should use a proper HTML sanitizing algorithm instead.
+    /// Pass diagrams (such as [`Diagram::defaults()`][Diagram::defaults])
+    /// to turn fenced code with certain languages (such as ` ```mermaid `)
+    /// into other elements (such as `<pre class="mermaid">`), to be rendered
+    /// by something like Mermaid in the browser.
+    /// Diagrams are not highlighted (see
+    /// [`highlight`][CompileOptions::highlight]).
     ///
     /// ## Examples
     ///
     /// ```
-    /// use markdown::{to_html_with_options, CompileOptions, Options, ParseOptions};
+    /// use markdown::{to_html_with_options, CompileOptions, Diagram, Options};
     /// # fn main() -> Result<(), String> {
     ///
-    /// // With `allow_dangerous_html`, `markdown-rs` passes HTML through untouched:
     /// assert_eq!(
     ///     to_html_with_options(
-    ///         "<iframe>",
+    ///         "```mermaid\ngraph TD;\na-->b\n```\n\n```dot\ndigraph { c -> d }\n```",
     ///         &Options {
-    ///             parse: ParseOptions::gfm(),
     ///             compile: CompileOptions {
-    ///               allow_dangerous_html: true,
+    ///               diagrams: Diagram::defaults(),
     ///               ..CompileOptions::default()
-    ///             }
+    ///             },
+    ///             ..Options::default()
     ///         }
     ///     )?,
-    ///     "<iframe>"
+    ///     "<pre class=\"mermaid\">graph TD;\na--&gt;b\n</pre>\n<div class=\"diagram language-dot\">digraph { c -&gt; d }\n</div>"
     /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub diagrams: Vec<Diagram>,
+
+    /// Function to turn diagrams into HTML with.
+    ///
+    /// The default is `None`, which turns diagrams into the elements
+    /// configured in [`diagrams`][CompileOptions::diagrams].
+    ///
+    /// Pass a function to render diagrams when compiling instead, such as
+    /// to SVG.
+    /// The function gets the language of the diagram (the
+    /// [`lang`][Diagram::lang] of the matching diagram) and its code.
+    /// It returns the HTML to use instead of the element, or `None` to use
+    /// the element.
+    /// The returned HTML is used as it is, so it must be safe.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, CompileOptions, Diagram, Options};
+    /// use std::sync::Arc;
+    /// # fn main() -> Result<(), String> {
     ///
-    /// // Pass `gfm_tagfilter: true` to make some of that safe:
     /// assert_eq!(
     ///     to_html_with_options(
-    ///         "<iframe>",
+    ///         "```dot\ndigraph { a -> b }\n```",
     ///         &Options {
-    ///             parse: ParseOptions::gfm(),
     ///             compile: CompileOptions {
-    ///               allow_dangerous_html: true,
-    ///               gfm_tagfilter: true,
+    ///               diagrams: Diagram::defaults(),
+    ///               render_diagram: Some(Arc::new(|lang, _code| {
+    ///                   if lang == "dot" {
+    ///                       Some("<svg><!-- … --></svg>".into())
+    ///                   } else {
+    ///                       None
+    ///                   }
+    ///               })),
     ///               ..CompileOptions::default()
-    ///             }
+    ///             },
+    ///             ..Options::default()
     ///         }
     ///     )?,
-    ///     "&lt;iframe>"
+    ///     "<svg><!-- … --></svg>"
     /// );
     /// # Ok(())
     /// # }
     /// ```
-    ///
-    /// ## References
-    ///
-    /// *   [*§ 6.1 Disallowed Raw HTML (extension)* in GFM](https://github.github.com/gfm/#disallowed-raw-html-extension-)
-    /// *   [`cmark-gfm#extensions/tagfilter.c`](https://github.com/github/cmark-gfm/blob/master/extensions/tagfilter.c)
-    pub gfm_tagfilter: bool,
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub render_diagram: Option<Arc<RenderDiagram>>,
+}
+
+impl fmt::Debug for CompileOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("CompileOptions")
+            .field("allow_dangerous_html", &self.allow_dangerous_html)
+            .field("allow_dangerous_protocol", &self.allow_dangerous_protocol)
+            .field("protocol_href", &self.protocol_href)
+            .field("protocol_src", &self.protocol_src)
+            .field("sanitize_html", &self.sanitize_html)
+            .field("default_line_ending", &self.default_line_ending)
+            .field("html_syntax", &self.html_syntax)
+            .field("gfm_footnote_label", &self.gfm_footnote_label)
+            .field(
+                "gfm_footnote_label_tag_name",
+                &self.gfm_footnote_label_tag_name,
+            )
+            .field(
+                "gfm_footnote_label_attributes",
+                &self.gfm_footnote_label_attributes,
+            )
+            .field("gfm_footnote_back_label", &self.gfm_footnote_back_label)
+            .field(
+                "gfm_footnote_clobber_prefix",
+                &self.gfm_footnote_clobber_prefix,
+            )
+            .field("gfm_tagfilter", &self.gfm_tagfilter)
+            .field("sourcepos", &self.sourcepos)
+            .field(
+                "rewrite_url",
+                &self.rewrite_url.as_ref().map(|_d| "[Function]"),
+            )
+            .field("link_external_attributes", &self.link_external_attributes)
+            .field("link_internal_hosts", &self.link_internal_hosts)
+            .field("image_attributes", &self.image_attributes)
+            .field("image_figure", &self.image_figure)
+            .field("render", &self.render.as_ref().map(|_d| "[Render]"))
+            .field("highlight", &self.highlight.as_ref().map(|_d| "[Function]"))
+            .field("code_line_numbers", &self.code_line_numbers)
+            .field("code_highlight_lines", &self.code_highlight_lines)
+            .field("math", &self.math.as_ref().map(|_d| "[Function]"))
+            .field("diagrams", &self.diagrams)
+            .field(
+                "render_diagram",
+                &self.render_diagram.as_ref().map(|_d| "[Function]"),
+            )
+            .finish()
+    }
 }
 
 impl CompileOptions {
@@ -906,6 +1752,366 @@ impl CompileOptions {
     }
 }
 
+/// Syntax of HTML to produce.
+///
+/// Used in [`html_syntax`][CompileOptions::html_syntax].
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::HtmlSyntax;
+/// # fn main() {
+///
+/// // Use HTML5 syntax, such as `<br>`:
+/// let html5 = HtmlSyntax::Html5;
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HtmlSyntax {
+    /// Syntax that works in both HTML and XHTML.
+    ///
+    /// `<br />`, `<input type="checkbox" disabled="" />`.
+    #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "default"))]
+    Default,
+    /// HTML5.
+    ///
+    /// `<br>`, `<input type="checkbox" disabled>`.
+    #[cfg_attr(feature = "serde", serde(rename = "html5"))]
+    Html5,
+    /// Strict XHTML.
+    ///
+    /// `<br />`, `<input type="checkbox" disabled="disabled" />`, and only
+    /// numeric character references in raw HTML.
+    #[cfg_attr(feature = "serde", serde(rename = "xhtml"))]
+    Xhtml,
+}
+
+/// Schema that describes which raw HTML to keep when sanitizing.
+///
+/// Used in [`sanitize_html`][CompileOptions::sanitize_html].
+/// Names are compared case-insensitively.
+/// Attributes that start with `on` (event handlers) are always dropped.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::HtmlSchema;
+/// # fn main() {
+///
+/// // Use the default trait to get the schema that GitHub uses:
+/// let github = HtmlSchema::default();
+///
+/// // Also allow `<mark>`, and `class` on `<span>`:
+/// let mut schema = HtmlSchema::github();
+/// schema.tag_names.push("mark".into());
+/// schema.attributes.push(("span".into(), vec!["class".into()]));
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct HtmlSchema {
+    /// Tag names of elements to keep.
+    ///
+    /// Other elements are dropped, but their content is kept.
+    pub tag_names: Vec<String>,
+    /// Tag names of elements to drop, including their content.
+    pub strip: Vec<String>,
+    /// Attributes to keep, as a tag name (or `*` for all elements) and
+    /// attribute names.
+    pub attributes: Vec<(String, Vec<String>)>,
+    /// Protocols to allow in attributes with URLs, as an attribute name and
+    /// protocols.
+    ///
+    /// Values with other protocols are dropped.
+    /// Values without protocol are always allowed.
+    pub protocols: Vec<(String, Vec<String>)>,
+    /// Attributes whose values are prefixed with
+    /// [`clobber_prefix`][HtmlSchema::clobber_prefix], to prevent them from
+    /// clobbering other things on the page.
+    pub clobber: Vec<String>,
+    /// Prefix to use for [`clobber`][HtmlSchema::clobber].
+    pub clobber_prefix: String,
+}
+
+impl Default for HtmlSchema {
+    /// The schema that GitHub uses.
+    fn default() -> Self {
+        Self::github()
+    }
+}
+
+impl HtmlSchema {
+    /// The schema that GitHub uses.
+    ///
+    /// This allows safe elements such as `<details>`, `<kbd>`, `<sub>`, and
+    /// `<sup>`, and safe attributes such as `id` (prefixed with
+    /// `user-content-`), and drops `<script>` and `<style>`.
+    ///
+    /// ## References
+    ///
+    /// *   [`hast-util-sanitize`](https://github.com/syntax-tree/hast-util-sanitize)
+    pub fn github() -> Self {
+        fn strings(values: &[&str]) -> Vec<String> {
+            values.iter().map(|d| (*d).into()).collect()
+        }
+
+        Self {
+            tag_names: strings(&[
+                "a",
+                "b",
+                "blockquote",
+                "br",
+                "code",
+                "dd",
+                "del",
+                "details",
+                "div",
+                "dl",
+                "dt",
+                "em",
+                "h1",
+                "h2",
+                "h3",
+                "h4",
+                "h5",
+                "h6",
+                "hr",
+                "i",
+                "img",
+                "input",
+                "ins",
+                "kbd",
+                "li",
+                "ol",
+                "p",
+                "picture",
+                "pre",
+                "q",
+                "rp",
+                "rt",
+                "ruby",
+                "s",
+                "samp",
+                "section",
+                "source",
+                "span",
+                "strike",
+                "strong",
+                "sub",
+                "summary",
+                "sup",
+                "table",
+                "tbody",
+                "td",
+                "tfoot",
+                "th",
+                "thead",
+                "tr",
+                "tt",
+                "ul",
+                "var",
+            ]),
+            strip: strings(&["script", "style"]),
+            attributes: vec![
+                ("a".into(), strings(&["href"])),
+                ("blockquote".into(), strings(&["cite"])),
+                ("del".into(), strings(&["cite"])),
+                ("div".into(), strings(&["itemscope", "itemtype"])),
+                ("img".into(), strings(&["longdesc", "src"])),
+                ("input".into(), strings(&["checked", "disabled", "type"])),
+                ("ins".into(), strings(&["cite"])),
+                ("q".into(), strings(&["cite"])),
+                ("source".into(), strings(&["srcset"])),
+                (
+                    "*".into(),
+                    strings(&[
+                        "abbr",
+                        "accept",
+                        "accept-charset",
+                        "accesskey",
+                        "action",
+                        "align",
+                        "alt",
+                        "aria-describedby",
+                        "aria-hidden",
+                        "aria-label",
+                        "aria-labelledby",
+                        "axis",
+                        "border",
+                        "cellpadding",
+                        "cellspacing",
+                        "char",
+                        "charoff",
+                        "charset",
+                        "checked",
+                        "clear",
+                        "color",
+                        "cols",
+                        "colspan",
+                        "compact",
+                        "coords",
+                        "datetime",
+                        "dir",
+                        "disabled",
+                        "enctype",
+                        "for",
+                        "frame",
+                        "headers",
+                        "height",
+                        "hreflang",
+                        "hspace",
+                        "id",
+                        "ismap",
+                        "itemprop",
+                        "label",
+                        "lang",
+                        "maxlength",
+                        "media",
+                        "method",
+                        "multiple",
+                        "name",
+                        "nohref",
+                        "noshade",
+                        "nowrap",
+                        "open",
+                        "prompt",
+                        "readonly",
+                        "rev",
+                        "rows",
+                        "rowspan",
+                        "rules",
+                        "scope",
+                        "selected",
+                        "shape",
+                        "size",
+                        "span",
+                        "start",
+                        "summary",
+                        "tabindex",
+                        "title",
+                        "usemap",
+                        "valign",
+                        "value",
+                        "width",
+                    ]),
+                ),
+            ],
+            protocols: vec![
+                ("cite".into(), strings(&["http", "https"])),
+                (
+                    "href".into(),
+                    strings(&["http", "https", "irc", "ircs", "mailto", "xmpp"]),
+                ),
+                ("longdesc".into(), strings(&["http", "https"])),
+                ("src".into(), strings(&["http", "https"])),
+            ],
+            clobber: strings(&["id", "name"]),
+            clobber_prefix: "user-content-".into(),
+        }
+    }
+}
+
+/// Diagram to recognize in fenced code.
+///
+/// Used in [`diagrams`][CompileOptions::diagrams].
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::Diagram;
+/// # fn main() {
+///
+/// // Turn ` ```mermaid ` into `<pre class="mermaid">`, and more:
+/// let mut diagrams = Diagram::defaults();
+///
+/// // Also turn ` ```d2 ` into `<div class="d2">`:
+/// diagrams.push(Diagram {
+///     lang: "d2".into(),
+///     tag_name: "div".into(),
+///     class_name: "d2".into(),
+/// });
+/// # }
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Diagram {
+    /// Language of fenced code (the first word of the info string), such as
+    /// `mermaid`.
+    ///
+    /// Compared case-insensitively.
+    pub lang: String,
+    /// Tag name of the element to use, such as `pre` or `div`.
+    pub tag_name: String,
+    /// Class name(s) of the element, such as `mermaid`.
+    pub class_name: String,
+}
+
+impl Diagram {
+    /// Common diagrams.
+    ///
+    /// This turns Mermaid (`mermaid`) into `<pre class="mermaid">`, which
+    /// is what Mermaid looks for, and Graphviz (`dot`, `graphviz`) and
+    /// `PlantUML` (`plantuml`) into `<div class="diagram language-x">`.
+    pub fn defaults() -> Vec<Self> {
+        let diagram = |lang: &str, tag_name: &str, class_name: &str| Self {
+            lang: lang.into(),
+            tag_name: tag_name.into(),
+            class_name: class_name.into(),
+        };
+
+        vec![
+            diagram("mermaid", "pre", "mermaid"),
+            diagram("dot", "div", "diagram language-dot"),
+            diagram("graphviz", "div", "diagram language-graphviz"),
+            diagram("plantuml", "div", "diagram language-plantuml"),
+        ]
+    }
+}
+
+/// Kind of URL passed to [`RewriteUrl`][].
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum UrlKind {
+    /// URL of a link (`[a](b)`), or of a definition used by a link reference
+    /// (`[a]`).
+    Link,
+    /// URL of an image (`![a](b)`), or of a definition used by an image
+    /// reference (`![a]`).
+    Image,
+    /// URL of an autolink (`<https://a.b>`) or GFM autolink literal
+    /// (`https://a.b`).
+    Autolink,
+}
+
+/// Signature of a function that rewrites URLs.
+///
+/// See [`rewrite_url`][CompileOptions::rewrite_url] in
+/// [`CompileOptions`][].
+pub type RewriteUrl = dyn Fn(&str, &UrlKind, &Position) -> Option<String> + Send + Sync;
+
+/// Signature of a function that highlights code.
+///
+/// See [`highlight`][CompileOptions::highlight] in [`CompileOptions`][].
+pub type Highlight = dyn Fn(Option<&str>, Option<&str>, &str) -> Option<String> + Send + Sync;
+
+/// Signature of a function that turns math into HTML.
+///
+/// See [`math`][CompileOptions::math] in [`CompileOptions`][].
+pub type Math = dyn Fn(&str, bool) -> Option<String> + Send + Sync;
+
+/// Signature of a function that turns diagrams into HTML.
+///
+/// See [`render_diagram`][CompileOptions::render_diagram] in
+/// [`CompileOptions`][].
+pub type RenderDiagram = dyn Fn(&str, &str) -> Option<String> + Send + Sync;
+
+/// Signature of a function that checks whether to stop parsing.
+///
+/// See [`cancel`][ParseOptions::cancel] in [`ParseOptions`][].
+pub type Cancel = dyn Fn() -> bool;
+
 /// Configuration that describes how to parse from markdown.
 ///
 /// You can use this:
@@ -930,6 +2136,7 @@ impl CompileOptions {
 /// # }
 /// ```
 #[allow(clippy::struct_excessive_bools)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct ParseOptions {
     // Note: when adding fields, don’t forget to add them to `fmt::Debug` below.
     /// Which constructs to enable and disable.
@@ -970,6 +2177,53 @@ pub struct ParseOptions {
     /// ```
     pub constructs: Constructs,
 
+    /// Which fences form frontmatter.
+    ///
+    /// This option does nothing if `frontmatter` is not turned on in
+    /// `constructs`.
+    ///
+    /// The default is YAML (`---`) and TOML (`+++`), which can only occur at
+    /// the start of the document.
+    /// Add [`Matter::json()`][Matter::json] to support JSON too.
+    /// The first matter whose opening fence is found is used.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, to_mdast, mdast, Constructs, Matter, Options, ParseOptions};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// let mut frontmatter_matters = ParseOptions::default().frontmatter_matters;
+    /// frontmatter_matters.push(Matter {
+    ///   kind: "meta".into(),
+    ///   open: "~~~meta".into(),
+    ///   close: "~~~".into(),
+    ///   anywhere: true,
+    /// });
+    ///
+    /// let options = ParseOptions {
+    ///   constructs: Constructs {
+    ///     frontmatter: true,
+    ///     ..Constructs::default()
+    ///   },
+    ///   frontmatter_matters,
+    ///   ..ParseOptions::default()
+    /// };
+    ///
+    /// let tree = to_mdast("# Venus\n\n~~~meta\norbit: 225\n~~~", &options)?;
+    ///
+    /// match &tree.children().unwrap()[1] {
+    ///   mdast::Node::Frontmatter(node) => {
+    ///     assert_eq!(node.kind, "meta");
+    ///     assert_eq!(node.value, "orbit: 225");
+    ///   }
+    ///   _ => unreachable!(),
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub frontmatter_matters: Vec<Matter>,
+
     /// Whether to support GFM strikethrough with a single tilde
     ///
     /// This option does nothing if `gfm_strikethrough` is not turned on in
@@ -1081,6 +2335,158 @@ pub struct ParseOptions {
     /// ```
     pub math_text_single_dollar: bool,
 
+    /// Max size of the document, in bytes.
+    ///
+    /// The default is `None`, which allows any size.
+    /// Pass a number to error for bigger documents, before parsing starts,
+    /// which is useful when parsing untrusted markdown.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, Options, ParseOptions};
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "abc",
+    ///         &Options {
+    ///             parse: ParseOptions {
+    ///               size_max: Some(2),
+    ///               ..ParseOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     ),
+    ///     Err("1:1: Unexpected document of 3 bytes, expected at most 2 bytes (markdown-rs:size-max)".into())
+    /// );
+    /// ```
+    pub size_max: Option<usize>,
+
+    /// Max number of nested containers (block quotes, list items, and GFM
+    /// footnote definitions).
+    ///
+    /// The default is `None`, which allows any depth.
+    /// Pass a number to error when containers are nested deeper, which is
+    /// useful when parsing untrusted markdown.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, Options, ParseOptions};
+    ///
+    /// let options = Options {
+    ///     parse: ParseOptions {
+    ///       container_depth_max: Some(2),
+    ///       ..ParseOptions::default()
+    ///     },
+    ///     ..Options::default()
+    /// };
+    ///
+    /// assert!(to_html_with_options("> * a", &options).is_ok());
+    /// assert_eq!(
+    ///     to_html_with_options("> * > a", &options),
+    ///     Err("1:7: Unexpected container nested 3 deep, expected at most 2 nested containers (markdown-rs:container-depth-max)".into())
+    /// );
+    /// ```
+    pub container_depth_max: Option<usize>,
+
+    /// Max number of events, the internal representation of the syntax of
+    /// the document.
+    ///
+    /// The default is `None`, which allows any number.
+    /// Pass a number to error when more events are made, which is useful
+    /// to limit the memory used for untrusted markdown.
+    /// Each construct makes a couple of events, such as 6 for `*a*`.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, Options, ParseOptions};
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "a *b* c",
+    ///         &Options {
+    ///             parse: ParseOptions {
+    ///               events_max: Some(8),
+    ///               ..ParseOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     ),
+    ///     Err("1:7: Unexpected event, expected at most 8 events (markdown-rs:events-max)".into())
+    /// );
+    /// ```
+    pub events_max: Option<usize>,
+
+    /// Max distance, in bytes, to look back from a closing bracket (`]`) for
+    /// its opening bracket (`[` or `![`).
+    ///
+    /// The default is `None`, which allows any distance.
+    /// Pass a number to not form links, images, and footnote calls whose
+    /// label is longer, which is useful to bound the work done for
+    /// untrusted markdown.
+    /// Labels of definitions and references are always capped at 999
+    /// bytes.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, Options, ParseOptions};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// assert_eq!(
+    ///     to_html_with_options(
+    ///         "[a](b) [cdef](g)",
+    ///         &Options {
+    ///             parse: ParseOptions {
+    ///               label_size_max: Some(3),
+    ///               ..ParseOptions::default()
+    ///             },
+    ///             ..Options::default()
+    ///         }
+    ///     )?,
+    ///     "<p><a href=\"b\">a</a> [cdef](g)</p>"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub label_size_max: Option<usize>,
+
+    /// Unit of `column` and `offset` in positions.
+    ///
+    /// The default is [`PositionEncoding::Bytes`][], which matches Rust
+    /// string indices.
+    /// Pass [`PositionEncoding::Utf16`][] for JavaScript and editors (LSP),
+    /// or [`PositionEncoding::Chars`][] for Unicode scalar values.
+    /// Used for positions in the syntax tree from
+    /// [`to_mdast()`][crate::to_mdast()] (and the functions that use it),
+    /// and for the places in error messages.
+    /// Lines are not affected.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_mdast, ParseOptions, PositionEncoding};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// let tree = to_mdast(
+    ///     "😀 *a*",
+    ///     &ParseOptions {
+    ///       position_encoding: PositionEncoding::Utf16,
+    ///       ..ParseOptions::default()
+    ///     }
+    /// )?;
+    ///
+    /// assert_eq!(
+    ///     format!("{:?}", tree.children().unwrap()[0].children().unwrap()[1].position()),
+    ///     "Some(1:4-1:7 (3-6))"
+    /// );
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub position_encoding: PositionEncoding,
+
     /// Function to parse expressions with.
     ///
     /// This function can be used to add support for arbitrary programming
@@ -1091,6 +2497,7 @@ pub struct ParseOptions {
     ///
     /// For an example that adds support for JavaScript with SWC, see
     /// `tests/test_utils/mod.rs`.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub mdx_expression_parse: Option<Box<MdxExpressionParse>>,
 
     /// Function to parse ESM with.
@@ -1107,7 +2514,37 @@ pub struct ParseOptions {
     ///
     /// For an example that adds support for JavaScript with SWC, see
     /// `tests/test_utils/mod.rs`.
+    #[cfg_attr(feature = "serde", serde(skip))]
     pub mdx_esm_parse: Option<Box<MdxEsmParse>>,
+
+    /// Function to check whether to stop parsing.
+    ///
+    /// This function is called every now and then while parsing, and while
+    /// compiling the result.
+    /// When it returns `true`, parsing stops with an error.
+    /// It can be used to stop parsing untrusted markdown after some time,
+    /// or when the result is no longer needed.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_html_with_options, Options, ParseOptions};
+    ///
+    /// let result = to_html_with_options(
+    ///     &"a ".repeat(10_000),
+    ///     &Options {
+    ///         parse: ParseOptions {
+    ///           cancel: Some(Box::new(|| true)),
+    ///           ..ParseOptions::default()
+    ///         },
+    ///         ..Options::default()
+    ///     }
+    /// );
+    ///
+    /// assert!(result.unwrap_err().ends_with("Unexpected cancel, parsing was stopped by `cancel` (markdown-rs:cancel)"));
+    /// ```
+    #[cfg_attr(feature = "serde", serde(skip))]
+    pub cancel: Option<Box<Cancel>>,
     // Note: when adding fields, don’t forget to add them to `fmt::Debug` below.
 }
 
@@ -1115,11 +2552,17 @@ impl fmt::Debug for ParseOptions {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         f.debug_struct("ParseOptions")
             .field("constructs", &self.constructs)
+            .field("frontmatter_matters", &self.frontmatter_matters)
             .field(
                 "gfm_strikethrough_single_tilde",
                 &self.gfm_strikethrough_single_tilde,
             )
             .field("math_text_single_dollar", &self.math_text_single_dollar)
+            .field("size_max", &self.size_max)
+            .field("container_depth_max", &self.container_depth_max)
+            .field("events_max", &self.events_max)
+            .field("label_size_max", &self.label_size_max)
+            .field("position_encoding", &self.position_encoding)
             .field(
                 "mdx_expression_parse",
                 &self.mdx_expression_parse.as_ref().map(|_d| "[Function]"),
@@ -1128,6 +2571,7 @@ impl fmt::Debug for ParseOptions {
                 "mdx_esm_parse",
                 &self.mdx_esm_parse.as_ref().map(|_d| "[Function]"),
             )
+            .field("cancel", &self.cancel.as_ref().map(|_d| "[Function]"))
             .finish()
     }
 }
@@ -1137,10 +2581,17 @@ impl Default for ParseOptions {
     fn default() -> Self {
         Self {
             constructs: Constructs::default(),
+            frontmatter_matters: vec![Matter::new("yaml", "---"), Matter::new("toml", "+++")],
             gfm_strikethrough_single_tilde: true,
             math_text_single_dollar: true,
+            size_max: None,
+            container_depth_max: None,
+            events_max: None,
+            label_size_max: None,
+            position_encoding: PositionEncoding::default(),
             mdx_expression_parse: None,
             mdx_esm_parse: None,
+            cancel: None,
         }
     }
 }
@@ -1208,6 +2659,7 @@ impl ParseOptions {
 /// ```
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct Options {
     /// Configuration that describes how to parse from markdown.
     pub parse: ParseOptions,
@@ -1333,7 +2785,7 @@ mod tests {
 
         assert_eq!(
             format!("{:?}", ParseOptions::default()),
-            "ParseOptions { constructs: Constructs { attention: true, autolink: true, block_quote: true, character_escape: true, character_reference: true, code_indented: true, code_fenced: true, code_text: true, definition: true, frontmatter: false, gfm_autolink_literal: false, gfm_footnote_definition: false, gfm_label_start_footnote: false, gfm_strikethrough: false, gfm_table: false, gfm_task_list_item: false, hard_break_escape: true, hard_break_trailing: true, heading_atx: true, heading_setext: true, html_flow: true, html_text: true, label_start_image: true, label_start_link: true, label_end: true, list_item: true, math_flow: false, math_text: false, mdx_esm: false, mdx_expression_flow: false, mdx_expression_text: false, mdx_jsx_flow: false, mdx_jsx_text: false, thematic_break: true }, gfm_strikethrough_single_tilde: true, math_text_single_dollar: true, mdx_expression_parse: None, mdx_esm_parse: None }",
+            "ParseOptions { constructs: Constructs { attention: true, autolink: true, block_quote: true, character_escape: true, character_reference: true, code_indented: true, code_fenced: true, code_text: true, definition: true, frontmatter: false, gfm_autolink_literal: false, gfm_footnote_definition: false, gfm_label_start_footnote: false, gfm_strikethrough: false, gfm_table: false, gfm_task_list_item: false, hard_break_escape: true, hard_break_trailing: true, heading_atx: true, heading_setext: true, html_flow: true, html_text: true, label_start_image: true, label_start_link: true, label_end: true, list_item: true, math_flow: false, math_text: false, mdx_esm: false, mdx_expression_flow: false, mdx_expression_text: false, mdx_jsx_flow: false, mdx_jsx_text: false, thematic_break: true }, frontmatter_matters: [Matter { kind: \"yaml\", open: \"---\", close: \"---\", anywhere: false }, Matter { kind: \"toml\", open: \"+++\", close: \"+++\", anywhere: false }], gfm_strikethrough_single_tilde: true, math_text_single_dollar: true, size_max: None, container_depth_max: None, events_max: None, label_size_max: None, position_encoding: Bytes, mdx_expression_parse: None, mdx_esm_parse: None, cancel: None }",
             "should support `Debug` trait"
         );
         assert_eq!(
@@ -1346,7 +2798,7 @@ mod tests {
                 })),
                 ..Default::default()
             }),
-            "ParseOptions { constructs: Constructs { attention: true, autolink: true, block_quote: true, character_escape: true, character_reference: true, code_indented: true, code_fenced: true, code_text: true, definition: true, frontmatter: false, gfm_autolink_literal: false, gfm_footnote_definition: false, gfm_label_start_footnote: false, gfm_strikethrough: false, gfm_table: false, gfm_task_list_item: false, hard_break_escape: true, hard_break_trailing: true, heading_atx: true, heading_setext: true, html_flow: true, html_text: true, label_start_image: true, label_start_link: true, label_end: true, list_item: true, math_flow: false, math_text: false, mdx_esm: false, mdx_expression_flow: false, mdx_expression_text: false, mdx_jsx_flow: false, mdx_jsx_text: false, thematic_break: true }, gfm_strikethrough_single_tilde: true, math_text_single_dollar: true, mdx_expression_parse: Some(\"[Function]\"), mdx_esm_parse: Some(\"[Function]\") }",
+            "ParseOptions { constructs: Constructs { attention: true, autolink: true, block_quote: true, character_escape: true, character_reference: true, code_indented: true, code_fenced: true, code_text: true, definition: true, frontmatter: false, gfm_autolink_literal: false, gfm_footnote_definition: false, gfm_label_start_footnote: false, gfm_strikethrough: false, gfm_table: false, gfm_task_list_item: false, hard_break_escape: true, hard_break_trailing: true, heading_atx: true, heading_setext: true, html_flow: true, html_text: true, label_start_image: true, label_start_link: true, label_end: true, list_item: true, math_flow: false, math_text: false, mdx_esm: false, mdx_expression_flow: false, mdx_expression_text: false, mdx_jsx_flow: false, mdx_jsx_text: false, thematic_break: true }, frontmatter_matters: [Matter { kind: \"yaml\", open: \"---\", close: \"---\", anywhere: false }, Matter { kind: \"toml\", open: \"+++\", close: \"+++\", anywhere: false }], gfm_strikethrough_single_tilde: true, math_text_single_dollar: true, size_max: None, container_depth_max: None, events_max: None, label_size_max: None, position_encoding: Bytes, mdx_expression_parse: Some(\"[Function]\"), mdx_esm_parse: Some(\"[Function]\"), cancel: None }",
             "should support `Debug` trait on mdx functions"
         );
     }
@@ -1377,6 +2829,14 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_compile_options_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+
+        // Checked when compiling: options can be shared between threads.
+        assert_send_sync::<CompileOptions>();
+    }
+
     #[test]
     fn test_options() {
         Options::default();
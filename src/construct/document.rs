@@ -14,7 +14,7 @@ use crate::state::{Name as StateName, State};
 use crate::subtokenize::divide_events;
 use crate::tokenizer::{Container, ContainerState, Tokenizer};
 use crate::util::skip;
-use alloc::{boxed::Box, string::String, vec::Vec};
+use alloc::{boxed::Box, format, string::String, vec::Vec};
 
 /// Phases where we can exit containers.
 #[derive(Debug, PartialEq)]
@@ -253,6 +253,17 @@ pub fn container_new_before_not_footnote_definition(tokenizer: &mut Tokenizer) -
 ///       ^
 /// ```
 pub fn container_new_after(tokenizer: &mut Tokenizer) -> State {
+    if let Some(max) = tokenizer.parse_state.options.container_depth_max {
+        let depth = tokenizer.tokenize_state.document_continued + 1;
+
+        if depth > max {
+            return State::Error(format!(
+                "{}:{}: Unexpected container nested {} deep, expected at most {} nested containers (markdown-rs:container-depth-max)",
+                tokenizer.point.line, tokenizer.point.column, depth, max
+            ));
+        }
+    }
+
     // It was a new block quote, list item, or footnote definition.
     // Swap the new container (in the middle) with the existing one (at the end).
     // Take the new container.
@@ -374,6 +385,11 @@ pub fn flow_end(tokenizer: &mut Tokenizer) -> State {
         state,
     );
 
+    // Stop when flow errors, such as when parsing is canceled.
+    if let State::Error(message) = state {
+        return State::Error(message);
+    }
+
     tokenizer.tokenize_state.document_child_state = Some(state);
 
     // If we’re in a lazy line, and the previous (lazy or not) line is something
@@ -12,6 +12,8 @@
 //!
 //! *   [Blank line][crate::construct::blank_line]
 //! *   [Code (indented)][crate::construct::code_indented]
+//! *   [Frontmatter][crate::construct::frontmatter] (only matters allowed
+//!     anywhere)
 //! *   [Heading (atx)][crate::construct::heading_atx]
 //! *   [Heading (setext)][crate::construct::heading_setext]
 //! *   [HTML (flow)][crate::construct::html_flow]
@@ -36,6 +38,32 @@ use crate::tokenizer::Tokenizer;
 ///     ^
 /// ```
 pub fn start(tokenizer: &mut Tokenizer) -> State {
+    // Frontmatter fences can start with anything, so they go first.
+    if tokenizer.parse_state.options.constructs.frontmatter
+        && tokenizer
+            .parse_state
+            .options
+            .frontmatter_matters
+            .iter()
+            .any(|d| d.anywhere)
+    {
+        tokenizer.attempt(
+            State::Next(StateName::FlowAfter),
+            State::Next(StateName::FlowBeforeConstruct),
+        );
+        State::Retry(StateName::FrontmatterAnywhereStart)
+    } else {
+        State::Retry(StateName::FlowBeforeConstruct)
+    }
+}
+
+/// At a construct other than frontmatter.
+///
+/// ```markdown
+/// > | ## alpha
+///     ^
+/// ```
+pub fn before_construct(tokenizer: &mut Tokenizer) -> State {
     match tokenizer.current {
         Some(b'#') => {
             tokenizer.attempt(
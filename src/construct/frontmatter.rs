@@ -1,5 +1,10 @@
 //! Frontmatter occurs at the start of the document.
 //!
+//! Which fences are used for which kinds of frontmatter is configured with
+//! [`frontmatter_matters`][crate::ParseOptions::frontmatter_matters].
+//! Matters that are allowed *anywhere* can also occur in [flow][] (including
+//! in containers), but they cannot interrupt a paragraph.
+//!
 //! ## Grammar
 //!
 //! Frontmatter forms with the following BNF
@@ -8,13 +13,27 @@
 //! ```bnf
 //! frontmatter ::= fence_open *( eol *byte ) eol fence_close
 //! fence_open ::= sequence *space_or_tab
-//! ; Restriction: markers in `sequence` must match markers in opening sequence.
 //! fence_close ::= sequence *space_or_tab
-//! sequence ::= 3'+' | 3'-'
+//! ; Restriction: `sequence` in `fence_open` must be the opening fence of a
+//! ; matter, and `sequence` in `fence_close` the closing fence of that matter.
 //! ```
 //!
-//! Frontmatter can only occur once.
-//! It cannot occur in a container.
+//! By default, the following matters are supported:
+//!
+//! | Kind   | Opening fence | Closing fence |
+//! | ------ | ------------- | ------------- |
+//! | `yaml` | `---`         | `---`         |
+//! | `toml` | `+++`         | `+++`         |
+//!
+//! [`Matter::json()`][crate::Matter::json] adds the following matters:
+//!
+//! | Kind   | Opening fence | Closing fence |
+//! | ------ | ------------- | ------------- |
+//! | `json` | `;;;`         | `;;;`         |
+//! | `json` | `{`           | `}`           |
+//!
+//! Frontmatter at the start of the document can only occur once.
+//! Unless its matter is allowed anywhere, it cannot occur in a container.
 //! It must have a closing fence.
 //! Like flow constructs, it must be followed by an eol (line ending) or
 //! eof (end of file).
@@ -29,6 +48,13 @@
 //! As there is no spec for frontmatter in markdown, this extension follows how
 //! YAML frontmatter works on `github.com`.
 //! It also parses TOML frontmatter, just like YAML except that it uses a `+`.
+//! JSON frontmatter can be turned on too, either fenced with `;;;`, or as an
+//! object whose opening brace (`{`) and closing brace (`}`) are each on their
+//! own line.
+//! When the fences are exactly `{` and `}`, they are part of the value.
+//! As a brace on its own line would otherwise start an expression in MDX,
+//! fences starting with `{` are not supported when
+//! [`mdx_expression_flow`][crate::Constructs::mdx_expression_flow] is on.
 //!
 //! ## Recommendation
 //!
@@ -55,14 +81,14 @@
 //! *   [`micromark-extension-frontmatter`](https://github.com/micromark/micromark-extension-frontmatter)
 //!
 //! [constructs]: crate::Constructs
+//! [flow]: crate::construct::flow
 
 use crate::construct::partial_space_or_tab::space_or_tab;
 use crate::event::Name;
 use crate::state::{Name as StateName, State};
 use crate::tokenizer::Tokenizer;
-use crate::util::constant::FRONTMATTER_SEQUENCE_SIZE;
 
-/// Start of frontmatter.
+/// Start of frontmatter, at the start of the document.
 ///
 /// ```markdown
 /// > | ---
@@ -72,17 +98,36 @@ use crate::util::constant::FRONTMATTER_SEQUENCE_SIZE;
 /// ```
 pub fn start(tokenizer: &mut Tokenizer) -> State {
     // Indent not allowed.
+    if tokenizer.parse_state.options.constructs.frontmatter {
+        if let Some(index) = matter_at(tokenizer, false) {
+            return open(tokenizer, index);
+        }
+    }
+
+    State::Nok
+}
+
+/// Start of frontmatter, in flow.
+///
+/// ```markdown
+///   | # Venus
+/// > | ~~~meta
+///     ^
+///   | title: "Venus"
+///   | ~~~
+/// ```
+pub fn anywhere_start(tokenizer: &mut Tokenizer) -> State {
+    // Do not interrupt paragraphs.
     if tokenizer.parse_state.options.constructs.frontmatter
-        && matches!(tokenizer.current, Some(b'+' | b'-'))
+        && !tokenizer.interrupt
+        && !tokenizer.lazy
     {
-        tokenizer.tokenize_state.marker = tokenizer.current.unwrap();
-        tokenizer.enter(Name::Frontmatter);
-        tokenizer.enter(Name::FrontmatterFence);
-        tokenizer.enter(Name::FrontmatterSequence);
-        State::Retry(StateName::FrontmatterOpenSequence)
-    } else {
-        State::Nok
+        if let Some(index) = matter_at(tokenizer, true) {
+            return open(tokenizer, index);
+        }
     }
+
+    State::Nok
 }
 
 /// In open sequence.
@@ -94,11 +139,20 @@ pub fn start(tokenizer: &mut Tokenizer) -> State {
 ///   | ---
 /// ```
 pub fn open_sequence(tokenizer: &mut Tokenizer) -> State {
-    if tokenizer.current == Some(tokenizer.tokenize_state.marker) {
+    let fence = tokenizer.parse_state.options.frontmatter_matters[tokenizer.tokenize_state.size_b]
+        .open
+        .as_bytes();
+
+    if tokenizer.tokenize_state.size < fence.len() {
+        debug_assert_eq!(
+            tokenizer.current,
+            Some(fence[tokenizer.tokenize_state.size]),
+            "expected fence"
+        );
         tokenizer.tokenize_state.size += 1;
         tokenizer.consume();
         State::Next(StateName::FrontmatterOpenSequence)
-    } else if tokenizer.tokenize_state.size == FRONTMATTER_SEQUENCE_SIZE {
+    } else {
         tokenizer.tokenize_state.size = 0;
         tokenizer.exit(Name::FrontmatterSequence);
 
@@ -108,10 +162,6 @@ pub fn open_sequence(tokenizer: &mut Tokenizer) -> State {
         } else {
             State::Retry(StateName::FrontmatterOpenAfter)
         }
-    } else {
-        tokenizer.tokenize_state.marker = 0;
-        tokenizer.tokenize_state.size = 0;
-        State::Nok
     }
 }
 
@@ -124,20 +174,29 @@ pub fn open_sequence(tokenizer: &mut Tokenizer) -> State {
 ///   | ---
 /// ```
 pub fn open_after(tokenizer: &mut Tokenizer) -> State {
-    if let Some(b'\n') = tokenizer.current {
-        tokenizer.exit(Name::FrontmatterFence);
-        tokenizer.enter(Name::LineEnding);
-        tokenizer.consume();
-        tokenizer.exit(Name::LineEnding);
-        tokenizer.attempt(
-            State::Next(StateName::FrontmatterAfter),
-            State::Next(StateName::FrontmatterContentStart),
-        );
-        State::Next(StateName::FrontmatterCloseStart)
-    } else {
-        tokenizer.tokenize_state.marker = 0;
-        State::Nok
-    }
+    debug_assert_eq!(tokenizer.current, Some(b'\n'), "expected eol");
+    tokenizer.exit(Name::FrontmatterFence);
+    tokenizer.enter(Name::LineEnding);
+    tokenizer.consume();
+    tokenizer.exit(Name::LineEnding);
+    State::Next(StateName::FrontmatterLineStart)
+}
+
+/// At the start of a line, which is either a closing fence or content.
+///
+/// ```markdown
+///   | ---
+/// > | title: "Venus"
+///     ^
+/// > | ---
+///     ^
+/// ```
+pub fn line_start(tokenizer: &mut Tokenizer) -> State {
+    tokenizer.attempt(
+        State::Next(StateName::FrontmatterAfter),
+        State::Next(StateName::FrontmatterContentStart),
+    );
+    State::Retry(StateName::FrontmatterCloseStart)
 }
 
 /// Start of close sequence.
@@ -149,7 +208,11 @@ pub fn open_after(tokenizer: &mut Tokenizer) -> State {
 ///     ^
 /// ```
 pub fn close_start(tokenizer: &mut Tokenizer) -> State {
-    if tokenizer.current == Some(tokenizer.tokenize_state.marker) {
+    let fence = tokenizer.parse_state.options.frontmatter_matters[tokenizer.tokenize_state.size_b]
+        .close
+        .as_bytes();
+
+    if !tokenizer.lazy && fence_at(tokenizer.parse_state.bytes, tokenizer.point.index, fence) {
         tokenizer.enter(Name::FrontmatterFence);
         tokenizer.enter(Name::FrontmatterSequence);
         State::Retry(StateName::FrontmatterCloseSequence)
@@ -167,11 +230,20 @@ pub fn close_start(tokenizer: &mut Tokenizer) -> State {
 ///     ^
 /// ```
 pub fn close_sequence(tokenizer: &mut Tokenizer) -> State {
-    if tokenizer.current == Some(tokenizer.tokenize_state.marker) {
+    let fence = tokenizer.parse_state.options.frontmatter_matters[tokenizer.tokenize_state.size_b]
+        .close
+        .as_bytes();
+
+    if tokenizer.tokenize_state.size < fence.len() {
+        debug_assert_eq!(
+            tokenizer.current,
+            Some(fence[tokenizer.tokenize_state.size]),
+            "expected fence"
+        );
         tokenizer.tokenize_state.size += 1;
         tokenizer.consume();
         State::Next(StateName::FrontmatterCloseSequence)
-    } else if tokenizer.tokenize_state.size == FRONTMATTER_SEQUENCE_SIZE {
+    } else {
         tokenizer.tokenize_state.size = 0;
         tokenizer.exit(Name::FrontmatterSequence);
 
@@ -181,9 +253,6 @@ pub fn close_sequence(tokenizer: &mut Tokenizer) -> State {
         } else {
             State::Retry(StateName::FrontmatterCloseAfter)
         }
-    } else {
-        tokenizer.tokenize_state.size = 0;
-        State::Nok
     }
 }
 
@@ -196,13 +265,12 @@ pub fn close_sequence(tokenizer: &mut Tokenizer) -> State {
 ///        ^
 /// ```
 pub fn close_after(tokenizer: &mut Tokenizer) -> State {
-    match tokenizer.current {
-        None | Some(b'\n') => {
-            tokenizer.exit(Name::FrontmatterFence);
-            State::Ok
-        }
-        _ => State::Nok,
-    }
+    debug_assert!(
+        matches!(tokenizer.current, None | Some(b'\n')),
+        "expected eol/eof"
+    );
+    tokenizer.exit(Name::FrontmatterFence);
+    State::Ok
 }
 
 /// Start of content chunk.
@@ -214,6 +282,11 @@ pub fn close_after(tokenizer: &mut Tokenizer) -> State {
 ///   | ---
 /// ```
 pub fn content_start(tokenizer: &mut Tokenizer) -> State {
+    if tokenizer.lazy {
+        tokenizer.tokenize_state.size_b = 0;
+        return State::Nok;
+    }
+
     match tokenizer.current {
         None | Some(b'\n') => State::Retry(StateName::FrontmatterContentEnd),
         Some(_) => {
@@ -255,18 +328,14 @@ pub fn content_inside(tokenizer: &mut Tokenizer) -> State {
 pub fn content_end(tokenizer: &mut Tokenizer) -> State {
     match tokenizer.current {
         None => {
-            tokenizer.tokenize_state.marker = 0;
+            tokenizer.tokenize_state.size_b = 0;
             State::Nok
         }
         Some(b'\n') => {
             tokenizer.enter(Name::LineEnding);
             tokenizer.consume();
             tokenizer.exit(Name::LineEnding);
-            tokenizer.attempt(
-                State::Next(StateName::FrontmatterAfter),
-                State::Next(StateName::FrontmatterContentStart),
-            );
-            State::Next(StateName::FrontmatterCloseStart)
+            State::Next(StateName::FrontmatterLineStart)
         }
         Some(_) => unreachable!("expected eof/eol"),
     }
@@ -285,6 +354,58 @@ pub fn after(tokenizer: &mut Tokenizer) -> State {
         matches!(tokenizer.current, None | Some(b'\n')),
         "expected eol/eof after closing fence"
     );
+    tokenizer.tokenize_state.size_b = 0;
     tokenizer.exit(Name::Frontmatter);
     State::Ok
 }
+
+/// At the opening fence of the matter at `index`.
+///
+/// ```markdown
+/// > | ---
+///     ^
+///   | title: "Venus"
+///   | ---
+/// ```
+fn open(tokenizer: &mut Tokenizer, index: usize) -> State {
+    tokenizer.tokenize_state.size_b = index;
+    tokenizer.enter(Name::Frontmatter);
+    tokenizer.enter(Name::FrontmatterFence);
+    tokenizer.enter(Name::FrontmatterSequence);
+    State::Retry(StateName::FrontmatterOpenSequence)
+}
+
+/// Find the first matter whose opening fence is at the current place.
+///
+/// When not at the start of the document (`anywhere` is `true`), only matters
+/// that are allowed anywhere are checked.
+fn matter_at(tokenizer: &Tokenizer, anywhere: bool) -> Option<usize> {
+    let options = tokenizer.parse_state.options;
+    let bytes = tokenizer.parse_state.bytes;
+    let index = tokenizer.point.index;
+
+    options.frontmatter_matters.iter().position(|matter| {
+        (matter.anywhere || !anywhere)
+            && !(options.constructs.mdx_expression_flow && matter.open.starts_with('{'))
+            && fence_at(bytes, index, matter.open.as_bytes())
+            // There must be a line after the opening fence.
+            && bytes[index + matter.open.len()..]
+                .iter()
+                .find(|d| !matches!(d, b'\t' | b' '))
+                .map_or(false, |d| matches!(d, b'\n' | b'\r'))
+    })
+}
+
+/// Check if `fence` is at `index` in `bytes`, followed by only whitespace on
+/// its line.
+fn fence_at(bytes: &[u8], index: usize, fence: &[u8]) -> bool {
+    !fence.is_empty()
+        && !fence
+            .iter()
+            .any(|d| matches!(d, b'\t' | b'\n' | b'\r' | b' '))
+        && bytes[index..].starts_with(fence)
+        && bytes[index + fence.len()..]
+            .iter()
+            .find(|d| !matches!(d, b'\t' | b' '))
+            .map_or(true, |d| matches!(d, b'\n' | b'\r'))
+}
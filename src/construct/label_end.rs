@@ -225,6 +225,15 @@ pub fn start(tokenizer: &mut Tokenizer) -> State {
                 return State::Retry(StateName::LabelEndNok);
             }
 
+            // If the label is too big, it’s also just brackets.
+            if let Some(max) = tokenizer.parse_state.options.label_size_max {
+                let start_index = tokenizer.events[label_start.start.1].point.index;
+
+                if tokenizer.point.index - start_index > max {
+                    return State::Retry(StateName::LabelEndNok);
+                }
+            }
+
             tokenizer.enter(Name::LabelEnd);
             tokenizer.enter(Name::LabelMarker);
             tokenizer.consume();
@@ -71,6 +71,8 @@
 //! For that, the math is compiled as a `<pre>`, and a `<code>` element with two
 //! classes: `language-math` and `math-display`.
 //! Client side JavaScript can look for these classes to process them further.
+//! To render math when compiling instead, pass
+//! [`math`][crate::CompileOptions::math].
 //!
 //! The `info` is, when rendering to HTML, typically exposed as a class.
 //! This behavior stems from the HTML spec ([*§ 4.5.15 The `code`
@@ -75,6 +75,8 @@
 //! For that, the math is compiled as a `<code>` element with two classes:
 //! `language-math` and `math-inline`.
 //! Client side JavaScript can look for these classes to process them further.
+//! To render math when compiling instead, pass
+//! [`math`][crate::CompileOptions::math].
 //!
 //! When turning markdown into HTML, each line ending in raw (text) is turned
 //! into a space.
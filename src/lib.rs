@@ -9,18 +9,74 @@
 //!     — like `to_html` but lets you configure how markdown is turned into
 //!     HTML, such as allowing dangerous HTML or turning on/off different
 //!     constructs (GFM, MDX, and the like)
+//! *   [`to_html_write()`][]
+//!     — like `to_html_with_options` but writes HTML to an output while it
+//!     is generated, instead of building one big string
+//! *   [`to_html_document()`][]
+//!     — like `to_html_with_options` but makes a complete HTML page, with a
+//!     title from frontmatter or the first heading
 //! *   [`to_mdast()`][]
 //!     — turn markdown into a syntax tree
+//! *   [`to_mdast_json()`][]
+//!     — turn markdown into a syntax tree as JSON, the same as mdast in
+//!     JavaScript
+//! *   [`sanitize_mdast()`][]
+//!     — drop URLs with dangerous protocols from a syntax tree, like
+//!     `to_html` does
+//! *   [`resolve_url()`][]
+//!     — resolve a URL against a base URL, useful with
+//!     [`CompileOptions::rewrite_url`][]
+//! *   [`to_mdast_reparse()`][]
+//!     — turn an edited document into a syntax tree, reusing the previous
+//!     tree (useful in editors)
+//! *   [`to_frontmatter()`][]
+//!     — turn frontmatter in markdown into structured data
+//! *   [`lint()`][]
+//!     — check markdown for style problems, such as skipped heading ranks
+//! *   [`format()`][]
+//!     — format markdown in a consistent style (like Prettier)
+//! *   [`to_markdown()`][]
+//!     — turn a syntax tree into markdown
+//! *   [`language_server()`][]
+//!     — run a language server (LSP) for markdown, for editors
 //!
 //! ## Features
 //!
 //! *   **`default`**
 //!     — nothing is enabled by default
+//! *   **`std`**
+//!     — enable [`to_html_write_io()`][] to write HTML to
+//!     [`std::io::Write`][]
 //! *   **`serde`**
-//!     — enable serde to serialize the AST (includes `dep:serde`)
+//!     — enable serde to serialize the AST, and to serialize and
+//!     deserialize [`Options`][] (such as from a config file, with presets
+//!     like `"gfm"` and partial overrides) (includes `dep:serde`)
+//! *   **`json`**
+//!     — enable [`to_mdast_json()`][] and [`mdast::Node::from_json()`][] to
+//!     turn the AST into JSON that JavaScript understands, and back
+//! *   **`lsp`**
+//!     — enable [`language_server()`][] and the `markdown-lsp` binary
+//!     (includes `std` and `json`)
+//! *   **`capi`**
+//!     — enable the [C API][capi], to use `markdown-rs` from C and other
+//!     languages (includes `std` and `json`)
+//! *   **`wasm`**
+//!     — enable the [WebAssembly API][wasm], to use `markdown-rs` from
+//!     JavaScript (includes `std`, `json`, `dep:wasm-bindgen`, and
+//!     `dep:js-sys`)
+//! *   **`highlight`**
+//!     — enable [`highlight()`][], a small built-in syntax highlighter to
+//!     use with [`CompileOptions::highlight`][]
+//! *   **`mathml`**
+//!     — enable [`to_mathml()`][], to turn a subset of LaTeX into `MathML`,
+//!     to use with [`CompileOptions::math`][]
 //! *   **`log`**
 //!     — enable logging (includes `dep:log`);
 //!     you can show logs with `RUST_LOG=debug`
+//! *   **`toml`**
+//!     — enable turning TOML frontmatter into data (includes `dep:toml`)
+//! *   **`yaml`**
+//!     — enable turning YAML frontmatter into data (includes `dep:yaml-rust2`)
 
 #![no_std]
 #![deny(clippy::pedantic)]
@@ -33,20 +89,33 @@
 )]
 
 extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
 mod configuration;
 mod construct;
 mod event;
+mod lint;
+#[cfg(feature = "lsp")]
+mod lsp;
 mod parser;
+mod render;
+mod reparse;
 mod resolve;
 mod state;
 mod subtokenize;
 mod to_html;
+mod to_html_document;
+mod to_markdown;
 mod to_mdast;
 mod tokenizer;
 mod util;
 
+#[cfg(feature = "capi")]
+pub mod capi;
 pub mod mdast; // To do: externalize?
 pub mod unist; // To do: externalize.
+#[cfg(feature = "wasm")]
+pub mod wasm;
 
 #[doc(hidden)]
 pub use util::identifier::{id_cont, id_start};
@@ -54,19 +123,46 @@ pub use util::identifier::{id_cont, id_start};
 #[doc(hidden)]
 pub use util::sanitize_uri::sanitize;
 
+pub use util::resolve_url::resolve_url;
+
+#[cfg(feature = "highlight")]
+pub use util::highlight::highlight;
+
+#[cfg(feature = "mathml")]
+pub use util::mathml::to_mathml;
+
 #[doc(hidden)]
 pub use util::location::Location;
 
+pub use util::frontmatter::Value as FrontmatterValue;
+
 pub use util::line_ending::LineEnding;
 
+pub use reparse::{Edit, Reparse};
+
+pub use render::{DefaultRender, Render};
+
+pub use to_html_document::DocumentOptions;
+
+pub use lint::{EmphasisMarker, LintMessage, LintOptions, LintRules, ListItemIndent};
+
+pub use to_markdown::{
+    AttentionMarker, BulletMarker, FenceMarker, FormatOptions, HeadingStyle, ListNumbering,
+};
+
 pub use util::mdx::{
     EsmParse as MdxEsmParse, ExpressionKind as MdxExpressionKind,
     ExpressionParse as MdxExpressionParse, Signal as MdxSignal,
 };
 
-pub use configuration::{CompileOptions, Constructs, Options, ParseOptions};
+pub use configuration::{
+    Cancel, CompileOptions, Constructs, Diagram, Highlight, HtmlSchema, HtmlSyntax, Math, Matter,
+    Options, ParseOptions, PositionEncoding, RenderDiagram, RewriteUrl, UrlKind,
+};
 
-use alloc::string::String;
+use alloc::{string::String, vec::Vec};
+use core::fmt;
+use util::location::Encoder;
 
 /// Turn markdown into HTML.
 ///
@@ -121,12 +217,180 @@ pub fn to_html(value: &str) -> String {
 /// # }
 /// ```
 pub fn to_html_with_options(value: &str, options: &Options) -> Result<String, String> {
-    let (events, parse_state) = parser::parse(value, &options.parse)?;
-    Ok(to_html::compile(
-        &events,
-        parse_state.bytes,
-        &options.compile,
-    ))
+    parser::parse(value, &options.parse)
+        .and_then(|(events, parse_state)| {
+            to_html::compile(
+                &events,
+                parse_state.bytes,
+                &options.compile,
+                options.parse.cancel.as_deref(),
+            )
+        })
+        .map_err(|message| {
+            Encoder::new(value.as_bytes(), options.parse.position_encoding).message(&message)
+        })
+}
+
+/// Turn markdown into HTML, with configuration, writing it to `output`.
+///
+/// Compiles the same HTML as [`to_html_with_options()`][], but writes it to
+/// `output` block by block while it is generated.
+/// This lets you start sending big documents early, and keeps their HTML
+/// from being built as one big string.
+///
+/// ## Errors
+///
+/// Errors like [`to_html_with_options()`][] for syntax errors in MDX.
+/// Also errors if `output` errors.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{to_html_write, Options};
+/// # fn main() -> Result<(), String> {
+///
+/// let mut result = String::new();
+///
+/// to_html_write("# Hello, world!", &Options::default(), &mut result)?;
+///
+/// assert_eq!(result, "<h1>Hello, world!</h1>");
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_html_write(
+    value: &str,
+    options: &Options,
+    output: &mut impl fmt::Write,
+) -> Result<(), String> {
+    parser::parse(value, &options.parse)
+        .and_then(|(events, parse_state)| {
+            to_html::write(
+                &events,
+                parse_state.bytes,
+                &options.compile,
+                options.parse.cancel.as_deref(),
+                output,
+            )
+        })
+        .map_err(|message| {
+            Encoder::new(value.as_bytes(), options.parse.position_encoding).message(&message)
+        })
+}
+
+/// Turn markdown into HTML, with configuration, writing it to `output`.
+///
+/// Like [`to_html_write()`][], but for [`std::io::Write`][], such as files,
+/// sockets, or stdout.
+/// Needs the `std` feature.
+///
+/// ## Errors
+///
+/// Errors like [`to_html_with_options()`][] for syntax errors in MDX.
+/// Also errors if `output` errors, with the message of that error.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{to_html_write_io, Options};
+/// # fn main() -> Result<(), String> {
+///
+/// let mut result = vec![];
+///
+/// to_html_write_io("# Hello, world!", &Options::default(), &mut result)?;
+///
+/// assert_eq!(result, b"<h1>Hello, world!</h1>");
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "std")]
+pub fn to_html_write_io(
+    value: &str,
+    options: &Options,
+    output: &mut impl std::io::Write,
+) -> Result<(), String> {
+    /// Adapter from `io::Write` to `fmt::Write`, which keeps the error.
+    struct Adapter<'a, W: std::io::Write> {
+        inner: &'a mut W,
+        error: Option<std::io::Error>,
+    }
+
+    impl<W: std::io::Write> fmt::Write for Adapter<'_, W> {
+        fn write_str(&mut self, value: &str) -> fmt::Result {
+            self.inner.write_all(value.as_bytes()).map_err(|error| {
+                self.error = Some(error);
+                fmt::Error
+            })
+        }
+    }
+
+    let mut adapter = Adapter {
+        inner: output,
+        error: None,
+    };
+
+    match to_html_write(value, options, &mut adapter) {
+        Err(_) if adapter.error.is_some() => Err(alloc::format!(
+            "Cannot write HTML: {}",
+            adapter.error.unwrap()
+        )),
+        result => result,
+    }
+}
+
+/// Turn markdown into a complete HTML document.
+///
+/// Compiles the body like [`to_html_with_options()`][], and wraps it in a
+/// page with `<!doctype html>`, a `lang` attribute, `<meta charset>`, a
+/// `<title>`, and links to stylesheets (see [`DocumentOptions`][]).
+/// The title is the `title` field in frontmatter, if it is a string, or
+/// otherwise the text content of the first heading.
+/// Frontmatter that cannot be parsed is ignored.
+/// There is no `<title>` if there is neither.
+///
+/// ## Errors
+///
+/// Errors like [`to_html_with_options()`][] for syntax errors in MDX.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{to_html_document, Constructs, DocumentOptions, Matter, ParseOptions};
+/// # fn main() -> Result<(), String> {
+///
+/// assert_eq!(
+///     to_html_document("# Hello, *world*!", &DocumentOptions::default())?,
+///     "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n<title>Hello, world!</title>\n</head>\n<body>\n<h1>Hello, <em>world</em>!</h1>\n</body>\n</html>\n"
+/// );
+///
+/// // Turn on frontmatter to take the title from it:
+/// let options = DocumentOptions {
+///     parse: ParseOptions {
+///         constructs: Constructs {
+///             frontmatter: true,
+///             ..Constructs::default()
+///         },
+///         frontmatter_matters: Matter::json(),
+///         ..ParseOptions::default()
+///     },
+///     stylesheets: vec!["style.css".into()],
+///     ..DocumentOptions::default()
+/// };
+///
+/// assert_eq!(
+///     to_html_document(";;;\n{\"title\": \"Venus\"}\n;;;\n\n# Hello", &options)?,
+///     "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n<title>Venus</title>\n<link rel=\"stylesheet\" href=\"style.css\" />\n</head>\n<body>\n<h1>Hello</h1>\n</body>\n</html>\n"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_html_document(value: &str, options: &DocumentOptions) -> Result<String, String> {
+    parser::parse(value, &options.parse)
+        .and_then(|(events, parse_state)| {
+            to_html_document::compile(&events, parse_state.bytes, options)
+        })
+        .map_err(|message| {
+            Encoder::new(value.as_bytes(), options.parse.position_encoding).message(&message)
+        })
 }
 
 /// Turn markdown into a syntax tree.
@@ -153,7 +417,358 @@ pub fn to_html_with_options(value: &str, options: &Options) -> Result<String, St
 /// # }
 /// ```
 pub fn to_mdast(value: &str, options: &ParseOptions) -> Result<mdast::Node, String> {
-    let (events, parse_state) = parser::parse(value, options)?;
-    let node = to_mdast::compile(&events, parse_state.bytes)?;
-    Ok(node)
+    let result = parser::parse(value, options)
+        .and_then(|(events, parse_state)| to_mdast::compile(&events, parse_state.bytes, options));
+
+    if options.position_encoding == PositionEncoding::Bytes {
+        result
+    } else {
+        let encoder = Encoder::new(value.as_bytes(), options.position_encoding);
+        match result {
+            Ok(mut node) => {
+                encoder.node(&mut node);
+                Ok(node)
+            }
+            Err(message) => Err(encoder.message(&message)),
+        }
+    }
+}
+
+/// Turn markdown into a syntax tree, as JSON.
+///
+/// Needs the `json` feature.
+/// The JSON is the same, byte for byte, as `JSON.stringify(tree)` of what
+/// `mdast-util-from-markdown` (and its extensions for GFM, math, MDX, and
+/// frontmatter) gives in JavaScript.
+/// Use [`mdast::Node::from_json()`][] to turn it back into a tree.
+///
+/// ## Errors
+///
+/// Errors like [`to_mdast()`][] does for syntax errors in MDX.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{to_mdast_json, ParseOptions};
+/// # fn main() -> Result<(), String> {
+///
+/// assert_eq!(
+///     to_mdast_json("*a*", &ParseOptions::default())?,
+///     "{\"type\":\"root\",\"children\":[{\"type\":\"paragraph\",\"children\":[{\"type\":\"emphasis\",\"children\":[{\"type\":\"text\",\"value\":\"a\",\"position\":{\"start\":{\"line\":1,\"column\":2,\"offset\":1},\"end\":{\"line\":1,\"column\":3,\"offset\":2}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "json")]
+pub fn to_mdast_json(value: &str, options: &ParseOptions) -> Result<String, String> {
+    Ok(to_mdast(value, options)?.to_json())
+}
+
+/// Drop URLs with dangerous protocols from a syntax tree.
+///
+/// Applies the same policy as [`to_html_with_options()`][] to URLs in links,
+/// images, and definitions, based on
+/// [`allow_dangerous_protocol`][CompileOptions::allow_dangerous_protocol],
+/// [`protocol_href`][CompileOptions::protocol_href], and
+/// [`protocol_src`][CompileOptions::protocol_src] in `options`.
+/// Dropped URLs are replaced by an empty string.
+/// Definitions used by image references are checked against `protocol_src`,
+/// and those used by both image and link references against both lists.
+/// This is useful when turning the tree into something else than HTML with
+/// `markdown-rs`.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{mdast, sanitize_mdast, to_mdast, CompileOptions, ParseOptions};
+/// # fn main() -> Result<(), String> {
+///
+/// let mut tree = to_mdast("[a](javascript:alert(1)) [b](tel:123)", &ParseOptions::default())?;
+///
+/// sanitize_mdast(
+///     &mut tree,
+///     &CompileOptions {
+///         protocol_href: Some(vec!["tel".into()]),
+///         ..CompileOptions::default()
+///     },
+/// );
+///
+/// let urls = tree.children().unwrap()[0]
+///     .children()
+///     .unwrap()
+///     .iter()
+///     .filter_map(|node| match node {
+///         mdast::Node::Link(link) => Some(link.url.as_str()),
+///         _ => None,
+///     })
+///     .collect::<Vec<_>>();
+///
+/// assert_eq!(urls, vec!["", "tel:123"]);
+/// # Ok(())
+/// # }
+/// ```
+pub fn sanitize_mdast(tree: &mut mdast::Node, options: &CompileOptions) {
+    util::sanitize_uri::sanitize_tree(tree, options);
+}
+
+/// Turn an edited document into a syntax tree, reusing the previous tree.
+///
+/// `value` is the new document, `previous` is the tree of the document
+/// before `edit` (from [`to_mdast()`][] or this function, with the same
+/// `options`).
+/// Only the top-level blocks around the edit are parsed again, the other
+/// nodes are reused (with their positions moved).
+/// When an edit affects things elsewhere, such as when it adds or removes
+/// a definition, the whole document is parsed again.
+/// Either way, the resulting tree is the same as what `to_mdast()` would
+/// give.
+///
+/// Returns the tree, and which of its top-level children and which part of
+/// `value` were parsed again.
+///
+/// ## Errors
+///
+/// Errors like [`to_mdast()`][] does for syntax errors in MDX.
+/// Also errors if `previous` is not a root with a position, or if `edit`
+/// does not fit `previous` and `value`.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{to_mdast, to_mdast_reparse, Edit, ParseOptions};
+/// # fn main() -> Result<(), String> {
+///
+/// let options = ParseOptions::default();
+/// let tree = to_mdast("# a\n\nb\n\nc\n\nd", &options)?;
+///
+/// // Replace `c` with `*xyz*`:
+/// let value = "# a\n\nb\n\n*xyz*\n\nd";
+/// let edit = Edit {
+///     start: 8,
+///     old_end: 9,
+///     new_end: 13,
+/// };
+/// let result = to_mdast_reparse(value, &tree, &edit, &options)?;
+///
+/// assert_eq!(result.tree, to_mdast(value, &options)?);
+/// assert_eq!(result.children, 1..4);
+/// assert_eq!(result.range, 5..16);
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_mdast_reparse(
+    value: &str,
+    previous: &mdast::Node,
+    edit: &Edit,
+    options: &ParseOptions,
+) -> Result<Reparse, String> {
+    reparse::reparse(value, previous, edit, options)
+}
+
+/// Turn frontmatter in markdown into structured data.
+///
+/// Frontmatter must be turned on with
+/// [`Constructs::frontmatter`][Constructs::frontmatter].
+/// JSON frontmatter (see [`Matter::json()`][Matter::json]) can always be
+/// parsed; YAML and TOML frontmatter need the `yaml` and `toml` features.
+///
+/// Returns `None` if there is no frontmatter.
+///
+/// ## Errors
+///
+/// Errors if the frontmatter is not valid, with a position relative to the
+/// document, or if the feature needed for its language is not enabled.
+/// Like [`to_mdast()`][], this also errors for syntax errors in MDX.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{to_frontmatter, Constructs, FrontmatterValue, Matter, ParseOptions};
+/// # fn main() -> Result<(), String> {
+///
+/// let options = ParseOptions {
+///     constructs: Constructs {
+///         frontmatter: true,
+///         ..Constructs::default()
+///     },
+///     frontmatter_matters: Matter::json(),
+///     ..ParseOptions::default()
+/// };
+///
+/// let data = to_frontmatter("{\n  \"title\": \"Venus\"\n}\n\n# Venus", &options)?;
+///
+/// assert_eq!(
+///     data,
+///     Some(FrontmatterValue::Object(vec![(
+///         "title".into(),
+///         FrontmatterValue::String("Venus".into())
+///     )]))
+/// );
+///
+/// let error = to_frontmatter(";;;\n{\"title\" \"Venus\"}\n;;;", &options).unwrap_err();
+///
+/// assert_eq!(
+///     error,
+///     "2:10: Unexpected character `\"` after key, expected `:` (frontmatter-json:parse)"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_frontmatter(
+    value: &str,
+    options: &ParseOptions,
+) -> Result<Option<FrontmatterValue>, String> {
+    parser::parse(value, options)
+        .and_then(|(events, parse_state)| to_mdast::compile(&events, parse_state.bytes, options))
+        .and_then(|tree| {
+            let children = tree.children().expect("expected root");
+            match children.first().and_then(util::frontmatter::from_node) {
+                Some(result) => result.map(Some),
+                None => Ok(None),
+            }
+        })
+        .map_err(|message| {
+            Encoder::new(value.as_bytes(), options.position_encoding).message(&message)
+        })
+}
+
+/// Check markdown for style problems.
+///
+/// Messages are sorted by where they start.
+/// See [`LintRules`][] for the rules and how to configure them.
+///
+/// ## Errors
+///
+/// `lint()` never errors with normal markdown because markdown does not
+/// have syntax errors, so feel free to `unwrap()`.
+/// However, MDX does have syntax errors.
+/// When MDX is turned on, there are several errors that can occur with how
+/// JSX, expressions, or ESM are written.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{lint, LintOptions};
+/// # fn main() -> Result<(), String> {
+///
+/// let messages = lint("# a\n\n### b", &LintOptions::default())?;
+///
+/// assert_eq!(
+///     messages[0].to_string(),
+///     "3:1: Unexpected heading rank `3`, expected rank `2` (markdown-lint:heading-increment)"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn lint(value: &str, options: &LintOptions) -> Result<Vec<LintMessage>, String> {
+    lint::lint(value, options)
+}
+
+/// Turn a syntax tree into markdown.
+///
+/// The style of the markdown comes from `options`, not from how the tree
+/// was written (positions are ignored).
+/// Text is escaped where needed, so that parsing the result with
+/// `options.parse` gives the same tree again (except for positions).
+/// Line endings are line feeds (`\n`).
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{to_markdown, to_mdast, FormatOptions, ParseOptions};
+/// # fn main() -> Result<(), String> {
+///
+/// let tree = to_mdast("Hey, *you*!\n====", &ParseOptions::default())?;
+///
+/// assert_eq!(
+///     to_markdown(&tree, &FormatOptions::default()),
+///     "# Hey, _you_!\n"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_markdown(tree: &mdast::Node, options: &FormatOptions) -> String {
+    to_markdown::serialize(tree, options)
+}
+
+/// Format markdown.
+///
+/// Parses `value` with `options.parse` and serializes it again with
+/// [`to_markdown()`][], so that bullets, emphasis and strong markers,
+/// headings, fences, tables, and list numbering are in one style.
+/// HTML (including comments), frontmatter, and MDX are kept as they are.
+/// Formatting is idempotent, and the formatted markdown gives the same tree
+/// as the original (except for positions).
+/// The first line ending in `value` is used for the whole result.
+///
+/// ## Errors
+///
+/// `format()` never errors with normal markdown because markdown does not
+/// have syntax errors, so feel free to `unwrap()`.
+/// However, MDX does have syntax errors.
+/// When MDX is turned on, there are several errors that can occur with how
+/// JSX, expressions, or ESM are written.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{format, FormatOptions};
+/// # fn main() -> Result<(), String> {
+///
+/// assert_eq!(
+///     format("* a\n* __b__\n\n  ~~~\n  c\n  ~~~", &FormatOptions::default())?,
+///     "- a\n- **b**\n\n  ```\n  c\n  ```\n"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn format(value: &str, options: &FormatOptions) -> Result<String, String> {
+    to_markdown::format(value, options)
+}
+
+/// Run a language server (LSP) for markdown.
+///
+/// Reads JSON-RPC messages from `input` and writes them to `output` (such as
+/// stdin and stdout), until the client sends `exit` or `input` ends.
+/// Needs the `lsp` feature; the `markdown-lsp` binary runs this over stdio.
+///
+/// Supports go to definition (from link, image, and footnote references),
+/// hover (for footnote references), document symbols (from headings),
+/// diagnostics (from syntax errors in MDX), and completion (of definition
+/// labels).
+/// Documents are synced fully, and parsed with `options`.
+///
+/// ## Errors
+///
+/// Errors if `input` or `output` error, or if `input` is not made of
+/// messages with `Content-Length` headers.
+/// To not run out of memory, messages larger than 64 MiB and header lines
+/// longer than 4096 bytes are errors too.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{language_server, ParseOptions};
+/// # fn main() -> std::io::Result<()> {
+///
+/// let body = r#"{"jsonrpc":"2.0","id":1,"method":"shutdown"}"#;
+/// let input = format!("Content-Length: {}\r\n\r\n{}", body.len(), body);
+/// let mut output = vec![];
+///
+/// language_server(&mut input.as_bytes(), &mut output, &ParseOptions::default())?;
+///
+/// assert_eq!(
+///     String::from_utf8(output).unwrap(),
+///     "Content-Length: 38\r\n\r\n{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":null}"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[cfg(feature = "lsp")]
+pub fn language_server(
+    input: &mut impl std::io::BufRead,
+    output: &mut impl std::io::Write,
+    options: &ParseOptions,
+) -> std::io::Result<()> {
+    lsp::serve(input, output, options)
 }
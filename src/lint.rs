@@ -0,0 +1,647 @@
+//! Check markdown for style problems.
+//!
+//! Rules look at the events, which know which markers were used (such as
+//! `*` or `_` in an `EmphasisSequence`), and at the syntax tree, which knows
+//! what things mean (such as which definitions are referenced).
+
+use crate::event::{Event, Kind, Name};
+use crate::mdast::Node;
+use crate::parser::parse;
+use crate::to_mdast::compile;
+use crate::unist::Position;
+use crate::util::{constant::TAB_SIZE, location::Encoder};
+use crate::{ParseOptions, PositionEncoding};
+use alloc::{format, string::String, vec, vec::Vec};
+use core::fmt;
+
+/// Configuration that describes how to lint markdown.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{LintOptions, LintRules, ParseOptions};
+/// # fn main() {
+///
+/// // Use the default trait to use the recommended rules:
+/// let recommended = LintOptions::default();
+///
+/// // Allow longer lines in GFM:
+/// let custom = LintOptions {
+///   parse: ParseOptions::gfm(),
+///   rules: LintRules {
+///     maximum_line_length: Some(120),
+///     ..LintRules::default()
+///   },
+/// };
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct LintOptions {
+    /// Configuration that describes how to parse from markdown.
+    pub parse: ParseOptions,
+    /// Which rules to use, and how.
+    pub rules: LintRules,
+}
+
+/// Which rules to use, and how.
+///
+/// Each rule is turned off with `None` or `false`.
+/// The default turns all rules on.
+#[allow(clippy::struct_excessive_bools)]
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LintRules {
+    /// `emphasis-marker`: which marker to use for emphasis.
+    ///
+    /// ```markdown
+    /// *a* and _b_
+    /// ```
+    ///
+    /// The default is `Some(EmphasisMarker::Consistent)`.
+    pub emphasis_marker: Option<EmphasisMarker>,
+    /// `heading-increment`: whether headings can go down more than one rank
+    /// at a time.
+    ///
+    /// ```markdown
+    /// # a
+    ///
+    /// ### b
+    /// ```
+    ///
+    /// The default is `true`.
+    pub heading_increment: bool,
+    /// `no-duplicate-definitions`: whether definitions must have unique
+    /// identifiers.
+    ///
+    /// ```markdown
+    /// [a]: b
+    /// [A]: c
+    /// ```
+    ///
+    /// The default is `true`.
+    pub no_duplicate_definitions: bool,
+    /// `no-unused-definitions`: whether definitions must be referenced.
+    ///
+    /// ```markdown
+    /// [a]: b
+    /// ```
+    ///
+    /// The default is `true`.
+    pub no_unused_definitions: bool,
+    /// `list-item-indent`: how much space to put between list item markers
+    /// and their content.
+    ///
+    /// ```markdown
+    /// -   a
+    /// ```
+    ///
+    /// The default is `Some(ListItemIndent::One)`.
+    pub list_item_indent: Option<ListItemIndent>,
+    /// `fenced-code-flag`: whether fenced code must have an info string
+    /// (such as `rust`).
+    ///
+    /// ````markdown
+    /// ```
+    /// a
+    /// ```
+    /// ````
+    ///
+    /// The default is `true`.
+    pub fenced_code_flag: bool,
+    /// `maximum-line-length`: how many characters lines can have.
+    ///
+    /// Headings, tables, code, math, definitions, HTML, frontmatter, and MDX
+    /// (ESM and flow expressions) are ignored.
+    /// So are lines where a link, image, or code (text) starts before the
+    /// limit and ends after it, when nothing but whitespace follows it.
+    ///
+    /// The default is `Some(80)`.
+    pub maximum_line_length: Option<usize>,
+}
+
+impl Default for LintRules {
+    /// Recommended rules.
+    fn default() -> Self {
+        Self {
+            emphasis_marker: Some(EmphasisMarker::Consistent),
+            heading_increment: true,
+            no_duplicate_definitions: true,
+            no_unused_definitions: true,
+            list_item_indent: Some(ListItemIndent::One),
+            fenced_code_flag: true,
+            maximum_line_length: Some(80),
+        }
+    }
+}
+
+/// Marker to use for emphasis.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum EmphasisMarker {
+    /// Whichever marker is used first.
+    Consistent,
+    /// Asterisk (`*`).
+    Asterisk,
+    /// Underscore (`_`).
+    Underscore,
+}
+
+/// Space between list item markers and their content.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ListItemIndent {
+    /// One space.
+    ///
+    /// ```markdown
+    /// - a
+    /// 10. b
+    /// ```
+    One,
+    /// Up to the next tab stop (of 4 spaces).
+    ///
+    /// ```markdown
+    /// -   a
+    /// 10. b
+    /// ```
+    Tab,
+    /// One space for items that span one line, up to the next tab stop
+    /// otherwise.
+    Mixed,
+}
+
+/// Problem found in a document.
+///
+/// Displays as `line:column: reason (markdown-lint:rule)`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LintMessage {
+    /// Name of the rule (such as `heading-increment`).
+    pub rule: &'static str,
+    /// Human readable description of the problem.
+    pub reason: String,
+    /// Where the problem is.
+    pub position: Position,
+}
+
+impl fmt::Display for LintMessage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}:{}: {} (markdown-lint:{})",
+            self.position.start.line, self.position.start.column, self.reason, self.rule
+        )
+    }
+}
+
+/// Check a document.
+///
+/// Rules work with byte-based positions, which are encoded at the end (see
+/// `ParseOptions::position_encoding`).
+pub fn lint(value: &str, options: &LintOptions) -> Result<Vec<LintMessage>, String> {
+    let encoder = Encoder::new(value.as_bytes(), options.parse.position_encoding);
+    let (events, parse_state) =
+        parse(value, &options.parse).map_err(|message| encoder.message(&message))?;
+    let tree = compile(&events, parse_state.bytes, &options.parse)
+        .map_err(|message| encoder.message(&message))?;
+    let bytes = parse_state.bytes;
+    let rules = &options.rules;
+    let mut messages = vec![];
+
+    if let Some(marker) = rules.emphasis_marker {
+        emphasis_marker(&events, bytes, marker, &mut messages);
+    }
+
+    if rules.heading_increment {
+        heading_increment(&tree, &mut None, &mut messages);
+    }
+
+    if rules.no_duplicate_definitions || rules.no_unused_definitions {
+        let mut definitions = vec![];
+        let mut references = vec![];
+        collect_definitions(&tree, &mut definitions, &mut references);
+
+        if rules.no_duplicate_definitions {
+            no_duplicate_definitions(&definitions, &mut messages);
+        }
+
+        if rules.no_unused_definitions {
+            no_unused_definitions(&definitions, &references, &mut messages);
+        }
+    }
+
+    if let Some(style) = rules.list_item_indent {
+        list_item_indent(&events, bytes, style, &mut messages);
+    }
+
+    if rules.fenced_code_flag {
+        fenced_code_flag(&events, &mut messages);
+    }
+
+    if let Some(size) = rules.maximum_line_length {
+        maximum_line_length(value, &tree, size, &mut messages);
+    }
+
+    messages.sort_by(|a, b| {
+        a.position
+            .start
+            .offset
+            .cmp(&b.position.start.offset)
+            .then_with(|| a.rule.cmp(b.rule))
+    });
+
+    if options.parse.position_encoding != PositionEncoding::Bytes {
+        for message in &mut messages {
+            message.position.start = encoder.point(&message.position.start);
+            message.position.end = encoder.point(&message.position.end);
+        }
+    }
+
+    Ok(messages)
+}
+
+/// `emphasis-marker`.
+fn emphasis_marker(
+    events: &[Event],
+    bytes: &[u8],
+    marker: EmphasisMarker,
+    messages: &mut Vec<LintMessage>,
+) {
+    let mut expected = match marker {
+        EmphasisMarker::Consistent => None,
+        EmphasisMarker::Asterisk => Some(b'*'),
+        EmphasisMarker::Underscore => Some(b'_'),
+    };
+    let mut index = 0;
+
+    while index < events.len() {
+        let event = &events[index];
+
+        // Only the opening sequence, which comes right after the enter.
+        if event.kind == Kind::Enter
+            && event.name == Name::EmphasisSequence
+            && events[index - 1].name == Name::Emphasis
+        {
+            let byte = bytes[event.point.index];
+
+            if let Some(expected) = expected {
+                if byte != expected {
+                    let exit = &events[index + 1];
+                    messages.push(LintMessage {
+                        rule: "emphasis-marker",
+                        reason: format!(
+                            "Unexpected emphasis marker `{}`, expected `{}`",
+                            char::from(byte),
+                            char::from(expected)
+                        ),
+                        position: position_from_events(event, exit),
+                    });
+                }
+            } else {
+                expected = Some(byte);
+            }
+        }
+
+        index += 1;
+    }
+}
+
+/// `heading-increment`.
+fn heading_increment(node: &Node, previous: &mut Option<u8>, messages: &mut Vec<LintMessage>) {
+    if let Node::Heading(heading) = node {
+        if let Some(rank) = *previous {
+            if heading.depth > rank + 1 {
+                if let Some(position) = &heading.position {
+                    messages.push(LintMessage {
+                        rule: "heading-increment",
+                        reason: format!(
+                            "Unexpected heading rank `{}`, expected rank `{}`",
+                            heading.depth,
+                            rank + 1
+                        ),
+                        position: position.clone(),
+                    });
+                }
+            }
+        }
+
+        *previous = Some(heading.depth);
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            heading_increment(child, previous, messages);
+        }
+    }
+}
+
+/// Gather definitions (identifier, label, and position) and referenced
+/// identifiers.
+fn collect_definitions<'a>(
+    node: &'a Node,
+    definitions: &mut Vec<(&'a str, &'a str, &'a Position)>,
+    references: &mut Vec<&'a str>,
+) {
+    match node {
+        Node::Definition(definition) => {
+            if let Some(position) = &definition.position {
+                definitions.push((
+                    &definition.identifier,
+                    definition
+                        .label
+                        .as_deref()
+                        .unwrap_or(&definition.identifier),
+                    position,
+                ));
+            }
+        }
+        Node::LinkReference(reference) => references.push(&reference.identifier),
+        Node::ImageReference(reference) => references.push(&reference.identifier),
+        _ => {}
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            collect_definitions(child, definitions, references);
+        }
+    }
+}
+
+/// `no-duplicate-definitions`.
+fn no_duplicate_definitions(
+    definitions: &[(&str, &str, &Position)],
+    messages: &mut Vec<LintMessage>,
+) {
+    let mut index = 0;
+
+    while index < definitions.len() {
+        let (identifier, label, position) = definitions[index];
+
+        if let Some((_, _, first)) = definitions[..index]
+            .iter()
+            .find(|(other, _, _)| *other == identifier)
+        {
+            messages.push(LintMessage {
+                rule: "no-duplicate-definitions",
+                reason: format!(
+                    "Unexpected definition with an already defined identifier (`{}`), expected unique identifiers (first defined at {}:{})",
+                    label, first.start.line, first.start.column
+                ),
+                position: position.clone(),
+            });
+        }
+
+        index += 1;
+    }
+}
+
+/// `no-unused-definitions`.
+fn no_unused_definitions(
+    definitions: &[(&str, &str, &Position)],
+    references: &[&str],
+    messages: &mut Vec<LintMessage>,
+) {
+    for (identifier, label, position) in definitions {
+        if !references.contains(identifier) {
+            messages.push(LintMessage {
+                rule: "no-unused-definitions",
+                reason: format!(
+                    "Unexpected unused definition (`{}`), expected a reference to it",
+                    label
+                ),
+                position: (*position).clone(),
+            });
+        }
+    }
+}
+
+/// `list-item-indent`.
+fn list_item_indent(
+    events: &[Event],
+    bytes: &[u8],
+    style: ListItemIndent,
+    messages: &mut Vec<LintMessage>,
+) {
+    let mut index = 0;
+    let mut item_enter = 0;
+    let mut prefix_enter = 0;
+    let mut marker_exit = 0;
+
+    while index < events.len() {
+        let event = &events[index];
+
+        match (&event.kind, &event.name) {
+            (Kind::Enter, Name::ListItem) => item_enter = index,
+            (Kind::Enter, Name::ListItemPrefix) => prefix_enter = index,
+            (Kind::Exit, Name::ListItemMarker) => marker_exit = index,
+            (Kind::Exit, Name::ListItemPrefix) => {
+                let start = &events[prefix_enter].point;
+                let marker = &events[marker_exit].point;
+                let end = &event.point;
+
+                // Items that start with a blank line have no space to check.
+                if !matches!(bytes.get(end.index), None | Some(b'\n' | b'\r')) {
+                    let marker_size = marker.column - start.column;
+                    let actual = end.column - marker.column;
+                    let one_line = match style {
+                        ListItemIndent::One => true,
+                        ListItemIndent::Tab => false,
+                        ListItemIndent::Mixed => {
+                            // Trailing blank lines can be part of an item.
+                            let mut item_end = skip_to_exit(events, item_enter);
+                            while matches!(
+                                events[item_end - 1].name,
+                                Name::BlankLineEnding | Name::LineEnding | Name::SpaceOrTab
+                            ) {
+                                item_end -= 1;
+                            }
+                            events[item_end].point.line == events[item_enter].point.line
+                        }
+                    };
+                    let expected = if one_line {
+                        1
+                    } else {
+                        (marker_size + 1 + TAB_SIZE - 1) / TAB_SIZE * TAB_SIZE - marker_size
+                    };
+
+                    if actual != expected {
+                        messages.push(LintMessage {
+                            rule: "list-item-indent",
+                            reason: format!(
+                                "Unexpected `{}` {} between list item marker and content, expected `{}` {}",
+                                actual,
+                                if actual == 1 { "space" } else { "spaces" },
+                                expected,
+                                if expected == 1 { "space" } else { "spaces" }
+                            ),
+                            position: position_from_events(&events[marker_exit], event),
+                        });
+                    }
+                }
+            }
+            _ => {}
+        }
+
+        index += 1;
+    }
+}
+
+/// `fenced-code-flag`.
+fn fenced_code_flag(events: &[Event], messages: &mut Vec<LintMessage>) {
+    let mut index = 0;
+
+    while index < events.len() {
+        let event = &events[index];
+
+        if event.kind == Kind::Enter && event.name == Name::CodeFenced {
+            // The opening fence is first, and it ends before the first line
+            // ending.
+            let fence_exit = skip_to_exit(events, index + 1);
+            let has_info = events[index + 1..fence_exit]
+                .iter()
+                .any(|event| event.name == Name::CodeFencedFenceInfo);
+
+            if !has_info {
+                messages.push(LintMessage {
+                    rule: "fenced-code-flag",
+                    reason: "Unexpected missing fenced code language flag in info string, expected keyword".into(),
+                    position: position_from_events(&events[index + 1], &events[fence_exit]),
+                });
+            }
+        }
+
+        index += 1;
+    }
+}
+
+/// `maximum-line-length`.
+fn maximum_line_length(value: &str, tree: &Node, size: usize, messages: &mut Vec<LintMessage>) {
+    let mut ignored = vec![];
+    collect_ignored_lines(value.as_bytes(), tree, size, &mut ignored);
+    let bytes = value.as_bytes();
+    let mut offset = 0;
+    let mut line = 1;
+
+    while offset < bytes.len() {
+        let mut end = offset;
+
+        while end < bytes.len() && !matches!(bytes[end], b'\n' | b'\r') {
+            end += 1;
+        }
+
+        let content = &value[offset..end];
+
+        if !ignored
+            .iter()
+            .any(|(start, end)| line >= *start && line <= *end)
+        {
+            if let Some((overflow, _)) = content.char_indices().nth(size) {
+                let length = content.chars().count();
+                messages.push(LintMessage {
+                    rule: "maximum-line-length",
+                    reason: format!(
+                        "Unexpected `{}` character line, expected at most `{}` characters, remove `{}` {}",
+                        length,
+                        size,
+                        length - size,
+                        if length - size == 1 {
+                            "character"
+                        } else {
+                            "characters"
+                        }
+                    ),
+                    position: Position::new(
+                        line,
+                        overflow + 1,
+                        offset + overflow,
+                        line,
+                        content.len() + 1,
+                        end,
+                    ),
+                });
+            }
+        }
+
+        // Line endings are `\r\n`, `\r`, or `\n`.
+        offset = if bytes.get(end) == Some(&b'\r') && bytes.get(end + 1) == Some(&b'\n') {
+            end + 2
+        } else {
+            end + 1
+        };
+        line += 1;
+    }
+}
+
+/// Gather ranges of lines (inclusive) that `maximum-line-length` ignores.
+fn collect_ignored_lines(
+    bytes: &[u8],
+    node: &Node,
+    size: usize,
+    ignored: &mut Vec<(usize, usize)>,
+) {
+    let ignore = match node {
+        Node::Heading(_)
+        | Node::Table(_)
+        | Node::Code(_)
+        | Node::Math(_)
+        | Node::Definition(_)
+        | Node::Html(_)
+        | Node::Toml(_)
+        | Node::Yaml(_)
+        | Node::Json(_)
+        | Node::Frontmatter(_)
+        | Node::MdxjsEsm(_)
+        | Node::MdxFlowExpression(_) => true,
+        // Allow things that cannot be wrapped, when they end the line.
+        Node::Link(_) | Node::Image(_) | Node::InlineCode(_) => {
+            if let Some(position) = node.position() {
+                position.start.column <= size + 1
+                    && position.end.column > size + 1
+                    && bytes[position.end.offset..]
+                        .iter()
+                        .take_while(|byte| !matches!(byte, b'\n' | b'\r'))
+                        .all(|byte| matches!(byte, b'\t' | b' '))
+            } else {
+                false
+            }
+        }
+        _ => false,
+    };
+
+    if ignore {
+        if let Some(position) = node.position() {
+            ignored.push((position.start.line, position.end.line));
+        }
+    } else if let Some(children) = node.children() {
+        for child in children {
+            collect_ignored_lines(bytes, child, size, ignored);
+        }
+    }
+}
+
+/// Get the index of the exit event that matches the enter at `index`.
+fn skip_to_exit(events: &[Event], index: usize) -> usize {
+    let mut depth = 0;
+    let mut index = index;
+
+    loop {
+        if events[index].kind == Kind::Enter {
+            depth += 1;
+        } else {
+            depth -= 1;
+
+            if depth == 0 {
+                return index;
+            }
+        }
+
+        index += 1;
+    }
+}
+
+/// Create a position from the points of two events.
+fn position_from_events(start: &Event, end: &Event) -> Position {
+    Position::new(
+        start.point.line,
+        start.point.column,
+        start.point.index,
+        end.point.line,
+        end.point.column,
+        end.point.index,
+    )
+}
@@ -0,0 +1,820 @@
+//! Language server (LSP) for markdown.
+//!
+//! Speaks JSON-RPC over a reader and a writer (such as stdin and stdout),
+//! with `Content-Length` headers, like the
+//! [spec](https://microsoft.github.io/language-server-protocol/) says.
+//! Documents are synced fully: each change sends the whole document, which
+//! is then parsed again with [`to_mdast`][crate::to_mdast].
+//!
+//! Supports:
+//!
+//! *   go to definition, from link, image, and footnote references
+//! *   hover, for footnote references
+//! *   document symbols, from headings
+//! *   diagnostics, from syntax errors in MDX
+//! *   completion, of definition and footnote definition labels
+
+use crate::mdast::{Node, Root};
+use crate::parser;
+use crate::to_markdown::{serialize, FormatOptions};
+use crate::to_mdast;
+use crate::unist::Position;
+use crate::util::{
+    frontmatter::{line_column, parse_json, Value},
+    location::Location,
+    mdast_json::string_to_json,
+};
+use crate::ParseOptions;
+use alloc::{
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::convert::TryFrom;
+use std::io::{self, BufRead, Read, Write};
+
+/// Error code when a message is not JSON.
+const PARSE_ERROR: i64 = -32700;
+/// Error code when a request is for a method that is not supported.
+const METHOD_NOT_FOUND: i64 = -32601;
+/// Error code when the parameters of a request are wrong.
+const INVALID_PARAMS: i64 = -32602;
+
+/// Maximum size of the body of a message, in bytes (64 MiB).
+const MESSAGE_SIZE_MAX: usize = 64 * 1024 * 1024;
+/// Maximum size of a header line, in bytes.
+const HEADER_SIZE_MAX: usize = 4096;
+
+/// `SymbolKind.String`, used for headings (like VS Code does).
+const SYMBOL_KIND_STRING: i64 = 15;
+/// `CompletionItemKind.Reference`, used for definition labels.
+const COMPLETION_ITEM_KIND_REFERENCE: i64 = 18;
+/// `DiagnosticSeverity.Error`.
+const DIAGNOSTIC_SEVERITY_ERROR: i64 = 1;
+/// `TextDocumentSyncKind.Full`.
+const TEXT_DOCUMENT_SYNC_FULL: i64 = 1;
+
+/// Error of a request: code and message.
+type Failure = (i64, String);
+
+/// Analyzed document.
+#[derive(Debug)]
+struct Document {
+    /// Source.
+    value: String,
+    /// Index of lines, to turn offsets into points and back.
+    location: Location,
+    /// Tree, if there were no errors.
+    tree: Option<Node>,
+    /// Normalized identifiers of definitions, from the parser.
+    definitions: Vec<String>,
+    /// Normalized identifiers of footnote definitions, from the parser.
+    gfm_footnote_definitions: Vec<String>,
+    /// Error, if there was one (such as for a syntax error in MDX).
+    message: Option<String>,
+}
+
+impl Document {
+    /// Parse `value`.
+    fn new(value: String, options: &ParseOptions) -> Self {
+        let location = Location::new(value.as_bytes());
+        let mut tree = None;
+        let mut definitions = vec![];
+        let mut gfm_footnote_definitions = vec![];
+        let mut message = None;
+
+        match parser::parse(&value, options) {
+            Ok((events, parse_state)) => {
+                match to_mdast::compile(&events, parse_state.bytes, options) {
+                    Ok(node) => {
+                        tree = Some(node);
+                        definitions = parse_state.definitions;
+                        gfm_footnote_definitions = parse_state.gfm_footnote_definitions;
+                    }
+                    Err(reason) => message = Some(reason),
+                }
+            }
+            Err(reason) => message = Some(reason),
+        }
+
+        Document {
+            value,
+            location,
+            tree,
+            definitions,
+            gfm_footnote_definitions,
+            message,
+        }
+    }
+
+    /// Turn a byte offset into an LSP position (0-indexed line, UTF-16
+    /// character).
+    fn position(&self, offset: usize) -> Value {
+        let (line, start) = self
+            .location
+            .to_point(offset)
+            .map_or((0, 0), |point| (point.line - 1, offset + 1 - point.column));
+        let character = self
+            .value
+            .get(start..offset)
+            .map_or(0, |slice| slice.encode_utf16().count());
+        object(vec![
+            ("line", number(line)),
+            ("character", number(character)),
+        ])
+    }
+
+    /// Turn a unist position into an LSP range.
+    fn range(&self, position: &Position) -> Value {
+        self.range_between(position.start.offset, position.end.offset)
+    }
+
+    /// Turn two byte offsets into an LSP range.
+    fn range_between(&self, start: usize, end: usize) -> Value {
+        object(vec![
+            ("start", self.position(start)),
+            ("end", self.position(end)),
+        ])
+    }
+
+    /// Turn an LSP position into a byte offset.
+    ///
+    /// Characters past the end of a line are at the end of that line.
+    fn offset(&self, position: &Value) -> Option<usize> {
+        let line = integer(position.get("line"))?;
+        let character = integer(position.get("character"))?;
+        let start = self.location.to_offset(line + 1, 1)?;
+        let mut offset = start;
+        let mut units = 0;
+
+        for char in self.value[start..].chars() {
+            if units >= character || char == '\n' || char == '\r' {
+                break;
+            }
+
+            units += char.len_utf16();
+            offset += char.len_utf8();
+        }
+
+        Some(offset)
+    }
+
+    /// Get the definition of the reference at `offset`.
+    fn definition(&self, uri: &str, offset: usize) -> Value {
+        if let Some(tree) = &self.tree {
+            if let Some(reference) = reference_at(tree, offset) {
+                let definition = match reference {
+                    Node::LinkReference(x) => find_definition(tree, &x.identifier, false),
+                    Node::ImageReference(x) => find_definition(tree, &x.identifier, false),
+                    Node::FootnoteReference(x) => find_definition(tree, &x.identifier, true),
+                    _ => None,
+                };
+
+                if let Some(position) = definition.and_then(Node::position) {
+                    return object(vec![("uri", string(uri)), ("range", self.range(position))]);
+                }
+            }
+        }
+
+        Value::Null
+    }
+
+    /// Get the content of the footnote definition of the footnote reference
+    /// at `offset`.
+    fn hover(&self, offset: usize, options: &FormatOptions) -> Value {
+        if let Some(tree) = &self.tree {
+            if let Some(Node::FootnoteReference(reference)) = reference_at(tree, offset) {
+                if let Some(Node::FootnoteDefinition(definition)) =
+                    find_definition(tree, &reference.identifier, true)
+                {
+                    let root = Node::Root(Root {
+                        children: definition.children.clone(),
+                        position: None,
+                    });
+                    let value = serialize(&root, options);
+                    let mut fields = vec![(
+                        "contents",
+                        object(vec![
+                            ("kind", string("markdown")),
+                            ("value", string(value.trim_end())),
+                        ]),
+                    )];
+
+                    if let Some(position) = &reference.position {
+                        fields.push(("range", self.range(position)));
+                    }
+
+                    return object(fields);
+                }
+            }
+        }
+
+        Value::Null
+    }
+
+    /// Get symbols for the headings, nested in sections.
+    fn symbols(&self) -> Value {
+        let mut headings = vec![];
+
+        if let Some(tree) = &self.tree {
+            collect_headings(tree, &mut headings);
+        }
+
+        // A section ends where the next heading of the same or a higher rank
+        // starts.
+        let ends = headings
+            .iter()
+            .enumerate()
+            .map(|(index, (depth, _, _))| {
+                headings[index + 1..]
+                    .iter()
+                    .find(|(other, _, _)| other <= depth)
+                    .map_or(self.value.len(), |(_, _, position)| position.start.offset)
+            })
+            .collect::<Vec<_>>();
+        let mut index = 0;
+        Value::Array(self.nest(&headings, &ends, &mut index, 0))
+    }
+
+    /// Turn headings, starting at `index`, that are deeper than `depth` into
+    /// symbols.
+    fn nest(
+        &self,
+        headings: &[(u8, String, &Position)],
+        ends: &[usize],
+        index: &mut usize,
+        depth: u8,
+    ) -> Vec<Value> {
+        let mut symbols = vec![];
+
+        while *index < headings.len() && headings[*index].0 > depth {
+            let (rank, name, position) = &headings[*index];
+            let end = ends[*index];
+            *index += 1;
+            let children = self.nest(headings, ends, index, *rank);
+            symbols.push(object(vec![
+                ("name", string(name)),
+                ("kind", Value::Integer(SYMBOL_KIND_STRING)),
+                ("range", self.range_between(position.start.offset, end)),
+                ("selectionRange", self.range(position)),
+                ("children", Value::Array(children)),
+            ]));
+        }
+
+        symbols
+    }
+
+    /// Get labels of definitions and footnote definitions.
+    fn completion(&self) -> Value {
+        let mut items = vec![];
+
+        if let Some(tree) = &self.tree {
+            let mut seen: Vec<&String> = vec![];
+
+            for identifier in &self.definitions {
+                if !seen.contains(&identifier) {
+                    seen.push(identifier);
+
+                    if let Some(Node::Definition(definition)) =
+                        find_normalized_definition(tree, identifier, false)
+                    {
+                        items.push(object(vec![
+                            (
+                                "label",
+                                string(definition.label.as_ref().unwrap_or(identifier)),
+                            ),
+                            ("kind", Value::Integer(COMPLETION_ITEM_KIND_REFERENCE)),
+                            ("detail", string(&definition.url)),
+                        ]));
+                    }
+                }
+            }
+
+            for identifier in &self.gfm_footnote_definitions {
+                if !seen.contains(&identifier) {
+                    seen.push(identifier);
+
+                    if let Some(Node::FootnoteDefinition(definition)) =
+                        find_normalized_definition(tree, identifier, true)
+                    {
+                        items.push(object(vec![
+                            (
+                                "label",
+                                string(&format!(
+                                    "^{}",
+                                    definition.label.as_ref().unwrap_or(identifier)
+                                )),
+                            ),
+                            ("kind", Value::Integer(COMPLETION_ITEM_KIND_REFERENCE)),
+                        ]));
+                    }
+                }
+            }
+        }
+
+        Value::Array(items)
+    }
+
+    /// Get diagnostics for the error, if there is one.
+    fn diagnostics(&self) -> Value {
+        Value::Array(
+            self.message
+                .iter()
+                .map(|message| self.diagnostic(message))
+                .collect(),
+        )
+    }
+
+    /// Turn an error (`line:column: reason (source:rule)`) into a diagnostic.
+    fn diagnostic(&self, message: &str) -> Value {
+        let mut offset = 0;
+        let mut reason = message;
+        let mut parts = message.splitn(3, ':');
+
+        if let (Some(line), Some(column), Some(rest)) = (parts.next(), parts.next(), parts.next()) {
+            if let (Ok(line), Ok(column)) = (line.parse(), column.parse()) {
+                offset = self
+                    .location
+                    .to_offset(line, column)
+                    .unwrap_or(self.value.len());
+                reason = rest.trim_start();
+            }
+        }
+
+        let mut source = "markdown-rs";
+        let mut rule = None;
+
+        if let Some(index) = reason.rfind(" (") {
+            if let Some(origin) = reason[index + 2..].strip_suffix(')') {
+                if let Some((before, after)) = origin.split_once(':') {
+                    source = before;
+                    rule = Some(after);
+                    reason = &reason[..index];
+                }
+            }
+        }
+
+        let mut fields = vec![
+            ("range", self.range_between(offset, offset)),
+            ("severity", Value::Integer(DIAGNOSTIC_SEVERITY_ERROR)),
+            ("source", string(source)),
+            ("message", string(reason)),
+        ];
+
+        if let Some(rule) = rule {
+            fields.push(("code", string(rule)));
+        }
+
+        object(fields)
+    }
+}
+
+/// State of the server.
+struct Server<'a> {
+    /// Options passed to `to_mdast`.
+    options: &'a ParseOptions,
+    /// Options used to serialize footnote definitions for hovers.
+    format: FormatOptions,
+    /// Open documents, by URI.
+    documents: BTreeMap<String, Document>,
+}
+
+impl<'a> Server<'a> {
+    /// Create a server.
+    fn new(options: &'a ParseOptions) -> Self {
+        Server {
+            options,
+            format: FormatOptions {
+                parse: ParseOptions {
+                    constructs: options.constructs.clone(),
+                    gfm_strikethrough_single_tilde: options.gfm_strikethrough_single_tilde,
+                    math_text_single_dollar: options.math_text_single_dollar,
+                    ..ParseOptions::default()
+                },
+                ..FormatOptions::default()
+            },
+            documents: BTreeMap::new(),
+        }
+    }
+
+    /// Handle a message.
+    ///
+    /// Returns whether to exit.
+    fn handle(&mut self, message: &Value, output: &mut impl Write) -> io::Result<bool> {
+        let method = message.get("method").and_then(Value::as_str);
+        let params = message.get("params");
+
+        match (method, message.get("id")) {
+            (Some(method), Some(id)) => {
+                let body = match self.request(method, params) {
+                    Ok(result) => object(vec![
+                        ("jsonrpc", string("2.0")),
+                        ("id", id.clone()),
+                        ("result", result),
+                    ]),
+                    Err((code, reason)) => error(id.clone(), code, &reason),
+                };
+                send(output, &body)?;
+            }
+            (Some("exit"), None) => return Ok(true),
+            (Some(method), None) => self.notification(method, params, output)?,
+            // Responses to requests we did not make.
+            _ => {}
+        }
+
+        Ok(false)
+    }
+
+    /// Handle a request.
+    fn request(&self, method: &str, params: Option<&Value>) -> Result<Value, Failure> {
+        match method {
+            "initialize" => Ok(object(vec![
+                (
+                    "capabilities",
+                    object(vec![
+                        ("textDocumentSync", Value::Integer(TEXT_DOCUMENT_SYNC_FULL)),
+                        ("definitionProvider", Value::Bool(true)),
+                        ("hoverProvider", Value::Bool(true)),
+                        ("documentSymbolProvider", Value::Bool(true)),
+                        (
+                            "completionProvider",
+                            object(vec![("triggerCharacters", Value::Array(vec![string("[")]))]),
+                        ),
+                    ]),
+                ),
+                (
+                    "serverInfo",
+                    object(vec![
+                        ("name", string("markdown-rs")),
+                        ("version", string(env!("CARGO_PKG_VERSION"))),
+                    ]),
+                ),
+            ])),
+            "shutdown" => Ok(Value::Null),
+            "textDocument/definition" => {
+                let (uri, document, offset) = self.document_at(params)?;
+                Ok(document.map_or(Value::Null, |document| {
+                    offset.map_or(Value::Null, |offset| document.definition(uri, offset))
+                }))
+            }
+            "textDocument/hover" => {
+                let (_, document, offset) = self.document_at(params)?;
+                Ok(document.map_or(Value::Null, |document| {
+                    offset.map_or(Value::Null, |offset| document.hover(offset, &self.format))
+                }))
+            }
+            "textDocument/documentSymbol" => {
+                let (_, document, _) = self.document_at(params)?;
+                Ok(document.map_or(Value::Null, Document::symbols))
+            }
+            "textDocument/completion" => {
+                let (_, document, _) = self.document_at(params)?;
+                Ok(document.map_or(Value::Null, Document::completion))
+            }
+            _ => Err((
+                METHOD_NOT_FOUND,
+                format!("Cannot handle unknown method `{}`", method),
+            )),
+        }
+    }
+
+    /// Get the URI, document, and offset of `params.textDocument` and
+    /// `params.position`.
+    ///
+    /// The document is `None` if it is not open, the offset is `None` if
+    /// there is no position or it is not in the document.
+    fn document_at<'b>(
+        &self,
+        params: Option<&'b Value>,
+    ) -> Result<(&'b str, Option<&Document>, Option<usize>), Failure> {
+        let uri = params
+            .and_then(|params| params.get("textDocument"))
+            .and_then(|document| document.get("uri"))
+            .and_then(Value::as_str)
+            .ok_or_else(|| {
+                (
+                    INVALID_PARAMS,
+                    "Expected `params.textDocument.uri`".to_string(),
+                )
+            })?;
+        let document = self.documents.get(uri);
+        let offset = document.and_then(|document| {
+            params
+                .and_then(|params| params.get("position"))
+                .and_then(|position| document.offset(position))
+        });
+        Ok((uri, document, offset))
+    }
+
+    /// Handle a notification.
+    fn notification(
+        &mut self,
+        method: &str,
+        params: Option<&Value>,
+        output: &mut impl Write,
+    ) -> io::Result<()> {
+        let document = params.and_then(|params| params.get("textDocument"));
+        let uri = document
+            .and_then(|document| document.get("uri"))
+            .and_then(Value::as_str);
+
+        if let Some(uri) = uri {
+            let text = match method {
+                "textDocument/didOpen" => document
+                    .and_then(|document| document.get("text"))
+                    .and_then(Value::as_str),
+                // Last full change.
+                "textDocument/didChange" => {
+                    match params.and_then(|params| params.get("contentChanges")) {
+                        Some(Value::Array(changes)) => changes
+                            .iter()
+                            .rev()
+                            .find_map(|change| change.get("text").and_then(Value::as_str)),
+                        _ => None,
+                    }
+                }
+                "textDocument/didClose" => {
+                    self.documents.remove(uri);
+                    publish(output, uri, Value::Array(vec![]))?;
+                    None
+                }
+                _ => None,
+            };
+
+            if let Some(text) = text {
+                let document = Document::new(text.into(), self.options);
+                publish(output, uri, document.diagnostics())?;
+                self.documents.insert(uri.into(), document);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Run a language server, reading messages from `input` and writing
+/// messages to `output`, until the client sends `exit` or `input` ends.
+pub fn serve(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    options: &ParseOptions,
+) -> io::Result<()> {
+    let mut server = Server::new(options);
+
+    while let Some(body) = receive(input)? {
+        match parse_json(&body) {
+            Ok(message) => {
+                if server.handle(&message, output)? {
+                    break;
+                }
+            }
+            Err((offset, reason)) => {
+                let (line, column) = line_column(&body, offset);
+                let reason = format!("{}:{}: {}", line, column, reason);
+                send(output, &error(Value::Null, PARSE_ERROR, &reason))?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Read a message.
+///
+/// Returns `None` when `input` ends.
+/// Errors if a header line is longer than `HEADER_SIZE_MAX`, or if
+/// `Content-Length` is invalid or larger than `MESSAGE_SIZE_MAX`.
+fn receive(input: &mut impl BufRead) -> io::Result<Option<String>> {
+    let mut length = None;
+    let mut line = String::new();
+
+    loop {
+        line.clear();
+
+        if input
+            .by_ref()
+            .take(HEADER_SIZE_MAX as u64)
+            .read_line(&mut line)?
+            == 0
+        {
+            return Ok(None);
+        }
+
+        if !line.ends_with('\n') && line.len() == HEADER_SIZE_MAX {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Header is longer than {} bytes", HEADER_SIZE_MAX),
+            ));
+        }
+
+        let header = line.trim_end_matches(&['\r', '\n'][..]);
+
+        if header.is_empty() {
+            if length.is_some() {
+                break;
+            }
+        } else if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                let size = value.trim().parse::<usize>().map_err(|_| {
+                    io::Error::new(io::ErrorKind::InvalidData, "Invalid `Content-Length`")
+                })?;
+
+                if size > MESSAGE_SIZE_MAX {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!(
+                            "`Content-Length` of {} is larger than {} bytes",
+                            size, MESSAGE_SIZE_MAX
+                        ),
+                    ));
+                }
+
+                length = Some(size);
+            }
+        }
+    }
+
+    let mut body = vec![0; length.unwrap_or(0)];
+    input.read_exact(&mut body)?;
+    String::from_utf8(body)
+        .map(Some)
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "Invalid UTF-8 in message"))
+}
+
+/// Write a message.
+fn send(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let mut body = String::new();
+    stringify(&mut body, message);
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)?;
+    output.flush()
+}
+
+/// Write diagnostics for a document.
+fn publish(output: &mut impl Write, uri: &str, diagnostics: Value) -> io::Result<()> {
+    send(
+        output,
+        &object(vec![
+            ("jsonrpc", string("2.0")),
+            ("method", string("textDocument/publishDiagnostics")),
+            (
+                "params",
+                object(vec![("uri", string(uri)), ("diagnostics", diagnostics)]),
+            ),
+        ]),
+    )
+}
+
+/// Create an error response.
+fn error(id: Value, code: i64, reason: &str) -> Value {
+    object(vec![
+        ("jsonrpc", string("2.0")),
+        ("id", id),
+        (
+            "error",
+            object(vec![
+                ("code", Value::Integer(code)),
+                ("message", string(reason)),
+            ]),
+        ),
+    ])
+}
+
+/// Find the innermost reference in `node` at `offset`.
+fn reference_at(node: &Node, offset: usize) -> Option<&Node> {
+    for child in node.children()? {
+        if let Some(position) = child.position() {
+            if position.start.offset <= offset && offset <= position.end.offset {
+                if let Some(reference) = reference_at(child, offset) {
+                    return Some(reference);
+                }
+
+                if matches!(
+                    child,
+                    Node::LinkReference(_) | Node::ImageReference(_) | Node::FootnoteReference(_)
+                ) {
+                    return Some(child);
+                }
+            }
+        }
+    }
+
+    None
+}
+
+/// Find the first definition (or footnote definition) in `node` for
+/// `identifier` (as in mdast, lowercase).
+fn find_definition<'a>(node: &'a Node, identifier: &str, footnote: bool) -> Option<&'a Node> {
+    match node {
+        Node::Definition(x) if !footnote && x.identifier == identifier => Some(node),
+        Node::FootnoteDefinition(x) if footnote && x.identifier == identifier => Some(node),
+        _ => node.children().and_then(|children| {
+            children
+                .iter()
+                .find_map(|child| find_definition(child, identifier, footnote))
+        }),
+    }
+}
+
+/// Find the first definition (or footnote definition) in `node` for
+/// `identifier` (as in the parser, uppercase).
+fn find_normalized_definition<'a>(
+    node: &'a Node,
+    identifier: &str,
+    footnote: bool,
+) -> Option<&'a Node> {
+    match node {
+        Node::Definition(x) if !footnote && x.identifier.to_uppercase() == identifier => Some(node),
+        Node::FootnoteDefinition(x) if footnote && x.identifier.to_uppercase() == identifier => {
+            Some(node)
+        }
+        _ => node.children().and_then(|children| {
+            children
+                .iter()
+                .find_map(|child| find_normalized_definition(child, identifier, footnote))
+        }),
+    }
+}
+
+/// Collect the depth, text, and position of headings in `node`.
+fn collect_headings<'a>(node: &'a Node, headings: &mut Vec<(u8, String, &'a Position)>) {
+    if let Node::Heading(heading) = node {
+        if let Some(position) = &heading.position {
+            let name = node.to_string();
+            let name = name.trim();
+            // Names of symbols cannot be empty.
+            let name = if name.is_empty() {
+                "#".repeat(heading.depth.into())
+            } else {
+                name.into()
+            };
+            headings.push((heading.depth, name, position));
+        }
+    } else if let Some(children) = node.children() {
+        for child in children {
+            collect_headings(child, headings);
+        }
+    }
+}
+
+/// Create an object.
+fn object(fields: Vec<(&str, Value)>) -> Value {
+    Value::Object(
+        fields
+            .into_iter()
+            .map(|(key, value)| (key.into(), value))
+            .collect(),
+    )
+}
+
+/// Create a string.
+fn string(value: &str) -> Value {
+    Value::String(value.into())
+}
+
+/// Create a number.
+fn number(value: usize) -> Value {
+    Value::Integer(i64::try_from(value).unwrap_or(i64::MAX))
+}
+
+/// Get a non-negative integer.
+fn integer(value: Option<&Value>) -> Option<usize> {
+    match value {
+        Some(Value::Integer(value)) => usize::try_from(*value).ok(),
+        _ => None,
+    }
+}
+
+/// Serialize a value as JSON.
+fn stringify(buffer: &mut String, value: &Value) {
+    match value {
+        Value::Null => buffer.push_str("null"),
+        Value::Bool(value) => buffer.push_str(if *value { "true" } else { "false" }),
+        Value::Integer(value) => buffer.push_str(&value.to_string()),
+        Value::Float(value) => buffer.push_str(&value.to_string()),
+        Value::String(value) => string_to_json(buffer, value),
+        Value::Array(values) => {
+            buffer.push('[');
+            for (index, value) in values.iter().enumerate() {
+                if index > 0 {
+                    buffer.push(',');
+                }
+                stringify(buffer, value);
+            }
+            buffer.push(']');
+        }
+        Value::Object(fields) => {
+            buffer.push('{');
+            for (index, (key, value)) in fields.iter().enumerate() {
+                if index > 0 {
+                    buffer.push(',');
+                }
+                string_to_json(buffer, key);
+                buffer.push(':');
+                stringify(buffer, value);
+            }
+            buffer.push('}');
+        }
+    }
+}
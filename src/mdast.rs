@@ -110,6 +110,10 @@ pub enum Node {
     Toml(Toml),
     /// Yaml.
     Yaml(Yaml),
+    /// Json.
+    Json(Json),
+    /// Frontmatter of another kind.
+    Frontmatter(Frontmatter),
 
     // Phrasing:
     /// Break.
@@ -190,6 +194,8 @@ impl fmt::Debug for Node {
             Node::MdxjsEsm(x) => x.fmt(f),
             Node::Toml(x) => x.fmt(f),
             Node::Yaml(x) => x.fmt(f),
+            Node::Json(x) => x.fmt(f),
+            Node::Frontmatter(x) => x.fmt(f),
             Node::Break(x) => x.fmt(f),
             Node::InlineCode(x) => x.fmt(f),
             Node::InlineMath(x) => x.fmt(f),
@@ -250,6 +256,8 @@ impl ToString for Node {
             Node::MdxjsEsm(x) => x.value.clone(),
             Node::Toml(x) => x.value.clone(),
             Node::Yaml(x) => x.value.clone(),
+            Node::Json(x) => x.value.clone(),
+            Node::Frontmatter(x) => x.value.clone(),
             Node::InlineCode(x) => x.value.clone(),
             Node::InlineMath(x) => x.value.clone(),
             Node::MdxTextExpression(x) => x.value.clone(),
@@ -333,6 +341,8 @@ impl Node {
             Node::MdxjsEsm(x) => x.position.as_ref(),
             Node::Toml(x) => x.position.as_ref(),
             Node::Yaml(x) => x.position.as_ref(),
+            Node::Json(x) => x.position.as_ref(),
+            Node::Frontmatter(x) => x.position.as_ref(),
             Node::Break(x) => x.position.as_ref(),
             Node::InlineCode(x) => x.position.as_ref(),
             Node::InlineMath(x) => x.position.as_ref(),
@@ -372,6 +382,8 @@ impl Node {
             Node::MdxjsEsm(x) => x.position.as_mut(),
             Node::Toml(x) => x.position.as_mut(),
             Node::Yaml(x) => x.position.as_mut(),
+            Node::Json(x) => x.position.as_mut(),
+            Node::Frontmatter(x) => x.position.as_mut(),
             Node::Break(x) => x.position.as_mut(),
             Node::InlineCode(x) => x.position.as_mut(),
             Node::InlineMath(x) => x.position.as_mut(),
@@ -411,6 +423,8 @@ impl Node {
             Node::MdxjsEsm(x) => x.position = position,
             Node::Toml(x) => x.position = position,
             Node::Yaml(x) => x.position = position,
+            Node::Json(x) => x.position = position,
+            Node::Frontmatter(x) => x.position = position,
             Node::Break(x) => x.position = position,
             Node::InlineCode(x) => x.position = position,
             Node::InlineMath(x) => x.position = position,
@@ -439,6 +453,49 @@ impl Node {
             Node::Paragraph(x) => x.position = position,
         }
     }
+
+    /// Turn JSON, such as from `JSON.stringify(tree)` in JavaScript, into a
+    /// node.
+    ///
+    /// Needs the `json` feature.
+    /// Unknown fields (such as `data`) are ignored, and MDX nodes get no
+    /// `stops`.
+    ///
+    /// ## Errors
+    ///
+    /// Errors if `value` is not JSON, or if it is not mdast.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{mdast::{Node, Root}, to_mdast, ParseOptions};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// let tree = Node::from_json(r#"{"type":"root","children":[]}"#)?;
+    ///
+    /// assert_eq!(tree, Node::Root(Root { children: vec![], position: None }));
+    ///
+    /// let tree = to_mdast("# a", &ParseOptions::default())?;
+    ///
+    /// assert_eq!(Node::from_json(&tree.to_json())?, tree);
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_json(value: &str) -> Result<Node, String> {
+        crate::util::mdast_json::parse(value)
+    }
+
+    /// Turn a node into JSON, the same as `JSON.stringify(tree)` in
+    /// JavaScript.
+    ///
+    /// Needs the `json` feature.
+    /// See [`to_mdast_json()`][crate::to_mdast_json] for more info.
+    #[cfg(feature = "json")]
+    #[must_use]
+    pub fn to_json(&self) -> String {
+        crate::util::mdast_json::stringify(self)
+    }
 }
 
 /// MDX: attribute content.
@@ -1217,6 +1274,59 @@ pub struct Toml {
     pub position: Option<Position>,
 }
 
+/// Frontmatter: json.
+///
+/// ```markdown
+/// > | ;;;
+///     ^^^
+/// > | {"a": "b"}
+///     ^^^^^^^^^^
+/// > | ;;;
+///     ^^^
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename = "json")
+)]
+pub struct Json {
+    // Void.
+    /// Content model.
+    pub value: String,
+    /// Positional info.
+    pub position: Option<Position>,
+}
+
+/// Frontmatter: other kinds.
+///
+/// See [`Matter`][crate::Matter] for how to define kinds of frontmatter.
+///
+/// ```markdown
+/// > | ~~~meta
+///     ^^^^^^^
+/// > | a: b
+///     ^^^^
+/// > | ~~~
+///     ^^^
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(tag = "type", rename = "frontmatter")
+)]
+pub struct Frontmatter {
+    // Void.
+    /// Content model.
+    pub value: String,
+    /// Positional info.
+    pub position: Option<Position>,
+    // Extra.
+    /// Kind of frontmatter (such as `meta`).
+    pub kind: String,
+}
+
 /// MDX: ESM.
 ///
 /// ```markdown
@@ -1651,6 +1761,57 @@ mod tests {
         );
     }
 
+    #[test]
+    fn json() {
+        let mut node = Node::Json(Json {
+            value: "a".into(),
+            position: None,
+        });
+
+        assert_eq!(
+            format!("{:?}", node),
+            "Json { value: \"a\", position: None }",
+            "should support `Debug`"
+        );
+        assert_eq!(node.to_string(), "a", "should support `ToString`");
+        assert_eq!(node.children_mut(), None, "should support `children_mut`");
+        assert_eq!(node.children(), None, "should support `children`");
+        assert_eq!(node.position(), None, "should support `position`");
+        assert_eq!(node.position_mut(), None, "should support `position`");
+        node.position_set(Some(Position::new(1, 1, 0, 1, 2, 1)));
+        assert_eq!(
+            format!("{:?}", node),
+            "Json { value: \"a\", position: Some(1:1-1:2 (0-1)) }",
+            "should support `position_set`"
+        );
+    }
+
+    #[test]
+    fn frontmatter() {
+        let mut node = Node::Frontmatter(Frontmatter {
+            value: "a".into(),
+            position: None,
+            kind: "b".into(),
+        });
+
+        assert_eq!(
+            format!("{:?}", node),
+            "Frontmatter { value: \"a\", position: None, kind: \"b\" }",
+            "should support `Debug`"
+        );
+        assert_eq!(node.to_string(), "a", "should support `ToString`");
+        assert_eq!(node.children_mut(), None, "should support `children_mut`");
+        assert_eq!(node.children(), None, "should support `children`");
+        assert_eq!(node.position(), None, "should support `position`");
+        assert_eq!(node.position_mut(), None, "should support `position`");
+        node.position_set(Some(Position::new(1, 1, 0, 1, 2, 1)));
+        assert_eq!(
+            format!("{:?}", node),
+            "Frontmatter { value: \"a\", position: Some(1:1-1:2 (0-1)), kind: \"b\" }",
+            "should support `position_set`"
+        );
+    }
+
     // Voids.
 
     #[test]
@@ -4,9 +4,10 @@ use crate::event::{Event, Point};
 use crate::state::{Name as StateName, State};
 use crate::subtokenize::subtokenize;
 use crate::tokenizer::Tokenizer;
-use crate::util::location::Location;
-use crate::ParseOptions;
-use alloc::{string::String, vec, vec::Vec};
+use crate::util::{constant::CANCEL_INTERVAL, location::Location};
+use crate::{Cancel, ParseOptions};
+use alloc::{format, string::String, vec, vec::Vec};
+use core::cell::Cell;
 
 /// Info needed, in all content types, when parsing markdown.
 ///
@@ -24,6 +25,18 @@ pub struct ParseState<'a> {
     pub definitions: Vec<String>,
     /// Set of defined GFM footnote definition identifiers.
     pub gfm_footnote_definitions: Vec<String>,
+    /// Number of steps taken, by all tokenizers, used to call `cancel`
+    /// sometimes.
+    pub moves: Cell<usize>,
+}
+
+impl ParseState<'_> {
+    /// Count a step, and check whether parsing is canceled.
+    ///
+    /// Returns an error message, at `line` and `column`, if so.
+    pub fn cancel(&self, line: usize, column: usize) -> Result<(), String> {
+        cancel(self.options.cancel.as_deref(), &self.moves, line, column)
+    }
 }
 
 /// Turn a string of markdown into events.
@@ -32,9 +45,34 @@ pub struct ParseState<'a> {
 pub fn parse<'a>(
     value: &'a str,
     options: &'a ParseOptions,
+) -> Result<(Vec<Event>, ParseState<'a>), String> {
+    parse_with_definitions(value, options, vec![], vec![])
+}
+
+/// Turn a string of markdown into events, knowing that some definitions
+/// exist elsewhere.
+///
+/// Used when reparsing part of a document: `definitions` and
+/// `gfm_footnote_definitions` are normalized identifiers (as in, from
+/// `normalize_identifier`) of definitions outside `value`.
+pub fn parse_with_definitions<'a>(
+    value: &'a str,
+    options: &'a ParseOptions,
+    definitions: Vec<String>,
+    gfm_footnote_definitions: Vec<String>,
 ) -> Result<(Vec<Event>, ParseState<'a>), String> {
     let bytes = value.as_bytes();
 
+    if let Some(max) = options.size_max {
+        if bytes.len() > max {
+            return Err(format!(
+                "1:1: Unexpected document of {} bytes, expected at most {} bytes (markdown-rs:size-max)",
+                bytes.len(),
+                max
+            ));
+        }
+    }
+
     let mut parse_state = ParseState {
         options,
         bytes,
@@ -43,8 +81,9 @@ pub fn parse<'a>(
         } else {
             None
         },
-        definitions: vec![],
-        gfm_footnote_definitions: vec![],
+        definitions,
+        gfm_footnote_definitions,
+        moves: Cell::new(0),
     };
 
     let start = Point {
@@ -69,6 +108,16 @@ pub fn parse<'a>(
         fn_defs.append(&mut result.gfm_footnote_definitions);
         defs.append(&mut result.definitions);
 
+        if let Some(max) = options.events_max {
+            if events.len() > max {
+                let point = &events[max].point;
+                return Err(format!(
+                    "{}:{}: Unexpected event, expected at most {} events (markdown-rs:events-max)",
+                    point.line, point.column, max
+                ));
+            }
+        }
+
         if result.done {
             return Ok((events, parse_state));
         }
@@ -76,3 +125,30 @@ pub fn parse<'a>(
         result = subtokenize(&mut events, &parse_state, &None)?;
     }
 }
+
+/// Count a step in `moves`, calling `cancel` every so often.
+///
+/// Used while parsing, and by the compilers, which also take a while on big
+/// documents.
+/// Returns an error message, at `line` and `column`, if `cancel` returns
+/// `true`.
+pub fn cancel(
+    cancel: Option<&Cancel>,
+    moves: &Cell<usize>,
+    line: usize,
+    column: usize,
+) -> Result<(), String> {
+    if let Some(cancel) = cancel {
+        let count = moves.get() + 1;
+        moves.set(count);
+
+        if count % CANCEL_INTERVAL == 0 && cancel() {
+            return Err(format!(
+                "{}:{}: Unexpected cancel, parsing was stopped by `cancel` (markdown-rs:cancel)",
+                line, column
+            ));
+        }
+    }
+
+    Ok(())
+}
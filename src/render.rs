@@ -0,0 +1,163 @@
+//! Customize how certain constructs are turned into HTML.
+
+use crate::HtmlSyntax;
+use alloc::{format, string::String};
+
+/// Turn constructs into HTML.
+///
+/// Pass an implementation as [`render`][crate::CompileOptions::render] in
+/// [`CompileOptions`][crate::CompileOptions] to change how certain
+/// constructs are turned into HTML.
+/// Each method has a default implementation which produces what `to_html`
+/// produces without it, so only the methods for the constructs you want to
+/// change need to be implemented.
+/// To use the default output in your own, call the method on
+/// [`DefaultRender`][].
+///
+/// All values passed are HTML: they are already encoded (and URLs are
+/// already sanitized), so they can be used as they are.
+/// `attributes` are extra attributes, such as `data-sourcepos` (see
+/// [`sourcepos`][crate::CompileOptions::sourcepos]), each preceded by a
+/// space, or an empty string if there are none.
+/// `syntax` is [`html_syntax`][crate::CompileOptions::html_syntax], for
+/// methods whose default output depends on it.
+/// The HTML that is returned is used as it is.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{to_html_with_options, CompileOptions, DefaultRender, Options, Render};
+/// use std::sync::Arc;
+/// # fn main() -> Result<(), String> {
+///
+/// struct Custom;
+///
+/// impl Render for Custom {
+///     fn code(&self, lang: Option<&str>, _meta: Option<&str>, _attributes: &str, value: &str) -> String {
+///         format!("<code-block lang=\"{}\">{}</code-block>", lang.unwrap_or_default(), value)
+///     }
+///
+///     fn table(&self, attributes: &str, children: &str) -> String {
+///         format!("<div class=\"scroll\">{}</div>", DefaultRender.table(attributes, children))
+///     }
+/// }
+///
+/// assert_eq!(
+///     to_html_with_options(
+///         "```js\nconsole.log(1)\n```",
+///         &Options {
+///             compile: CompileOptions {
+///               render: Some(Arc::new(Custom)),
+///               ..CompileOptions::default()
+///             },
+///             ..Options::default()
+///         }
+///     )?,
+///     "<code-block lang=\"js\">console.log(1)\n</code-block>"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub trait Render {
+    /// Turn a heading (ATX or setext) into HTML.
+    ///
+    /// `rank` is from `1` to `6`, and `children` is the content of the
+    /// heading.
+    fn heading(&self, rank: u8, attributes: &str, children: &str) -> String {
+        format!("<h{}{}>{}</h{}>", rank, attributes, children, rank)
+    }
+
+    /// Turn code (fenced or indented) into HTML.
+    ///
+    /// `lang` is the first word of the info string of fenced code, if any,
+    /// and `meta` the rest of it, if any.
+    /// `value` is the code, ending in a line ending if it is not empty.
+    fn code(
+        &self,
+        lang: Option<&str>,
+        meta: Option<&str>,
+        attributes: &str,
+        value: &str,
+    ) -> String {
+        let _ = meta;
+        let class = lang.map_or(String::new(), |lang| {
+            format!(" class=\"language-{}\"", lang)
+        });
+        format!("<pre{}><code{}>{}</code></pre>", attributes, class, value)
+    }
+
+    /// Turn a link (including autolinks and GFM autolink literals) into
+    /// HTML.
+    ///
+    /// `url` is empty if there is no URL or if it was dropped.
+    fn link(&self, url: &str, title: Option<&str>, attributes: &str, children: &str) -> String {
+        let title = title.map_or(String::new(), |title| format!(" title=\"{}\"", title));
+        format!(
+            "<a href=\"{}\"{}{}>{}</a>",
+            url, title, attributes, children
+        )
+    }
+
+    /// Turn an image into HTML.
+    ///
+    /// `url` is empty if there is no URL or if it was dropped.
+    /// `alt` is the text content of the label of the image.
+    fn image(
+        &self,
+        syntax: HtmlSyntax,
+        url: &str,
+        alt: &str,
+        title: Option<&str>,
+        attributes: &str,
+    ) -> String {
+        let title = title.map_or(String::new(), |title| format!(" title=\"{}\"", title));
+        let end = if syntax == HtmlSyntax::Html5 {
+            ">"
+        } else {
+            " />"
+        };
+        format!(
+            "<img src=\"{}\" alt=\"{}\"{}{}{}",
+            url, alt, title, attributes, end
+        )
+    }
+
+    /// Turn a GFM table into HTML.
+    ///
+    /// `children` contains the table head and body.
+    fn table(&self, attributes: &str, children: &str) -> String {
+        format!("<table{}>{}</table>", attributes, children)
+    }
+
+    /// Turn a list item into HTML.
+    ///
+    /// `children` contains the content of the item, including the checkbox
+    /// of GFM task list items.
+    fn list_item(&self, attributes: &str, children: &str) -> String {
+        format!("<li{}>{}</li>", attributes, children)
+    }
+
+    /// Turn the section of GFM footnotes, at the end of the document, into
+    /// HTML.
+    ///
+    /// `children` contains the label and the list of footnotes.
+    fn footnote_section(&self, syntax: HtmlSyntax, children: &str) -> String {
+        let data = if syntax == HtmlSyntax::Html5 {
+            "data-footnotes"
+        } else {
+            "data-footnotes=\"\""
+        };
+        format!(
+            "<section {} class=\"footnotes\">{}</section>",
+            data, children
+        )
+    }
+}
+
+/// Turn constructs into HTML, the same as `to_html` does by default.
+///
+/// Useful to get the default output in your own [`Render`][].
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct DefaultRender;
+
+impl Render for DefaultRender {}
@@ -0,0 +1,440 @@
+//! Turn an edited document into a syntax tree, reusing a previous tree.
+//!
+//! Top-level nodes in the previous tree, which have positions, are used to
+//! find the blocks that an edit affects.
+//! Only those blocks (and a neighbor on each side) are parsed again, with
+//! the definitions from elsewhere in the document.
+//! The nodes before them are kept, and the nodes after them are kept with
+//! their positions moved.
+//!
+//! Reparsing starts at the start of a line, between two top-level blocks,
+//! where no container is open.
+//! What comes after a container (a block quote, list, footnote definition,
+//! or JSX element) depends on it, so the region is widened to not start
+//! right after one or end with one.
+//! If the last reparsed node is not the same as it was before, such as when
+//! the edit opens a fenced code block that runs further, one more block is
+//! reparsed, until they match or the end of the document is reached.
+//!
+//! No parser state is reused: the region is tokenized, subtokenized, and
+//! compiled from scratch, with only the identifiers of definitions outside
+//! it passed in.
+//! What is saved is the work for the unchanged top-level blocks around the
+//! region, so reparsing is cheap for small edits in long documents made of
+//! many blocks, and no cheaper than `to_mdast` for edits in one large
+//! container, such as a long list.
+//!
+//! Everything is parsed again when that is simpler or needed to be correct:
+//! when identifiers of definitions or footnote definitions are added or
+//! removed (as they affect references everywhere), when frontmatter or a
+//! BOM would start the reparsed part, when reparsing a part errors (so that
+//! the error is the same as for the whole document), or when positions are
+//! not in bytes (see `ParseOptions::position_encoding`).
+
+use crate::mdast::{AttributeContent, AttributeValue, Node, Root};
+use crate::parser::parse_with_definitions;
+use crate::to_mdast::compile;
+use crate::unist::{Point, Position};
+use crate::{ParseOptions, PositionEncoding};
+use alloc::{collections::BTreeSet, string::String, vec::Vec};
+use core::ops::Range;
+
+/// A change to a document.
+///
+/// All fields are byte indices.
+/// `start` and `old_end` are into the previous document, `start` and
+/// `new_end` are into the new document.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::Edit;
+///
+/// // Replacing `b` in `a b c` with `xyz`, which results in `a xyz c`:
+/// let edit = Edit {
+///     start: 2,
+///     old_end: 3,
+///     new_end: 5,
+/// };
+/// # assert_eq!(edit.new_end - edit.start, 3);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Edit {
+    /// Where the change starts.
+    pub start: usize,
+    /// Where the replaced text ended in the previous document.
+    pub old_end: usize,
+    /// Where the replacement ends in the new document.
+    pub new_end: usize,
+}
+
+/// Result of reparsing.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reparse {
+    /// Syntax tree of the new document.
+    pub tree: Node,
+    /// Indices of the children of the root in `tree` that were parsed again.
+    ///
+    /// The other children were kept from the previous tree.
+    pub children: Range<usize>,
+    /// Byte range in the new document that was parsed again.
+    pub range: Range<usize>,
+}
+
+/// Turn an edited document into a syntax tree, reusing `previous`.
+pub fn reparse(
+    value: &str,
+    previous: &Node,
+    edit: &Edit,
+    options: &ParseOptions,
+) -> Result<Reparse, String> {
+    let root = if let Node::Root(root) = previous {
+        root
+    } else {
+        return Err("Cannot reparse, expected a root as the previous tree".into());
+    };
+
+    let previous_len = root.position.as_ref().map(|d| d.end.offset);
+    // Positions in other encodings cannot be matched with `edit`.
+    let in_bytes = options.position_encoding == PositionEncoding::Bytes;
+
+    if in_bytes
+        && (edit.start > edit.old_end
+            || edit.start > edit.new_end
+            || edit.new_end > value.len()
+            || previous_len.map_or(true, |len| {
+                edit.old_end > len || len - edit.old_end != value.len() - edit.new_end
+            }))
+    {
+        return Err(
+            "Cannot reparse, expected `edit` to fit the previous tree and the new value".into(),
+        );
+    }
+
+    if let Some(result) = in_bytes
+        .then(|| reparse_children(value, root, edit, options))
+        .flatten()
+    {
+        Ok(result)
+    } else {
+        let tree = crate::to_mdast(value, options)?;
+        let size = tree.children().map_or(0, Vec::len);
+        Ok(Reparse {
+            tree,
+            children: 0..size,
+            range: 0..value.len(),
+        })
+    }
+}
+
+/// Reparse the children affected by `edit`.
+///
+/// Returns `None` if everything must be parsed again.
+fn reparse_children(
+    value: &str,
+    root: &Root,
+    edit: &Edit,
+    options: &ParseOptions,
+) -> Option<Reparse> {
+    let children = &root.children;
+    let mut positions = Vec::with_capacity(children.len());
+
+    for child in children {
+        positions.push(child.position()?);
+    }
+
+    if positions.len() < 3 {
+        return None;
+    }
+
+    let last_index = positions.len() - 1;
+    // First node that ends at or after the edit, and last node that starts
+    // at or before it, plus one on each side.
+    let mut first = positions
+        .iter()
+        .position(|d| d.end.offset >= edit.start)
+        .unwrap_or(last_index);
+    let mut last = positions
+        .iter()
+        .rposition(|d| d.start.offset <= edit.old_end)
+        .unwrap_or(0);
+
+    if first > last {
+        core::mem::swap(&mut first, &mut last);
+    }
+
+    first = first.saturating_sub(1);
+    last = (last + 1).min(last_index);
+
+    // What follows a container depends on how it was closed, so the region
+    // does not start right after one or end with one.
+    while first > 0 && is_container(&children[first - 1]) {
+        first -= 1;
+    }
+
+    while last < last_index && is_container(&children[last]) {
+        last += 1;
+    }
+
+    let (start, start_line) = if first == 0 {
+        (0, 1)
+    } else {
+        let start = &positions[first].start;
+        let line_start = value[..start.offset]
+            .rfind(|d| d == '\n' || d == '\r')
+            .map_or(0, |index| index + 1);
+        (line_start, start.line)
+    };
+
+    // Containers and frontmatter are not open before the region, and a BOM is
+    // only allowed at the start of the document.
+    if start != 0 && value[start..].starts_with('\u{FEFF}') {
+        return None;
+    }
+
+    loop {
+        if first == 0 && last == last_index {
+            return None;
+        }
+
+        let mut definitions = Vec::new();
+        let mut gfm_footnote_definitions = Vec::new();
+        let mut previous_definitions = Vec::new();
+        let mut previous_gfm_footnote_definitions = Vec::new();
+
+        for (index, child) in children.iter().enumerate() {
+            if index >= first && index <= last {
+                collect_definitions(
+                    child,
+                    &mut previous_definitions,
+                    &mut previous_gfm_footnote_definitions,
+                );
+            } else {
+                collect_definitions(child, &mut definitions, &mut gfm_footnote_definitions);
+            }
+        }
+
+        let end = if last == last_index {
+            value.len()
+        } else {
+            let end = positions[last].end.offset + edit.new_end - edit.old_end;
+            value[end..]
+                .find(|d| d == '\n' || d == '\r')
+                .map_or(value.len(), |index| end + index)
+        };
+
+        let (events, parse_state) = parse_with_definitions(
+            &value[start..end],
+            options,
+            definitions.clone(),
+            gfm_footnote_definitions.clone(),
+        )
+        .ok()?;
+        let mut tree = compile(&events, parse_state.bytes, options).ok()?;
+        shift(&mut tree, (1, 0), (start_line, start));
+
+        let (mut new_children, end_point) = match tree {
+            Node::Root(Root {
+                children,
+                position: Some(position),
+            }) => (children, position.end),
+            _ => return None,
+        };
+
+        if start != 0
+            && matches!(
+                new_children.first(),
+                Some(Node::Yaml(_) | Node::Toml(_) | Node::Json(_) | Node::Frontmatter(_))
+            )
+        {
+            return None;
+        }
+
+        // References elsewhere depend on which identifiers are defined.
+        let mut new_definitions = Vec::new();
+        let mut new_gfm_footnote_definitions = Vec::new();
+
+        for child in &new_children {
+            collect_definitions(
+                child,
+                &mut new_definitions,
+                &mut new_gfm_footnote_definitions,
+            );
+        }
+
+        if !same_identifiers(&previous_definitions, &new_definitions, &definitions)
+            || !same_identifiers(
+                &previous_gfm_footnote_definitions,
+                &new_gfm_footnote_definitions,
+                &gfm_footnote_definitions,
+            )
+        {
+            return None;
+        }
+
+        if last == last_index {
+            let size = new_children.len();
+            let mut result = children[..first].to_vec();
+            result.append(&mut new_children);
+
+            return Some(Reparse {
+                tree: Node::Root(Root {
+                    children: result,
+                    position: Some(Position {
+                        start: Point::new(1, 1, 0),
+                        end: end_point,
+                    }),
+                }),
+                children: first..(first + size),
+                range: start..end,
+            });
+        }
+
+        // Move the nodes after the region, and check that the last reparsed
+        // node is what it was.
+        let from = (positions[last].end.line, end + edit.old_end - edit.new_end);
+        let to = (end_point.line, end);
+        let mut expected = children[last].clone();
+        shift(&mut expected, from, to);
+
+        if new_children.last() == Some(&expected) {
+            let size = new_children.len();
+            let mut result = children[..first].to_vec();
+            result.append(&mut new_children);
+
+            for child in &children[(last + 1)..] {
+                let mut child = child.clone();
+                shift(&mut child, from, to);
+                result.push(child);
+            }
+
+            let mut end_point = root.position.as_ref()?.end.clone();
+            end_point.line = end_point.line + to.0 - from.0;
+            end_point.offset = end_point.offset + to.1 - from.1;
+
+            return Some(Reparse {
+                tree: Node::Root(Root {
+                    children: result,
+                    position: Some(Position {
+                        start: Point::new(1, 1, 0),
+                        end: end_point,
+                    }),
+                }),
+                children: first..(first + size),
+                range: start..end,
+            });
+        }
+
+        last += 1;
+
+        while last < last_index && is_container(&children[last]) {
+            last += 1;
+        }
+    }
+}
+
+/// Move the positions in `node`, from `from` to `to`, both `(line, offset)`.
+///
+/// Everything in `node` must be at or after `from`.
+/// Columns stay the same, as `from` and `to` are at the same column.
+fn shift(node: &mut Node, from: (usize, usize), to: (usize, usize)) {
+    if let Some(position) = node.position_mut() {
+        shift_point(&mut position.start, from, to);
+        shift_point(&mut position.end, from, to);
+    }
+
+    let stops = match node {
+        Node::MdxjsEsm(x) => Some(&mut x.stops),
+        Node::MdxFlowExpression(x) => Some(&mut x.stops),
+        Node::MdxTextExpression(x) => Some(&mut x.stops),
+        _ => None,
+    };
+
+    if let Some(stops) = stops {
+        for stop in stops {
+            stop.1 = stop.1 + to.1 - from.1;
+        }
+    }
+
+    let attributes = match node {
+        Node::MdxJsxFlowElement(x) => Some(&mut x.attributes),
+        Node::MdxJsxTextElement(x) => Some(&mut x.attributes),
+        _ => None,
+    };
+
+    if let Some(attributes) = attributes {
+        for attribute in attributes {
+            let stops = match attribute {
+                AttributeContent::Expression { stops, .. } => Some(stops),
+                AttributeContent::Property(property) => match &mut property.value {
+                    Some(AttributeValue::Expression(expression)) => Some(&mut expression.stops),
+                    _ => None,
+                },
+            };
+
+            if let Some(stops) = stops {
+                for stop in stops {
+                    stop.1 = stop.1 + to.1 - from.1;
+                }
+            }
+        }
+    }
+
+    if let Some(children) = node.children_mut() {
+        for child in children {
+            shift(child, from, to);
+        }
+    }
+}
+
+/// Move a point, from `from` to `to`, both `(line, offset)`.
+fn shift_point(point: &mut Point, from: (usize, usize), to: (usize, usize)) {
+    point.line = point.line + to.0 - from.0;
+    point.offset = point.offset + to.1 - from.1;
+}
+
+/// Check if `node` is a container, which other blocks can continue.
+fn is_container(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::BlockQuote(_)
+            | Node::List(_)
+            | Node::FootnoteDefinition(_)
+            | Node::MdxJsxFlowElement(_)
+    )
+}
+
+/// Check if the identifiers in `a` that are not in `outside` are the same
+/// as those in `b`.
+fn same_identifiers(a: &[String], b: &[String], outside: &[String]) -> bool {
+    let outside = outside.iter().collect::<BTreeSet<_>>();
+    let a = a
+        .iter()
+        .filter(|d| !outside.contains(d))
+        .collect::<BTreeSet<_>>();
+    let b = b
+        .iter()
+        .filter(|d| !outside.contains(d))
+        .collect::<BTreeSet<_>>();
+    a == b
+}
+
+/// Collect the identifiers of definitions and footnote definitions in
+/// `node`, normalized like the parser does.
+fn collect_definitions(
+    node: &Node,
+    definitions: &mut Vec<String>,
+    gfm_footnote_definitions: &mut Vec<String>,
+) {
+    match node {
+        Node::Definition(x) => definitions.push(x.identifier.to_uppercase()),
+        Node::FootnoteDefinition(x) => gfm_footnote_definitions.push(x.identifier.to_uppercase()),
+        _ => {}
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            collect_definitions(child, definitions, gfm_footnote_definitions);
+        }
+    }
+}
@@ -128,6 +128,7 @@ pub enum Name {
     DocumentFlowEnd,
 
     FlowStart,
+    FlowBeforeConstruct,
     FlowBeforeGfmTable,
     FlowBeforeCodeIndented,
     FlowBeforeRaw,
@@ -143,8 +144,10 @@ pub enum Name {
     FlowBeforeContent,
 
     FrontmatterStart,
+    FrontmatterAnywhereStart,
     FrontmatterOpenSequence,
     FrontmatterOpenAfter,
+    FrontmatterLineStart,
     FrontmatterAfter,
     FrontmatterContentStart,
     FrontmatterContentInside,
@@ -565,6 +568,7 @@ pub fn call(tokenizer: &mut Tokenizer, name: Name) -> State {
         Name::DocumentFlowInside => construct::document::flow_inside,
 
         Name::FlowStart => construct::flow::start,
+        Name::FlowBeforeConstruct => construct::flow::before_construct,
         Name::FlowBeforeGfmTable => construct::flow::before_gfm_table,
         Name::FlowBeforeCodeIndented => construct::flow::before_code_indented,
         Name::FlowBeforeRaw => construct::flow::before_raw,
@@ -580,8 +584,10 @@ pub fn call(tokenizer: &mut Tokenizer, name: Name) -> State {
         Name::FlowBeforeContent => construct::flow::before_content,
 
         Name::FrontmatterStart => construct::frontmatter::start,
+        Name::FrontmatterAnywhereStart => construct::frontmatter::anywhere_start,
         Name::FrontmatterOpenSequence => construct::frontmatter::open_sequence,
         Name::FrontmatterOpenAfter => construct::frontmatter::open_after,
+        Name::FrontmatterLineStart => construct::frontmatter::line_start,
         Name::FrontmatterAfter => construct::frontmatter::after,
         Name::FrontmatterContentStart => construct::frontmatter::content_start,
         Name::FrontmatterContentInside => construct::frontmatter::content_inside,
@@ -90,6 +90,7 @@ pub fn subtokenize(
 
     while index < events.len() {
         let event = &events[index];
+        parse_state.cancel(event.point.line, event.point.column)?;
 
         // Find each first opening chunk.
         if let Some(ref link) = event.link {
@@ -2,24 +2,31 @@
 use crate::event::{Event, Kind, Name};
 use crate::mdast::AlignKind;
 use crate::util::{
-    character_reference::decode as decode_character_reference,
-    constant::{SAFE_PROTOCOL_HREF, SAFE_PROTOCOL_SRC},
+    character_reference::{
+        decode as decode_character_reference, parse as decode_character_references,
+    },
     encode::encode,
     gfm_tagfilter::gfm_tagfilter,
     infer::{gfm_table_align, list_loose},
     normalize_identifier::normalize_identifier,
-    sanitize_uri::{sanitize, sanitize_with_protocols},
+    resolve_url::parse as parse_url,
+    sanitize_html::{close as close_html_elements, sanitize as sanitize_html, Open},
+    sanitize_uri::{sanitize, sanitize_with_options},
     skip,
     slice::{Position, Slice},
+    xhtml::to_xhtml,
+};
+use crate::{
+    parser::cancel, unist, Cancel, CompileOptions, DefaultRender, HtmlSyntax, LineEnding, Render,
+    UrlKind,
 };
-use crate::{CompileOptions, LineEnding};
 use alloc::{
     format,
     string::{String, ToString},
     vec,
     vec::Vec,
 };
-use core::str;
+use core::{cell::Cell, convert::TryFrom, fmt, str};
 
 /// Link, image, or footnote call.
 /// Resource or reference.
@@ -85,13 +92,17 @@ struct CompileContext<'a> {
     // Fields used by handlers to track the things they need to track to
     // compile markdown.
     /// Rank of heading (atx).
-    heading_atx_rank: Option<usize>,
+    heading_atx_rank: Option<u8>,
     /// Buffer of heading (setext) text.
     heading_setext_buffer: Option<String>,
     /// Whether raw (flow) (code (fenced), math (flow)) or code (indented) contains data.
     raw_flow_seen_data: Option<bool>,
     /// Number of raw (flow) fences.
     raw_flow_fences_count: Option<usize>,
+    /// Info (first word) of the current raw (flow).
+    raw_flow_info: Option<String>,
+    /// Meta (rest of info) of the current raw (flow).
+    raw_flow_meta: Option<String>,
     /// Whether we are in code (text).
     raw_text_inside: bool,
     /// Whether we are in image text.
@@ -104,6 +115,10 @@ struct CompileContext<'a> {
     media_stack: Vec<Media>,
     /// Stack of containers.
     tight_stack: Vec<bool>,
+    /// Whether the current paragraph is compiled as a figure.
+    paragraph_figure: bool,
+    /// Caption of the current figure.
+    figure_caption: Option<String>,
     /// List of definitions.
     definitions: Vec<Definition>,
     /// List of definitions.
@@ -116,6 +131,11 @@ struct CompileContext<'a> {
     gfm_table_align: Option<Vec<AlignKind>>,
     /// Current GFM table column.
     gfm_table_column: usize,
+    /// Stack of elements opened in sanitized raw HTML.
+    html_stack: Vec<Open>,
+    /// Number of constructs entered while stripping an element in sanitized
+    /// raw HTML, which are dropped.
+    html_strip_depth: usize,
     // Fields used to influance the current compilation.
     /// Ignore the next line ending.
     slurp_one_line_ending: bool,
@@ -127,6 +147,11 @@ struct CompileContext<'a> {
     // Intermediate results.
     /// Stack of buffers.
     buffers: Vec<String>,
+    /// Sizes of the stack of buffers when buffers for the children of
+    /// elements were pushed.
+    children_buffers: Vec<usize>,
+    /// Last byte of what was already flushed from the root buffer.
+    flushed_last_byte: Option<u8>,
     /// Current event index.
     index: usize,
 }
@@ -146,6 +171,8 @@ impl<'a> CompileContext<'a> {
             heading_setext_buffer: None,
             raw_flow_seen_data: None,
             raw_flow_fences_count: None,
+            raw_flow_info: None,
+            raw_flow_meta: None,
             raw_text_inside: false,
             character_reference_marker: None,
             list_expect_first_marker: None,
@@ -157,17 +184,61 @@ impl<'a> CompileContext<'a> {
             gfm_table_in_head: false,
             gfm_table_align: None,
             gfm_table_column: 0,
+            html_stack: vec![],
+            html_strip_depth: 0,
             tight_stack: vec![],
+            paragraph_figure: false,
+            figure_caption: None,
             slurp_one_line_ending: false,
             image_alt_inside: false,
             encode_html: true,
             line_ending_default: line_ending,
             buffers: vec![String::new()],
+            children_buffers: vec![],
+            flushed_last_byte: None,
             index: 0,
             options,
         }
     }
 
+    /// Get the renderer.
+    fn render(&self) -> &'a dyn Render {
+        if let Some(render) = &self.options.render {
+            render.as_ref()
+        } else {
+            &DefaultRender
+        }
+    }
+
+    /// Get the end of a void element (` />` or `>`).
+    fn void_end(&self) -> &'static str {
+        if self.options.html_syntax == HtmlSyntax::Html5 {
+            ">"
+        } else {
+            " />"
+        }
+    }
+
+    /// Get an attribute without value (such as `disabled`), preceded by a
+    /// space.
+    ///
+    /// Pass `boolean` for boolean attributes, which repeat their name as
+    /// their value in XHTML.
+    fn empty_attribute(&self, name: &str, boolean: bool) -> String {
+        match self.options.html_syntax {
+            HtmlSyntax::Html5 => format!(" {}", name),
+            HtmlSyntax::Xhtml if boolean => format!(" {}=\"{}\"", name, name),
+            _ => format!(" {}=\"\"", name),
+        }
+    }
+
+    /// Whether raw HTML is buffered, to sanitize it or turn it into XHTML
+    /// when it ends.
+    fn raw_html_buffered(&self) -> bool {
+        self.options.sanitize_html.is_some()
+            || (self.options.allow_dangerous_html && self.options.html_syntax == HtmlSyntax::Xhtml)
+    }
+
     /// Push a buffer.
     fn buffer(&mut self) {
         self.buffers.push(String::new());
@@ -178,6 +249,20 @@ impl<'a> CompileContext<'a> {
         self.buffers.pop().expect("Cannot resume w/o buffer")
     }
 
+    /// Push a buffer for the children of an element, which acts as if it
+    /// comes right after the opening tag of that element.
+    fn buffer_children(&mut self) {
+        self.buffer();
+        self.children_buffers.push(self.buffers.len());
+    }
+
+    /// Pop a buffer for the children of an element, returning its value.
+    fn resume_children(&mut self) -> String {
+        let index = self.children_buffers.pop();
+        debug_assert_eq!(index, Some(self.buffers.len()), "expected children buffer");
+        self.resume()
+    }
+
     /// Push a str to the last buffer.
     fn push(&mut self, value: &str) {
         let last_buf_opt = self.buffers.last_mut();
@@ -195,20 +280,138 @@ impl<'a> CompileContext<'a> {
     fn line_ending_if_needed(&mut self) {
         let last_buf_opt = self.buffers.last();
         let last_buf = last_buf_opt.expect("at least one buffer should exist");
-        let last_byte = last_buf.as_bytes().last();
+        let mut last_byte = last_buf.as_bytes().last().copied();
+
+        if last_byte.is_none() {
+            // Look at what was already written, if the root buffer is empty.
+            if self.buffers.len() == 1 {
+                last_byte = self.flushed_last_byte;
+            }
+            // Act as if after an opening tag, if this is a children buffer.
+            else if self.children_buffers.last() == Some(&self.buffers.len()) {
+                last_byte = Some(b'>');
+            }
+        }
 
         if !matches!(last_byte, None | Some(b'\n' | b'\r')) {
             self.line_ending();
         }
     }
+
+    /// Add a `data-sourcepos` attribute for the construct entered at
+    /// `index`, if `sourcepos` is on.
+    fn sourcepos(&mut self, index: usize) {
+        let value = self.sourcepos_attribute(index);
+        self.push(&value);
+    }
+
+    /// Get a `data-sourcepos` attribute for the construct exited at the
+    /// current event, if `sourcepos` is on.
+    fn sourcepos_exit(&self) -> String {
+        if self.options.sourcepos {
+            let exit = &self.events[self.index];
+            let mut index = self.index;
+            let mut balance = 0;
+
+            loop {
+                let event = &self.events[index];
+
+                if event.name == exit.name {
+                    if event.kind == Kind::Exit {
+                        balance += 1;
+                    } else {
+                        balance -= 1;
+
+                        if balance == 0 {
+                            break;
+                        }
+                    }
+                }
+
+                index -= 1;
+            }
+
+            self.sourcepos_attribute(index)
+        } else {
+            String::new()
+        }
+    }
+
+    /// Get a `data-sourcepos` attribute for the construct entered at
+    /// `index`, if `sourcepos` is on.
+    fn sourcepos_attribute(&self, index: usize) -> String {
+        if !self.options.sourcepos {
+            return String::new();
+        }
+
+        let enter = &self.events[index];
+        let mut exit = index;
+        let mut balance = 0;
+
+        while exit < self.events.len() {
+            let event = &self.events[exit];
+
+            if event.name == enter.name {
+                if event.kind == Kind::Enter {
+                    balance += 1;
+                } else {
+                    balance -= 1;
+
+                    if balance == 0 {
+                        break;
+                    }
+                }
+            }
+
+            exit += 1;
+        }
+
+        // Trailing whitespace and blank lines (such as in list items)
+        // are not included.
+        let start = &enter.point;
+        let mut end_index = self.events[exit].point.index;
+
+        while end_index > start.index
+            && matches!(self.bytes[end_index - 1], b'\t' | b'\n' | b'\r' | b' ')
+        {
+            end_index -= 1;
+        }
+
+        while exit > index && self.events[exit].point.index > end_index {
+            exit -= 1;
+        }
+
+        let end = &self.events[exit].point;
+        format!(
+            " data-sourcepos=\"{}:{}-{}:{}\"",
+            start.line,
+            start.column,
+            end.line,
+            end.column + (end_index - end.index) - 1
+        )
+    }
+
+    /// Write the root buffer to `output`, if nothing else is buffered.
+    fn flush(&mut self, output: &mut impl fmt::Write) -> fmt::Result {
+        if self.buffers.len() == 1 {
+            let root = &mut self.buffers[0];
+
+            if let Some(byte) = root.as_bytes().last() {
+                self.flushed_last_byte = Some(*byte);
+                output.write_str(root)?;
+                root.clear();
+            }
+        }
+
+        Ok(())
+    }
 }
 
-/// Turn events and bytes into a string of HTML.
-pub fn compile(events: &[Event], bytes: &[u8], options: &CompileOptions) -> String {
+/// Figure out which line ending style to use: the first one in the
+/// document, or otherwise the configured default.
+pub fn line_ending(events: &[Event], bytes: &[u8], options: &CompileOptions) -> LineEnding {
     let mut index = 0;
-    let mut line_ending_inferred = None;
 
-    // First, we figure out what the used line ending style is.
     // Stop when we find a line ending.
     while index < events.len() {
         let event = &events[index];
@@ -217,21 +420,46 @@ pub fn compile(events: &[Event], bytes: &[u8], options: &CompileOptions) -> Stri
             && (event.name == Name::BlankLineEnding || event.name == Name::LineEnding)
         {
             let slice = Slice::from_position(bytes, &Position::from_exit_event(events, index));
-            line_ending_inferred = Some(slice.as_str().parse().unwrap());
-            break;
+            return slice.as_str().parse().unwrap();
         }
 
         index += 1;
     }
 
-    // Figure out which line ending style we’ll use.
-    let line_ending_default =
-        line_ending_inferred.unwrap_or_else(|| options.default_line_ending.clone());
+    options.default_line_ending.clone()
+}
 
+/// Turn events and bytes into a string of HTML.
+///
+/// Errors if `cancel` returns `true`.
+pub fn compile(
+    events: &[Event],
+    bytes: &[u8],
+    options: &CompileOptions,
+    cancel: Option<&Cancel>,
+) -> Result<String, String> {
+    let mut result = String::new();
+    write(events, bytes, options, cancel, &mut result)?;
+    Ok(result)
+}
+
+/// Turn events and bytes into HTML, writing it to `output` as it is
+/// generated.
+///
+/// Errors if `cancel` returns `true`, or if `output` errors.
+pub fn write(
+    events: &[Event],
+    bytes: &[u8],
+    options: &CompileOptions,
+    cancel_fn: Option<&Cancel>,
+    output: &mut impl fmt::Write,
+) -> Result<(), String> {
+    let line_ending_default = line_ending(events, bytes, options);
     let mut context = CompileContext::new(events, bytes, options, line_ending_default);
     let mut definition_indices = vec![];
     let mut index = 0;
     let mut definition_inside = false;
+    let moves = Cell::new(0);
 
     // Handle all definitions first.
     // We must do two passes because we need to compile the events in
@@ -246,6 +474,7 @@ pub fn compile(events: &[Event], bytes: &[u8], options: &CompileOptions) -> Stri
     // nested.
     while index < events.len() {
         let event = &events[index];
+        cancel(cancel_fn, &moves, event.point.line, event.point.column)?;
 
         if definition_inside {
             handle(&mut context, index);
@@ -266,6 +495,7 @@ pub fn compile(events: &[Event], bytes: &[u8], options: &CompileOptions) -> Stri
     }
 
     let mut index = 0;
+    let mut depth = 0;
     let jump_default = (events.len(), events.len());
     let mut definition_index = 0;
     let mut jump = definition_indices
@@ -280,29 +510,69 @@ pub fn compile(events: &[Event], bytes: &[u8], options: &CompileOptions) -> Stri
                 .get(definition_index)
                 .unwrap_or(&jump_default);
         } else {
+            let point = &events[index].point;
+            cancel(cancel_fn, &moves, point.line, point.column)?;
             handle(&mut context, index);
+
+            // Write each top-level construct when it is done.
+            if events[index].kind == Kind::Enter {
+                depth += 1;
+            } else {
+                depth -= 1;
+
+                if depth == 0 {
+                    context.flush(output).map_err(|_| output_error())?;
+                }
+            }
+
             index += 1;
         }
     }
 
+    close_html(&mut context, false);
+
     // No section to generate.
     if !context.gfm_footnote_definition_calls.is_empty() {
         generate_footnote_section(&mut context);
     }
 
     debug_assert_eq!(context.buffers.len(), 1, "expected 1 final buffer");
-    context
-        .buffers
-        .get(0)
-        .expect("expected 1 final buffer")
-        .into()
+    context.flush(output).map_err(|_| output_error())
+}
+
+/// Message for when `output` errors.
+fn output_error() -> String {
+    "Cannot write HTML, the output errored".into()
 }
 
 /// Handle the event at `index`.
 fn handle(context: &mut CompileContext, index: usize) {
     context.index = index;
+    let event = &context.events[index];
+
+    // Drop constructs entered while stripping an element in sanitized raw
+    // HTML, but look at raw HTML (and line endings in it) for its closing
+    // tag, and handle exits of constructs entered before, so that they are
+    // closed.
+    if (context.html_strip_depth > 0 || context.html_stack.last().map_or(false, |d| d.strip))
+        && !matches!(
+            event.name,
+            Name::HtmlFlow | Name::HtmlFlowData | Name::HtmlText | Name::HtmlTextData
+        )
+        && (event.name != Name::LineEnding || context.encode_html)
+    {
+        if event.kind == Kind::Enter {
+            context.html_strip_depth += 1;
+            return;
+        }
+
+        if context.html_strip_depth > 0 {
+            context.html_strip_depth -= 1;
+            return;
+        }
+    }
 
-    if context.events[index].kind == Kind::Enter {
+    if event.kind == Kind::Enter {
         enter(context);
     } else {
         exit(context);
@@ -363,8 +633,7 @@ fn enter(context: &mut CompileContext) {
 /// Handle [`Exit`][Kind::Exit].
 fn exit(context: &mut CompileContext) {
     match context.events[context.index].name {
-        Name::CodeFencedFenceMeta
-        | Name::MathFlowFenceMeta
+        Name::MathFlowFenceMeta
         | Name::MdxJsxTextTag
         | Name::MdxTextExpression
         | Name::Resource => {
@@ -389,6 +658,7 @@ fn exit(context: &mut CompileContext) {
         Name::CodeFenced | Name::CodeIndented | Name::MathFlow => on_exit_raw_flow(context),
         Name::CodeFencedFence | Name::MathFlowFence => on_exit_raw_flow_fence(context),
         Name::CodeFencedFenceInfo => on_exit_raw_flow_fence_info(context),
+        Name::CodeFencedFenceMeta => on_exit_raw_flow_fence_meta(context),
         Name::CodeFlowChunk | Name::MathFlowChunk => on_exit_raw_flow_chunk(context),
         Name::CodeText | Name::MathText => on_exit_raw_text(context),
         Name::Definition => on_exit_definition(context),
@@ -452,39 +722,29 @@ fn on_enter_buffer(context: &mut CompileContext) {
 fn on_enter_block_quote(context: &mut CompileContext) {
     context.tight_stack.push(false);
     context.line_ending_if_needed();
-    context.push("<blockquote>");
+    context.push("<blockquote");
+    context.sourcepos(context.index);
+    context.push(">");
 }
 
 /// Handle [`Enter`][Kind::Enter]:[`CodeIndented`][Name::CodeIndented].
 fn on_enter_code_indented(context: &mut CompileContext) {
     context.raw_flow_seen_data = Some(false);
     context.line_ending_if_needed();
-    context.push("<pre><code>");
+    context.buffer();
 }
 
 /// Handle [`Enter`][Kind::Enter]:{[`CodeFenced`][Name::CodeFenced],[`MathFlow`][Name::MathFlow]}.
 fn on_enter_raw_flow(context: &mut CompileContext) {
     context.raw_flow_seen_data = Some(false);
     context.line_ending_if_needed();
-    // Note that no `>` is used, which is added later (due to info)
-    context.push("<pre><code");
     context.raw_flow_fences_count = Some(0);
-
-    if context.events[context.index].name == Name::MathFlow {
-        context.push(" class=\"language-math math-display\"");
-    }
+    context.buffer();
 }
 
 /// Handle [`Enter`][Kind::Enter]:{[`CodeText`][Name::CodeText],[`MathText`][Name::MathText]}.
 fn on_enter_raw_text(context: &mut CompileContext) {
     context.raw_text_inside = true;
-    if !context.image_alt_inside {
-        context.push("<code");
-        if context.events[context.index].name == Name::MathText {
-            context.push(" class=\"language-math math-inline\"");
-        }
-        context.push(">");
-    }
     context.buffer();
 }
 
@@ -548,7 +808,7 @@ fn on_enter_gfm_table(context: &mut CompileContext) {
     let align = gfm_table_align(context.events, context.index);
     context.gfm_table_align = Some(align);
     context.line_ending_if_needed();
-    context.push("<table>");
+    context.buffer_children();
 }
 
 /// Handle [`Enter`][Kind::Enter]:[`GfmTableBody`][Name::GfmTableBody].
@@ -574,6 +834,8 @@ fn on_enter_gfm_table_cell(context: &mut CompileContext) {
             context.push("<td");
         }
 
+        context.sourcepos(context.index);
+
         match value {
             AlignKind::Left => context.push(" align=\"left\""),
             AlignKind::Right => context.push(" align=\"right\""),
@@ -595,27 +857,40 @@ fn on_enter_gfm_table_head(context: &mut CompileContext) {
 /// Handle [`Enter`][Kind::Enter]:[`GfmTableRow`][Name::GfmTableRow].
 fn on_enter_gfm_table_row(context: &mut CompileContext) {
     context.line_ending_if_needed();
-    context.push("<tr>");
+    context.push("<tr");
+    context.sourcepos(context.index);
+    context.push(">");
 }
 
 /// Handle [`Enter`][Kind::Enter]:[`GfmTaskListItemCheck`][Name::GfmTaskListItemCheck].
 fn on_enter_gfm_task_list_item_check(context: &mut CompileContext) {
     if !context.image_alt_inside {
-        context.push("<input type=\"checkbox\" disabled=\"\" ");
+        let disabled = context.empty_attribute("disabled", true);
+        context.push("<input type=\"checkbox\"");
+        context.push(&disabled);
     }
 }
 
 /// Handle [`Enter`][Kind::Enter]:[`HtmlFlow`][Name::HtmlFlow].
 fn on_enter_html_flow(context: &mut CompileContext) {
-    context.line_ending_if_needed();
-    if context.options.allow_dangerous_html {
+    if context.raw_html_buffered() {
+        context.buffer();
         context.encode_html = false;
+    } else {
+        context.line_ending_if_needed();
+
+        if context.options.allow_dangerous_html {
+            context.encode_html = false;
+        }
     }
 }
 
 /// Handle [`Enter`][Kind::Enter]:[`HtmlText`][Name::HtmlText].
 fn on_enter_html_text(context: &mut CompileContext) {
-    if context.options.allow_dangerous_html {
+    if context.raw_html_buffered() {
+        context.buffer();
+        context.encode_html = false;
+    } else if context.options.allow_dangerous_html {
         context.encode_html = false;
     }
 }
@@ -657,6 +932,7 @@ fn on_enter_list(context: &mut CompileContext) {
     } else {
         "<ul"
     });
+    context.sourcepos(context.index);
     context.list_expect_first_marker = Some(true);
 }
 
@@ -667,18 +943,29 @@ fn on_enter_list_item_marker(context: &mut CompileContext) {
     }
 
     context.line_ending_if_needed();
-
-    context.push("<li>");
+    context.buffer_children();
     context.list_expect_first_marker = Some(false);
 }
 
 /// Handle [`Enter`][Kind::Enter]:[`Paragraph`][Name::Paragraph].
 fn on_enter_paragraph(context: &mut CompileContext) {
-    let tight = context.tight_stack.last().unwrap_or(&false);
+    let tight = *context.tight_stack.last().unwrap_or(&false);
 
-    if !tight {
+    if context.options.image_figure && paragraph_only_image(context.events, context.index) {
+        context.paragraph_figure = true;
+
+        if !tight {
+            context.line_ending_if_needed();
+        }
+
+        context.push("<figure");
+        context.sourcepos(context.index);
+        context.push(">");
+    } else if !tight {
         context.line_ending_if_needed();
-        context.push("<p>");
+        context.push("<p");
+        context.sourcepos(context.index);
+        context.push(">");
     }
 }
 
@@ -734,7 +1021,8 @@ fn on_exit_autolink_protocol(context: &mut CompileContext) {
 /// Handle [`Exit`][Kind::Exit]:{[`HardBreakEscape`][Name::HardBreakEscape],[`HardBreakTrailing`][Name::HardBreakTrailing]}.
 fn on_exit_break(context: &mut CompileContext) {
     if !context.image_alt_inside {
-        context.push("<br />");
+        context.push("<br");
+        context.push(context.void_end());
     }
 }
 
@@ -747,6 +1035,7 @@ fn on_exit_blank_line_ending(context: &mut CompileContext) {
 
 /// Handle [`Exit`][Kind::Exit]:[`BlockQuote`][Name::BlockQuote].
 fn on_exit_block_quote(context: &mut CompileContext) {
+    close_html(context, false);
     context.tight_stack.pop();
     context.line_ending_if_needed();
     context.slurp_one_line_ending = false;
@@ -805,7 +1094,6 @@ fn on_exit_raw_flow_fence(context: &mut CompileContext) {
         .expect("expected `raw_flow_fences_count`");
 
     if count == 0 {
-        context.push(">");
         context.slurp_one_line_ending = true;
     }
 
@@ -816,10 +1104,12 @@ fn on_exit_raw_flow_fence(context: &mut CompileContext) {
 ///
 /// Note: math (flow) does not support `info`.
 fn on_exit_raw_flow_fence_info(context: &mut CompileContext) {
-    let value = context.resume();
-    context.push(" class=\"language-");
-    context.push(&value);
-    context.push("\"");
+    context.raw_flow_info = Some(context.resume());
+}
+
+/// Handle [`Exit`][Kind::Exit]:[`CodeFencedFenceMeta`][Name::CodeFencedFenceMeta].
+fn on_exit_raw_flow_fence_meta(context: &mut CompileContext) {
+    context.raw_flow_meta = Some(context.resume());
 }
 
 /// Handle [`Exit`][Kind::Exit]:{[`CodeFenced`][Name::CodeFenced],[`CodeIndented`][Name::CodeIndented],[`MathFlow`][Name::MathFlow]}.
@@ -851,7 +1141,69 @@ fn on_exit_raw_flow(context: &mut CompileContext) {
         context.line_ending_if_needed();
     }
 
-    context.push("</code></pre>");
+    let code = context.resume();
+    let attributes = context.sourcepos_exit();
+    let info = context.raw_flow_info.take();
+    let meta = context.raw_flow_meta.take();
+    let value = if context.events[context.index].name == Name::MathFlow {
+        render_math(context, &code, true).unwrap_or_else(|| {
+            format!(
+                "<pre{}><code class=\"language-math math-display\">{}</code></pre>",
+                attributes, code
+            )
+        })
+    } else if let Some(diagram) = info.as_deref().and_then(|lang| {
+        let lang = decode_character_references(lang, false);
+        context
+            .options
+            .diagrams
+            .iter()
+            .find(|d| d.lang.eq_ignore_ascii_case(&lang))
+    }) {
+        let rendered =
+            context.options.render_diagram.as_ref().and_then(|render| {
+                render(&diagram.lang, &decode_character_references(&code, false))
+            });
+
+        rendered.unwrap_or_else(|| {
+            format!(
+                "<{0} class=\"{1}\"{2}>{3}</{0}>",
+                diagram.tag_name,
+                encode(&diagram.class_name, true),
+                attributes,
+                code
+            )
+        })
+    } else {
+        let options = context.options;
+        let highlighted = options.highlight.as_ref().and_then(|highlight| {
+            // Code, info, and meta are encoded, so decode them.
+            highlight(
+                info.as_deref()
+                    .map(|d| decode_character_references(d, false))
+                    .as_deref(),
+                meta.as_deref()
+                    .map(|d| decode_character_references(d, false))
+                    .as_deref(),
+                &decode_character_references(&code, false),
+            )
+        });
+        let mut code = highlighted.unwrap_or(code);
+
+        if options.code_line_numbers || options.code_highlight_lines {
+            let highlighted_lines = if options.code_highlight_lines {
+                meta.as_deref().map(meta_lines).unwrap_or_default()
+            } else {
+                vec![]
+            };
+            code = code_lines(&code, options.code_line_numbers, &highlighted_lines);
+        }
+
+        context
+            .render()
+            .code(info.as_deref(), meta.as_deref(), &attributes, &code)
+    };
+    context.push(&value);
 
     if let Some(count) = context.raw_flow_fences_count.take() {
         if count < 2 {
@@ -906,9 +1258,21 @@ fn on_exit_raw_text(context: &mut CompileContext) {
     }
 
     context.raw_text_inside = false;
-    context.push(str::from_utf8(&bytes).unwrap());
+    let value = str::from_utf8(&bytes).unwrap();
 
-    if !context.image_alt_inside {
+    if context.image_alt_inside {
+        context.push(value);
+    } else if context.events[context.index].name == Name::MathText {
+        if let Some(html) = render_math(context, value, false) {
+            context.push(&html);
+        } else {
+            context.push("<code class=\"language-math math-inline\">");
+            context.push(value);
+            context.push("</code>");
+        }
+    } else {
+        context.push("<code>");
+        context.push(value);
         context.push("</code>");
     }
 }
@@ -1113,7 +1477,10 @@ fn on_exit_gfm_footnote_call(context: &mut CompileContext) {
                 .to_string(),
         );
     }
-    context.push("\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">");
+    context.push("\"");
+    let footnote_ref = context.empty_attribute("data-footnote-ref", false);
+    context.push(&footnote_ref);
+    context.push(" aria-describedby=\"footnote-label\">");
 
     context.push(&(call_index + 1).to_string());
     context.push("</a></sup>");
@@ -1136,6 +1503,7 @@ fn on_exit_gfm_footnote_definition_prefix(context: &mut CompileContext) {
 
 /// Handle [`Exit`][Kind::Exit]:[`GfmFootnoteDefinition`][Name::GfmFootnoteDefinition].
 fn on_exit_gfm_footnote_definition(context: &mut CompileContext) {
+    close_html(context, false);
     let value = context.resume();
     let indices = context.gfm_footnote_definition_stack.pop().unwrap();
     context.tight_stack.pop();
@@ -1156,7 +1524,9 @@ fn on_exit_gfm_strikethrough(context: &mut CompileContext) {
 fn on_exit_gfm_table(context: &mut CompileContext) {
     context.gfm_table_align = None;
     context.line_ending_if_needed();
-    context.push("</table>");
+    let children = context.resume_children();
+    let value = context.render().table(&context.sourcepos_exit(), &children);
+    context.push(&value);
 }
 
 /// Handle [`Exit`][Kind::Exit]:[`GfmTableBody`][Name::GfmTableBody].
@@ -1167,6 +1537,7 @@ fn on_exit_gfm_table_body(context: &mut CompileContext) {
 
 /// Handle [`Exit`][Kind::Exit]:[`GfmTableCell`][Name::GfmTableCell].
 fn on_exit_gfm_table_cell(context: &mut CompileContext) {
+    close_html(context, true);
     let align = context.gfm_table_align.as_ref().unwrap();
 
     if context.gfm_table_column < align.len() {
@@ -1211,14 +1582,15 @@ fn on_exit_gfm_table_row(context: &mut CompileContext) {
 /// Handle [`Exit`][Kind::Exit]:[`GfmTaskListItemCheck`][Name::GfmTaskListItemCheck].
 fn on_exit_gfm_task_list_item_check(context: &mut CompileContext) {
     if !context.image_alt_inside {
-        context.push("/>");
+        context.push(context.void_end());
     }
 }
 
 /// Handle [`Exit`][Kind::Exit]:[`GfmTaskListItemValueChecked`][Name::GfmTaskListItemValueChecked].
 fn on_exit_gfm_task_list_item_value_checked(context: &mut CompileContext) {
     if !context.image_alt_inside {
-        context.push("checked=\"\" ");
+        let checked = context.empty_attribute("checked", true);
+        context.push(&checked);
     }
 }
 
@@ -1229,9 +1601,12 @@ fn on_exit_heading_atx(context: &mut CompileContext) {
         .take()
         .expect("`heading_atx_rank` must be set in headings");
 
-    context.push("</h");
-    context.push(&rank.to_string());
-    context.push(">");
+    close_html(context, true);
+    let children = context.resume();
+    let value = context
+        .render()
+        .heading(rank, &context.sourcepos_exit(), &children);
+    context.push(&value);
 }
 
 /// Handle [`Exit`][Kind::Exit]:[`HeadingAtxSequence`][Name::HeadingAtxSequence].
@@ -1244,10 +1619,9 @@ fn on_exit_heading_atx_sequence(context: &mut CompileContext) {
         )
         .len();
         context.line_ending_if_needed();
-        context.heading_atx_rank = Some(rank);
-        context.push("<h");
-        context.push(&rank.to_string());
-        context.push(">");
+        context.heading_atx_rank =
+            Some(u8::try_from(rank).expect("expected rank to be at most `6`"));
+        context.buffer();
     }
 }
 
@@ -1259,6 +1633,7 @@ fn on_exit_heading_atx_text(context: &mut CompileContext) {
 
 /// Handle [`Exit`][Kind::Exit]:[`HeadingSetextText`][Name::HeadingSetextText].
 fn on_exit_heading_setext_text(context: &mut CompileContext) {
+    close_html(context, true);
     let buf = context.resume();
     context.heading_setext_buffer = Some(buf);
     context.slurp_one_line_ending = true;
@@ -1272,20 +1647,48 @@ fn on_exit_heading_setext_underline_sequence(context: &mut CompileContext) {
         .expect("`heading_atx_rank` must be set in headings");
     let position = Position::from_exit_event(context.events, context.index);
     let head = context.bytes[position.start.index];
-    let rank = if head == b'-' { "2" } else { "1" };
+    let rank = if head == b'-' { 2 } else { 1 };
+    let attributes = context.sourcepos_attribute(skip::to_back(
+        context.events,
+        context.index,
+        &[Name::HeadingSetext],
+    ));
 
     context.line_ending_if_needed();
-    context.push("<h");
-    context.push(rank);
-    context.push(">");
-    context.push(&text);
-    context.push("</h");
-    context.push(rank);
-    context.push(">");
+    let value = context.render().heading(rank, &attributes, &text);
+    context.push(&value);
 }
 
 /// Handle [`Exit`][Kind::Exit]:{[`HtmlFlow`][Name::HtmlFlow],[`HtmlText`][Name::HtmlText]}.
 fn on_exit_html(context: &mut CompileContext) {
+    if context.raw_html_buffered() {
+        let value = context.resume();
+        let value = if let Some(schema) = &context.options.sanitize_html {
+            sanitize_html(
+                &value,
+                schema,
+                &mut context.html_stack,
+                context.events[context.index].name == Name::HtmlText,
+                context.tight_stack.len(),
+                context.image_alt_inside,
+                context.options.html_syntax != HtmlSyntax::Html5,
+            )
+        } else {
+            to_xhtml(&value)
+        };
+
+        if context.events[context.index].name == Name::HtmlFlow {
+            if value.is_empty() {
+                // Ignore the line ending after empty HTML (flow).
+                context.slurp_one_line_ending = true;
+            } else {
+                context.line_ending_if_needed();
+            }
+        }
+
+        context.push(&value);
+    }
+
     context.encode_html = true;
 }
 
@@ -1297,7 +1700,10 @@ fn on_exit_html_data(context: &mut CompileContext) {
     );
     let value = slice.as_str();
 
-    let encoded = if context.options.gfm_tagfilter && context.options.allow_dangerous_html {
+    let encoded = if context.options.gfm_tagfilter
+        && context.options.allow_dangerous_html
+        && context.options.sanitize_html.is_none()
+    {
         encode(&gfm_tagfilter(value), context.encode_html)
     } else {
         encode(value, context.encode_html)
@@ -1354,6 +1760,7 @@ fn on_exit_list(context: &mut CompileContext) {
 
 /// Handle [`Exit`][Kind::Exit]:[`ListItem`][Name::ListItem].
 fn on_exit_list_item(context: &mut CompileContext) {
+    close_html(context, false);
     let tight = context.tight_stack.last().unwrap_or(&false);
     let before_item = skip::opt_back(
         context.events,
@@ -1378,7 +1785,11 @@ fn on_exit_list_item(context: &mut CompileContext) {
         context.line_ending_if_needed();
     }
 
-    context.push("</li>");
+    let children = context.resume_children();
+    let value = context
+        .render()
+        .list_item(&context.sourcepos_exit(), &children);
+    context.push(&value);
 }
 
 /// Handle [`Exit`][Kind::Exit]:[`ListItemValue`][Name::ListItemValue].
@@ -1439,84 +1850,81 @@ fn on_exit_media(context: &mut CompileContext) {
         None
     };
 
-    if !is_in_image {
-        if media.image {
-            context.push("<img src=\"");
-        } else {
-            context.push("<a href=\"");
-        };
-
-        let destination = if let Some(index) = definition_index {
-            context.definitions[index].destination.as_ref()
-        } else {
-            media.destination.as_ref()
-        };
-
-        if let Some(destination) = destination {
-            let url = if context.options.allow_dangerous_protocol {
-                sanitize(destination)
-            } else {
-                sanitize_with_protocols(
-                    destination,
-                    if media.image {
-                        &SAFE_PROTOCOL_SRC
-                    } else {
-                        &SAFE_PROTOCOL_HREF
-                    },
-                )
-            };
-            context.push(&url);
-        }
-
-        if media.image {
-            context.push("\" alt=\"");
-        };
-    }
-
-    if media.image {
+    if is_in_image {
         context.push(&label);
+        return;
     }
 
-    if !is_in_image {
-        context.push("\"");
-
-        let title = if let Some(index) = definition_index {
-            context.definitions[index].title.clone()
-        } else {
-            media.title
-        };
+    let image = media.image;
+    let kind = if image { UrlKind::Image } else { UrlKind::Link };
+    // URLs of definitions are rewritten where they are used.
+    let destination = if let Some(index) = definition_index {
+        context.definitions[index].destination.clone()
+    } else {
+        media.destination
+    }
+    .and_then(|url| rewrite_url(context, url, kind));
 
-        if let Some(title) = title {
-            context.push(" title=\"");
-            context.push(&title);
-            context.push("\"");
-        };
+    let url = destination.map_or(String::new(), |destination| {
+        sanitize_with_options(&destination, context.options, image)
+    });
 
-        if media.image {
-            context.push(" /");
-        }
+    let title = if let Some(index) = definition_index {
+        context.definitions[index].title.clone()
+    } else {
+        media.title
+    };
 
-        context.push(">");
+    let attributes = if media.image {
+        context.options.image_attributes.as_deref()
+    } else {
+        link_external_attributes(context.options, &url)
     }
+    .map_or(String::new(), |attributes| format!(" {}", attributes));
 
-    if !media.image {
-        context.push(&label);
+    let value = if media.image {
+        context.render().image(
+            context.options.html_syntax,
+            &url,
+            &label,
+            title.as_deref(),
+            &attributes,
+        )
+    } else {
+        context
+            .render()
+            .link(&url, title.as_deref(), &attributes, &label)
+    };
 
-        if !is_in_image {
-            context.push("</a>");
-        }
+    context.push(&value);
+
+    if media.image && context.paragraph_figure && context.media_stack.is_empty() {
+        context.figure_caption = title;
     }
 }
 
 /// Handle [`Exit`][Kind::Exit]:[`Paragraph`][Name::Paragraph].
 fn on_exit_paragraph(context: &mut CompileContext) {
-    let tight = context.tight_stack.last().unwrap_or(&false);
+    let tight = *context.tight_stack.last().unwrap_or(&false);
+    close_html(context, true);
 
-    if *tight {
-        context.slurp_one_line_ending = true;
-    } else {
+    if context.paragraph_figure {
+        context.paragraph_figure = false;
+
+        if let Some(caption) = context.figure_caption.take() {
+            context.push("<figcaption>");
+            context.push(&caption);
+            context.push("</figcaption>");
+        }
+
+        context.push("</figure>");
+    } else if !tight {
         context.push("</p>");
     }
+
+    if tight {
+        context.slurp_one_line_ending = true;
+    }
 }
 
 /// Handle [`Exit`][Kind::Exit]:[`ReferenceString`][Name::ReferenceString].
@@ -1551,13 +1959,106 @@ fn on_exit_strong(context: &mut CompileContext) {
 /// Handle [`Exit`][Kind::Exit]:[`ThematicBreak`][Name::ThematicBreak].
 fn on_exit_thematic_break(context: &mut CompileContext) {
     context.line_ending_if_needed();
-    context.push("<hr />");
+    context.push("<hr");
+    context.sourcepos(skip::to_back(
+        context.events,
+        context.index - 1,
+        &[Name::ThematicBreak],
+    ));
+    context.push(context.void_end());
+}
+
+/// Turn math into HTML with [`math`][CompileOptions::math], if given.
+///
+/// `value` is encoded, `display` is whether it is math (flow).
+fn render_math(context: &CompileContext, value: &str, display: bool) -> Option<String> {
+    context
+        .options
+        .math
+        .as_ref()
+        .and_then(|math| math(&decode_character_references(value, false), display))
+}
+
+/// Wrap each line of code in an element.
+///
+/// Lines are numbered if `numbers` is on, and lines in `highlighted` (pairs
+/// of start and end line numbers, inclusive) get a `highlighted` class.
+fn code_lines(value: &str, numbers: bool, highlighted: &[(usize, usize)]) -> String {
+    let bytes = value.as_bytes();
+    let mut result = String::with_capacity(value.len());
+    let mut line = 1;
+    let mut start = 0;
+    let mut index = 0;
+
+    while start < bytes.len() {
+        while index < bytes.len() && !matches!(bytes[index], b'\n' | b'\r') {
+            index += 1;
+        }
+
+        result.push_str("<span class=\"line");
+        if highlighted
+            .iter()
+            .any(|(from, to)| *from <= line && line <= *to)
+        {
+            result.push_str(" highlighted");
+        }
+        result.push('"');
+        if numbers {
+            result.push_str(" data-line-number=\"");
+            result.push_str(&line.to_string());
+            result.push('"');
+        }
+        result.push('>');
+        result.push_str(&value[start..index]);
+        result.push_str("</span>");
+
+        start = index;
+
+        if index < bytes.len() && bytes[index] == b'\r' {
+            index += 1;
+        }
+
+        if index < bytes.len() && bytes[index] == b'\n' {
+            index += 1;
+        }
+
+        result.push_str(&value[start..index]);
+        start = index;
+        line += 1;
+    }
+
+    result
+}
+
+/// Get the lines listed in the meta of code, such as `{1,3-5}`, as pairs
+/// of start and end line numbers (inclusive).
+fn meta_lines(meta: &str) -> Vec<(usize, usize)> {
+    let mut result = vec![];
+
+    if let Some(open) = meta.find('{') {
+        if let Some(close) = meta[open..].find('}') {
+            for range in meta[open + 1..open + close].split(',') {
+                let mut parts = range.splitn(2, '-');
+                let from = parts.next().unwrap().trim().parse::<usize>();
+                let to = parts.next().map(|d| d.trim().parse::<usize>());
+
+                match (from, to) {
+                    (Ok(from), None) => result.push((from, from)),
+                    (Ok(from), Some(Ok(to))) => result.push((from, to)),
+                    _ => {}
+                }
+            }
+        }
+    }
+
+    result
 }
 
 /// Generate a footnote section.
 fn generate_footnote_section(context: &mut CompileContext) {
     context.line_ending_if_needed();
-    context.push("<section data-footnotes=\"\" class=\"footnotes\"><");
+    context.buffer();
+    context.push("<");
     if let Some(ref value) = context.options.gfm_footnote_label_tag_name {
         context.push(&encode(value, context.encode_html));
     } else {
@@ -1594,7 +2095,11 @@ fn generate_footnote_section(context: &mut CompileContext) {
     context.line_ending();
     context.push("</ol>");
     context.line_ending();
-    context.push("</section>");
+    let children = context.resume();
+    let value = context
+        .render()
+        .footnote_section(context.options.html_syntax, &children);
+    context.push(&value);
     context.line_ending();
 }
 
@@ -1649,7 +2154,9 @@ fn generate_footnote_item(context: &mut CompileContext, index: usize) {
             backreferences.push('-');
             backreferences.push_str(&(reference_index + 1).to_string());
         }
-        backreferences.push_str("\" data-footnote-backref=\"\" aria-label=\"");
+        backreferences.push('"');
+        backreferences.push_str(&context.empty_attribute("data-footnote-backref", false));
+        backreferences.push_str(" aria-label=\"");
         if let Some(ref value) = context.options.gfm_footnote_back_label {
             backreferences.push_str(&encode(value, context.encode_html));
         } else {
@@ -1700,6 +2207,124 @@ fn generate_footnote_item(context: &mut CompileContext, index: usize) {
     context.push("</li>");
 }
 
+/// Rewrite `url` of `kind` with the `rewrite_url` option, if given, for the
+/// construct exited at the current event.
+fn rewrite_url(context: &CompileContext, url: String, kind: UrlKind) -> Option<String> {
+    if let Some(rewrite_url) = &context.options.rewrite_url {
+        let position = Position::from_exit_event(context.events, context.index);
+        let position = unist::Position::new(
+            position.start.line,
+            position.start.column,
+            position.start.index,
+            position.end.line,
+            position.end.column,
+            position.end.index,
+        );
+        rewrite_url(&url, &kind, &position)
+    } else {
+        Some(url)
+    }
+}
+
+/// Close elements opened in sanitized raw HTML: those opened in the current
+/// text (if `inline`), or those opened in the current container.
+fn close_html(context: &mut CompileContext, inline: bool) {
+    let value = close_html_elements(&mut context.html_stack, inline, context.tight_stack.len());
+
+    if !value.is_empty() {
+        if !inline {
+            context.line_ending_if_needed();
+        }
+
+        context.push(&value);
+    }
+}
+
+/// Get the attributes to add to a link to `url`, if it is external.
+fn link_external_attributes<'a>(options: &'a CompileOptions, url: &str) -> Option<&'a str> {
+    let attributes = options.link_external_attributes.as_ref()?;
+    let authority = url_authority(url)?;
+    // Drop user info and port.
+    let host = authority.rsplit('@').next().unwrap();
+    let host = if host.starts_with('[') {
+        &host[0..host.find(']').map_or(host.len(), |index| index + 1)]
+    } else {
+        host.split(':').next().unwrap()
+    };
+    let host = host.trim_end_matches('.').to_lowercase();
+
+    if host.is_empty() {
+        return None;
+    }
+
+    let internal = options.link_internal_hosts.as_ref().map_or(false, |hosts| {
+        hosts.iter().any(|internal| {
+            let internal = internal.trim_end_matches('.').to_lowercase();
+            host == internal || host.ends_with(&format!(".{}", internal))
+        })
+    });
+
+    if internal {
+        None
+    } else {
+        Some(attributes)
+    }
+}
+
+/// Get the authority of `url`, the way browsers see it.
+///
+/// Browsers do not need two slashes after a special scheme, so `https:a.b`,
+/// `https:/a.b`, and `https:///a.b` all link to the host `a.b`.
+fn url_authority(url: &str) -> Option<&str> {
+    let parts = parse_url(url);
+    let scheme = match parts.scheme {
+        Some(scheme)
+            if ["ftp", "http", "https", "ws", "wss"]
+                .iter()
+                .any(|d| scheme.eq_ignore_ascii_case(d)) =>
+        {
+            scheme
+        }
+        _ => return parts.authority,
+    };
+
+    let end = url.find(&['?', '#'][..]).unwrap_or(url.len());
+    let rest = url[scheme.len() + 1..end].trim_start_matches('/');
+    Some(&rest[0..rest.find('/').unwrap_or(rest.len())])
+}
+
+/// Check whether the paragraph entered at `index` contains only an image.
+fn paragraph_only_image(events: &[Event], index: usize) -> bool {
+    let mut index = index + 1;
+    let mut balance = 0;
+
+    if !(events[index].kind == Kind::Enter && events[index].name == Name::Image) {
+        return false;
+    }
+
+    while index < events.len() {
+        let event = &events[index];
+
+        if event.name == Name::Image {
+            if event.kind == Kind::Enter {
+                balance += 1;
+            } else {
+                balance -= 1;
+
+                if balance == 0 {
+                    break;
+                }
+            }
+        }
+
+        index += 1;
+    }
+
+    index + 1 < events.len()
+        && events[index + 1].kind == Kind::Exit
+        && events[index + 1].name == Name::Paragraph
+}
+
 /// Generate an autolink (used by unicode autolinks and GFM autolink literals).
 fn generate_autolink(
     context: &mut CompileContext,
@@ -1718,27 +2343,26 @@ fn generate_autolink(
         index += 1;
     }
 
-    if !context.image_alt_inside && (!is_in_link || !is_gfm_literal) {
-        context.push("<a href=\"");
-        let url = if let Some(protocol) = protocol {
-            format!("{}{}", protocol, value)
-        } else {
-            value.into()
-        };
+    let children = encode(value, context.encode_html);
 
-        let url = if context.options.allow_dangerous_protocol {
-            sanitize(&url)
-        } else {
-            sanitize_with_protocols(&url, &SAFE_PROTOCOL_HREF)
-        };
-
-        context.push(&url);
-        context.push("\">");
+    if context.image_alt_inside || (is_in_link && is_gfm_literal) {
+        context.push(&children);
+        return;
     }
 
-    context.push(&encode(value, context.encode_html));
+    let url = if let Some(protocol) = protocol {
+        format!("{}{}", protocol, value)
+    } else {
+        value.into()
+    };
 
-    if !context.image_alt_inside && (!is_in_link || !is_gfm_literal) {
-        context.push("</a>");
-    }
+    let url = rewrite_url(context, url, UrlKind::Autolink)
+        .map(|url| sanitize_with_options(&url, context.options, false))
+        .unwrap_or_default();
+
+    let attributes = link_external_attributes(context.options, &url)
+        .map_or(String::new(), |attributes| format!(" {}", attributes));
+
+    let value = context.render().link(&url, None, &attributes, &children);
+    context.push(&value);
 }
@@ -0,0 +1,203 @@
+//! Turn events into a complete HTML document.
+
+use crate::event::{Event, Kind, Name};
+use crate::mdast::Node;
+use crate::util::{encode::encode, frontmatter::from_node as frontmatter_from_node};
+use crate::{to_html, to_mdast, CompileOptions, HtmlSyntax, ParseOptions};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Configuration that describes how to turn markdown into an HTML document.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{DocumentOptions, ParseOptions};
+/// # fn main() {
+///
+/// // Use the default trait to get an English document without styles:
+/// let default = DocumentOptions::default();
+///
+/// // Or, configure it:
+/// let custom = DocumentOptions {
+///   parse: ParseOptions::gfm(),
+///   lang: Some("nl".into()),
+///   stylesheets: vec!["style.css".into()],
+///   ..DocumentOptions::default()
+/// };
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct DocumentOptions {
+    /// Configuration that describes how to parse from markdown.
+    ///
+    /// Turn on `frontmatter` in its constructs to get the title from
+    /// frontmatter.
+    pub parse: ParseOptions,
+    /// Configuration that describes how to compile to HTML.
+    ///
+    /// Its `html_syntax` is also used for the HTML around the body.
+    pub compile: CompileOptions,
+    /// Language of the document, used as the `lang` attribute.
+    ///
+    /// The default is `Some("en")`.
+    /// Pass `None` to not add the attribute.
+    pub lang: Option<String>,
+    /// URLs of stylesheets to link.
+    ///
+    /// The default is `vec![]`.
+    pub stylesheets: Vec<String>,
+}
+
+impl Default for DocumentOptions {
+    /// English, `CommonMark`, and no stylesheets.
+    fn default() -> Self {
+        Self {
+            parse: ParseOptions::default(),
+            compile: CompileOptions::default(),
+            lang: Some("en".into()),
+            stylesheets: vec![],
+        }
+    }
+}
+
+/// Turn events and bytes into a complete HTML document.
+///
+/// The title is the `title` field in frontmatter, if it is a string, or
+/// otherwise the text content of the first heading.
+/// Frontmatter that cannot be parsed is ignored.
+pub fn compile(
+    events: &[Event],
+    bytes: &[u8],
+    options: &DocumentOptions,
+) -> Result<String, String> {
+    let title = construct(events, bytes, options, &[Name::Frontmatter])
+        .and_then(|node| frontmatter_from_node(&node))
+        .and_then(Result::ok)
+        .and_then(|d| d.get("title").and_then(|d| d.as_str()).map(String::from))
+        .or_else(|| {
+            construct(
+                events,
+                bytes,
+                options,
+                &[Name::HeadingAtx, Name::HeadingSetext],
+            )
+            .map(|node| node.to_string())
+        });
+
+    let body = to_html::compile(
+        events,
+        bytes,
+        &options.compile,
+        options.parse.cancel.as_deref(),
+    )?;
+    let line_ending = to_html::line_ending(events, bytes, &options.compile);
+    let line_ending = line_ending.as_str();
+    let xhtml = options.compile.html_syntax == HtmlSyntax::Xhtml;
+    let void_end = if options.compile.html_syntax == HtmlSyntax::Html5 {
+        ">"
+    } else {
+        " />"
+    };
+    let mut result = String::new();
+
+    result.push_str(if xhtml {
+        "<!DOCTYPE html>"
+    } else {
+        "<!doctype html>"
+    });
+    result.push_str(line_ending);
+    result.push_str("<html");
+
+    if xhtml {
+        result.push_str(" xmlns=\"http://www.w3.org/1999/xhtml\"");
+    }
+
+    if let Some(lang) = &options.lang {
+        let lang = encode(lang, true);
+        result.push_str(" lang=\"");
+        result.push_str(&lang);
+        result.push('"');
+
+        if xhtml {
+            result.push_str(" xml:lang=\"");
+            result.push_str(&lang);
+            result.push('"');
+        }
+    }
+
+    result.push('>');
+    result.push_str(line_ending);
+    result.push_str("<head>");
+    result.push_str(line_ending);
+    result.push_str("<meta charset=\"utf-8\"");
+    result.push_str(void_end);
+    result.push_str(line_ending);
+
+    if let Some(title) = title {
+        // Headings can span several lines.
+        let title = title.split_whitespace().collect::<Vec<_>>().join(" ");
+        result.push_str("<title>");
+        result.push_str(&encode(&title, true));
+        result.push_str("</title>");
+        result.push_str(line_ending);
+    }
+
+    for stylesheet in &options.stylesheets {
+        result.push_str("<link rel=\"stylesheet\" href=\"");
+        result.push_str(&encode(stylesheet, true));
+        result.push('"');
+        result.push_str(void_end);
+        result.push_str(line_ending);
+    }
+
+    result.push_str("</head>");
+    result.push_str(line_ending);
+    result.push_str("<body>");
+    result.push_str(line_ending);
+
+    if !body.is_empty() {
+        result.push_str(&body);
+
+        if !body.ends_with(['\n', '\r'].as_ref()) {
+            result.push_str(line_ending);
+        }
+    }
+
+    result.push_str("</body>");
+    result.push_str(line_ending);
+    result.push_str("</html>");
+    result.push_str(line_ending);
+
+    Ok(result)
+}
+
+/// Turn the first construct named one of `names` into a node.
+///
+/// Only the events of that construct are compiled, so the rest of the
+/// document does not need to be turned into a syntax tree.
+fn construct(
+    events: &[Event],
+    bytes: &[u8],
+    options: &DocumentOptions,
+    names: &[Name],
+) -> Option<Node> {
+    let enter = events
+        .iter()
+        .position(|d| d.kind == Kind::Enter && names.contains(&d.name))?;
+    let name = &events[enter].name;
+    // These constructs do not contain themselves.
+    let exit = enter
+        + events[enter..]
+            .iter()
+            .position(|d| d.kind == Kind::Exit && &d.name == name)?;
+    let tree = to_mdast::compile(&events[enter..=exit], bytes, &options.parse).ok()?;
+
+    match tree {
+        Node::Root(root) => root.children.into_iter().next(),
+        _ => None,
+    }
+}
@@ -0,0 +1,1802 @@
+//! Turn a syntax tree into markdown.
+//!
+//! The markdown is canonical: the style of things (such as which markers
+//! are used) comes from [`FormatOptions`][], not from how the tree was
+//! written.
+//! Text is escaped where needed, so that parsing the markdown again (with
+//! the same constructs) gives the same tree (except for positions).
+//! Values that are not markdown (HTML, frontmatter, and MDX ESM and
+//! expressions) are kept as they are.
+//! When formatting, MDX JSX tags are kept as they are too; otherwise, they
+//! are made from the tree.
+
+use crate::event::{Event, Kind as EventKind, Name};
+use crate::mdast::{
+    AlignKind, AttributeContent, AttributeValue, Code, Definition, FootnoteDefinition, Heading,
+    Image, ImageReference, InlineMath, Link, LinkReference, List, ListItem, Math,
+    MdxJsxFlowElement, MdxJsxTextElement, Node, ReferenceKind, Table,
+};
+use crate::parser;
+use crate::to_mdast;
+use crate::unist::Position;
+use crate::util::location::Encoder;
+use crate::util::{
+    char::{classify_opt, Kind as CharacterKind},
+    normalize_identifier::normalize_identifier,
+};
+use crate::ParseOptions;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Configuration that describes how to format markdown.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{AttentionMarker, BulletMarker, FormatOptions, ParseOptions};
+/// # fn main() {
+///
+/// // Use the default trait to format like Prettier:
+/// let prettier = FormatOptions::default();
+///
+/// // Or, mix and match:
+/// let custom = FormatOptions {
+///   parse: ParseOptions::gfm(),
+///   bullet: BulletMarker::Asterisk,
+///   emphasis: AttentionMarker::Asterisk,
+///   ..FormatOptions::default()
+/// };
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct FormatOptions {
+    /// Configuration that describes how to parse from markdown.
+    ///
+    /// Which constructs are on affects what is escaped.
+    /// Frontmatter fences are taken from its `frontmatter_matters`.
+    pub parse: ParseOptions,
+    /// Marker to use for bullets of unordered lists.
+    ///
+    /// The default is `BulletMarker::Dash`.
+    /// When a list directly follows another list, the other marker (`*` for
+    /// `-`, `-` otherwise) is used, as otherwise they would form one list.
+    pub bullet: BulletMarker,
+    /// Marker to use for emphasis.
+    ///
+    /// The default is `AttentionMarker::Underscore`.
+    /// The other marker is used where `_` does not work (inside words) and
+    /// where the same marker would be ambiguous (such as emphasis directly
+    /// in emphasis).
+    pub emphasis: AttentionMarker,
+    /// Marker to use for strong.
+    ///
+    /// The default is `AttentionMarker::Asterisk`.
+    pub strong: AttentionMarker,
+    /// Style of headings.
+    ///
+    /// The default is `HeadingStyle::Atx`.
+    /// Setext is only used for ranks 1 and 2; it is always used for headings
+    /// that span several lines.
+    pub heading: HeadingStyle,
+    /// Marker to use for fenced code.
+    ///
+    /// The default is `FenceMarker::Backtick`.
+    /// Tildes are used when the info string includes a backtick.
+    /// Code is always fenced.
+    pub fence: FenceMarker,
+    /// Whether to pad cells in GFM tables so that columns align.
+    ///
+    /// The default is `true`.
+    pub table_align: bool,
+    /// How to number items in ordered lists.
+    ///
+    /// The default is `ListNumbering::Increment`.
+    pub list_numbering: ListNumbering,
+}
+
+impl Default for FormatOptions {
+    /// Style like Prettier.
+    fn default() -> Self {
+        Self {
+            parse: ParseOptions::default(),
+            bullet: BulletMarker::Dash,
+            emphasis: AttentionMarker::Underscore,
+            strong: AttentionMarker::Asterisk,
+            heading: HeadingStyle::Atx,
+            fence: FenceMarker::Backtick,
+            table_align: true,
+            list_numbering: ListNumbering::Increment,
+        }
+    }
+}
+
+/// Marker to use for bullets of unordered lists.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum BulletMarker {
+    /// Dash (`-`).
+    Dash,
+    /// Asterisk (`*`).
+    Asterisk,
+    /// Plus sign (`+`).
+    Plus,
+}
+
+/// Marker to use for emphasis or strong.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum AttentionMarker {
+    /// Asterisk (`*a*`, `**a**`).
+    Asterisk,
+    /// Underscore (`_a_`, `__a__`).
+    Underscore,
+}
+
+/// Style of headings.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum HeadingStyle {
+    /// ATX (`# a`).
+    Atx,
+    /// Setext (`a` followed by `===`).
+    Setext,
+}
+
+/// Marker to use for fenced code.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum FenceMarker {
+    /// Grave accent (`` ``` ``).
+    Backtick,
+    /// Tilde (`~~~`).
+    Tilde,
+}
+
+/// How to number items in ordered lists.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ListNumbering {
+    /// Count up from the start of the list (`1.`, `2.`, `3.`).
+    Increment,
+    /// Use the start of the list for every item (`1.`, `1.`, `1.`).
+    Same,
+}
+
+/// Where phrasing content is.
+#[derive(Clone, Copy, Debug)]
+struct Context {
+    /// Character before the content.
+    ///
+    /// `None` means the start of a line.
+    before: Option<char>,
+    /// Character after the content.
+    ///
+    /// `None` means the end of a line.
+    after: Option<char>,
+    /// Whether the content starts at the start of a line.
+    line_start: bool,
+    /// Whether the content is in a GFM table cell.
+    table: bool,
+    /// Marker of attention directly around the content.
+    attention: Option<char>,
+    /// Whether the content is in a link, where links cannot be.
+    link: bool,
+}
+
+/// Serialized phrasing node.
+#[derive(Debug)]
+struct Piece<'a> {
+    /// Markdown.
+    value: String,
+    /// Whether this is text, which can be changed.
+    text: bool,
+    /// Attention that still has to be serialized.
+    attention: Option<&'a Node>,
+}
+
+/// Whether to encode characters around attention (`*`, `_`, `~`), as
+/// character references.
+#[derive(Debug)]
+struct EncodeInfo {
+    /// Encode the character inside the marker.
+    inside: bool,
+    /// Encode the character outside the marker.
+    outside: bool,
+}
+
+/// State needed to serialize.
+#[derive(Debug)]
+struct State<'a> {
+    /// Configuration.
+    options: &'a FormatOptions,
+    /// Source of MDX JSX tags, as their start and end offsets and their
+    /// text, when formatting.
+    tags: Vec<(usize, usize, String)>,
+}
+
+/// Turn a syntax tree into markdown.
+pub fn serialize(tree: &Node, options: &FormatOptions) -> String {
+    serialize_with_state(
+        tree,
+        &State {
+            options,
+            tags: vec![],
+        },
+    )
+}
+
+/// Turn a syntax tree into markdown, with `state`.
+fn serialize_with_state(tree: &Node, state: &State) -> String {
+    let mut result = match tree {
+        Node::Root(root) => state.flow(&root.children, None),
+        node if is_phrasing(node) => state.phrasing(
+            core::slice::from_ref(node),
+            Context {
+                before: None,
+                after: None,
+                line_start: true,
+                table: false,
+                attention: None,
+                link: false,
+            },
+        ),
+        node => state.flow(core::slice::from_ref(node), None),
+    };
+
+    // HTML that is not closed would take the final line ending.
+    if !result.is_empty() && !ends_in_unclosed_html(tree) {
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Format markdown.
+pub fn format(value: &str, options: &FormatOptions) -> Result<String, String> {
+    // Positions stay in bytes, to match the events.
+    let (tree, tags) = parser::parse(value, &options.parse)
+        .and_then(|(events, parse_state)| {
+            let tree = to_mdast::compile(&events, parse_state.bytes, &options.parse)?;
+            Ok((tree, mdx_jsx_tags(&events, parse_state.bytes)))
+        })
+        .map_err(|message| {
+            Encoder::new(value.as_bytes(), options.parse.position_encoding).message(&message)
+        })?;
+    let result = serialize_with_state(&tree, &State { options, tags });
+    let bytes = value.as_bytes();
+    let mut index = 0;
+
+    // Use the first line ending in the document.
+    while index < bytes.len() {
+        match bytes[index] {
+            b'\r' if bytes.get(index + 1) == Some(&b'\n') => {
+                return Ok(result.replace('\n', "\r\n"));
+            }
+            b'\r' => return Ok(result.replace('\n', "\r")),
+            b'\n' => break,
+            _ => {}
+        }
+
+        index += 1;
+    }
+
+    Ok(result)
+}
+
+impl<'a> State<'a> {
+    /// Serialize flow children, joined by blank lines (or line endings in
+    /// tight lists).
+    ///
+    /// `spread` is the spread of the parent list or list item, if any.
+    fn flow(&self, children: &'a [Node], spread: Option<bool>) -> String {
+        let mut result = String::new();
+        let mut other = false;
+        let mut index = 0;
+
+        while index < children.len() {
+            let child = &children[index];
+
+            if index > 0 {
+                let left = &children[index - 1];
+                result.push_str(self.join(left, child, spread));
+                // Adjacent lists need different markers.
+                other = matches!(
+                    (left, child),
+                    (Node::List(left), Node::List(right)) if left.ordered == right.ordered
+                ) && !other;
+            }
+
+            let value = match child {
+                Node::List(list) => self.list(list, other),
+                node => self.block(node),
+            };
+            result.push_str(&value);
+            index += 1;
+        }
+
+        result
+    }
+
+    /// Get what to put between two flow nodes.
+    fn join(&self, left: &Node, right: &Node, spread: Option<bool>) -> &'static str {
+        if let Some(spread) = spread {
+            // Paragraphs cannot be interrupted by these.
+            if spread
+                || (matches!(left, Node::Paragraph(_))
+                    && (matches!(right, Node::Paragraph(_) | Node::Definition(_))
+                        || matches!(right, Node::Heading(heading) if self.setext(heading))))
+            {
+                "\n\n"
+            } else {
+                "\n"
+            }
+        } else if matches!((left, right), (Node::Definition(_), Node::Definition(_))) {
+            "\n"
+        } else {
+            "\n\n"
+        }
+    }
+
+    /// Serialize a flow node.
+    fn block(&self, node: &'a Node) -> String {
+        match node {
+            Node::BlockQuote(node) => {
+                let content = self.flow(&node.children, None);
+                if content.is_empty() {
+                    ">".into()
+                } else {
+                    content
+                        .split('\n')
+                        .map(|line| {
+                            if line.is_empty() {
+                                ">".into()
+                            } else {
+                                format!("> {}", line)
+                            }
+                        })
+                        .collect::<Vec<_>>()
+                        .join("\n")
+                }
+            }
+            Node::Code(node) => self.code(node),
+            Node::Definition(node) => definition(node),
+            Node::FootnoteDefinition(node) => self.footnote_definition(node),
+            Node::Heading(node) => self.heading(node),
+            Node::Html(node) => normalize(&node.value),
+            Node::List(node) => self.list(node, false),
+            Node::Math(node) => math(node),
+            Node::MdxFlowExpression(node) => format!("{{{}}}", normalize(&node.value)),
+            Node::MdxJsxFlowElement(node) => self.mdx_jsx_flow_element(node),
+            Node::MdxjsEsm(node) => normalize(&node.value),
+            Node::Paragraph(node) => encode_surrounding_whitespace(self.phrasing(
+                &node.children,
+                Context {
+                    before: None,
+                    after: None,
+                    line_start: true,
+                    table: false,
+                    attention: None,
+                    link: false,
+                },
+            )),
+            Node::Table(node) => self.table(node),
+            Node::ThematicBreak(_) => "***".into(),
+            Node::Yaml(node) => self.frontmatter("yaml", &node.value),
+            Node::Toml(node) => self.frontmatter("toml", &node.value),
+            Node::Json(node) => self.frontmatter("json", &node.value),
+            Node::Frontmatter(node) => self.frontmatter(&node.kind, &node.value),
+            // Things that are not flow, such as a list item or a table row
+            // on their own.
+            Node::ListItem(node) => self.list_item(node, "-", false),
+            Node::Root(node) => self.flow(&node.children, None),
+            Node::TableRow(_) | Node::TableCell(_) => {
+                let children = node.children().expect("expected children");
+                self.phrasing(
+                    children,
+                    Context {
+                        before: None,
+                        after: None,
+                        line_start: true,
+                        table: true,
+                        attention: None,
+                        link: false,
+                    },
+                )
+            }
+            node => self.phrasing(
+                core::slice::from_ref(node),
+                Context {
+                    before: None,
+                    after: None,
+                    line_start: true,
+                    table: false,
+                    attention: None,
+                    link: false,
+                },
+            ),
+        }
+    }
+
+    /// Serialize fenced code.
+    fn code(&self, node: &Code) -> String {
+        let info = match (&node.lang, &node.meta) {
+            (Some(lang), Some(meta)) => format!("{} {}", lang, meta),
+            (Some(lang), None) => lang.clone(),
+            (None, Some(meta)) => meta.clone(),
+            (None, None) => String::new(),
+        };
+        let marker = if self.options.fence == FenceMarker::Tilde || info.contains('`') {
+            '~'
+        } else {
+            '`'
+        };
+        let value = normalize(&node.value);
+        let fence = marker
+            .to_string()
+            .repeat((longest_run(&value, marker) + 1).max(3));
+
+        if value.is_empty() {
+            format!("{}{}\n{}", fence, info, fence)
+        } else {
+            format!("{}{}\n{}\n{}", fence, info, value, fence)
+        }
+    }
+
+    /// Serialize a GFM footnote definition.
+    fn footnote_definition(&self, node: &'a FootnoteDefinition) -> String {
+        let label = label(node.label.as_deref(), &node.identifier)
+            .unwrap_or_else(|| node.identifier.clone());
+        let content = self.flow(&node.children, None);
+
+        if content.is_empty() {
+            format!("[^{}]:", label)
+        } else {
+            indent(&content, &format!("[^{}]: ", label), "    ")
+        }
+    }
+
+    /// Serialize frontmatter, with the fences of the first matter of `kind`.
+    fn frontmatter(&self, kind: &str, value: &str) -> String {
+        let matter = self
+            .options
+            .parse
+            .frontmatter_matters
+            .iter()
+            .find(|matter| matter.kind == kind);
+        let (open, close) = if let Some(matter) = matter {
+            (matter.open.as_str(), matter.close.as_str())
+        } else if kind == "toml" {
+            ("+++", "+++")
+        } else {
+            ("---", "---")
+        };
+        let value = normalize(value);
+
+        // Braces around a JSON object are part of its value.
+        if open == "{" && close == "}" {
+            value
+        } else if value.is_empty() {
+            format!("{}\n{}", open, close)
+        } else {
+            format!("{}\n{}\n{}", open, value, close)
+        }
+    }
+
+    /// Whether a heading is serialized as setext.
+    fn setext(&self, node: &Heading) -> bool {
+        !node.children.is_empty()
+            && node.depth < 3
+            && (self.options.heading == HeadingStyle::Setext || has_line_ending(&node.children))
+    }
+
+    /// Serialize a heading.
+    fn heading(&self, node: &'a Heading) -> String {
+        if self.setext(node) {
+            let content = encode_surrounding_whitespace(self.phrasing(
+                &node.children,
+                Context {
+                    before: None,
+                    after: None,
+                    line_start: true,
+                    table: false,
+                    attention: None,
+                    link: false,
+                },
+            ));
+            let size = content
+                .rsplit('\n')
+                .next()
+                .map_or(0, |line| line.chars().count())
+                .max(3);
+            let marker = if node.depth == 1 { "=" } else { "-" };
+            format!("{}\n{}", content, marker.repeat(size))
+        } else {
+            let sequence = "#".repeat(node.depth.into());
+            let mut content = encode_surrounding_whitespace(self.phrasing(
+                &node.children,
+                Context {
+                    before: Some(' '),
+                    after: None,
+                    line_start: false,
+                    table: false,
+                    attention: None,
+                    link: false,
+                },
+            ));
+
+            // A closing sequence would be removed.
+            let trimmed = content.trim_end_matches('#');
+            if trimmed.len() < content.len()
+                && (trimmed.is_empty() || trimmed.ends_with(' ') || trimmed.ends_with('\t'))
+                && !trimmed.ends_with('\\')
+            {
+                content.insert(trimmed.len(), '\\');
+            }
+
+            if content.is_empty() {
+                sequence
+            } else {
+                format!("{} {}", sequence, content)
+            }
+        }
+    }
+
+    /// Serialize a list.
+    ///
+    /// `other` is whether to use the other marker, because the list directly
+    /// follows another list.
+    fn list(&self, node: &'a List, other: bool) -> String {
+        let bullet = match self.options.bullet {
+            BulletMarker::Dash if other => '*',
+            BulletMarker::Dash => '-',
+            _ if other => '-',
+            BulletMarker::Asterisk => '*',
+            BulletMarker::Plus => '+',
+        };
+        let delimiter = if other { ')' } else { '.' };
+        let start = node.start.unwrap_or(1);
+        let mut result = String::new();
+        let mut index = 0;
+
+        while index < node.children.len() {
+            if index > 0 {
+                result.push_str(match &node.children[index - 1] {
+                    // An item with one child is only spread when a blank
+                    // line is in it, which takes two blank lines.
+                    Node::ListItem(item) if item.spread && item.children.len() < 2 => "\n\n\n",
+                    _ if node.spread => "\n\n",
+                    _ => "\n",
+                });
+            }
+
+            let marker = if node.ordered {
+                let number = match self.options.list_numbering {
+                    ListNumbering::Increment => start as usize + index,
+                    ListNumbering::Same => start as usize,
+                };
+                format!("{}{}", number, delimiter)
+            } else {
+                bullet.to_string()
+            };
+
+            let value = match &node.children[index] {
+                Node::ListItem(item) => self.list_item(item, &marker, bullet == '*'),
+                child => self.block(child),
+            };
+            result.push_str(&value);
+            index += 1;
+        }
+
+        result
+    }
+
+    /// Serialize a list item.
+    ///
+    /// `asterisk` is whether the marker is `*`, in which case a thematic
+    /// break cannot be made with `*`.
+    fn list_item(&self, node: &'a ListItem, marker: &str, asterisk: bool) -> String {
+        let mut content =
+            if let (true, Some(Node::ThematicBreak(_))) = (asterisk, node.children.first()) {
+                let rest = self.flow(&node.children[1..], Some(node.spread));
+                let join = if node.children.len() > 1 {
+                    self.join(&node.children[0], &node.children[1], Some(node.spread))
+                } else {
+                    ""
+                };
+                format!("---{}{}", join, rest)
+            } else {
+                self.flow(&node.children, Some(node.spread))
+            };
+
+        if let Some(checked) = node.checked {
+            content = format!("[{}] {}", if checked { "x" } else { " " }, content);
+        }
+
+        if content.is_empty() {
+            marker.into()
+        } else {
+            let mut rest = String::new();
+            rest.push_str(&" ".repeat(marker.len() + 1));
+            indent(&content, &format!("{} ", marker), &rest)
+        }
+    }
+
+    /// Get the source of the opening tag, and of the closing tag (if not
+    /// self-closing), of an MDX JSX element at `position`.
+    fn mdx_jsx_source(&self, position: Option<&Position>) -> Option<(&str, Option<&str>)> {
+        let position = position?;
+        let open = self
+            .tags
+            .iter()
+            .find(|d| d.0 == position.start.offset)
+            .map(|d| d.2.as_str())?;
+
+        if open.ends_with("/>") {
+            Some((open, None))
+        } else {
+            let close = self
+                .tags
+                .iter()
+                .find(|d| d.1 == position.end.offset && d.2.starts_with("</"))
+                .map(|d| d.2.as_str())?;
+            Some((open, Some(close)))
+        }
+    }
+
+    /// Serialize an MDX JSX element (flow).
+    fn mdx_jsx_flow_element(&self, node: &'a MdxJsxFlowElement) -> String {
+        if let Some((open, close)) = self.mdx_jsx_source(node.position.as_ref()) {
+            return match close {
+                None => open.into(),
+                Some(close) if node.children.is_empty() => format!("{}{}", open, close),
+                Some(close) => format!(
+                    "{}\n{}\n{}",
+                    open,
+                    indent(&self.flow(&node.children, None), "  ", "  "),
+                    close
+                ),
+            };
+        }
+
+        let name = node.name.as_deref().unwrap_or("");
+        let attributes = mdx_jsx_attributes(&node.attributes);
+
+        if node.children.is_empty() {
+            if node.name.is_some() {
+                format!("<{}{} />", name, attributes)
+            } else {
+                "<></>".into()
+            }
+        } else {
+            let content = self.flow(&node.children, None);
+            format!(
+                "<{}{}>\n{}\n</{}>",
+                name,
+                attributes,
+                indent(&content, "  ", "  "),
+                name
+            )
+        }
+    }
+
+    /// Serialize a GFM table.
+    fn table(&self, node: &'a Table) -> String {
+        let mut rows = vec![];
+        let mut columns = node.align.len();
+
+        for row in &node.children {
+            let mut cells = vec![];
+
+            for cell in row.children().map_or(&[][..], |d| &d[..]) {
+                let content = self.phrasing(
+                    cell.children().map_or(&[][..], |d| &d[..]),
+                    Context {
+                        before: Some(' '),
+                        after: Some(' '),
+                        line_start: false,
+                        table: true,
+                        attention: None,
+                        link: false,
+                    },
+                );
+                cells.push(encode_surrounding_whitespace(content));
+            }
+
+            columns = columns.max(cells.len());
+            rows.push(cells);
+        }
+
+        let mut sizes = vec![if self.options.table_align { 3 } else { 1 }; columns];
+        let mut index = 0;
+
+        while index < columns {
+            let align = node.align.get(index).unwrap_or(&AlignKind::None);
+
+            if !self.options.table_align && *align == AlignKind::Center {
+                sizes[index] = 3;
+            } else if !self.options.table_align && *align != AlignKind::None {
+                sizes[index] = 2;
+            }
+
+            if self.options.table_align {
+                for row in &rows {
+                    if let Some(cell) = row.get(index) {
+                        sizes[index] = sizes[index].max(cell.chars().count());
+                    }
+                }
+            }
+
+            index += 1;
+        }
+
+        let mut lines = vec![];
+
+        for (row_index, row) in rows.iter().enumerate() {
+            let mut cells = vec![];
+
+            for (index, cell) in row.iter().enumerate() {
+                let align = node.align.get(index).unwrap_or(&AlignKind::None);
+                let pad = if self.options.table_align {
+                    sizes[index] - cell.chars().count()
+                } else {
+                    0
+                };
+                let (left, right) = match align {
+                    AlignKind::Right => (pad, 0),
+                    AlignKind::Center => (pad / 2, pad - pad / 2),
+                    AlignKind::Left | AlignKind::None => (0, pad),
+                };
+                cells.push(format!("{}{}{}", " ".repeat(left), cell, " ".repeat(right)));
+            }
+
+            lines.push(format!("| {} |", cells.join(" | ")));
+
+            // Delimiter row, which has to match the header row.
+            if row_index == 0 {
+                let mut cells = vec![];
+                let mut index = 0;
+
+                while index < row.len() {
+                    let size = sizes[index];
+                    cells.push(match node.align.get(index).unwrap_or(&AlignKind::None) {
+                        AlignKind::Left => format!(":{}", "-".repeat(size - 1)),
+                        AlignKind::Right => format!("{}:", "-".repeat(size - 1)),
+                        AlignKind::Center => format!(":{}:", "-".repeat(size - 2)),
+                        AlignKind::None => "-".repeat(size),
+                    });
+                    index += 1;
+                }
+
+                lines.push(format!("| {} |", cells.join(" | ")));
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    /// Serialize phrasing children.
+    fn phrasing(&self, children: &'a [Node], context: Context) -> String {
+        let mut pieces: Vec<Piece> = vec![];
+
+        // Everything but attention, which needs to know what is next to it.
+        for (index, child) in children.iter().enumerate() {
+            let (line_start, after_bracket) = if let Some(previous) = pieces.last() {
+                (
+                    previous.value.ends_with('\n'),
+                    previous.value.ends_with(']') && previous.attention.is_none(),
+                )
+            } else {
+                (context.line_start, false)
+            };
+
+            let piece = match child {
+                Node::Text(node) => Piece {
+                    value: self.text(&node.value, line_start, after_bracket, context.table),
+                    text: true,
+                    attention: None,
+                },
+                Node::Emphasis(_) | Node::Strong(_) | Node::Delete(_) => Piece {
+                    value: String::new(),
+                    text: false,
+                    attention: Some(child),
+                },
+                Node::Link(node) if self.literal(node, children.get(index + 1), context) => Piece {
+                    value: node.children[0].to_string(),
+                    text: false,
+                    attention: None,
+                },
+                node => Piece {
+                    value: self.inline(node, context),
+                    text: false,
+                    attention: None,
+                },
+            };
+
+            pieces.push(piece);
+        }
+
+        // An exclamation mark before a link would form an image.
+        let mut index = 1;
+        while index < pieces.len() {
+            if pieces[index].value.starts_with('[')
+                && pieces[index - 1].text
+                && pieces[index - 1].value.ends_with('!')
+                && !pieces[index - 1].value.ends_with("\\!")
+            {
+                let value = &mut pieces[index - 1].value;
+                value.insert(value.len() - 1, '\\');
+            }
+            index += 1;
+        }
+
+        let mut index = 0;
+        while index < pieces.len() {
+            if let Some(node) = pieces[index].attention {
+                let before = if index == 0 {
+                    context.before
+                } else {
+                    pieces[index - 1].value.chars().last()
+                };
+                let after = if index + 1 == pieces.len() {
+                    context.after
+                } else if pieces[index + 1].attention.is_some() {
+                    Some('*')
+                } else {
+                    pieces[index + 1].value.chars().next()
+                };
+                let edge = index == 0 || index + 1 == pieces.len();
+                let (value, info_before, info_after) =
+                    self.attention(node, before, after, edge, context);
+
+                if info_before {
+                    encode_last(&mut pieces[index - 1].value);
+                }
+
+                if info_after {
+                    encode_first(&mut pieces[index + 1].value);
+                }
+
+                pieces[index].value = value;
+            }
+
+            index += 1;
+        }
+
+        let mut result = String::new();
+        for piece in pieces {
+            result.push_str(&piece.value);
+        }
+        result
+    }
+
+    /// Serialize attention (emphasis, strong, GFM strikethrough).
+    ///
+    /// Returns the markdown, and whether the character before and after it
+    /// have to be encoded.
+    fn attention(
+        &self,
+        node: &'a Node,
+        before: Option<char>,
+        after: Option<char>,
+        edge: bool,
+        context: Context,
+    ) -> (String, bool, bool) {
+        let (preferred, size) = match node {
+            Node::Emphasis(_) => (self.options.emphasis, 1),
+            Node::Strong(_) => (self.options.strong, 2),
+            _ => (AttentionMarker::Asterisk, 2),
+        };
+        let marker = if matches!(node, Node::Delete(_)) {
+            '~'
+        } else {
+            let preferred = if preferred == AttentionMarker::Asterisk {
+                '*'
+            } else {
+                '_'
+            };
+            let other = if preferred == '*' { '_' } else { '*' };
+            [preferred, other]
+                .iter()
+                .copied()
+                .find(|marker| {
+                    // Same marker directly inside the same marker is
+                    // ambiguous (`**a**` for emphasis in emphasis).
+                    !(edge && context.attention == Some(*marker))
+                        // Same marker next to it would merge.
+                        && before != Some(*marker)
+                        && after != Some(*marker)
+                        // Underscores do not work inside words.
+                        && !(*marker == '_'
+                            && (classify_opt(before) == CharacterKind::Other
+                                || classify_opt(after) == CharacterKind::Other))
+                })
+                .unwrap_or(preferred)
+        };
+        let mut inside = self.phrasing(
+            node.children().expect("expected children"),
+            Context {
+                before: Some(marker),
+                after: Some(marker),
+                line_start: false,
+                table: context.table,
+                attention: Some(marker),
+                link: context.link,
+            },
+        );
+        let open = encode_info(before, inside.chars().next(), marker);
+        let close = encode_info(after, inside.chars().last(), marker);
+
+        if open.inside {
+            encode_first(&mut inside);
+        }
+
+        if close.inside {
+            encode_last(&mut inside);
+        }
+
+        let sequence = marker.to_string().repeat(size);
+        (
+            format!("{}{}{}", sequence, inside, sequence),
+            open.outside,
+            close.outside,
+        )
+    }
+
+    /// Serialize a phrasing node, other than text and attention.
+    fn inline(&self, node: &'a Node, context: Context) -> String {
+        let bracket = Context {
+            before: Some('['),
+            after: Some(']'),
+            line_start: false,
+            table: context.table,
+            attention: None,
+            link: true,
+        };
+
+        match node {
+            Node::Break(_) => "\\\n".into(),
+            Node::FootnoteReference(node) => {
+                format!(
+                    "[^{}]",
+                    label(node.label.as_deref(), &node.identifier)
+                        .unwrap_or_else(|| node.identifier.clone())
+                )
+            }
+            Node::Html(node) => normalize(&node.value),
+            Node::Image(node) => self.image(node, context),
+            Node::ImageReference(node) => self.image_reference(node, context),
+            Node::InlineCode(node) => surround(&raw_text(&node.value, context), '`', 1),
+            Node::InlineMath(node) => self.inline_math(node, context),
+            Node::Link(node) => self.link(node, bracket),
+            Node::LinkReference(node) => self.link_reference(node, bracket),
+            Node::MdxJsxTextElement(node) => self.mdx_jsx_text_element(node, context),
+            Node::MdxTextExpression(node) => format!("{{{}}}", normalize(&node.value)),
+            node => self.block(node),
+        }
+    }
+
+    /// Whether a link can be serialized as a GFM autolink literal
+    /// (`www.example.com`, and in links also `https://example.com` and
+    /// `contact@example.com`).
+    ///
+    /// Such links cannot be written as normal links, as their text would form
+    /// a literal again, and `www` links are not autolinks (no protocol).
+    /// Literals are the only links that can be in links.
+    /// `next` is the sibling after the link: the literal must stop there.
+    fn literal(&self, node: &Link, next: Option<&Node>, context: Context) -> bool {
+        let after = match next {
+            Some(Node::Text(text)) => {
+                let rest = text
+                    .value
+                    .split(|d: char| d.is_whitespace())
+                    .next()
+                    .unwrap_or("");
+                // Trailing punctuation is not part of literals.
+                if !rest
+                    .chars()
+                    .all(|d| matches!(d, '?' | '!' | '.' | ',' | ':' | '*' | '_' | '~'))
+                {
+                    return false;
+                }
+                text.value.chars().next()
+            }
+            Some(Node::Emphasis(_) | Node::Strong(_) | Node::Delete(_)) => Some('*'),
+            Some(Node::Html(_) | Node::MdxJsxTextElement(_)) => Some('<'),
+            Some(_) => return false,
+            None => context.after,
+        };
+
+        if let (true, None, [Node::Text(text)]) = (
+            self.options.parse.constructs.gfm_autolink_literal && !context.table,
+            &node.title,
+            &node.children[..],
+        ) {
+            let value = &text.value;
+            let www = node.url.strip_prefix("http://") == Some(value)
+                && value
+                    .get(..4)
+                    .map_or(false, |d| d.eq_ignore_ascii_case("www."));
+            let protocol = node.url == *value
+                && (value
+                    .get(..7)
+                    .map_or(false, |d| d.eq_ignore_ascii_case("http://"))
+                    || value
+                        .get(..8)
+                        .map_or(false, |d| d.eq_ignore_ascii_case("https://")));
+            let email = node.url.strip_prefix("mailto:") == Some(value) && value.contains('@');
+
+            // Emails that are not autolinks can only be literals.
+            (www || (email && !is_email(value)) || (context.link && (protocol || email)))
+                && !text.value.contains(|d: char| d.is_whitespace() || d == '<')
+                && after.map_or(true, |d| {
+                    d.is_whitespace()
+                        || matches!(d, '?' | '!' | '.' | ',' | ':' | '*' | '_' | '~' | '<')
+                        || (context.link && d == ']')
+                })
+        } else {
+            false
+        }
+    }
+
+    /// Serialize an image.
+    fn image(&self, node: &Image, context: Context) -> String {
+        format!(
+            "![{}]({})",
+            self.text(&node.alt, false, false, context.table),
+            destination(&node.url, node.title.as_deref())
+        )
+    }
+
+    /// Serialize an image reference.
+    fn image_reference(&self, node: &ImageReference, context: Context) -> String {
+        match (&node.reference_kind, &node.label) {
+            (ReferenceKind::Shortcut | ReferenceKind::Collapsed, Some(_)) => format!(
+                "![{}]{}",
+                label(node.label.as_deref(), &node.identifier).unwrap_or_else(|| self.text(
+                    &node.alt,
+                    false,
+                    false,
+                    context.table
+                )),
+                if node.reference_kind == ReferenceKind::Collapsed {
+                    "[]"
+                } else {
+                    ""
+                }
+            ),
+            _ => format!(
+                "![{}][{}]",
+                self.text(&node.alt, false, false, context.table),
+                label(node.label.as_deref(), &node.identifier)
+                    .unwrap_or_else(|| node.identifier.clone())
+            ),
+        }
+    }
+
+    /// Serialize math (text).
+    fn inline_math(&self, node: &InlineMath, context: Context) -> String {
+        let value = raw_text(&node.value, context);
+        let size = if self.options.parse.math_text_single_dollar {
+            1
+        } else {
+            2
+        };
+        surround(&value, '$', size)
+    }
+
+    /// Serialize a link.
+    fn link(&self, node: &'a Link, context: Context) -> String {
+        if let (true, None, [Node::Text(text)]) = (
+            self.options.parse.constructs.autolink,
+            &node.title,
+            &node.children[..],
+        ) {
+            let url = &node.url;
+            let value = &text.value;
+
+            if !url.is_empty()
+                && !url
+                    .chars()
+                    .any(|d| d.is_whitespace() || d.is_control() || d == '<' || d == '>')
+                && ((value == url && has_scheme(url))
+                    || (url.strip_prefix("mailto:") == Some(value) && is_email(value)))
+            {
+                return format!("<{}>", value);
+            }
+        }
+
+        format!(
+            "[{}]({})",
+            self.phrasing(&node.children, context),
+            destination(&node.url, node.title.as_deref())
+        )
+    }
+
+    /// Serialize a link reference.
+    fn link_reference(&self, node: &'a LinkReference, context: Context) -> String {
+        match (&node.reference_kind, &node.label) {
+            (ReferenceKind::Shortcut | ReferenceKind::Collapsed, Some(_)) => format!(
+                "[{}]{}",
+                label(node.label.as_deref(), &node.identifier)
+                    .unwrap_or_else(|| self.phrasing(&node.children, context)),
+                if node.reference_kind == ReferenceKind::Collapsed {
+                    "[]"
+                } else {
+                    ""
+                }
+            ),
+            _ => format!(
+                "[{}][{}]",
+                self.phrasing(&node.children, context),
+                label(node.label.as_deref(), &node.identifier)
+                    .unwrap_or_else(|| node.identifier.clone())
+            ),
+        }
+    }
+
+    /// Serialize an MDX JSX element (text).
+    fn mdx_jsx_text_element(&self, node: &'a MdxJsxTextElement, context: Context) -> String {
+        let name = node.name.as_deref().unwrap_or("");
+        let attributes = mdx_jsx_attributes(&node.attributes);
+        let source = self.mdx_jsx_source(node.position.as_ref());
+
+        if let Some((open, None)) = source {
+            open.into()
+        } else if node.children.is_empty() && source.is_none() {
+            if node.name.is_some() {
+                format!("<{}{} />", name, attributes)
+            } else {
+                "<></>".into()
+            }
+        } else {
+            let (open, close) = if let Some((open, Some(close))) = source {
+                (open.into(), close.into())
+            } else {
+                (format!("<{}{}>", name, attributes), format!("</{}>", name))
+            };
+            format!(
+                "{}{}{}",
+                open,
+                self.phrasing(
+                    &node.children,
+                    Context {
+                        before: Some('>'),
+                        after: Some('<'),
+                        line_start: false,
+                        table: context.table,
+                        attention: None,
+                        link: context.link,
+                    }
+                ),
+                close
+            )
+        }
+    }
+
+    /// Escape text.
+    ///
+    /// `line_start` is whether the text starts at the start of a line,
+    /// `after_bracket` is whether it comes right after a `]`.
+    fn text(&self, value: &str, line_start: bool, after_bracket: bool, table: bool) -> String {
+        let constructs = &self.options.parse.constructs;
+        let chars = normalize(value).chars().collect::<Vec<_>>();
+        let mut result = String::new();
+        let mut at_line_start = line_start;
+        let mut escape_at = None;
+        let mut index = 0;
+
+        while index < chars.len() {
+            let char = chars[index];
+            let previous = if index == 0 {
+                None
+            } else {
+                Some(chars[index - 1])
+            };
+            let next = chars.get(index + 1).copied();
+            let start = at_line_start;
+            at_line_start = false;
+
+            // Blank lines would end the paragraph.
+            if char == '\n' && (start || next == Some('\n')) {
+                result.push_str(&character_reference(char));
+                index += 1;
+                continue;
+            }
+
+            if char == '\n' {
+                result.push('\n');
+                at_line_start = true;
+                index += 1;
+                continue;
+            }
+
+            // Whitespace is removed at the start and end of lines.
+            if (char == ' ' || char == '\t') && (start || next == Some('\n')) {
+                result.push_str(&character_reference(char));
+                index += 1;
+                continue;
+            }
+
+            if start {
+                // An ordered list item marker.
+                let mut end = index;
+                while end < chars.len() && end - index < 10 && chars[end].is_ascii_digit() {
+                    end += 1;
+                }
+                if end > index && matches!(chars.get(end), Some('.' | ')')) {
+                    escape_at = Some(end);
+                }
+            }
+
+            let escape = match char {
+                '#' | '>' | '-' | '+' | '=' => start,
+                ':' => {
+                    start
+                        || (constructs.gfm_autolink_literal
+                            && next == Some('/')
+                            && (ends_with_ignore_case(&chars[..index], "http")
+                                || ends_with_ignore_case(&chars[..index], "https")))
+                }
+                '|' => table || (start && constructs.gfm_table),
+                '\\' => next.map_or(true, |d| d == '\n' || d.is_ascii_punctuation()),
+                '*' | '`' | '[' | ']' => true,
+                '_' => !(previous.map_or(false, char::is_alphanumeric)
+                    && next.map_or(false, char::is_alphanumeric)),
+                '<' => {
+                    constructs.mdx_jsx_text
+                        || constructs.mdx_jsx_flow
+                        || next.map_or(true, |d| d.is_ascii_alphabetic() || matches!(d, '/' | '!' | '?'))
+                }
+                '&' => is_character_reference(&chars[index..]),
+                '~' => constructs.gfm_strikethrough || start,
+                '$' => constructs.math_text || (constructs.math_flow && start),
+                '{' => {
+                    constructs.mdx_expression_text || (constructs.mdx_expression_flow && start)
+                }
+                '(' => index == 0 && after_bracket,
+                // GFM autolink literals (`a@b.c`, `www.a`, `http://a`).
+                '@' => constructs.gfm_autolink_literal,
+                '.' => {
+                    constructs.gfm_autolink_literal
+                        && index >= 3
+                        && chars[index - 3..index].iter().all(|d| matches!(d, 'w' | 'W'))
+                        && next.map_or(false, |d| d.is_alphanumeric() || matches!(d, '-' | '_'))
+                }
+                _ => false,
+            } || escape_at == Some(index)
+                // `:` after a reference would form a definition.
+                || (char == ':' && index == 0 && after_bracket);
+
+            if escape {
+                result.push('\\');
+            }
+
+            result.push(char);
+            index += 1;
+        }
+
+        result
+    }
+}
+
+/// Serialize a definition.
+fn definition(node: &Definition) -> String {
+    format!(
+        "[{}]: {}",
+        label(node.label.as_deref(), &node.identifier).unwrap_or_else(|| node.identifier.clone()),
+        destination(&node.url, node.title.as_deref())
+    )
+}
+
+/// Serialize a label, so that it matches `identifier` again.
+///
+/// Labels are decoded, but identifiers are made from the source, so the
+/// escapes that were used have to come back.
+/// Gives `None` if that does not work, such as when the source had markup.
+fn label(label: Option<&str>, identifier: &str) -> Option<String> {
+    if let Some(label) = label {
+        let chars = label.chars().collect::<Vec<_>>();
+        let mut escaped = String::new();
+        let mut index = 0;
+
+        while index < chars.len() {
+            let char = chars[index];
+            if matches!(char, '[' | ']')
+                || (char == '\\'
+                    && chars
+                        .get(index + 1)
+                        .map_or(true, char::is_ascii_punctuation))
+            {
+                escaped.push('\\');
+            }
+            escaped.push(char);
+            index += 1;
+        }
+
+        for candidate in &[escaped.as_str(), label] {
+            if normalize_identifier(candidate).to_lowercase() == identifier {
+                return Some((*candidate).into());
+            }
+        }
+    }
+
+    None
+}
+
+/// Serialize math (flow).
+fn math(node: &Math) -> String {
+    let value = normalize(&node.value);
+    let fence = "$".repeat((longest_run(&value, '$') + 1).max(2));
+    let meta = node.meta.as_deref().unwrap_or("");
+
+    if value.is_empty() {
+        format!("{}{}\n{}", fence, meta, fence)
+    } else {
+        format!("{}{}\n{}\n{}", fence, meta, value, fence)
+    }
+}
+
+/// Collect the source of MDX JSX tags in `events`, as their start and end
+/// offsets and their text.
+///
+/// Container prefixes (such as `> ` of block quotes, and the indent of list
+/// items) in tags that span lines are left out, as they are added again when
+/// serializing.
+fn mdx_jsx_tags(events: &[Event], bytes: &[u8]) -> Vec<(usize, usize, String)> {
+    let mut tags = vec![];
+    let mut index = 0;
+
+    while index < events.len() {
+        let event = &events[index];
+
+        if event.kind == EventKind::Enter
+            && matches!(event.name, Name::MdxJsxFlowTag | Name::MdxJsxTextTag)
+        {
+            let start = event.point.index;
+            let mut value = String::new();
+            let mut prefix = 0;
+            let mut after_line_ending = false;
+            index += 1;
+
+            while !(events[index].kind == EventKind::Exit
+                && matches!(
+                    events[index].name,
+                    Name::MdxJsxFlowTag | Name::MdxJsxTextTag
+                ))
+            {
+                let event = &events[index];
+
+                if event.name == Name::BlockQuotePrefix {
+                    if event.kind == EventKind::Enter {
+                        prefix += 1;
+                    } else {
+                        prefix -= 1;
+                    }
+                } else if prefix == 0
+                    && event.kind == EventKind::Enter
+                    && events[index + 1].kind == EventKind::Exit
+                {
+                    // A leaf.
+                    if !(after_line_ending && event.name == Name::SpaceOrTab) {
+                        value.push_str(
+                            core::str::from_utf8(
+                                &bytes[event.point.index..events[index + 1].point.index],
+                            )
+                            .unwrap(),
+                        );
+                    }
+
+                    after_line_ending = event.name == Name::LineEnding;
+                }
+
+                index += 1;
+            }
+
+            tags.push((start, events[index].point.index, value));
+        }
+
+        index += 1;
+    }
+
+    tags
+}
+
+/// Serialize MDX JSX attributes.
+fn mdx_jsx_attributes(attributes: &[AttributeContent]) -> String {
+    let mut result = String::new();
+
+    for attribute in attributes {
+        result.push(' ');
+
+        match attribute {
+            AttributeContent::Expression { value, .. } => {
+                result.push('{');
+                result.push_str(&normalize(value));
+                result.push('}');
+            }
+            AttributeContent::Property(property) => {
+                result.push_str(&property.name);
+
+                match &property.value {
+                    Some(AttributeValue::Expression(expression)) => {
+                        result.push_str("={");
+                        result.push_str(&normalize(&expression.value));
+                        result.push('}');
+                    }
+                    Some(AttributeValue::Literal(value)) => {
+                        result.push_str("=\"");
+                        result.push_str(&value.replace('&', "&amp;").replace('"', "&quot;"));
+                        result.push('"');
+                    }
+                    None => {}
+                }
+            }
+        }
+    }
+
+    result
+}
+
+/// Serialize a destination, and optionally a title.
+fn destination(url: &str, title: Option<&str>) -> String {
+    let mut result = String::new();
+
+    if url.is_empty()
+        || url
+            .chars()
+            .any(|d| d.is_whitespace() || d.is_control() || d == '<' || d == '>')
+    {
+        result.push('<');
+        for char in url.chars() {
+            if matches!(char, '<' | '>' | '\\') {
+                result.push('\\');
+            }
+            result.push(char);
+        }
+        result.push('>');
+    } else {
+        let chars = url.chars().collect::<Vec<_>>();
+        let mut index = 0;
+        while index < chars.len() {
+            let char = chars[index];
+            if matches!(char, '(' | ')')
+                || (char == '\\'
+                    && chars
+                        .get(index + 1)
+                        .map_or(true, char::is_ascii_punctuation))
+                || (char == '&' && is_character_reference(&chars[index..]))
+            {
+                result.push('\\');
+            }
+            result.push(char);
+            index += 1;
+        }
+    }
+
+    if let Some(title) = title {
+        let chars = normalize(title).chars().collect::<Vec<_>>();
+        let mut index = 0;
+        result.push_str(" \"");
+        while index < chars.len() {
+            let char = chars[index];
+            if char == '"'
+                || (char == '\\'
+                    && chars
+                        .get(index + 1)
+                        .map_or(true, char::is_ascii_punctuation))
+                || (char == '&' && is_character_reference(&chars[index..]))
+            {
+                result.push('\\');
+            }
+            result.push(char);
+            index += 1;
+        }
+        result.push('"');
+    }
+
+    result
+}
+
+/// Get the value of code or math (text), with pipes escaped in tables.
+fn raw_text(value: &str, context: Context) -> String {
+    let value = normalize(value);
+
+    if context.table {
+        value.replace('|', "\\|")
+    } else {
+        value
+    }
+}
+
+/// Put a sequence of `marker` around `value`, longer than any sequence of it
+/// in `value`.
+fn surround(value: &str, marker: char, min: usize) -> String {
+    let mut size = min;
+
+    while has_run(value, marker, size) {
+        size += 1;
+    }
+
+    let sequence = marker.to_string().repeat(size);
+    // Padding is kept in the tree, so it is only added when a marker next to
+    // the sequence would join it.
+    if value.starts_with(marker) || value.ends_with(marker) {
+        format!("{} {} {}", sequence, value, sequence)
+    } else {
+        format!("{}{}{}", sequence, value, sequence)
+    }
+}
+
+/// Whether `value` includes a sequence of exactly `size` of `marker`.
+fn has_run(value: &str, marker: char, size: usize) -> bool {
+    let mut run = 0;
+
+    for char in value.chars() {
+        if char == marker {
+            run += 1;
+        } else {
+            if run == size {
+                return true;
+            }
+            run = 0;
+        }
+    }
+
+    run == size
+}
+
+/// Get the size of the longest sequence of `marker` in `value`.
+fn longest_run(value: &str, marker: char) -> usize {
+    let mut longest = 0;
+    let mut run = 0;
+
+    for char in value.chars() {
+        if char == marker {
+            run += 1;
+            longest = longest.max(run);
+        } else {
+            run = 0;
+        }
+    }
+
+    longest
+}
+
+/// Whether `chars`, which start with `&`, form a character reference.
+fn is_character_reference(chars: &[char]) -> bool {
+    let mut index = 1;
+
+    if chars.get(index) == Some(&'#') {
+        index += 1;
+    }
+
+    let start = index;
+
+    while index < chars.len() && chars[index].is_ascii_alphanumeric() {
+        index += 1;
+    }
+
+    index > start && chars.get(index) == Some(&';')
+}
+
+/// Whether a value is an email, as needed for autolinks.
+fn is_email(value: &str) -> bool {
+    if let Some((local, domain)) = value.split_once('@') {
+        !local.is_empty()
+            && local
+                .chars()
+                .all(|d| d.is_ascii_alphanumeric() || ".!#$%&'*+/=?^_`{|}~-".contains(d))
+            && domain.split('.').all(|label| {
+                !label.is_empty()
+                    && label.len() <= 63
+                    && !label.starts_with('-')
+                    && !label.ends_with('-')
+                    && label.chars().all(|d| d.is_ascii_alphanumeric() || d == '-')
+            })
+    } else {
+        false
+    }
+}
+
+/// Whether a URL starts with a scheme, as needed for autolinks.
+fn has_scheme(url: &str) -> bool {
+    let mut chars = url.chars();
+
+    if !chars.next().map_or(false, |d| d.is_ascii_alphabetic()) {
+        return false;
+    }
+
+    for (size, char) in chars.enumerate() {
+        if char == ':' {
+            return size > 0;
+        }
+
+        if !(char.is_ascii_alphanumeric() || matches!(char, '+' | '.' | '-')) || size == 31 {
+            return false;
+        }
+    }
+
+    false
+}
+
+/// Whether the last flow node in `node` is HTML that runs to the end of the
+/// document.
+fn ends_in_unclosed_html(node: &Node) -> bool {
+    match node {
+        Node::Html(node) => {
+            let value = node.value.to_ascii_lowercase();
+            let raw = ["<pre", "<script", "<style", "<textarea"]
+                .iter()
+                .find(|name| {
+                    value.starts_with(*name)
+                        && value[name.len()..]
+                            .chars()
+                            .next()
+                            .map_or(true, |d| d.is_ascii_whitespace() || d == '>')
+                });
+
+            if let Some(name) = raw {
+                !value.contains(&format!("</{}>", &name[1..]))
+            } else if let Some(rest) = value.strip_prefix("<![cdata[") {
+                !rest.contains("]]>")
+            } else if value.starts_with("<!--") {
+                !value[2..].contains("-->")
+            } else if let Some(rest) = value.strip_prefix("<?") {
+                !rest.contains("?>")
+            } else if value.starts_with("<!") {
+                !value.contains('>')
+            } else {
+                false
+            }
+        }
+        Node::Root(_)
+        | Node::BlockQuote(_)
+        | Node::List(_)
+        | Node::ListItem(_)
+        | Node::FootnoteDefinition(_) => node
+            .children()
+            .and_then(|children| children.last())
+            .map_or(false, ends_in_unclosed_html),
+        _ => false,
+    }
+}
+
+/// Whether `chars` ends with `value` (ASCII case-insensitive).
+fn ends_with_ignore_case(chars: &[char], value: &str) -> bool {
+    let size = value.len();
+    chars.len() >= size
+        && chars[chars.len() - size..]
+            .iter()
+            .zip(value.chars())
+            .all(|(a, b)| a.eq_ignore_ascii_case(&b))
+}
+
+/// Whether phrasing content has line endings.
+fn has_line_ending(children: &[Node]) -> bool {
+    children.iter().any(|child| match child {
+        Node::Break(_) => true,
+        Node::Text(node) => node.value.contains(['\n', '\r'].as_ref()),
+        Node::Html(node) => node.value.contains(['\n', '\r'].as_ref()),
+        node => node.children().map_or(false, |d| has_line_ending(d)),
+    })
+}
+
+/// Whether a node is phrasing content.
+fn is_phrasing(node: &Node) -> bool {
+    matches!(
+        node,
+        Node::Break(_)
+            | Node::Delete(_)
+            | Node::Emphasis(_)
+            | Node::FootnoteReference(_)
+            | Node::Html(_)
+            | Node::Image(_)
+            | Node::ImageReference(_)
+            | Node::InlineCode(_)
+            | Node::InlineMath(_)
+            | Node::Link(_)
+            | Node::LinkReference(_)
+            | Node::MdxJsxTextElement(_)
+            | Node::MdxTextExpression(_)
+            | Node::Strong(_)
+            | Node::Text(_)
+    )
+}
+
+/// Figure out which characters around attention have to be encoded.
+///
+/// Port of `encodeInfo` in `mdast-util-to-markdown`.
+fn encode_info(outside: Option<char>, inside: Option<char>, marker: char) -> EncodeInfo {
+    let outside = classify_opt(outside);
+    let inside = classify_opt(inside);
+
+    match (outside, inside) {
+        // Letter outside and inside: `_` needs both to be encoded.
+        (CharacterKind::Other, CharacterKind::Other) => EncodeInfo {
+            inside: marker == '_',
+            outside: marker == '_',
+        },
+        // Letter or whitespace outside, whitespace inside: encode both.
+        (CharacterKind::Other | CharacterKind::Whitespace, CharacterKind::Whitespace) => {
+            EncodeInfo {
+                inside: true,
+                outside: true,
+            }
+        }
+        // Letter outside, punctuation inside: encode outside.
+        (CharacterKind::Other, CharacterKind::Punctuation) => EncodeInfo {
+            inside: false,
+            outside: true,
+        },
+        // Punctuation outside, whitespace inside: encode inside.
+        (CharacterKind::Punctuation, CharacterKind::Whitespace) => EncodeInfo {
+            inside: true,
+            outside: false,
+        },
+        // Otherwise it already forms.
+        _ => EncodeInfo {
+            inside: false,
+            outside: false,
+        },
+    }
+}
+
+/// Indent the lines of `value`: the first with `first`, the rest with
+/// `rest`, except for empty lines.
+fn indent(value: &str, first: &str, rest: &str) -> String {
+    let mut result = String::new();
+
+    for (index, line) in value.split('\n').enumerate() {
+        if index > 0 {
+            result.push('\n');
+        }
+
+        if index == 0 {
+            result.push_str(first);
+        } else if !line.is_empty() {
+            result.push_str(rest);
+        }
+
+        result.push_str(line);
+    }
+
+    result
+}
+
+/// Encode whitespace at the start and end of phrasing, which would be
+/// removed otherwise.
+fn encode_surrounding_whitespace(mut value: String) -> String {
+    if value.starts_with([' ', '\t', '\n'].as_ref()) {
+        encode_first(&mut value);
+    }
+
+    if value.ends_with([' ', '\t', '\n'].as_ref()) {
+        encode_last(&mut value);
+    }
+
+    value
+}
+
+/// Turn the first character of `value` into a character reference.
+fn encode_first(value: &mut String) {
+    if let Some(char) = value.chars().next() {
+        value.replace_range(..char.len_utf8(), &character_reference(char));
+    }
+}
+
+/// Turn the last character of `value` into a character reference.
+fn encode_last(value: &mut String) {
+    if let Some(char) = value.chars().last() {
+        let start = value.len() - char.len_utf8();
+        value.replace_range(start.., &character_reference(char));
+    }
+}
+
+/// Turn a character into a character reference.
+fn character_reference(char: char) -> String {
+    format!("&#x{:X};", char as u32)
+}
+
+/// Turn line endings into line feeds.
+fn normalize(value: &str) -> String {
+    value.replace("\r\n", "\n").replace('\r', "\n")
+}
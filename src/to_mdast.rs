@@ -1,14 +1,16 @@
 //! Turn events into a syntax tree.
 
+use crate::configuration::{Matter, ParseOptions};
 use crate::event::{Event, Kind, Name, Point as EventPoint};
 use crate::mdast::{
     AttributeContent, AttributeValue, AttributeValueExpression, BlockQuote, Break, Code,
-    Definition, Delete, Emphasis, FootnoteDefinition, FootnoteReference, Heading, Html, Image,
-    ImageReference, InlineCode, InlineMath, Link, LinkReference, List, ListItem, Math,
-    MdxFlowExpression, MdxJsxAttribute, MdxJsxFlowElement, MdxJsxTextElement, MdxTextExpression,
-    MdxjsEsm, Node, Paragraph, ReferenceKind, Root, Strong, Table, TableCell, TableRow, Text,
-    ThematicBreak, Toml, Yaml,
+    Definition, Delete, Emphasis, FootnoteDefinition, FootnoteReference, Frontmatter, Heading,
+    Html, Image, ImageReference, InlineCode, InlineMath, Json, Link, LinkReference, List, ListItem,
+    Math, MdxFlowExpression, MdxJsxAttribute, MdxJsxFlowElement, MdxJsxTextElement,
+    MdxTextExpression, MdxjsEsm, Node, Paragraph, ReferenceKind, Root, Strong, Table, TableCell,
+    TableRow, Text, ThematicBreak, Toml, Yaml,
 };
+use crate::parser::cancel;
 use crate::unist::{Point, Position};
 use crate::util::{
     character_reference::{
@@ -25,7 +27,7 @@ use alloc::{
     vec,
     vec::Vec,
 };
-use core::str;
+use core::{cell::Cell, str};
 
 /// A reference to something.
 #[derive(Debug)]
@@ -87,6 +89,8 @@ struct CompileContext<'a> {
     events: &'a [Event],
     /// List of bytes.
     bytes: &'a [u8],
+    /// Configuration.
+    options: &'a ParseOptions,
     // Fields used by handlers to track the things they need to track to
     // compile markdown.
     character_reference_marker: u8,
@@ -106,7 +110,7 @@ struct CompileContext<'a> {
 
 impl<'a> CompileContext<'a> {
     /// Create a new compile context.
-    fn new(events: &'a [Event], bytes: &'a [u8]) -> CompileContext<'a> {
+    fn new(events: &'a [Event], bytes: &'a [u8], options: &'a ParseOptions) -> CompileContext<'a> {
         let tree = Node::Root(Root {
             children: vec![],
             position: Some(Position {
@@ -126,6 +130,7 @@ impl<'a> CompileContext<'a> {
         CompileContext {
             events,
             bytes,
+            options,
             character_reference_marker: 0,
             gfm_table_inside: false,
             hard_break_after: false,
@@ -222,11 +227,14 @@ impl<'a> CompileContext<'a> {
 }
 
 /// Turn events and bytes into a syntax tree.
-pub fn compile(events: &[Event], bytes: &[u8]) -> Result<Node, String> {
-    let mut context = CompileContext::new(events, bytes);
+pub fn compile(events: &[Event], bytes: &[u8], options: &ParseOptions) -> Result<Node, String> {
+    let mut context = CompileContext::new(events, bytes, options);
+    let moves = Cell::new(0);
 
     let mut index = 0;
     while index < events.len() {
+        let point = &events[index].point;
+        cancel(options.cancel.as_deref(), &moves, point.line, point.column)?;
         handle(&mut context, index)?;
         index += 1;
     }
@@ -645,18 +653,26 @@ fn on_enter_hard_break(context: &mut CompileContext) {
 
 /// Handle [`Enter`][Kind::Enter]:[`Frontmatter`][Name::Frontmatter].
 fn on_enter_frontmatter(context: &mut CompileContext) {
-    let index = context.events[context.index].point.index;
-    let byte = context.bytes[index];
-    let node = if byte == b'+' {
-        Node::Toml(Toml {
-            value: String::new(),
+    let matter = frontmatter_matter(context, context.index);
+    let value = String::new();
+    let node = match matter.kind.as_str() {
+        "yaml" => Node::Yaml(Yaml {
+            value,
             position: None,
-        })
-    } else {
-        Node::Yaml(Yaml {
-            value: String::new(),
+        }),
+        "toml" => Node::Toml(Toml {
+            value,
             position: None,
-        })
+        }),
+        "json" => Node::Json(Json {
+            value,
+            position: None,
+        }),
+        kind => Node::Frontmatter(Frontmatter {
+            value,
+            position: None,
+            kind: kind.into(),
+        }),
     };
 
     context.tail_push(node);
@@ -1139,12 +1155,31 @@ fn on_exit_drop(context: &mut CompileContext) {
 
 /// Handle [`Exit`][Kind::Exit]:[`Frontmatter`][Name::Frontmatter].
 fn on_exit_frontmatter(context: &mut CompileContext) -> Result<(), String> {
-    let value = trim_eol(context.resume().to_string(), true, true);
+    let mut value = trim_eol(context.resume().to_string(), true, true);
+    let mut enter = context.index;
+    while context.events[enter].name != Name::Frontmatter
+        || context.events[enter].kind != Kind::Enter
+    {
+        enter -= 1;
+    }
+    let matter = frontmatter_matter(context, enter);
+
+    // Braces around a JSON object are part of its value.
+    if matter.open == "{" && matter.close == "}" {
+        let start = context.events[enter].point.index;
+        let end = context.events[context.index].point.index;
+        value = Slice::from_indices(context.bytes, start, end)
+            .as_str()
+            .trim_end_matches(|d| d == ' ' || d == '\t')
+            .into();
+    }
 
     match context.tail_mut() {
         Node::Yaml(node) => node.value = value,
         Node::Toml(node) => node.value = value,
-        _ => unreachable!("expected yaml/toml on stack for value"),
+        Node::Json(node) => node.value = value,
+        Node::Frontmatter(node) => node.value = value,
+        _ => unreachable!("expected frontmatter on stack for value"),
     }
 
     on_exit(context)?;
@@ -1618,6 +1653,7 @@ fn on_exit_mdx_jsx_tag_attribute_value_literal(context: &mut CompileContext) {
     {
         node.value = Some(AttributeValue::Literal(parse_character_reference(
             &value.to_string(),
+            false,
         )));
     } else {
         unreachable!("expected property")
@@ -1698,6 +1734,33 @@ fn delve_mut<'tree>(mut node: &'tree mut Node, stack: &'tree [usize]) -> &'tree
     node
 }
 
+/// Find the matter of the frontmatter that is entered at `enter`, by its
+/// fences.
+fn frontmatter_matter<'a>(context: &CompileContext<'a>, enter: usize) -> &'a Matter {
+    let mut sequences = vec![];
+    let mut index = enter;
+
+    while context.events[index].name != Name::Frontmatter
+        || context.events[index].kind != Kind::Exit
+    {
+        if context.events[index].name == Name::FrontmatterSequence
+            && context.events[index].kind == Kind::Exit
+        {
+            let start = context.events[index - 1].point.index;
+            let end = context.events[index].point.index;
+            sequences.push(str::from_utf8(&context.bytes[start..end]).unwrap());
+        }
+        index += 1;
+    }
+
+    context
+        .options
+        .frontmatter_matters
+        .iter()
+        .find(|d| d.open == sequences[0] && d.close == sequences[sequences.len() - 1])
+        .expect("expected matter for frontmatter")
+}
+
 /// Remove initial/final EOLs.
 fn trim_eol(value: String, at_start: bool, at_end: bool) -> String {
     let bytes = value.as_bytes();
@@ -18,7 +18,7 @@ use crate::subtokenize::Subresult;
 use crate::util::char::format_byte_opt;
 
 use crate::util::{constant::TAB_SIZE, edit_map::EditMap};
-use alloc::{boxed::Box, string::String, vec, vec::Vec};
+use alloc::{boxed::Box, format, string::String, vec, vec::Vec};
 
 /// Containers.
 ///
@@ -175,6 +175,8 @@ struct Progress {
     current: Option<u8>,
     /// Current place in the file.
     point: Point,
+    /// Whether the line is lazy.
+    lazy: bool,
 }
 
 /// A lot of shared fields used to tokenize things.
@@ -272,8 +274,11 @@ pub struct TokenizeState<'a> {
 #[allow(clippy::struct_excessive_bools)]
 #[derive(Debug)]
 pub struct Tokenizer<'a> {
-    /// Jump between line endings.
-    column_start: Vec<(usize, usize)>,
+    /// Jump between line endings, and whether each line is lazy.
+    ///
+    /// Lines are tokenized again when attempts spanning several lines fail,
+    /// so whether they are lazy is kept for each line.
+    column_start: Vec<(usize, usize, bool)>,
     // First line where this tokenizer starts.
     first_line: usize,
     /// Current point after the last line ending (excluding jump).
@@ -408,7 +413,7 @@ impl<'a> Tokenizer<'a> {
     pub fn define_skip(&mut self, mut point: Point) {
         move_point_back(self, &mut point);
 
-        let info = (point.index, point.vs);
+        let info = (point.index, point.vs, self.lazy);
 
         #[cfg(feature = "log")]
         log::debug!("position: define skip: {:?} -> ({:?})", point.line, info);
@@ -430,7 +435,9 @@ impl<'a> Tokenizer<'a> {
         let at = self.point.line - self.first_line;
 
         if self.point.column == 1 && at != self.column_start.len() {
-            self.move_to(self.column_start[at]);
+            let (index, vs, lazy) = self.column_start[at];
+            self.move_to((index, vs));
+            self.lazy = lazy;
         }
     }
 
@@ -477,7 +484,8 @@ impl<'a> Tokenizer<'a> {
                     self.point.column = 1;
 
                     if self.point.line - self.first_line + 1 > self.column_start.len() {
-                        self.column_start.push((self.point.index, self.point.vs));
+                        self.column_start
+                            .push((self.point.index, self.point.vs, self.lazy));
                     }
 
                     self.line_start = self.point.clone();
@@ -493,6 +501,26 @@ impl<'a> Tokenizer<'a> {
         }
     }
 
+    /// Check whether a limit is reached or parsing is canceled.
+    ///
+    /// Returns an error message if so.
+    fn limit(&mut self) -> Option<String> {
+        let options = self.parse_state.options;
+
+        if let Some(max) = options.events_max {
+            if self.events.len() > max {
+                return Some(format!(
+                    "{}:{}: Unexpected event, expected at most {} events (markdown-rs:events-max)",
+                    self.point.line, self.point.column, max
+                ));
+            }
+        }
+
+        self.parse_state
+            .cancel(self.point.line, self.point.column)
+            .err()
+    }
+
     /// Move (virtual) bytes.
     fn move_to(&mut self, to: (usize, usize)) {
         let (to_index, to_vs) = to;
@@ -562,6 +590,7 @@ impl<'a> Tokenizer<'a> {
             point: self.point.clone(),
             events_len: self.events.len(),
             stack_len: self.stack.len(),
+            lazy: self.lazy,
         }
     }
 
@@ -570,6 +599,7 @@ impl<'a> Tokenizer<'a> {
         self.previous = previous.previous;
         self.current = previous.current;
         self.point = previous.point;
+        self.lazy = previous.lazy;
         debug_assert!(
             self.events.len() >= previous.events_len,
             "expected to restore less events than before"
@@ -643,6 +673,9 @@ impl<'a> Tokenizer<'a> {
             let defs = &mut value.definitions;
             let fn_defs = &mut value.gfm_footnote_definitions;
             while index < resolvers.len() {
+                self.parse_state
+                    .cancel(self.point.line, self.point.column)?;
+
                 if let Some(mut result) = call_resolve(self, resolvers[index])? {
                     fn_defs.append(&mut result.gfm_footnote_definitions);
                     defs.append(&mut result.definitions);
@@ -730,6 +763,11 @@ fn push_impl(
                 }
             }
             State::Next(name) => {
+                if let Some(message) = tokenizer.limit() {
+                    state = State::Error(message);
+                    continue;
+                }
+
                 let action = if tokenizer.point.index < to.0
                     || (tokenizer.point.index == to.0 && tokenizer.point.vs < to.1)
                 {
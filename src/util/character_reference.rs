@@ -155,12 +155,10 @@ pub fn value_test(marker: u8) -> fn(&u8) -> bool {
 
 /// Decode character references in a string.
 ///
-/// > 👉 **Note**: this currently only supports the 252 named character
-/// > references from HTML 4, as it’s only used for JSX.
-/// >
-/// > If it’s ever needed to support HTML 5 (which is what normal markdown
-/// > uses), a boolean parameter can be added here.
-pub fn parse(value: &str) -> String {
+/// Pass `html5: false` to only support the 252 named character references
+/// from HTML 4 (which is what JSX uses), or `html5: true` to support the
+/// 2125 from HTML 5 (which is what HTML uses).
+pub fn parse(value: &str, html5: bool) -> String {
     let bytes = value.as_bytes();
     let mut index = 0;
     let len = bytes.len();
@@ -197,7 +195,7 @@ pub fn parse(value: &str) -> String {
                 if let Some(decoded) = decode(
                     str::from_utf8(&bytes[value_start..value_end]).unwrap(),
                     marker,
-                    false,
+                    html5,
                 ) {
                     result.push_str(&value[start..index]);
                     result.push_str(&decoded);
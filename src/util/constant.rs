@@ -67,13 +67,6 @@ pub const CHARACTER_REFERENCE_NAMED_SIZE_MAX: usize = 31;
 /// [raw_flow]: crate::construct::raw_flow
 pub const CODE_FENCED_SEQUENCE_SIZE_MIN: usize = 3;
 
-/// The number of markers needed for [frontmatter][] to form.
-///
-/// Like many things in markdown, the number is `3`.
-///
-/// [frontmatter]: crate::construct::frontmatter
-pub const FRONTMATTER_SEQUENCE_SIZE: usize = 3;
-
 /// The number of the longest tag name in [`GFM_HTML_TAGFILTER_NAMES`][].
 ///
 /// This is currently the size of `plaintext`.
@@ -231,6 +224,62 @@ pub const HTML_RAW_NAMES: [&str; 4] = ["pre", "script", "style", "textarea"];
 /// This is currently the size of `textarea`.
 pub const HTML_RAW_SIZE_MAX: usize = 8;
 
+/// List of HTML tag names of void elements, which have no content and no
+/// closing tag.
+///
+/// Tag name matching must be performed insensitive to case, and thus this list
+/// includes lowercase tag names.
+///
+/// ## References
+///
+/// *   [*§ 13.1.2 Elements* in the HTML spec](https://html.spec.whatwg.org/multipage/syntax.html#void-elements)
+pub const HTML_VOID_NAMES: [&str; 13] = [
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// List of HTML attribute names of boolean attributes, whose value is their
+/// name in XHTML.
+///
+/// Attribute name matching must be performed insensitive to case, and thus
+/// this list includes lowercase attribute names.
+///
+/// ## References
+///
+/// *   [*§ 2.3.2 Boolean attributes* in the HTML spec](https://html.spec.whatwg.org/multipage/common-microsyntaxes.html#boolean-attributes)
+pub const HTML_BOOLEAN_NAMES: [&str; 24] = [
+    "allowfullscreen",
+    "async",
+    "autofocus",
+    "autoplay",
+    "checked",
+    "controls",
+    "default",
+    "defer",
+    "disabled",
+    "formnovalidate",
+    "inert",
+    "ismap",
+    "itemscope",
+    "loop",
+    "multiple",
+    "muted",
+    "nomodule",
+    "novalidate",
+    "open",
+    "playsinline",
+    "readonly",
+    "required",
+    "reversed",
+    "selected",
+];
+
+/// The number of times a tokenizer moves between states before it calls
+/// [`cancel`][crate::ParseOptions::cancel] again.
+///
+/// Calling it on every move would be slow.
+pub const CANCEL_INTERVAL: usize = 4096;
+
 /// To safeguard performance, labels are capped at a large number: `999`.
 pub const LINK_REFERENCE_SIZE_MAX: usize = 999;
 
@@ -0,0 +1,569 @@
+//! Turn frontmatter into structured data.
+//!
+//! JSON can always be parsed.
+//! YAML needs the `yaml` feature and TOML the `toml` feature.
+
+use crate::mdast::Node;
+use alloc::{format, string::String, vec::Vec};
+use core::str;
+
+/// Structured data, from frontmatter.
+///
+/// Modelled after `serde_json::Value`, but keeps keys in objects in the order
+/// in which they appear in the document.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    /// Nothing (`null` in JSON, `~` in YAML).
+    Null,
+    /// Boolean.
+    Bool(bool),
+    /// Whole number.
+    Integer(i64),
+    /// Floating point number.
+    Float(f64),
+    /// String.
+    ///
+    /// TOML dates and times are turned into strings too.
+    String(String),
+    /// List of values.
+    Array(Vec<Value>),
+    /// Map of keys to values, in document order.
+    Object(Vec<(String, Value)>),
+}
+
+impl Value {
+    /// Get the value of `key`, if this is an object that contains it.
+    ///
+    /// ## Examples
+    ///
+    /// ```
+    /// use markdown::{to_frontmatter, Constructs, FrontmatterValue, Matter, ParseOptions};
+    /// # fn main() -> Result<(), String> {
+    ///
+    /// let options = ParseOptions {
+    ///     constructs: Constructs {
+    ///         frontmatter: true,
+    ///         ..Constructs::default()
+    ///     },
+    ///     frontmatter_matters: Matter::json(),
+    ///     ..ParseOptions::default()
+    /// };
+    /// let data = to_frontmatter(";;;\n{\"title\": \"Venus\"}\n;;;", &options)?.unwrap();
+    ///
+    /// assert_eq!(data.get("title"), Some(&FrontmatterValue::String("Venus".into())));
+    /// assert_eq!(data.get("description"), None);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn get(&self, key: &str) -> Option<&Value> {
+        if let Value::Object(entries) = self {
+            entries.iter().find(|d| d.0 == key).map(|d| &d.1)
+        } else {
+            None
+        }
+    }
+
+    /// Get the string, if this is a string.
+    pub fn as_str(&self) -> Option<&str> {
+        if let Value::String(value) = self {
+            Some(value)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::Serialize for Value {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::{SerializeMap, SerializeSeq};
+
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(value) => serializer.serialize_bool(*value),
+            Value::Integer(value) => serializer.serialize_i64(*value),
+            Value::Float(value) => serializer.serialize_f64(*value),
+            Value::String(value) => serializer.serialize_str(value),
+            Value::Array(values) => {
+                let mut seq = serializer.serialize_seq(Some(values.len()))?;
+                for value in values {
+                    seq.serialize_element(value)?;
+                }
+                seq.end()
+            }
+            Value::Object(entries) => {
+                let mut map = serializer.serialize_map(Some(entries.len()))?;
+                for (key, value) in entries {
+                    map.serialize_entry(key, value)?;
+                }
+                map.end()
+            }
+        }
+    }
+}
+
+/// Turn a frontmatter node into structured data.
+///
+/// Returns `None` if `node` is not frontmatter.
+///
+/// ## Errors
+///
+/// Errors if the value of `node` is not valid in its language, or if the
+/// feature needed to parse that language is not enabled.
+/// Errors are positioned relative to the document that `node` came from,
+/// when `node` has a position.
+pub fn from_node(node: &Node) -> Option<Result<Value, String>> {
+    let (kind, value) = match node {
+        Node::Json(d) => ("json", d.value.as_str()),
+        Node::Toml(d) => ("toml", d.value.as_str()),
+        Node::Yaml(d) => ("yaml", d.value.as_str()),
+        Node::Frontmatter(d) => (d.kind.as_str(), d.value.as_str()),
+        _ => return None,
+    };
+
+    // The value starts on the line after the opening fence, except when the
+    // fences are part of the value (JSON objects in braces), in which case
+    // the value spans as many lines as the node.
+    let line = node.position().map_or(1, |d| {
+        let lines = value.split('\n').count();
+        if d.end.line - d.start.line + 1 == lines {
+            d.start.line
+        } else {
+            d.start.line + 1
+        }
+    });
+
+    Some(parse(kind, value, line))
+}
+
+/// Parse `value`, in the language `kind`, which starts at `line` in a document.
+pub fn parse(kind: &str, value: &str, line: usize) -> Result<Value, String> {
+    let result = match kind {
+        "json" => parse_json(value),
+        "toml" => parse_toml(value),
+        "yaml" => parse_yaml(value),
+        _ => Err((
+            0,
+            format!(
+                "Cannot parse frontmatter of kind `{}`, expected `json`, `toml`, or `yaml`",
+                kind
+            ),
+        )),
+    };
+
+    result.map_err(|(offset, reason)| {
+        let (relative_line, column) = line_column(value, offset);
+        format!(
+            "{}:{}: {} (frontmatter-{}:parse)",
+            line + relative_line - 1,
+            column,
+            reason,
+            kind
+        )
+    })
+}
+
+/// Get the 1-indexed line and column of a byte `offset` in `value`.
+pub fn line_column(value: &str, offset: usize) -> (usize, usize) {
+    let before = &value.as_bytes()[..offset.min(value.len())];
+    let line = before.split(|d| *d == b'\n').count();
+    let line_start = before
+        .iter()
+        .rposition(|d| *d == b'\n')
+        .map_or(0, |index| index + 1);
+    (line, before.len() - line_start + 1)
+}
+
+/// Parse YAML.
+#[cfg(feature = "yaml")]
+fn parse_yaml(value: &str) -> Result<Value, (usize, String)> {
+    use alloc::string::ToString;
+    use yaml_rust2::{Yaml, YamlLoader};
+
+    fn convert(yaml: Yaml) -> Value {
+        match yaml {
+            Yaml::Real(value) => value.parse().map_or(Value::String(value), Value::Float),
+            Yaml::Integer(value) => Value::Integer(value),
+            Yaml::String(value) => Value::String(value),
+            Yaml::Boolean(value) => Value::Bool(value),
+            Yaml::Array(values) => Value::Array(values.into_iter().map(convert).collect()),
+            Yaml::Hash(entries) => Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(key, value)| {
+                        let key = match convert(key) {
+                            Value::String(key) => key,
+                            Value::Integer(key) => key.to_string(),
+                            Value::Float(key) => key.to_string(),
+                            Value::Bool(key) => key.to_string(),
+                            _ => String::new(),
+                        };
+                        (key, convert(value))
+                    })
+                    .collect(),
+            ),
+            Yaml::Alias(_) | Yaml::Null | Yaml::BadValue => Value::Null,
+        }
+    }
+
+    match YamlLoader::load_from_str(value) {
+        Ok(documents) => Ok(documents.into_iter().next().map_or(Value::Null, convert)),
+        Err(error) => {
+            // Markers count characters, not bytes.
+            let offset = value
+                .char_indices()
+                .nth(error.marker().index())
+                .map_or(value.len(), |d| d.0);
+            Err((offset, error.info().into()))
+        }
+    }
+}
+
+/// Parse YAML.
+#[cfg(not(feature = "yaml"))]
+fn parse_yaml(_value: &str) -> Result<Value, (usize, String)> {
+    Err((
+        0,
+        "Cannot parse YAML frontmatter, expected the `yaml` feature to be enabled".into(),
+    ))
+}
+
+/// Parse TOML.
+#[cfg(feature = "toml")]
+fn parse_toml(value: &str) -> Result<Value, (usize, String)> {
+    use alloc::string::ToString;
+
+    fn convert(toml: toml::Value) -> Value {
+        match toml {
+            toml::Value::String(value) => Value::String(value),
+            toml::Value::Integer(value) => Value::Integer(value),
+            toml::Value::Float(value) => Value::Float(value),
+            toml::Value::Boolean(value) => Value::Bool(value),
+            toml::Value::Datetime(value) => Value::String(value.to_string()),
+            toml::Value::Array(values) => Value::Array(values.into_iter().map(convert).collect()),
+            toml::Value::Table(table) => Value::Object(
+                table
+                    .into_iter()
+                    .map(|(key, value)| (key, convert(value)))
+                    .collect(),
+            ),
+        }
+    }
+
+    // Without a final line ending, errors at the end have no message.
+    match format!("{}\n", value).parse::<toml::Table>() {
+        Ok(table) => Ok(convert(toml::Value::Table(table))),
+        Err(error) => Err((
+            error.span().map_or(0, |d| d.start),
+            error.message().trim_end().replace('\n', ", "),
+        )),
+    }
+}
+
+/// Parse TOML.
+#[cfg(not(feature = "toml"))]
+fn parse_toml(_value: &str) -> Result<Value, (usize, String)> {
+    Err((
+        0,
+        "Cannot parse TOML frontmatter, expected the `toml` feature to be enabled".into(),
+    ))
+}
+
+/// Parse JSON.
+pub fn parse_json(value: &str) -> Result<Value, (usize, String)> {
+    let mut parser = JsonParser {
+        bytes: value.as_bytes(),
+        index: 0,
+    };
+    parser.whitespace();
+
+    // Empty frontmatter.
+    if parser.index == parser.bytes.len() {
+        return Ok(Value::Null);
+    }
+
+    let result = parser.value(0)?;
+    parser.whitespace();
+
+    if parser.index == parser.bytes.len() {
+        Ok(result)
+    } else {
+        Err(parser.unexpected("after value, expected end of input"))
+    }
+}
+
+/// Maximum depth of arrays and objects in JSON.
+const JSON_DEPTH_MAX: usize = 128;
+
+/// Small JSON parser.
+struct JsonParser<'a> {
+    /// Input.
+    bytes: &'a [u8],
+    /// Current place in `bytes`.
+    index: usize,
+}
+
+impl JsonParser<'_> {
+    /// Create an error at the current place.
+    fn unexpected(&self, expected: &str) -> (usize, String) {
+        let found = if self.index == self.bytes.len() {
+            "end of input".into()
+        } else {
+            let rest = str::from_utf8(&self.bytes[self.index..]).unwrap_or_default();
+            format!("character `{}`", rest.chars().next().unwrap_or_default())
+        };
+        (self.index, format!("Unexpected {} {}", found, expected))
+    }
+
+    /// Skip whitespace.
+    fn whitespace(&mut self) {
+        while matches!(
+            self.bytes.get(self.index),
+            Some(b'\t' | b'\n' | b'\r' | b' ')
+        ) {
+            self.index += 1;
+        }
+    }
+
+    /// Eat `literal`, or fail.
+    fn literal(&mut self, literal: &str, value: Value) -> Result<Value, (usize, String)> {
+        if self.bytes[self.index..].starts_with(literal.as_bytes()) {
+            self.index += literal.len();
+            Ok(value)
+        } else {
+            Err(self.unexpected("in value, expected `true`, `false`, or `null`"))
+        }
+    }
+
+    /// Parse any value.
+    fn value(&mut self, depth: usize) -> Result<Value, (usize, String)> {
+        match self.bytes.get(self.index) {
+            Some(b'{' | b'[') if depth == JSON_DEPTH_MAX => {
+                Err(self.unexpected("in value, expected less nesting"))
+            }
+            Some(b'{') => self.object(depth + 1),
+            Some(b'[') => self.array(depth + 1),
+            Some(b'"') => Ok(Value::String(self.string()?)),
+            Some(b'-' | b'0'..=b'9') => self.number(),
+            Some(b't') => self.literal("true", Value::Bool(true)),
+            Some(b'f') => self.literal("false", Value::Bool(false)),
+            Some(b'n') => self.literal("null", Value::Null),
+            _ => Err(self.unexpected("before value, expected a value")),
+        }
+    }
+
+    /// Parse an object.
+    fn object(&mut self, depth: usize) -> Result<Value, (usize, String)> {
+        let mut entries = Vec::new();
+        self.index += 1;
+        self.whitespace();
+
+        if self.bytes.get(self.index) == Some(&b'}') {
+            self.index += 1;
+            return Ok(Value::Object(entries));
+        }
+
+        loop {
+            if self.bytes.get(self.index) != Some(&b'"') {
+                return Err(self.unexpected("before key, expected a string"));
+            }
+
+            let key = self.string()?;
+            self.whitespace();
+
+            if self.bytes.get(self.index) != Some(&b':') {
+                return Err(self.unexpected("after key, expected `:`"));
+            }
+
+            self.index += 1;
+            self.whitespace();
+            let value = self.value(depth)?;
+            entries.push((key, value));
+            self.whitespace();
+
+            match self.bytes.get(self.index) {
+                Some(b',') => {
+                    self.index += 1;
+                    self.whitespace();
+                }
+                Some(b'}') => {
+                    self.index += 1;
+                    return Ok(Value::Object(entries));
+                }
+                _ => return Err(self.unexpected("after value in object, expected `,` or `}`")),
+            }
+        }
+    }
+
+    /// Parse an array.
+    fn array(&mut self, depth: usize) -> Result<Value, (usize, String)> {
+        let mut values = Vec::new();
+        self.index += 1;
+        self.whitespace();
+
+        if self.bytes.get(self.index) == Some(&b']') {
+            self.index += 1;
+            return Ok(Value::Array(values));
+        }
+
+        loop {
+            values.push(self.value(depth)?);
+            self.whitespace();
+
+            match self.bytes.get(self.index) {
+                Some(b',') => {
+                    self.index += 1;
+                    self.whitespace();
+                }
+                Some(b']') => {
+                    self.index += 1;
+                    return Ok(Value::Array(values));
+                }
+                _ => return Err(self.unexpected("after value in array, expected `,` or `]`")),
+            }
+        }
+    }
+
+    /// Parse a string.
+    fn string(&mut self) -> Result<String, (usize, String)> {
+        let mut result = String::new();
+        self.index += 1;
+        let mut start = self.index;
+
+        loop {
+            match self.bytes.get(self.index) {
+                Some(b'"') => {
+                    result.push_str(str::from_utf8(&self.bytes[start..self.index]).unwrap());
+                    self.index += 1;
+                    return Ok(result);
+                }
+                Some(b'\\') => {
+                    result.push_str(str::from_utf8(&self.bytes[start..self.index]).unwrap());
+                    self.index += 1;
+                    let escape = match self.bytes.get(self.index) {
+                        Some(b'"') => '"',
+                        Some(b'\\') => '\\',
+                        Some(b'/') => '/',
+                        Some(b'b') => '\u{8}',
+                        Some(b'f') => '\u{c}',
+                        Some(b'n') => '\n',
+                        Some(b'r') => '\r',
+                        Some(b't') => '\t',
+                        Some(b'u') => self.unicode_escape()?,
+                        _ => return Err(self.unexpected("after backslash, expected an escape")),
+                    };
+                    result.push(escape);
+                    self.index += 1;
+                    start = self.index;
+                }
+                Some(0..=0x1F) | None => {
+                    return Err(self.unexpected("in string, expected a closing quote"))
+                }
+                Some(_) => self.index += 1,
+            }
+        }
+    }
+
+    /// Parse the hexadecimal digits of a `\u` escape, and a second one if this
+    /// is a surrogate pair.
+    ///
+    /// Unpaired surrogates turn into a replacement character (`U+FFFD`).
+    ///
+    /// Leaves `index` at the last digit.
+    fn unicode_escape(&mut self) -> Result<char, (usize, String)> {
+        let high = self.hex()?;
+        let mut code = high;
+
+        if (0xD800..0xDC00).contains(&high) && self.bytes[self.index + 1..].starts_with(b"\\u") {
+            let index = self.index;
+            self.index += 2;
+            let low = self.hex()?;
+
+            if (0xDC00..0xE000).contains(&low) {
+                code = 0x10000 + ((high - 0xD800) << 10) + (low - 0xDC00);
+            } else {
+                // Not a pair: the second escape is parsed on its own.
+                self.index = index;
+            }
+        }
+
+        Ok(char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER))
+    }
+
+    /// Parse four hexadecimal digits after `index`.
+    fn hex(&mut self) -> Result<u32, (usize, String)> {
+        let mut code = 0;
+        let mut size = 0;
+
+        while size < 4 {
+            self.index += 1;
+            let digit = match self.bytes.get(self.index) {
+                Some(byte) => char::from(*byte).to_digit(16),
+                None => None,
+            };
+
+            if let Some(digit) = digit {
+                code = code * 16 + digit;
+                size += 1;
+            } else {
+                return Err(self.unexpected("in unicode escape, expected a hexadecimal digit"));
+            }
+        }
+
+        Ok(code)
+    }
+
+    /// Parse a number.
+    fn number(&mut self) -> Result<Value, (usize, String)> {
+        let start = self.index;
+        let mut float = false;
+
+        if self.bytes[self.index] == b'-' {
+            self.index += 1;
+        }
+
+        match self.bytes.get(self.index) {
+            Some(b'0') => self.index += 1,
+            Some(b'1'..=b'9') => self.digits(),
+            _ => return Err(self.unexpected("in number, expected a digit")),
+        }
+
+        if self.bytes.get(self.index) == Some(&b'.') {
+            float = true;
+            self.index += 1;
+            if !matches!(self.bytes.get(self.index), Some(b'0'..=b'9')) {
+                return Err(self.unexpected("after decimal point, expected a digit"));
+            }
+            self.digits();
+        }
+
+        if matches!(self.bytes.get(self.index), Some(b'e' | b'E')) {
+            float = true;
+            self.index += 1;
+            if matches!(self.bytes.get(self.index), Some(b'+' | b'-')) {
+                self.index += 1;
+            }
+            if !matches!(self.bytes.get(self.index), Some(b'0'..=b'9')) {
+                return Err(self.unexpected("in exponent, expected a digit"));
+            }
+            self.digits();
+        }
+
+        let value = str::from_utf8(&self.bytes[start..self.index]).unwrap();
+
+        if !float {
+            if let Ok(integer) = value.parse() {
+                return Ok(Value::Integer(integer));
+            }
+        }
+
+        Ok(Value::Float(value.parse().unwrap()))
+    }
+
+    /// Skip digits.
+    fn digits(&mut self) {
+        while matches!(self.bytes.get(self.index), Some(b'0'..=b'9')) {
+            self.index += 1;
+        }
+    }
+}
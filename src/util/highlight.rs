@@ -0,0 +1,288 @@
+//! Highlight code.
+
+use crate::util::encode::encode;
+use alloc::string::String;
+
+/// Language that can be highlighted.
+struct Language {
+    /// Keywords (and literals such as `true`).
+    keywords: &'static [&'static str],
+    /// Start of line comments.
+    line_comment: Option<&'static str>,
+    /// Start and end of block comments.
+    block_comment: Option<(&'static str, &'static str)>,
+    /// Quotes of strings.
+    quotes: &'static [u8],
+}
+
+/// JavaScript and TypeScript.
+const JAVASCRIPT: Language = Language {
+    keywords: &[
+        "as",
+        "async",
+        "await",
+        "break",
+        "case",
+        "catch",
+        "class",
+        "const",
+        "continue",
+        "debugger",
+        "default",
+        "delete",
+        "do",
+        "else",
+        "enum",
+        "export",
+        "extends",
+        "false",
+        "finally",
+        "for",
+        "from",
+        "function",
+        "if",
+        "implements",
+        "import",
+        "in",
+        "instanceof",
+        "interface",
+        "let",
+        "new",
+        "null",
+        "of",
+        "return",
+        "static",
+        "super",
+        "switch",
+        "this",
+        "throw",
+        "true",
+        "try",
+        "type",
+        "typeof",
+        "undefined",
+        "var",
+        "void",
+        "while",
+        "with",
+        "yield",
+    ],
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    quotes: b"\"'`",
+};
+
+/// JSON.
+const JSON: Language = Language {
+    keywords: &["false", "null", "true"],
+    line_comment: None,
+    block_comment: None,
+    quotes: b"\"",
+};
+
+/// Python.
+const PYTHON: Language = Language {
+    keywords: &[
+        "False", "None", "True", "and", "as", "assert", "async", "await", "break", "class",
+        "continue", "def", "del", "elif", "else", "except", "finally", "for", "from", "global",
+        "if", "import", "in", "is", "lambda", "nonlocal", "not", "or", "pass", "raise", "return",
+        "try", "while", "with", "yield",
+    ],
+    line_comment: Some("#"),
+    block_comment: None,
+    quotes: b"\"'",
+};
+
+/// Rust.
+const RUST: Language = Language {
+    keywords: &[
+        "as", "async", "await", "break", "const", "continue", "crate", "dyn", "else", "enum",
+        "extern", "false", "fn", "for", "if", "impl", "in", "let", "loop", "match", "mod", "move",
+        "mut", "pub", "ref", "return", "self", "Self", "static", "struct", "super", "trait",
+        "true", "type", "unsafe", "use", "where", "while",
+    ],
+    line_comment: Some("//"),
+    block_comment: Some(("/*", "*/")),
+    quotes: b"\"",
+};
+
+/// Shell.
+const SHELL: Language = Language {
+    keywords: &[
+        "case", "do", "done", "elif", "else", "esac", "export", "fi", "for", "function", "if",
+        "in", "local", "return", "then", "until", "while",
+    ],
+    line_comment: Some("#"),
+    block_comment: None,
+    quotes: b"\"'",
+};
+
+/// Highlight code, as a [`Highlight`][crate::Highlight] function.
+///
+/// Supports JavaScript and TypeScript (`js`, `jsx`, `ts`, `tsx`, and
+/// more), JSON (`json`), Python (`py`, `python`), Rust (`rs`, `rust`), and
+/// shell (`sh`, `bash`, `shell`, `zsh`).
+/// Comments, strings, numbers, and keywords are wrapped in `span` elements
+/// with classes (`hl-comment`, `hl-string`, `hl-number`, and `hl-keyword`),
+/// which can be styled with CSS.
+/// Elements do not span several lines.
+/// Returns `None` for other languages.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{highlight, to_html_with_options, CompileOptions, Options};
+/// use std::sync::Arc;
+/// # fn main() -> Result<(), String> {
+///
+/// assert_eq!(
+///     highlight(Some("rust"), None, "let a = \"b\"; // c").unwrap(),
+///     "<span class=\"hl-keyword\">let</span> a = <span class=\"hl-string\">&quot;b&quot;</span>; <span class=\"hl-comment\">// c</span>"
+/// );
+///
+/// assert_eq!(
+///     to_html_with_options(
+///         "```py\nreturn 1\n```",
+///         &Options {
+///             compile: CompileOptions {
+///               highlight: Some(Arc::new(highlight)),
+///               ..CompileOptions::default()
+///             },
+///             ..Options::default()
+///         }
+///     )?,
+///     "<pre><code class=\"language-py\"><span class=\"hl-keyword\">return</span> <span class=\"hl-number\">1</span>\n</code></pre>"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn highlight(lang: Option<&str>, _meta: Option<&str>, code: &str) -> Option<String> {
+    let language = match lang?.to_ascii_lowercase().as_str() {
+        "cjs" | "javascript" | "js" | "jsx" | "mjs" | "ts" | "tsx" | "typescript" => JAVASCRIPT,
+        "json" => JSON,
+        "py" | "python" => PYTHON,
+        "rs" | "rust" => RUST,
+        "bash" | "sh" | "shell" | "zsh" => SHELL,
+        _ => return None,
+    };
+
+    let bytes = code.as_bytes();
+    let mut result = String::with_capacity(code.len());
+    let mut index = 0;
+    let mut start = 0;
+
+    while index < bytes.len() {
+        let rest = &code[index..];
+        let byte = bytes[index];
+
+        let token = if let Some((open, close)) = language
+            .block_comment
+            .filter(|(open, _)| rest.starts_with(open))
+        {
+            let end = rest[open.len()..]
+                .find(close)
+                .map_or(bytes.len(), |d| index + open.len() + d + close.len());
+            Some(("comment", end))
+        } else if language
+            .line_comment
+            .map_or(false, |open| rest.starts_with(open))
+        {
+            Some(("comment", line_end(bytes, index)))
+        } else if language.quotes.contains(&byte) {
+            Some(("string", string_end(bytes, index)))
+        } else if byte.is_ascii_digit() && !(index > 0 && is_word(bytes[index - 1])) {
+            let mut end = index + 1;
+            while end < bytes.len() && (is_word(bytes[end]) || bytes[end] == b'.') {
+                end += 1;
+            }
+            Some(("number", end))
+        } else if is_word(byte) && !(index > 0 && is_word(bytes[index - 1])) {
+            let mut end = index + 1;
+            while end < bytes.len() && is_word(bytes[end]) {
+                end += 1;
+            }
+            if language.keywords.contains(&&code[index..end]) {
+                Some(("keyword", end))
+            } else {
+                index = end;
+                None
+            }
+        } else {
+            // Move past the whole character, which can be several bytes.
+            index += rest.chars().next().map_or(1, char::len_utf8);
+            None
+        };
+
+        if let Some((class, end)) = token {
+            result.push_str(&encode(&code[start..index], true));
+            push_token(&mut result, class, &code[index..end]);
+            index = end;
+            start = end;
+        }
+    }
+
+    result.push_str(&encode(&code[start..], true));
+
+    Some(result)
+}
+
+/// Push a token, wrapped in an element per line.
+fn push_token(result: &mut String, class: &str, value: &str) {
+    let mut start = 0;
+
+    for (index, char) in value.char_indices() {
+        if matches!(char, '\n' | '\r') {
+            push_span(result, class, &value[start..index]);
+            result.push(char);
+            start = index + 1;
+        }
+    }
+
+    push_span(result, class, &value[start..]);
+}
+
+/// Push an element, if there is a value.
+fn push_span(result: &mut String, class: &str, value: &str) {
+    if !value.is_empty() {
+        result.push_str("<span class=\"hl-");
+        result.push_str(class);
+        result.push_str("\">");
+        result.push_str(&encode(value, true));
+        result.push_str("</span>");
+    }
+}
+
+/// Find the end of a string starting at `index` (at its quote), which is
+/// after the closing quote, or at the line ending if it is not closed
+/// (except for template literals).
+fn string_end(bytes: &[u8], index: usize) -> usize {
+    let quote = bytes[index];
+    let mut end = index + 1;
+
+    while end < bytes.len() {
+        match bytes[end] {
+            b'\\' => end += 1,
+            b'\n' | b'\r' if quote != b'`' => return end,
+            byte if byte == quote => return end + 1,
+            _ => {}
+        }
+
+        end += 1;
+    }
+
+    bytes.len()
+}
+
+/// Find the line ending from `index`.
+fn line_end(bytes: &[u8], mut index: usize) -> usize {
+    while index < bytes.len() && !matches!(bytes[index], b'\n' | b'\r') {
+        index += 1;
+    }
+
+    index
+}
+
+/// Check whether `byte` can be in a word (identifier or number).
+fn is_word(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || matches!(byte, b'_' | b'$')
+}
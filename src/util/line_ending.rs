@@ -16,6 +16,7 @@ use alloc::{str::FromStr, string::String};
 /// # }
 /// ```
 #[derive(Clone, Debug, Default, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum LineEnding {
     /// Both a carriage return (`\r`) and a line feed (`\n`).
     ///
@@ -25,6 +26,7 @@ pub enum LineEnding {
     /// a␍␊
     /// b
     /// ```
+    #[cfg_attr(feature = "serde", serde(rename = "crlf", alias = "\r\n"))]
     CarriageReturnLineFeed,
     /// Sole carriage return (`\r`).
     ///
@@ -34,6 +36,7 @@ pub enum LineEnding {
     /// a␍
     /// b
     /// ```
+    #[cfg_attr(feature = "serde", serde(rename = "cr", alias = "\r"))]
     CarriageReturn,
     /// Sole line feed (`\n`).
     ///
@@ -44,6 +47,7 @@ pub enum LineEnding {
     /// b
     /// ```
     #[default]
+    #[cfg_attr(feature = "serde", serde(rename = "lf", alias = "\n"))]
     LineFeed,
 }
 
@@ -3,9 +3,17 @@
 //! * Convert between byte indices and unist points.
 //! * Convert between byte indices into a string which is built up of several
 //!   slices in a whole document, and byte indices into that whole document.
+//! * Convert byte-based points (and places in error messages) into points
+//!   in other encodings.
 
+use crate::configuration::PositionEncoding;
+use crate::mdast::Node;
 use crate::unist::Point;
-use alloc::{vec, vec::Vec};
+use alloc::{
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
 
 /// Each stop represents a new slice, which contains the byte index into the
 /// corresponding string where the slice starts (`0`), and the byte index into
@@ -82,6 +90,26 @@ impl Location {
         None
     }
 
+    /// Get the `offset` for a line and column-based point (both 1-indexed) in
+    /// the bound indices.
+    ///
+    /// Returns `None` when given out of bounds input.
+    ///
+    /// Port of <https://github.com/vfile/vfile-location/blob/main/index.js>
+    #[must_use]
+    pub fn to_offset(&self, line: usize, column: usize) -> Option<usize> {
+        if line > 0 && column > 0 && line <= self.indices.len() {
+            let start = if line > 1 { self.indices[line - 2] } else { 0 };
+            let offset = start + column - 1;
+
+            if offset < self.indices[line - 1] {
+                return Some(offset);
+            }
+        }
+
+        None
+    }
+
     /// Like `to_point`, but takes a relative offset from a certain string
     /// instead of an absolute offset into the whole document.
     ///
@@ -111,6 +139,163 @@ impl Location {
     }
 }
 
+/// Turns byte-based points into points in another encoding.
+#[derive(Debug)]
+pub struct Encoder<'a> {
+    /// Document.
+    bytes: &'a [u8],
+    /// Encoding to turn points into.
+    encoding: PositionEncoding,
+    /// Index of lines in `bytes`.
+    location: Location,
+    /// List, where each index is a line number (0-based), and each value is
+    /// the encoded offset where the line starts.
+    starts: Vec<usize>,
+}
+
+impl<'a> Encoder<'a> {
+    /// Get an encoder for `bytes`.
+    #[must_use]
+    pub fn new(bytes: &'a [u8], encoding: PositionEncoding) -> Self {
+        let location = Location::new(bytes);
+        let mut starts = vec![0];
+        let mut previous = 0;
+        let mut line = 2;
+
+        while let Some(start) = location.to_offset(line, 1) {
+            starts.push(starts[starts.len() - 1] + size(&bytes[previous..start], encoding));
+            previous = start;
+            line += 1;
+        }
+
+        Encoder {
+            bytes,
+            encoding,
+            location,
+            starts,
+        }
+    }
+
+    /// Get the encoded point for a byte-based `point`.
+    ///
+    /// Returns `point` when it is not in the document.
+    #[must_use]
+    pub fn point(&self, point: &Point) -> Point {
+        if let Some(start) = self.location.to_offset(point.line, 1) {
+            if point.offset >= start && point.offset <= self.bytes.len() {
+                let column = size(&self.bytes[start..point.offset], self.encoding) + 1;
+                return Point::new(point.line, column, self.starts[point.line - 1] + column - 1);
+            }
+        }
+
+        point.clone()
+    }
+
+    /// Encode the positions of `node` and its descendants.
+    pub fn node(&self, node: &mut Node) {
+        if let Some(position) = node.position_mut() {
+            position.start = self.point(&position.start);
+            position.end = self.point(&position.end);
+        }
+
+        if let Some(children) = node.children_mut() {
+            for child in children {
+                self.node(child);
+            }
+        }
+    }
+
+    /// Encode the byte-based places (`line:column`) in an error message:
+    /// the one it starts with, and ones in parens (such as `(1:2)`).
+    #[must_use]
+    pub fn message(&self, message: &str) -> String {
+        let bytes = message.as_bytes();
+        let mut result = String::new();
+        let mut last = 0;
+        let mut index = 0;
+
+        while index < bytes.len() {
+            if index == 0 || bytes[index - 1] == b'(' {
+                if let Some((line, column, end)) = place(bytes, index) {
+                    let after = if index == 0 { b':' } else { b')' };
+
+                    if bytes.get(end) == Some(&after) {
+                        if let Some(offset) = self.location.to_offset(line, column) {
+                            let point = self.point(&Point::new(line, column, offset));
+                            result.push_str(&message[last..index]);
+                            result.push_str(&point.line.to_string());
+                            result.push(':');
+                            result.push_str(&point.column.to_string());
+                            last = end;
+                        }
+
+                        index = end;
+                        continue;
+                    }
+                }
+            }
+
+            index += 1;
+        }
+
+        result.push_str(&message[last..]);
+        result
+    }
+}
+
+/// Get the size of `bytes` in `encoding`.
+fn size(bytes: &[u8], encoding: PositionEncoding) -> usize {
+    match encoding {
+        PositionEncoding::Bytes => bytes.len(),
+        // Count everything except continuation bytes.
+        PositionEncoding::Chars => bytes.iter().filter(|d| (**d & 0xC0) != 0x80).count(),
+        // Count like chars, but characters of four bytes take two units.
+        PositionEncoding::Utf16 => bytes
+            .iter()
+            .map(|d| match *d {
+                0x80..=0xBF => 0,
+                0xF0..=0xFF => 2,
+                _ => 1,
+            })
+            .sum(),
+    }
+}
+
+/// Parse a place (`line:column`) in `bytes` at `index`.
+///
+/// Returns the line, column, and the index after the place.
+fn place(bytes: &[u8], index: usize) -> Option<(usize, usize, usize)> {
+    let (line, index) = digits(bytes, index)?;
+
+    if bytes.get(index) == Some(&b':') {
+        let (column, index) = digits(bytes, index + 1)?;
+        Some((line, column, index))
+    } else {
+        None
+    }
+}
+
+/// Parse digits in `bytes` at `index`.
+///
+/// Returns the number and the index after it.
+fn digits(bytes: &[u8], mut index: usize) -> Option<(usize, usize)> {
+    let start = index;
+    let mut value: usize = 0;
+
+    while index < bytes.len() && bytes[index].is_ascii_digit() {
+        value = value
+            .checked_mul(10)?
+            .checked_add((bytes[index] - b'0').into())?;
+        index += 1;
+    }
+
+    if index > start {
+        Some((value, index))
+    } else {
+        None
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,6 +381,42 @@ mod tests {
             "should support some points (4)"
         );
     }
+
+    #[test]
+    fn test_location_to_offset() {
+        let location = Location::new("ab\nc".as_bytes());
+        assert_eq!(
+            location.to_offset(1, 1), // `a`
+            Some(0),
+            "should support some offsets (1)"
+        );
+        assert_eq!(
+            location.to_offset(1, 3), // `\n`
+            Some(2),
+            "should support some offsets (2)"
+        );
+        assert_eq!(
+            location.to_offset(2, 1), // `c`
+            Some(3),
+            "should support some offsets (3)"
+        );
+        assert_eq!(
+            location.to_offset(2, 2), // EOF
+            Some(4),
+            "should support some offsets (4)"
+        );
+        assert_eq!(
+            location.to_offset(1, 4), // Past the line ending
+            None,
+            "should support some offsets (5)"
+        );
+        assert_eq!(
+            location.to_offset(3, 1), // Out of bounds
+            None,
+            "should support some offsets (6)"
+        );
+    }
+
     #[test]
     fn test_empty() {
         let location = Location::new("".as_bytes());
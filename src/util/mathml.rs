@@ -0,0 +1,767 @@
+//! Turn LaTeX into `MathML`.
+
+use crate::util::encode::encode;
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+
+/// Letters and other identifiers, as names of commands and their values.
+const IDENTIFIERS: [(&str, &str); 44] = [
+    ("alpha", "α"),
+    ("beta", "β"),
+    ("gamma", "γ"),
+    ("delta", "δ"),
+    ("epsilon", "ϵ"),
+    ("varepsilon", "ε"),
+    ("zeta", "ζ"),
+    ("eta", "η"),
+    ("theta", "θ"),
+    ("vartheta", "ϑ"),
+    ("iota", "ι"),
+    ("kappa", "κ"),
+    ("lambda", "λ"),
+    ("mu", "μ"),
+    ("nu", "ν"),
+    ("xi", "ξ"),
+    ("pi", "π"),
+    ("varpi", "ϖ"),
+    ("rho", "ρ"),
+    ("varrho", "ϱ"),
+    ("sigma", "σ"),
+    ("varsigma", "ς"),
+    ("tau", "τ"),
+    ("upsilon", "υ"),
+    ("phi", "ϕ"),
+    ("varphi", "φ"),
+    ("chi", "χ"),
+    ("psi", "ψ"),
+    ("omega", "ω"),
+    ("infty", "∞"),
+    ("partial", "∂"),
+    ("nabla", "∇"),
+    ("emptyset", "∅"),
+    ("varnothing", "∅"),
+    ("hbar", "ℏ"),
+    ("ell", "ℓ"),
+    ("aleph", "ℵ"),
+    ("Re", "ℜ"),
+    ("Im", "ℑ"),
+    ("wp", "℘"),
+    ("angle", "∠"),
+    ("triangle", "△"),
+    ("top", "⊤"),
+    ("bot", "⊥"),
+];
+
+/// Uppercase Greek letters, which are upright, as names of commands and
+/// their values.
+const UPRIGHT_IDENTIFIERS: [(&str, &str); 11] = [
+    ("Gamma", "Γ"),
+    ("Delta", "Δ"),
+    ("Theta", "Θ"),
+    ("Lambda", "Λ"),
+    ("Xi", "Ξ"),
+    ("Pi", "Π"),
+    ("Sigma", "Σ"),
+    ("Upsilon", "Υ"),
+    ("Phi", "Φ"),
+    ("Psi", "Ψ"),
+    ("Omega", "Ω"),
+];
+
+/// Operators, as names of commands and their values.
+const OPERATORS: [(&str, &str); 84] = [
+    ("times", "×"),
+    ("cdot", "⋅"),
+    ("div", "÷"),
+    ("pm", "±"),
+    ("mp", "∓"),
+    ("ast", "∗"),
+    ("star", "⋆"),
+    ("circ", "∘"),
+    ("bullet", "∙"),
+    ("oplus", "⊕"),
+    ("otimes", "⊗"),
+    ("leq", "≤"),
+    ("le", "≤"),
+    ("geq", "≥"),
+    ("ge", "≥"),
+    ("neq", "≠"),
+    ("ne", "≠"),
+    ("approx", "≈"),
+    ("equiv", "≡"),
+    ("sim", "∼"),
+    ("simeq", "≃"),
+    ("cong", "≅"),
+    ("propto", "∝"),
+    ("ll", "≪"),
+    ("gg", "≫"),
+    ("in", "∈"),
+    ("notin", "∉"),
+    ("ni", "∋"),
+    ("subset", "⊂"),
+    ("subseteq", "⊆"),
+    ("supset", "⊃"),
+    ("supseteq", "⊇"),
+    ("cup", "∪"),
+    ("cap", "∩"),
+    ("setminus", "∖"),
+    ("forall", "∀"),
+    ("exists", "∃"),
+    ("neg", "¬"),
+    ("lnot", "¬"),
+    ("land", "∧"),
+    ("wedge", "∧"),
+    ("lor", "∨"),
+    ("vee", "∨"),
+    ("to", "→"),
+    ("rightarrow", "→"),
+    ("leftarrow", "←"),
+    ("gets", "←"),
+    ("leftrightarrow", "↔"),
+    ("Rightarrow", "⇒"),
+    ("Leftarrow", "⇐"),
+    ("Leftrightarrow", "⇔"),
+    ("implies", "⟹"),
+    ("iff", "⟺"),
+    ("mapsto", "↦"),
+    ("uparrow", "↑"),
+    ("downarrow", "↓"),
+    ("int", "∫"),
+    ("iint", "∬"),
+    ("oint", "∮"),
+    ("ldots", "…"),
+    ("dots", "…"),
+    ("cdots", "⋯"),
+    ("vdots", "⋮"),
+    ("ddots", "⋱"),
+    ("langle", "⟨"),
+    ("rangle", "⟩"),
+    ("lfloor", "⌊"),
+    ("rfloor", "⌋"),
+    ("lceil", "⌈"),
+    ("rceil", "⌉"),
+    ("vert", "|"),
+    ("lvert", "|"),
+    ("rvert", "|"),
+    ("Vert", "‖"),
+    ("lVert", "‖"),
+    ("rVert", "‖"),
+    ("mid", "∣"),
+    ("parallel", "∥"),
+    ("perp", "⊥"),
+    ("prime", "′"),
+    ("colon", ":"),
+    ("therefore", "∴"),
+    ("because", "∵"),
+    ("dagger", "†"),
+];
+
+/// Operators with limits below and above them (in display math), as names
+/// of commands and their values.
+const LIMIT_OPERATORS: [(&str, &str); 14] = [
+    ("sum", "∑"),
+    ("prod", "∏"),
+    ("coprod", "∐"),
+    ("bigcup", "⋃"),
+    ("bigcap", "⋂"),
+    ("bigoplus", "⨁"),
+    ("bigotimes", "⨂"),
+    ("lim", "lim"),
+    ("max", "max"),
+    ("min", "min"),
+    ("sup", "sup"),
+    ("inf", "inf"),
+    ("det", "det"),
+    ("gcd", "gcd"),
+];
+
+/// Functions, as names of commands.
+const FUNCTIONS: [&str; 22] = [
+    "sin", "cos", "tan", "cot", "sec", "csc", "arcsin", "arccos", "arctan", "sinh", "cosh", "tanh",
+    "coth", "log", "ln", "lg", "exp", "deg", "dim", "arg", "ker", "Pr",
+];
+
+/// Accents, as names of commands, their values, and whether they are below.
+const ACCENTS: [(&str, &str, bool); 9] = [
+    ("hat", "^", false),
+    ("widehat", "^", false),
+    ("bar", "‾", false),
+    ("overline", "‾", false),
+    ("vec", "→", false),
+    ("dot", "˙", false),
+    ("ddot", "¨", false),
+    ("tilde", "~", false),
+    ("underline", "_", true),
+];
+
+/// Spaces, as names of commands and their widths.
+const SPACES: [(&str, &str); 7] = [
+    (",", "0.167em"),
+    (":", "0.222em"),
+    (";", "0.278em"),
+    (" ", "0.333em"),
+    ("!", "-0.167em"),
+    ("quad", "1em"),
+    ("qquad", "2em"),
+];
+
+/// Matrix environments, as names and their opening and closing fences.
+const MATRICES: [(&str, &str, &str); 7] = [
+    ("matrix", "", ""),
+    ("pmatrix", "(", ")"),
+    ("bmatrix", "[", "]"),
+    ("Bmatrix", "{", "}"),
+    ("vmatrix", "|", "|"),
+    ("Vmatrix", "‖", "‖"),
+    ("cases", "{", ""),
+];
+
+/// Node, with whether scripts go below and above it.
+struct Atom {
+    /// `MathML`.
+    node: String,
+    /// Whether scripts are limits (below and above).
+    limits: bool,
+}
+
+/// Turn a subset of LaTeX into `MathML`, as a [`Math`][crate::Math] function.
+///
+/// Supports letters, numbers, and operators; groups (`{a}`); sub- and
+/// superscripts (`a_1^2`, `f'`); fractions (`\frac`); roots (`\sqrt`,
+/// `\sqrt[3]`); Greek letters (`\alpha`, `\Omega`); common operators and
+/// relations (`\times`, `\leq`, `\to`, `\sum`, `\int`, and more); functions
+/// (`\sin`, `\lim`); accents (`\hat`, `\vec`); text (`\text`, `\mathrm`,
+/// `\mathbf`); spaces (`\,`, `\quad`); delimiters (`\left(` and `\right)`);
+/// and matrices (`matrix`, `pmatrix`, `bmatrix`, `Bmatrix`, `vmatrix`,
+/// `Vmatrix`, and `cases` environments).
+/// Pass `display` for math that is displayed as a block.
+/// Returns `None` for LaTeX that is not supported or not valid, in which
+/// case the default (a `code` element) is used.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::{to_html_with_options, to_mathml, CompileOptions, Constructs, Options, ParseOptions};
+/// use std::sync::Arc;
+/// # fn main() -> Result<(), String> {
+///
+/// assert_eq!(
+///     to_mathml("x^2 + \\frac{1}{\\alpha}", false).unwrap(),
+///     "<math><msup><mi>x</mi><mn>2</mn></msup><mo>+</mo><mfrac><mn>1</mn><mi>α</mi></mfrac></math>"
+/// );
+///
+/// assert_eq!(
+///     to_html_with_options(
+///         "$$\n\\sqrt{a}\n$$",
+///         &Options {
+///             parse: ParseOptions {
+///               constructs: Constructs {
+///                 math_flow: true,
+///                 ..Constructs::default()
+///               },
+///               ..ParseOptions::default()
+///             },
+///             compile: CompileOptions {
+///               math: Some(Arc::new(to_mathml)),
+///               ..CompileOptions::default()
+///             }
+///         }
+///     )?,
+///     "<math display=\"block\"><msqrt><mi>a</mi></msqrt></math>"
+/// );
+/// # Ok(())
+/// # }
+/// ```
+pub fn to_mathml(value: &str, display: bool) -> Option<String> {
+    let mut parser = Parser {
+        chars: value.chars().collect(),
+        index: 0,
+        depth: 0,
+    };
+    let nodes = parser.row()?;
+
+    if parser.index < parser.chars.len() {
+        return None;
+    }
+
+    Some(format!(
+        "<math{}>{}</math>",
+        if display { " display=\"block\"" } else { "" },
+        nodes.concat()
+    ))
+}
+
+/// Maximum depth of groups and commands in LaTeX.
+const MATHML_DEPTH_MAX: usize = 128;
+
+/// LaTeX parser.
+struct Parser {
+    /// Characters.
+    chars: Vec<char>,
+    /// Current index.
+    index: usize,
+    /// Current depth of groups and commands.
+    depth: usize,
+}
+
+impl Parser {
+    /// Parse nodes, up to the end, `}`, `&`, `\\`, `\end`, or `\right`.
+    fn row(&mut self) -> Option<Vec<String>> {
+        let mut nodes = vec![];
+
+        loop {
+            self.skip_whitespace();
+
+            let base = match self.peek() {
+                None | Some('}' | '&') => break,
+                Some('\\')
+                    if matches!(self.peek_command().as_deref(), Some("\\" | "end" | "right")) =>
+                {
+                    break
+                }
+                // Scripts w/o base.
+                Some('^' | '_') => Atom {
+                    node: "<mrow></mrow>".into(),
+                    limits: false,
+                },
+                _ => self.atom()?,
+            };
+
+            nodes.push(self.scripts(base)?);
+        }
+
+        Some(nodes)
+    }
+
+    /// Parse an atom: a group, command, number, identifier, or operator.
+    fn atom(&mut self) -> Option<Atom> {
+        self.skip_whitespace();
+
+        let char = self.next()?;
+        let node = match char {
+            // Groups and commands nest, which is limited to not overflow the
+            // stack.
+            '{' | '\\' if self.depth == MATHML_DEPTH_MAX => return None,
+            '{' => {
+                self.depth += 1;
+                let nodes = self.row();
+                self.depth -= 1;
+                let nodes = nodes?;
+                self.expect('}')?;
+                mrow(&nodes)
+            }
+            '\\' => {
+                self.depth += 1;
+                let atom = self.command();
+                self.depth -= 1;
+                return atom;
+            }
+            '0'..='9' => {
+                let mut value = char.to_string();
+
+                while let Some(char) = self.peek() {
+                    if char.is_ascii_digit()
+                        || (char == '.'
+                            && matches!(self.chars.get(self.index + 1), Some('0'..='9')))
+                    {
+                        value.push(char);
+                        self.index += 1;
+                    } else {
+                        break;
+                    }
+                }
+
+                element("mn", &value)
+            }
+            '}' | '&' | '^' | '_' => return None,
+            '-' => element("mo", "−"),
+            '*' => element("mo", "∗"),
+            '\'' => element("mo", "′"),
+            '~' => element("mtext", "\u{a0}"),
+            char if char.is_alphabetic() => element("mi", &char.to_string()),
+            char => element("mo", &char.to_string()),
+        };
+
+        Some(Atom {
+            node,
+            limits: false,
+        })
+    }
+
+    /// Parse sub- and superscripts (and primes) of `base`.
+    fn scripts(&mut self, base: Atom) -> Option<String> {
+        let mut sub = None;
+        let mut sup = None;
+
+        loop {
+            self.skip_whitespace();
+
+            match self.peek() {
+                Some('_') if sub.is_none() => {
+                    self.index += 1;
+                    sub = Some(self.atom()?.node);
+                }
+                Some('^') if sup.is_none() => {
+                    self.index += 1;
+                    sup = Some(self.atom()?.node);
+                }
+                Some('\'') if sup.is_none() => {
+                    let mut primes = String::new();
+
+                    while self.peek() == Some('\'') {
+                        primes.push('′');
+                        self.index += 1;
+                    }
+
+                    sup = Some(element("mo", &primes));
+                }
+                _ => break,
+            }
+        }
+
+        let (under, over, under_over) = if base.limits {
+            ("munder", "mover", "munderover")
+        } else {
+            ("msub", "msup", "msubsup")
+        };
+
+        Some(match (sub, sup) {
+            (None, None) => base.node,
+            (Some(sub), None) => format!("<{0}>{1}{2}</{0}>", under, base.node, sub),
+            (None, Some(sup)) => format!("<{0}>{1}{2}</{0}>", over, base.node, sup),
+            (Some(sub), Some(sup)) => {
+                format!("<{0}>{1}{2}{3}</{0}>", under_over, base.node, sub, sup)
+            }
+        })
+    }
+
+    /// Parse a command, after `\`.
+    fn command(&mut self) -> Option<Atom> {
+        let name = self.command_name()?;
+        let name = name.as_str();
+
+        let node = if let Some((_, value)) = LIMIT_OPERATORS.iter().find(|d| d.0 == name) {
+            let attributes = if value.chars().count() > 1 {
+                " movablelimits=\"true\" form=\"prefix\""
+            } else {
+                ""
+            };
+
+            return Some(Atom {
+                node: format!("<mo{}>{}</mo>", attributes, value),
+                limits: true,
+            });
+        } else if let Some((_, value)) = IDENTIFIERS.iter().find(|d| d.0 == name) {
+            element("mi", value)
+        } else if let Some((_, value)) = UPRIGHT_IDENTIFIERS.iter().find(|d| d.0 == name) {
+            format!("<mi mathvariant=\"normal\">{}</mi>", value)
+        } else if let Some(value) = operator(name) {
+            element("mo", &value)
+        } else if FUNCTIONS.contains(&name) {
+            element("mi", name)
+        } else if let Some((_, value, under)) = ACCENTS.iter().find(|d| d.0 == name) {
+            let base = self.atom()?.node;
+
+            if *under {
+                format!("<munder>{}<mo>{}</mo></munder>", base, value)
+            } else {
+                format!("<mover accent=\"true\">{}<mo>{}</mo></mover>", base, value)
+            }
+        } else if let Some((_, width)) = SPACES.iter().find(|d| d.0 == name) {
+            format!("<mspace width=\"{}\"></mspace>", width)
+        } else {
+            match name {
+                "frac" | "dfrac" | "tfrac" => {
+                    let numerator = self.atom()?.node;
+                    let denominator = self.atom()?.node;
+                    format!("<mfrac>{}{}</mfrac>", numerator, denominator)
+                }
+                "sqrt" => {
+                    self.skip_whitespace();
+
+                    if self.peek() == Some('[') {
+                        self.index += 1;
+                        let index = self.raw(']')?;
+                        let mut parser = Parser {
+                            chars: index.chars().collect(),
+                            index: 0,
+                            depth: self.depth,
+                        };
+                        let index = mrow(&parser.row()?);
+
+                        if parser.index < parser.chars.len() {
+                            return None;
+                        }
+
+                        format!("<mroot>{}{}</mroot>", self.atom()?.node, index)
+                    } else {
+                        format!("<msqrt>{}</msqrt>", self.atom()?.node)
+                    }
+                }
+                "text" => {
+                    self.skip_whitespace();
+                    self.expect('{')?;
+                    element("mtext", &self.raw('}')?)
+                }
+                "mathrm" | "mathbf" | "mathit" | "operatorname" => {
+                    self.skip_whitespace();
+                    self.expect('{')?;
+                    let value = self.raw('}')?;
+                    let variant = match name {
+                        "mathbf" => "bold",
+                        "mathit" => "italic",
+                        _ => "normal",
+                    };
+                    format!(
+                        "<mi mathvariant=\"{}\">{}</mi>",
+                        variant,
+                        encode(value.trim(), true)
+                    )
+                }
+                "left" => {
+                    let open = self.delimiter()?;
+                    let nodes = self.row()?;
+
+                    if self.peek_command().as_deref() != Some("right") {
+                        return None;
+                    }
+
+                    self.index += 1;
+                    self.command_name()?;
+                    let close = self.delimiter()?;
+                    format!("<mrow>{}{}{}</mrow>", open, nodes.concat(), close)
+                }
+                "begin" => self.environment()?,
+                "{" | "}" | "|" | "%" | "$" | "#" | "&" | "_" => {
+                    element("mo", if name == "|" { "‖" } else { name })
+                }
+                _ => return None,
+            }
+        };
+
+        Some(Atom {
+            node,
+            limits: false,
+        })
+    }
+
+    /// Parse a delimiter, after `\left` or `\right`.
+    fn delimiter(&mut self) -> Option<String> {
+        self.skip_whitespace();
+
+        let value = match self.next()? {
+            '.' => return Some(String::new()),
+            '\\' => {
+                let name = self.command_name()?;
+
+                match name.as_str() {
+                    "{" | "}" => name,
+                    "|" => "‖".into(),
+                    name => operator(name)?,
+                }
+            }
+            char => char.to_string(),
+        };
+
+        Some(element("mo", &value))
+    }
+
+    /// Parse an environment, after `\begin`.
+    fn environment(&mut self) -> Option<String> {
+        self.skip_whitespace();
+        self.expect('{')?;
+        let name = self.raw('}')?;
+        let (_, open, close) = MATRICES.iter().find(|d| d.0 == name)?;
+        let mut rows: Vec<Vec<String>> = vec![];
+        let mut cells = vec![];
+
+        loop {
+            cells.push(format!("<mtd>{}</mtd>", self.row()?.concat()));
+
+            match self.peek() {
+                Some('&') => {
+                    self.index += 1;
+                }
+                Some('\\') => {
+                    self.index += 1;
+
+                    match self.command_name()?.as_str() {
+                        "\\" => {
+                            rows.push(cells);
+                            cells = vec![];
+                        }
+                        "end" => {
+                            self.skip_whitespace();
+                            self.expect('{')?;
+
+                            if self.raw('}')? != name {
+                                return None;
+                            }
+
+                            rows.push(cells);
+                            break;
+                        }
+                        _ => return None,
+                    }
+                }
+                _ => return None,
+            }
+        }
+
+        // Drop an empty last row, from a `\\` before `\end`.
+        if rows.len() > 1 && rows[rows.len() - 1] == ["<mtd></mtd>"] {
+            rows.pop();
+        }
+
+        let mut result = String::new();
+
+        if !open.is_empty() || !close.is_empty() {
+            result.push_str("<mrow>");
+        }
+
+        if !open.is_empty() {
+            result.push_str(&element("mo", open));
+        }
+
+        result.push_str(if name == "cases" {
+            "<mtable columnalign=\"left left\">"
+        } else {
+            "<mtable>"
+        });
+
+        for row in rows {
+            result.push_str("<mtr>");
+            result.push_str(&row.concat());
+            result.push_str("</mtr>");
+        }
+
+        result.push_str("</mtable>");
+
+        if !close.is_empty() {
+            result.push_str(&element("mo", close));
+        }
+
+        if !open.is_empty() || !close.is_empty() {
+            result.push_str("</mrow>");
+        }
+
+        Some(result)
+    }
+
+    /// Get the current character.
+    fn peek(&self) -> Option<char> {
+        self.chars.get(self.index).copied()
+    }
+
+    /// Get the current character, and move past it.
+    fn next(&mut self) -> Option<char> {
+        let char = self.peek();
+        self.index += 1;
+        char
+    }
+
+    /// Move past `char`, if it is the current character.
+    fn expect(&mut self, char: char) -> Option<()> {
+        if self.peek() == Some(char) {
+            self.index += 1;
+            Some(())
+        } else {
+            None
+        }
+    }
+
+    /// Get the name of a command, without moving, if at `\`.
+    fn peek_command(&self) -> Option<String> {
+        if self.peek() == Some('\\') {
+            let mut parser = Parser {
+                chars: self.chars[self.index + 1..].to_vec(),
+                index: 0,
+                depth: 0,
+            };
+            parser.command_name()
+        } else {
+            None
+        }
+    }
+
+    /// Get the name of a command, after `\`, and move past it.
+    ///
+    /// The name is either letters, or a single other character.
+    fn command_name(&mut self) -> Option<String> {
+        let first = self.next()?;
+        let mut name = first.to_string();
+
+        if first.is_ascii_alphabetic() {
+            while let Some(char) = self.peek() {
+                if char.is_ascii_alphabetic() {
+                    name.push(char);
+                    self.index += 1;
+                } else {
+                    break;
+                }
+            }
+        }
+
+        Some(name)
+    }
+
+    /// Get the characters up to `end`, with balanced braces, and move past
+    /// `end`.
+    fn raw(&mut self, end: char) -> Option<String> {
+        let mut value = String::new();
+        let mut depth = 0;
+
+        loop {
+            let char = self.next()?;
+
+            if char == end && depth == 0 {
+                return Some(value);
+            }
+
+            if char == '{' {
+                depth += 1;
+            } else if char == '}' {
+                if depth == 0 {
+                    return None;
+                }
+
+                depth -= 1;
+            }
+
+            value.push(char);
+        }
+    }
+
+    /// Move past whitespace.
+    fn skip_whitespace(&mut self) {
+        while matches!(self.peek(), Some(char) if char.is_whitespace()) {
+            self.index += 1;
+        }
+    }
+}
+
+/// Get the value of an operator command (such as `times`).
+fn operator(name: &str) -> Option<String> {
+    OPERATORS
+        .iter()
+        .find(|d| d.0 == name)
+        .map(|d| d.1.to_string())
+}
+
+/// Make an element, with an encoded value.
+fn element(name: &str, value: &str) -> String {
+    format!("<{0}>{1}</{0}>", name, encode(value, true))
+}
+
+/// Make an `mrow` for `nodes`, unless there is one node.
+fn mrow(nodes: &[String]) -> String {
+    if nodes.len() == 1 {
+        nodes.concat()
+    } else {
+        format!("<mrow>{}</mrow>", nodes.concat())
+    }
+}
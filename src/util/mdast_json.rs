@@ -0,0 +1,791 @@
+//! Turn mdast into JSON, and JSON into mdast.
+//!
+//! The JSON is the same as what `JSON.stringify(tree)` gives for trees made
+//! by `mdast-util-from-markdown` (and its extensions) in JavaScript: fields
+//! are in the order in which those utilities add them, `position` is left
+//! out when there is none, fields without a value are `null` (such as
+//! `checked` on list items that are not tasks), and math nodes get the same
+//! `data` fields.
+//!
+//! Some info that Rust has but JavaScript does not, is not included: `stops`
+//! of MDX nodes.
+//! Some info that JavaScript has but Rust does not, is ignored: `data`
+//! fields, positions of JSX attributes, and other unknown fields.
+
+use crate::mdast::{
+    AlignKind, AttributeContent, AttributeValue, AttributeValueExpression, BlockQuote, Break, Code,
+    Definition, Delete, Emphasis, FootnoteDefinition, FootnoteReference, Frontmatter, Heading,
+    Html, Image, ImageReference, InlineCode, InlineMath, Json, Link, LinkReference, List, ListItem,
+    Math, MdxFlowExpression, MdxJsxAttribute, MdxJsxFlowElement, MdxJsxTextElement,
+    MdxTextExpression, MdxjsEsm, Node, Paragraph, ReferenceKind, Root, Strong, Table, TableCell,
+    TableRow, Text, ThematicBreak, Toml, Yaml,
+};
+use crate::unist::{Point, Position};
+use crate::util::frontmatter::{line_column, parse_json, Value};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+use core::convert::TryFrom;
+
+/// Turn a node into JSON.
+pub fn stringify(node: &Node) -> String {
+    let mut buffer = String::new();
+    node_to_json(&mut buffer, node);
+    buffer
+}
+
+/// Turn JSON into a node.
+pub fn parse(value: &str) -> Result<Node, String> {
+    let json = parse_json(value).map_err(|(offset, reason)| {
+        let (line, column) = line_column(value, offset);
+        format!("{}:{}: {} (mdast-json:parse)", line, column, reason)
+    })?;
+
+    node_from_json(&json).map_err(|(reason, mut path)| {
+        let mut location = String::from("tree");
+        path.reverse();
+
+        for (field, index) in path {
+            location.push('.');
+            location.push_str(field);
+            location.push('[');
+            location.push_str(&index.to_string());
+            location.push(']');
+        }
+
+        format!(
+            "Cannot turn JSON into mdast, expected {} at `{}`",
+            reason, location
+        )
+    })
+}
+
+/// Serialize a node.
+fn node_to_json(buffer: &mut String, node: &Node) {
+    match node {
+        Node::Root(x) => {
+            open(buffer, "root");
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::BlockQuote(x) => {
+            open(buffer, "blockquote");
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::FootnoteDefinition(x) => {
+            open(buffer, "footnoteDefinition");
+            field_string(buffer, "identifier", &x.identifier);
+            field_option_string(buffer, "label", x.label.as_deref());
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::MdxJsxFlowElement(x) => {
+            open(buffer, "mdxJsxFlowElement");
+            field_option_string(buffer, "name", x.name.as_deref());
+            attributes_to_json(buffer, &x.attributes);
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::MdxJsxTextElement(x) => {
+            open(buffer, "mdxJsxTextElement");
+            field_option_string(buffer, "name", x.name.as_deref());
+            attributes_to_json(buffer, &x.attributes);
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::List(x) => {
+            open(buffer, "list");
+            field(buffer, "ordered");
+            buffer.push_str(if x.ordered { "true" } else { "false" });
+            field(buffer, "start");
+            buffer.push_str(&x.start.map_or_else(|| "null".into(), |d| d.to_string()));
+            field(buffer, "spread");
+            buffer.push_str(if x.spread { "true" } else { "false" });
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::ListItem(x) => {
+            open(buffer, "listItem");
+            field(buffer, "spread");
+            buffer.push_str(if x.spread { "true" } else { "false" });
+            field(buffer, "checked");
+            buffer.push_str(match x.checked {
+                Some(true) => "true",
+                Some(false) => "false",
+                None => "null",
+            });
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::MdxjsEsm(x) => literal_to_json(buffer, "mdxjsEsm", &x.value, x.position.as_ref()),
+        Node::Toml(x) => literal_to_json(buffer, "toml", &x.value, x.position.as_ref()),
+        Node::Yaml(x) => literal_to_json(buffer, "yaml", &x.value, x.position.as_ref()),
+        Node::Json(x) => literal_to_json(buffer, "json", &x.value, x.position.as_ref()),
+        Node::Frontmatter(x) => literal_to_json(buffer, &x.kind, &x.value, x.position.as_ref()),
+        Node::Break(x) => {
+            open(buffer, "break");
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::InlineCode(x) => literal_to_json(buffer, "inlineCode", &x.value, x.position.as_ref()),
+        Node::InlineMath(x) => {
+            open(buffer, "inlineMath");
+            field_string(buffer, "value", &x.value);
+            field(buffer, "data");
+            buffer.push_str("{\"hName\":\"code\",\"hProperties\":{\"className\":[\"language-math\",\"math-inline\"]},\"hChildren\":[{\"type\":\"text\",\"value\":");
+            string_to_json(buffer, &x.value);
+            buffer.push_str("}]}");
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::Delete(x) => parent_to_json(buffer, "delete", &x.children, x.position.as_ref()),
+        Node::Emphasis(x) => parent_to_json(buffer, "emphasis", &x.children, x.position.as_ref()),
+        Node::MdxTextExpression(x) => {
+            literal_to_json(buffer, "mdxTextExpression", &x.value, x.position.as_ref());
+        }
+        Node::FootnoteReference(x) => {
+            open(buffer, "footnoteReference");
+            field_string(buffer, "identifier", &x.identifier);
+            field_option_string(buffer, "label", x.label.as_deref());
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::Html(x) => literal_to_json(buffer, "html", &x.value, x.position.as_ref()),
+        Node::Image(x) => {
+            open(buffer, "image");
+            field_option_string(buffer, "title", x.title.as_deref());
+            field_string(buffer, "url", &x.url);
+            field_string(buffer, "alt", &x.alt);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::ImageReference(x) => {
+            open(buffer, "imageReference");
+            field_string(buffer, "alt", &x.alt);
+            position_to_json(buffer, x.position.as_ref());
+            field_option_string(buffer, "label", x.label.as_deref());
+            field_string(buffer, "identifier", &x.identifier);
+            reference_kind_to_json(buffer, x.reference_kind);
+        }
+        Node::Link(x) => {
+            open(buffer, "link");
+            field_option_string(buffer, "title", x.title.as_deref());
+            field_string(buffer, "url", &x.url);
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::LinkReference(x) => {
+            open(buffer, "linkReference");
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+            field_option_string(buffer, "label", x.label.as_deref());
+            field_string(buffer, "identifier", &x.identifier);
+            reference_kind_to_json(buffer, x.reference_kind);
+        }
+        Node::Strong(x) => parent_to_json(buffer, "strong", &x.children, x.position.as_ref()),
+        Node::Text(x) => literal_to_json(buffer, "text", &x.value, x.position.as_ref()),
+        Node::Code(x) => {
+            open(buffer, "code");
+            field_option_string(buffer, "lang", x.lang.as_deref());
+            field_option_string(buffer, "meta", x.meta.as_deref());
+            field_string(buffer, "value", &x.value);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::Math(x) => {
+            open(buffer, "math");
+            field_option_string(buffer, "meta", x.meta.as_deref());
+            field_string(buffer, "value", &x.value);
+            field(buffer, "data");
+            buffer.push_str("{\"hName\":\"pre\",\"hChildren\":[{\"type\":\"element\",\"tagName\":\"code\",\"properties\":{\"className\":[\"language-math\",\"math-display\"]},\"children\":[{\"type\":\"text\",\"value\":");
+            string_to_json(buffer, &x.value);
+            buffer.push_str("}]}]}");
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::MdxFlowExpression(x) => {
+            literal_to_json(buffer, "mdxFlowExpression", &x.value, x.position.as_ref());
+        }
+        Node::Heading(x) => {
+            open(buffer, "heading");
+            field(buffer, "depth");
+            buffer.push_str(&x.depth.to_string());
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::Table(x) => {
+            open(buffer, "table");
+            field(buffer, "align");
+            buffer.push('[');
+            for (index, align) in x.align.iter().enumerate() {
+                if index > 0 {
+                    buffer.push(',');
+                }
+                buffer.push_str(match align {
+                    AlignKind::Left => "\"left\"",
+                    AlignKind::Right => "\"right\"",
+                    AlignKind::Center => "\"center\"",
+                    AlignKind::None => "null",
+                });
+            }
+            buffer.push(']');
+            children_to_json(buffer, &x.children);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::ThematicBreak(x) => {
+            open(buffer, "thematicBreak");
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::TableRow(x) => parent_to_json(buffer, "tableRow", &x.children, x.position.as_ref()),
+        Node::TableCell(x) => parent_to_json(buffer, "tableCell", &x.children, x.position.as_ref()),
+        Node::Definition(x) => {
+            open(buffer, "definition");
+            field_string(buffer, "identifier", &x.identifier);
+            field_option_string(buffer, "label", x.label.as_deref());
+            field_option_string(buffer, "title", x.title.as_deref());
+            field_string(buffer, "url", &x.url);
+            position_to_json(buffer, x.position.as_ref());
+        }
+        Node::Paragraph(x) => parent_to_json(buffer, "paragraph", &x.children, x.position.as_ref()),
+    }
+
+    buffer.push('}');
+}
+
+/// Serialize the fields of a parent that has no other fields.
+fn parent_to_json(buffer: &mut String, kind: &str, children: &[Node], position: Option<&Position>) {
+    open(buffer, kind);
+    children_to_json(buffer, children);
+    position_to_json(buffer, position);
+}
+
+/// Serialize the fields of a literal that has no other fields.
+fn literal_to_json(buffer: &mut String, kind: &str, value: &str, position: Option<&Position>) {
+    open(buffer, kind);
+    field_string(buffer, "value", value);
+    position_to_json(buffer, position);
+}
+
+/// Open an object for a node of `kind`.
+fn open(buffer: &mut String, kind: &str) {
+    buffer.push_str("{\"type\":");
+    string_to_json(buffer, kind);
+}
+
+/// Add the key of a field, after another field.
+fn field(buffer: &mut String, key: &str) {
+    buffer.push(',');
+    string_to_json(buffer, key);
+    buffer.push(':');
+}
+
+/// Add a string field.
+fn field_string(buffer: &mut String, key: &str, value: &str) {
+    field(buffer, key);
+    string_to_json(buffer, value);
+}
+
+/// Add an optional string field, which is `null` if there is no value.
+fn field_option_string(buffer: &mut String, key: &str, value: Option<&str>) {
+    field(buffer, key);
+    if let Some(value) = value {
+        string_to_json(buffer, value);
+    } else {
+        buffer.push_str("null");
+    }
+}
+
+/// Add the children field.
+fn children_to_json(buffer: &mut String, children: &[Node]) {
+    field(buffer, "children");
+    buffer.push('[');
+    for (index, child) in children.iter().enumerate() {
+        if index > 0 {
+            buffer.push(',');
+        }
+        node_to_json(buffer, child);
+    }
+    buffer.push(']');
+}
+
+/// Add the position field, if there is a position.
+fn position_to_json(buffer: &mut String, position: Option<&Position>) {
+    if let Some(position) = position {
+        field(buffer, "position");
+        buffer.push_str("{\"start\":");
+        point_to_json(buffer, &position.start);
+        buffer.push_str(",\"end\":");
+        point_to_json(buffer, &position.end);
+        buffer.push('}');
+    }
+}
+
+/// Serialize a point.
+fn point_to_json(buffer: &mut String, point: &Point) {
+    buffer.push_str("{\"line\":");
+    buffer.push_str(&point.line.to_string());
+    buffer.push_str(",\"column\":");
+    buffer.push_str(&point.column.to_string());
+    buffer.push_str(",\"offset\":");
+    buffer.push_str(&point.offset.to_string());
+    buffer.push('}');
+}
+
+/// Add the reference type field.
+fn reference_kind_to_json(buffer: &mut String, kind: ReferenceKind) {
+    field(buffer, "referenceType");
+    buffer.push_str(match kind {
+        ReferenceKind::Shortcut => "\"shortcut\"",
+        ReferenceKind::Collapsed => "\"collapsed\"",
+        ReferenceKind::Full => "\"full\"",
+    });
+}
+
+/// Add the attributes field of JSX.
+fn attributes_to_json(buffer: &mut String, attributes: &[AttributeContent]) {
+    field(buffer, "attributes");
+    buffer.push('[');
+    for (index, attribute) in attributes.iter().enumerate() {
+        if index > 0 {
+            buffer.push(',');
+        }
+
+        match attribute {
+            AttributeContent::Expression { value, .. } => {
+                literal_to_json(buffer, "mdxJsxExpressionAttribute", value, None);
+            }
+            AttributeContent::Property(property) => {
+                open(buffer, "mdxJsxAttribute");
+                field_string(buffer, "name", &property.name);
+                field(buffer, "value");
+                match &property.value {
+                    Some(AttributeValue::Literal(value)) => string_to_json(buffer, value),
+                    Some(AttributeValue::Expression(expression)) => {
+                        literal_to_json(
+                            buffer,
+                            "mdxJsxAttributeValueExpression",
+                            &expression.value,
+                            None,
+                        );
+                        buffer.push('}');
+                    }
+                    None => buffer.push_str("null"),
+                }
+            }
+        }
+
+        buffer.push('}');
+    }
+    buffer.push(']');
+}
+
+/// Serialize a string, escaped like `JSON.stringify` does.
+pub fn string_to_json(buffer: &mut String, value: &str) {
+    buffer.push('"');
+    for char in value.chars() {
+        match char {
+            '"' => buffer.push_str("\\\""),
+            '\\' => buffer.push_str("\\\\"),
+            '\u{8}' => buffer.push_str("\\b"),
+            '\u{c}' => buffer.push_str("\\f"),
+            '\n' => buffer.push_str("\\n"),
+            '\r' => buffer.push_str("\\r"),
+            '\t' => buffer.push_str("\\t"),
+            '\0'..='\u{1f}' => {
+                let code = char as u32;
+                buffer.push_str("\\u00");
+                buffer.push(char::from_digit(code >> 4, 16).unwrap());
+                buffer.push(char::from_digit(code & 0xF, 16).unwrap());
+            }
+            _ => buffer.push(char),
+        }
+    }
+    buffer.push('"');
+}
+
+/// Reason and path (innermost first) of why JSON is not mdast.
+type Failure = (String, Vec<(&'static str, usize)>);
+
+/// Deserialize a node.
+fn node_from_json(value: &Value) -> Result<Node, Failure> {
+    let fields = object(value, "an object")?;
+    let kind = string(fields, "type")?;
+
+    let node = match kind.as_str() {
+        "root" => Node::Root(Root {
+            children: children(fields)?,
+            position: position(fields)?,
+        }),
+        "blockquote" => Node::BlockQuote(BlockQuote {
+            children: children(fields)?,
+            position: position(fields)?,
+        }),
+        "footnoteDefinition" => Node::FootnoteDefinition(FootnoteDefinition {
+            children: children(fields)?,
+            position: position(fields)?,
+            identifier: string(fields, "identifier")?,
+            label: option_string(fields, "label")?,
+        }),
+        "mdxJsxFlowElement" => Node::MdxJsxFlowElement(MdxJsxFlowElement {
+            children: children(fields)?,
+            position: position(fields)?,
+            name: option_string(fields, "name")?,
+            attributes: attributes(fields)?,
+        }),
+        "mdxJsxTextElement" => Node::MdxJsxTextElement(MdxJsxTextElement {
+            children: children(fields)?,
+            position: position(fields)?,
+            name: option_string(fields, "name")?,
+            attributes: attributes(fields)?,
+        }),
+        "list" => Node::List(List {
+            children: children(fields)?,
+            position: position(fields)?,
+            ordered: option_bool(fields, "ordered")?.unwrap_or(false),
+            start: match get(fields, "start") {
+                Some(value) => Some(
+                    integer(value)
+                        .and_then(|d| u32::try_from(d).ok())
+                        .ok_or_else(|| expected("`start` to be a whole number or `null`"))?,
+                ),
+                None => None,
+            },
+            spread: option_bool(fields, "spread")?.unwrap_or(false),
+        }),
+        "listItem" => Node::ListItem(ListItem {
+            children: children(fields)?,
+            position: position(fields)?,
+            spread: option_bool(fields, "spread")?.unwrap_or(false),
+            checked: option_bool(fields, "checked")?,
+        }),
+        "mdxjsEsm" => Node::MdxjsEsm(MdxjsEsm {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+            stops: Vec::new(),
+        }),
+        "toml" => Node::Toml(Toml {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+        }),
+        "yaml" => Node::Yaml(Yaml {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+        }),
+        "json" => Node::Json(Json {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+        }),
+        "break" => Node::Break(Break {
+            position: position(fields)?,
+        }),
+        "inlineCode" => Node::InlineCode(InlineCode {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+        }),
+        "inlineMath" => Node::InlineMath(InlineMath {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+        }),
+        "delete" => Node::Delete(Delete {
+            children: children(fields)?,
+            position: position(fields)?,
+        }),
+        "emphasis" => Node::Emphasis(Emphasis {
+            children: children(fields)?,
+            position: position(fields)?,
+        }),
+        "mdxTextExpression" => Node::MdxTextExpression(MdxTextExpression {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+            stops: Vec::new(),
+        }),
+        "footnoteReference" => Node::FootnoteReference(FootnoteReference {
+            position: position(fields)?,
+            identifier: string(fields, "identifier")?,
+            label: option_string(fields, "label")?,
+        }),
+        "html" => Node::Html(Html {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+        }),
+        "image" => Node::Image(Image {
+            position: position(fields)?,
+            alt: option_string(fields, "alt")?.unwrap_or_default(),
+            url: string(fields, "url")?,
+            title: option_string(fields, "title")?,
+        }),
+        "imageReference" => Node::ImageReference(ImageReference {
+            position: position(fields)?,
+            alt: option_string(fields, "alt")?.unwrap_or_default(),
+            reference_kind: reference_kind(fields)?,
+            identifier: string(fields, "identifier")?,
+            label: option_string(fields, "label")?,
+        }),
+        "link" => Node::Link(Link {
+            children: children(fields)?,
+            position: position(fields)?,
+            url: string(fields, "url")?,
+            title: option_string(fields, "title")?,
+        }),
+        "linkReference" => Node::LinkReference(LinkReference {
+            children: children(fields)?,
+            position: position(fields)?,
+            reference_kind: reference_kind(fields)?,
+            identifier: string(fields, "identifier")?,
+            label: option_string(fields, "label")?,
+        }),
+        "strong" => Node::Strong(Strong {
+            children: children(fields)?,
+            position: position(fields)?,
+        }),
+        "text" => Node::Text(Text {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+        }),
+        "code" => Node::Code(Code {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+            lang: option_string(fields, "lang")?,
+            meta: option_string(fields, "meta")?,
+        }),
+        "math" => Node::Math(Math {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+            meta: option_string(fields, "meta")?,
+        }),
+        "mdxFlowExpression" => Node::MdxFlowExpression(MdxFlowExpression {
+            value: string(fields, "value")?,
+            position: position(fields)?,
+            stops: Vec::new(),
+        }),
+        "heading" => Node::Heading(Heading {
+            children: children(fields)?,
+            position: position(fields)?,
+            depth: get(fields, "depth")
+                .and_then(integer)
+                .and_then(|d| u8::try_from(d).ok())
+                .filter(|d| (1..=6).contains(d))
+                .ok_or_else(|| expected("`depth` to be a number from 1 to 6"))?,
+        }),
+        "table" => Node::Table(Table {
+            children: children(fields)?,
+            position: position(fields)?,
+            align: match get(fields, "align") {
+                Some(Value::Array(values)) => values
+                    .iter()
+                    .map(|value| match value {
+                        Value::String(value) if value == "left" => Ok(AlignKind::Left),
+                        Value::String(value) if value == "right" => Ok(AlignKind::Right),
+                        Value::String(value) if value == "center" => Ok(AlignKind::Center),
+                        Value::Null => Ok(AlignKind::None),
+                        _ => Err(expected(
+                            "`align` to contain `\"left\"`, `\"right\"`, `\"center\"`, or `null`",
+                        )),
+                    })
+                    .collect::<Result<_, _>>()?,
+                Some(_) => return Err(expected("`align` to be an array or `null`")),
+                None => Vec::new(),
+            },
+        }),
+        "thematicBreak" => Node::ThematicBreak(ThematicBreak {
+            position: position(fields)?,
+        }),
+        "tableRow" => Node::TableRow(TableRow {
+            children: children(fields)?,
+            position: position(fields)?,
+        }),
+        "tableCell" => Node::TableCell(TableCell {
+            children: children(fields)?,
+            position: position(fields)?,
+        }),
+        "definition" => Node::Definition(Definition {
+            position: position(fields)?,
+            url: string(fields, "url")?,
+            title: option_string(fields, "title")?,
+            identifier: string(fields, "identifier")?,
+            label: option_string(fields, "label")?,
+        }),
+        "paragraph" => Node::Paragraph(Paragraph {
+            children: children(fields)?,
+            position: position(fields)?,
+        }),
+        // Other literals are frontmatter of other kinds.
+        _ if get(fields, "children").is_none() && get(fields, "value").is_some() => {
+            Node::Frontmatter(Frontmatter {
+                value: string(fields, "value")?,
+                position: position(fields)?,
+                kind: kind.clone(),
+            })
+        }
+        _ => return Err(expected("`type` to be a known node type")),
+    };
+
+    Ok(node)
+}
+
+/// Get the entries of an object.
+fn object<'a>(value: &'a Value, reason: &str) -> Result<&'a [(String, Value)], Failure> {
+    if let Value::Object(fields) = value {
+        Ok(fields)
+    } else {
+        Err(expected(reason))
+    }
+}
+
+/// Get a field, treating `null` as missing.
+fn get<'a>(fields: &'a [(String, Value)], key: &str) -> Option<&'a Value> {
+    fields
+        .iter()
+        .find(|d| d.0 == key)
+        .map(|d| &d.1)
+        .filter(|d| **d != Value::Null)
+}
+
+/// Get a whole number that is not negative.
+fn integer(value: &Value) -> Option<usize> {
+    if let Value::Integer(value) = value {
+        usize::try_from(*value).ok()
+    } else {
+        None
+    }
+}
+
+/// Get a required string field.
+fn string(fields: &[(String, Value)], key: &str) -> Result<String, Failure> {
+    option_string(fields, key)?.ok_or_else(|| expected(&format!("`{}` to be a string", key)))
+}
+
+/// Get an optional string field.
+fn option_string(fields: &[(String, Value)], key: &str) -> Result<Option<String>, Failure> {
+    match get(fields, key) {
+        Some(Value::String(value)) => Ok(Some(value.clone())),
+        Some(_) => Err(expected(&format!("`{}` to be a string or `null`", key))),
+        None => Ok(None),
+    }
+}
+
+/// Get an optional boolean field.
+fn option_bool(fields: &[(String, Value)], key: &str) -> Result<Option<bool>, Failure> {
+    match get(fields, key) {
+        Some(Value::Bool(value)) => Ok(Some(*value)),
+        Some(_) => Err(expected(&format!("`{}` to be a boolean or `null`", key))),
+        None => Ok(None),
+    }
+}
+
+/// Get the reference type field.
+fn reference_kind(fields: &[(String, Value)]) -> Result<ReferenceKind, Failure> {
+    match get(fields, "referenceType") {
+        Some(Value::String(value)) if value == "shortcut" => Ok(ReferenceKind::Shortcut),
+        Some(Value::String(value)) if value == "collapsed" => Ok(ReferenceKind::Collapsed),
+        Some(Value::String(value)) if value == "full" => Ok(ReferenceKind::Full),
+        _ => Err(expected(
+            "`referenceType` to be `\"shortcut\"`, `\"collapsed\"`, or `\"full\"`",
+        )),
+    }
+}
+
+/// Get the children field.
+fn children(fields: &[(String, Value)]) -> Result<Vec<Node>, Failure> {
+    if let Some(Value::Array(values)) = get(fields, "children") {
+        let mut children = Vec::with_capacity(values.len());
+
+        for (index, value) in values.iter().enumerate() {
+            children.push(node_from_json(value).map_err(|mut failure| {
+                failure.1.push(("children", index));
+                failure
+            })?);
+        }
+
+        Ok(children)
+    } else {
+        Err(expected("`children` to be an array"))
+    }
+}
+
+/// Get the position field.
+fn position(fields: &[(String, Value)]) -> Result<Option<Position>, Failure> {
+    if let Some(value) = get(fields, "position") {
+        let position = object(value, "`position` to be an object or `null`")?;
+        Ok(Some(Position {
+            start: point(position, "start")?,
+            end: point(position, "end")?,
+        }))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Get a point field in a position.
+fn point(position: &[(String, Value)], key: &str) -> Result<Point, Failure> {
+    let reason = format!(
+        "`position.{}` to be an object with `line`, `column`, and `offset`",
+        key
+    );
+    let point = object(get(position, key).unwrap_or(&Value::Null), &reason)?;
+    let line = get(point, "line").and_then(integer);
+    let column = get(point, "column").and_then(integer);
+    let offset = get(point, "offset").and_then(integer);
+
+    if let (Some(line), Some(column), Some(offset)) = (line, column, offset) {
+        Ok(Point::new(line, column, offset))
+    } else {
+        Err(expected(&reason))
+    }
+}
+
+/// Get the attributes field of JSX.
+fn attributes(fields: &[(String, Value)]) -> Result<Vec<AttributeContent>, Failure> {
+    if let Some(Value::Array(values)) = get(fields, "attributes") {
+        let mut attributes = Vec::with_capacity(values.len());
+
+        for (index, value) in values.iter().enumerate() {
+            attributes.push(attribute(value).map_err(|mut failure| {
+                failure.1.push(("attributes", index));
+                failure
+            })?);
+        }
+
+        Ok(attributes)
+    } else {
+        Err(expected("`attributes` to be an array"))
+    }
+}
+
+/// Get an attribute of JSX.
+fn attribute(value: &Value) -> Result<AttributeContent, Failure> {
+    let fields = object(value, "an object")?;
+
+    match string(fields, "type")?.as_str() {
+        "mdxJsxExpressionAttribute" => Ok(AttributeContent::Expression {
+            value: string(fields, "value")?,
+            stops: Vec::new(),
+        }),
+        "mdxJsxAttribute" => Ok(AttributeContent::Property(MdxJsxAttribute {
+            name: string(fields, "name")?,
+            value: match get(fields, "value") {
+                Some(Value::String(value)) => Some(AttributeValue::Literal(value.clone())),
+                Some(value) => {
+                    let fields = object(value, "`value` to be a string, object, or `null`")?;
+
+                    if string(fields, "type")? != "mdxJsxAttributeValueExpression" {
+                        return Err(expected(
+                            "`value.type` to be `\"mdxJsxAttributeValueExpression\"`",
+                        ));
+                    }
+
+                    Some(AttributeValue::Expression(AttributeValueExpression {
+                        value: string(fields, "value")?,
+                        stops: Vec::new(),
+                    }))
+                }
+                None => None,
+            },
+        })),
+        _ => Err(expected(
+            "`type` to be `\"mdxJsxAttribute\"` or `\"mdxJsxExpressionAttribute\"`",
+        )),
+    }
+}
+
+/// Create a failure.
+fn expected(reason: &str) -> Failure {
+    (reason.into(), Vec::new())
+}
@@ -5,15 +5,27 @@ pub mod character_reference;
 pub mod constant;
 pub mod edit_map;
 pub mod encode;
+pub mod frontmatter;
 pub mod gfm_tagfilter;
+#[cfg(feature = "highlight")]
+pub mod highlight;
 pub mod identifier;
 pub mod infer;
 pub mod line_ending;
 pub mod location;
+#[cfg(feature = "mathml")]
+pub mod mathml;
+#[cfg(feature = "json")]
+pub mod mdast_json;
 pub mod mdx;
 pub mod mdx_collect;
 pub mod normalize_identifier;
+pub mod resolve_url;
+pub mod sanitize_html;
 pub mod sanitize_uri;
+#[cfg(feature = "serde")]
+pub mod serde_configuration;
 pub mod skip;
 pub mod slice;
 pub mod unicode;
+pub mod xhtml;
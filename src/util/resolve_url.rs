@@ -0,0 +1,267 @@
+//! Resolve URLs against a base URL.
+
+use alloc::{string::String, vec::Vec};
+
+/// Parts of a URL (`scheme:` `//authority` `path` `?query` `#fragment`).
+pub struct Parts<'a> {
+    pub scheme: Option<&'a str>,
+    pub authority: Option<&'a str>,
+    pub path: &'a str,
+    pub query: Option<&'a str>,
+    pub fragment: Option<&'a str>,
+}
+
+/// Resolve `url` against `base`, like a browser does for links.
+///
+/// `base` can be a full URL (`https://example.com/docs/`) or only a path
+/// (`/docs/`).
+/// URLs with a protocol (such as `mailto:a@b.c`) are kept as they are, and
+/// dot segments (`./`, `../`) are removed.
+///
+/// ## Examples
+///
+/// ```
+/// use markdown::resolve_url;
+///
+/// assert_eq!(resolve_url("/docs/guide/", "../api.md#foo"), "/docs/api.md#foo");
+/// assert_eq!(resolve_url("https://a.b/c/d", "e"), "https://a.b/c/e");
+/// assert_eq!(resolve_url("https://a.b/c/d", "/e"), "https://a.b/e");
+/// assert_eq!(resolve_url("https://a.b/c/d", "#e"), "https://a.b/c/d#e");
+/// assert_eq!(resolve_url("https://a.b/c/d", "mailto:e@f.g"), "mailto:e@f.g");
+/// ```
+///
+/// ## References
+///
+/// *   [*§ 5.2 Relative Resolution* in RFC 3986](https://www.rfc-editor.org/rfc/rfc3986#section-5.2)
+pub fn resolve_url(base: &str, url: &str) -> String {
+    let base = parse(base);
+    let reference = parse(url);
+    let scheme = reference.scheme.or(base.scheme);
+
+    let (authority, path, query) = if reference.scheme.is_some() || reference.authority.is_some() {
+        (
+            reference.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else if reference.path.is_empty() {
+        (
+            base.authority,
+            base.path.into(),
+            reference.query.or(base.query),
+        )
+    } else if reference.path.starts_with('/') {
+        (
+            base.authority,
+            remove_dot_segments(reference.path),
+            reference.query,
+        )
+    } else {
+        let mut merged = String::new();
+
+        if base.authority.is_some() && base.path.is_empty() {
+            merged.push('/');
+        } else if let Some(index) = base.path.rfind('/') {
+            merged.push_str(&base.path[0..=index]);
+        }
+
+        merged.push_str(reference.path);
+        (
+            base.authority,
+            remove_dot_segments(&merged),
+            reference.query,
+        )
+    };
+
+    let mut result = String::new();
+
+    if let Some(scheme) = scheme {
+        result.push_str(scheme);
+        result.push(':');
+    }
+
+    if let Some(authority) = authority {
+        result.push_str("//");
+        result.push_str(authority);
+    }
+
+    result.push_str(&path);
+
+    if let Some(query) = query {
+        result.push('?');
+        result.push_str(query);
+    }
+
+    if let Some(fragment) = reference.fragment {
+        result.push('#');
+        result.push_str(fragment);
+    }
+
+    result
+}
+
+/// Split `url` into parts.
+pub fn parse(url: &str) -> Parts<'_> {
+    let mut rest = url;
+    let mut fragment = None;
+    let mut query = None;
+    let mut scheme = None;
+    let mut authority = None;
+
+    if let Some(index) = rest.find('#') {
+        fragment = Some(&rest[index + 1..]);
+        rest = &rest[0..index];
+    }
+
+    if let Some(index) = rest.find('?') {
+        query = Some(&rest[index + 1..]);
+        rest = &rest[0..index];
+    }
+
+    if let Some(index) = rest.find(':') {
+        let name = &rest[0..index];
+        let mut chars = name.chars();
+
+        if chars.next().map_or(false, |d| d.is_ascii_alphabetic())
+            && chars.all(|d| d.is_ascii_alphanumeric() || matches!(d, '+' | '-' | '.'))
+        {
+            scheme = Some(name);
+            rest = &rest[index + 1..];
+        }
+    }
+
+    if let Some(after) = rest.strip_prefix("//") {
+        let index = after.find('/').unwrap_or(after.len());
+        authority = Some(&after[0..index]);
+        rest = &after[index..];
+    }
+
+    Parts {
+        scheme,
+        authority,
+        path: rest,
+        query,
+        fragment,
+    }
+}
+
+/// Remove `.` and `..` segments from `path`.
+fn remove_dot_segments(path: &str) -> String {
+    let absolute = path.starts_with('/');
+    let segments = if absolute { &path[1..] } else { path };
+    let segments = segments.split('/').collect::<Vec<_>>();
+    let mut result: Vec<&str> = Vec::new();
+    let mut index = 0;
+
+    while index < segments.len() {
+        let segment = segments[index];
+        let last = index == segments.len() - 1;
+
+        if segment == "." || segment == ".." {
+            if segment == ".." {
+                result.pop();
+            }
+
+            // Keep a final slash.
+            if last {
+                result.push("");
+            }
+        } else {
+            result.push(segment);
+        }
+
+        index += 1;
+    }
+
+    let mut value = String::new();
+
+    if absolute {
+        value.push('/');
+    }
+
+    value.push_str(&result.join("/"));
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_url() {
+        let base = "http://a/b/c/d;p?q";
+        // See: <https://www.rfc-editor.org/rfc/rfc3986#section-5.4>.
+        let cases = [
+            ("g:h", "g:h"),
+            ("g", "http://a/b/c/g"),
+            ("./g", "http://a/b/c/g"),
+            ("g/", "http://a/b/c/g/"),
+            ("/g", "http://a/g"),
+            ("//g", "http://g"),
+            ("?y", "http://a/b/c/d;p?y"),
+            ("g?y", "http://a/b/c/g?y"),
+            ("#s", "http://a/b/c/d;p?q#s"),
+            ("g#s", "http://a/b/c/g#s"),
+            ("g?y#s", "http://a/b/c/g?y#s"),
+            (";x", "http://a/b/c/;x"),
+            ("g;x", "http://a/b/c/g;x"),
+            ("g;x?y#s", "http://a/b/c/g;x?y#s"),
+            ("", "http://a/b/c/d;p?q"),
+            (".", "http://a/b/c/"),
+            ("./", "http://a/b/c/"),
+            ("..", "http://a/b/"),
+            ("../", "http://a/b/"),
+            ("../g", "http://a/b/g"),
+            ("../..", "http://a/"),
+            ("../../", "http://a/"),
+            ("../../g", "http://a/g"),
+            ("../../../g", "http://a/g"),
+            ("../../../../g", "http://a/g"),
+            ("/./g", "http://a/g"),
+            ("/../g", "http://a/g"),
+            ("g.", "http://a/b/c/g."),
+            (".g", "http://a/b/c/.g"),
+            ("g..", "http://a/b/c/g.."),
+            ("..g", "http://a/b/c/..g"),
+            ("./../g", "http://a/b/g"),
+            ("./g/.", "http://a/b/c/g/"),
+            ("g/./h", "http://a/b/c/g/h"),
+            ("g/../h", "http://a/b/c/h"),
+            ("g;x=1/./y", "http://a/b/c/g;x=1/y"),
+            ("g;x=1/../y", "http://a/b/c/y"),
+            ("g?y/./x", "http://a/b/c/g?y/./x"),
+            ("g?y/../x", "http://a/b/c/g?y/../x"),
+            ("g#s/./x", "http://a/b/c/g#s/./x"),
+            ("g#s/../x", "http://a/b/c/g#s/../x"),
+            ("http:g", "http:g"),
+        ];
+
+        for (url, expected) in cases {
+            assert_eq!(
+                resolve_url(base, url),
+                expected,
+                "should resolve `{}` like RFC 3986",
+                url
+            );
+        }
+    }
+
+    #[test]
+    fn test_resolve_url_path() {
+        assert_eq!(
+            resolve_url("/docs/guide/", "../api.md#foo"),
+            "/docs/api.md#foo",
+            "should support a path as base"
+        );
+        assert_eq!(
+            resolve_url("/docs/guide", "api"),
+            "/docs/api",
+            "should support a path w/o final slash as base"
+        );
+        assert_eq!(
+            resolve_url("https://a.b", "c"),
+            "https://a.b/c",
+            "should support a base w/o path"
+        );
+    }
+}
@@ -0,0 +1,436 @@
+//! Make raw HTML safe.
+
+use crate::configuration::HtmlSchema;
+use crate::util::{
+    character_reference::parse as decode_character_references, constant::HTML_VOID_NAMES,
+    encode::encode, sanitize_uri::sanitize_with_protocols,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
+
+/// Element opened in sanitized HTML, that is not closed yet.
+#[derive(Debug)]
+pub struct Open {
+    /// Tag name (lowercase).
+    pub name: String,
+    /// Whether this was opened in HTML (text), in which case it is closed
+    /// when that text ends.
+    pub inline: bool,
+    /// Depth of containers (block quotes, list items, footnote definitions)
+    /// this was opened in, in which case it is closed when that container
+    /// ends.
+    pub depth: usize,
+    /// Whether this is an element to strip, whose closing tag is not found
+    /// yet.
+    ///
+    /// Everything is dropped until it is closed, including what comes
+    /// between raw HTML (see `strip` in `HtmlSchema`).
+    pub strip: bool,
+}
+
+/// Tag, as parsed by [`tag`][tag].
+struct Tag {
+    /// Tag name (lowercase).
+    name: String,
+    /// Whether this is a closing tag (`</a>`).
+    close: bool,
+    /// Whether this is a self-closing tag (`<a />`).
+    self_closing: bool,
+    /// Attributes, with their names (lowercase) and raw values.
+    attributes: Vec<(String, Option<String>)>,
+    /// Index after the tag.
+    end: usize,
+}
+
+/// Make raw HTML safe, according to `schema`.
+///
+/// Elements that are allowed are kept, with allowed attributes, and added
+/// to `stack`.
+/// Elements that are not allowed are dropped, except for their content.
+/// Elements to strip are dropped with their content; if they are not closed
+/// in `value`, they are added to `stack`, and later values are dropped up
+/// to their closing tag.
+/// Comments, declarations, and instructions are dropped.
+/// Closing tags that don’t match open elements are dropped, and closing
+/// tags of elements that contain other open elements close those first.
+///
+/// Pass `inline` for HTML (text), and `depth` for the current container
+/// depth, which are stored on open elements so they can be closed with
+/// [`close`][close].
+/// Pass `text_only` to drop all tags (such as in image alt text).
+/// Pass `close_void` to close void elements with a slash (`<br />`).
+///
+/// ## Examples
+///
+/// ```rust ignore
+/// use markdown::util::sanitize_html::sanitize;
+/// use markdown::HtmlSchema;
+///
+/// let mut stack = vec![];
+/// assert_eq!(
+///     sanitize("<kbd onclick=\"a\">b", &HtmlSchema::github(), &mut stack, true, 0, false, true),
+///     "<kbd>b"
+/// );
+/// assert_eq!(stack.len(), 1);
+/// ```
+pub fn sanitize(
+    value: &str,
+    schema: &HtmlSchema,
+    stack: &mut Vec<Open>,
+    inline: bool,
+    depth: usize,
+    text_only: bool,
+    close_void: bool,
+) -> String {
+    let bytes = value.as_bytes();
+    let mut result = String::with_capacity(value.len());
+    let mut index = 0;
+
+    // Drop everything up to the closing tag of an element to strip.
+    if let Some(open) = stack.last() {
+        if open.strip {
+            if let Some(end) = strip_end(value, 0, &open.name) {
+                stack.pop();
+                index = end;
+            } else {
+                return result;
+            }
+        }
+    }
+
+    let mut start = index;
+
+    while index < bytes.len() {
+        if bytes[index] == b'<' {
+            let rest = &value[index..];
+            let mut output = String::new();
+
+            let end = if rest.starts_with("<!--") {
+                // Comment.
+                Some(find(value, index + 4, "-->").map_or(bytes.len(), |d| d + 3))
+            } else if rest.starts_with("<!") || rest.starts_with("<?") {
+                // Declaration, CDATA, or instruction.
+                let close = if rest.starts_with("<![CDATA[") {
+                    "]]>"
+                } else {
+                    ">"
+                };
+                Some(find(value, index + 2, close).map_or(bytes.len(), |d| d + close.len()))
+            } else if let Some(tag) = tag(value, index) {
+                if !tag.close && includes(&schema.strip, &tag.name) {
+                    // Drop everything up to the closing tag.
+                    Some(strip_end(value, tag.end, &tag.name).unwrap_or_else(|| {
+                        stack.push(Open {
+                            name: tag.name.clone(),
+                            inline,
+                            depth,
+                            strip: true,
+                        });
+                        bytes.len()
+                    }))
+                } else {
+                    if !text_only && includes(&schema.tag_names, &tag.name) {
+                        if tag.close {
+                            push_close(&mut output, stack, &tag.name, inline);
+                        } else {
+                            push_open(&mut output, schema, stack, &tag, inline, depth, close_void);
+                        }
+                    }
+
+                    Some(tag.end)
+                }
+            } else {
+                None
+            };
+
+            if let Some(end) = end {
+                push_text(&mut result, &value[start..index]);
+                result.push_str(&output);
+                index = end;
+                start = end;
+                continue;
+            }
+        }
+
+        index += 1;
+    }
+
+    push_text(&mut result, &value[start..]);
+
+    result
+}
+
+/// Close open elements in `stack`: those opened in HTML (text), and, if not
+/// `inline`, those opened at `depth` or deeper.
+///
+/// Elements to strip stop being stripped, without a closing tag.
+pub fn close(stack: &mut Vec<Open>, inline: bool, depth: usize) -> String {
+    let mut result = String::new();
+
+    while let Some(open) = stack.last() {
+        if open.inline || (!inline && open.depth >= depth) {
+            if !open.strip {
+                result.push_str("</");
+                result.push_str(&open.name);
+                result.push('>');
+            }
+
+            stack.pop();
+        } else {
+            break;
+        }
+    }
+
+    result
+}
+
+/// Push an opening tag, with allowed attributes, and track it on `stack` if
+/// it needs to be closed.
+fn push_open(
+    result: &mut String,
+    schema: &HtmlSchema,
+    stack: &mut Vec<Open>,
+    tag: &Tag,
+    inline: bool,
+    depth: usize,
+    close_void: bool,
+) {
+    let mut seen: Vec<&str> = Vec::new();
+
+    result.push('<');
+    result.push_str(&tag.name);
+
+    for (name, value) in &tag.attributes {
+        // Event handlers are never allowed, and the first attribute wins.
+        if name.starts_with("on")
+            || seen.contains(&name.as_str())
+            || !schema.attributes.iter().any(|(tag_name, names)| {
+                (tag_name == "*" || tag_name.eq_ignore_ascii_case(&tag.name))
+                    && includes(names, name)
+            })
+        {
+            continue;
+        }
+
+        seen.push(name);
+
+        let mut value = decode_character_references(value.as_deref().unwrap_or_default(), true);
+
+        if includes(&schema.clobber, name) && !value.is_empty() {
+            value = format!("{}{}", schema.clobber_prefix, value);
+        }
+
+        let value = if let Some((_, protocols)) = schema
+            .protocols
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        {
+            let value = sanitize_with_protocols(
+                &value,
+                &protocols.iter().map(String::as_str).collect::<Vec<_>>(),
+            );
+
+            if value.is_empty() {
+                continue;
+            }
+
+            value
+        } else {
+            encode(&value, true)
+        };
+
+        result.push(' ');
+        result.push_str(name);
+        result.push_str("=\"");
+        result.push_str(&value);
+        result.push('"');
+    }
+
+    if HTML_VOID_NAMES.contains(&tag.name.as_str()) {
+        result.push_str(if close_void { " />" } else { ">" });
+    } else if tag.self_closing {
+        result.push_str("></");
+        result.push_str(&tag.name);
+        result.push('>');
+    } else {
+        result.push('>');
+        stack.push(Open {
+            name: tag.name.clone(),
+            inline,
+            depth,
+            strip: false,
+        });
+    }
+}
+
+/// Push a closing tag, if there is a matching open element on `stack`,
+/// closing elements opened after it first.
+///
+/// Closing tags in HTML (text) only close elements opened in HTML (text).
+fn push_close(result: &mut String, stack: &mut Vec<Open>, name: &str, inline: bool) {
+    if let Some(position) = stack
+        .iter()
+        .rposition(|open| open.name == name && (!inline || open.inline))
+    {
+        while stack.len() > position {
+            let open = stack.pop().unwrap();
+            result.push_str("</");
+            result.push_str(&open.name);
+            result.push('>');
+        }
+    }
+}
+
+/// Push text, with character references decoded and dangerous characters
+/// encoded.
+fn push_text(result: &mut String, value: &str) {
+    if !value.is_empty() {
+        result.push_str(&encode(&decode_character_references(value, true), true));
+    }
+}
+
+/// Parse a tag at `index` (at `<`) in `value`.
+fn tag(value: &str, index: usize) -> Option<Tag> {
+    let bytes = value.as_bytes();
+    let len = bytes.len();
+    let mut index = index + 1;
+    let close = index < len && bytes[index] == b'/';
+
+    if close {
+        index += 1;
+    }
+
+    if !(index < len && bytes[index].is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let name_start = index;
+
+    while index < len && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'-') {
+        index += 1;
+    }
+
+    let name = value[name_start..index].to_ascii_lowercase();
+    let mut attributes = Vec::new();
+    let mut self_closing = false;
+
+    loop {
+        index = skip_whitespace(bytes, index);
+
+        if index == len {
+            return None;
+        }
+
+        match bytes[index] {
+            b'>' => {
+                index += 1;
+                break;
+            }
+            b'/' => {
+                index += 1;
+
+                if index < len && bytes[index] == b'>' {
+                    self_closing = true;
+                    index += 1;
+                    break;
+                }
+            }
+            _ => {
+                let attribute_start = index;
+                // The first character can be `=`.
+                index += 1;
+
+                while index < len
+                    && !is_whitespace(bytes[index])
+                    && !matches!(bytes[index], b'/' | b'>' | b'=')
+                {
+                    index += 1;
+                }
+
+                let attribute_name = value[attribute_start..index].to_ascii_lowercase();
+                let mut attribute_value = None;
+                let after_name = skip_whitespace(bytes, index);
+
+                if after_name < len && bytes[after_name] == b'=' {
+                    index = skip_whitespace(bytes, after_name + 1);
+
+                    if index == len {
+                        return None;
+                    }
+
+                    let marker = bytes[index];
+
+                    if matches!(marker, b'"' | b'\'') {
+                        let value_start = index + 1;
+                        index = value_start;
+
+                        while index < len && bytes[index] != marker {
+                            index += 1;
+                        }
+
+                        if index == len {
+                            return None;
+                        }
+
+                        attribute_value = Some(value[value_start..index].to_string());
+                        index += 1;
+                    } else {
+                        let value_start = index;
+
+                        while index < len && !is_whitespace(bytes[index]) && bytes[index] != b'>' {
+                            index += 1;
+                        }
+
+                        attribute_value = Some(value[value_start..index].to_string());
+                    }
+                }
+
+                attributes.push((attribute_name, attribute_value));
+            }
+        }
+    }
+
+    Some(Tag {
+        name,
+        close,
+        self_closing,
+        attributes,
+        end: index,
+    })
+}
+
+/// Find the end of the closing tag of element `name` in `value`, from
+/// `index`.
+fn strip_end(value: &str, index: usize, name: &str) -> Option<usize> {
+    let closing = format!("</{}", name);
+    find(&value.to_ascii_lowercase(), index, &closing)
+        .and_then(|d| find(value, d, ">"))
+        .map(|d| d + 1)
+}
+
+/// Find `search` in `value`, from `index`.
+fn find(value: &str, index: usize, search: &str) -> Option<usize> {
+    value[index..].find(search).map(|d| d + index)
+}
+
+/// Check whether `names` includes `name`, case-insensitively.
+fn includes(names: &[String], name: &str) -> bool {
+    names.iter().any(|d| d.eq_ignore_ascii_case(name))
+}
+
+/// Move past HTML whitespace.
+fn skip_whitespace(bytes: &[u8], mut index: usize) -> usize {
+    while index < bytes.len() && is_whitespace(bytes[index]) {
+        index += 1;
+    }
+
+    index
+}
+
+/// Check whether `byte` is HTML whitespace.
+fn is_whitespace(byte: u8) -> bool {
+    matches!(byte, b'\t' | b'\n' | 12 /* `\f` */ | b'\r' | b' ')
+}
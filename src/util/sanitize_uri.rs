@@ -1,7 +1,13 @@
 //! Make urls safe.
 
-use crate::util::encode::encode;
-use alloc::{format, string::String, vec::Vec};
+use crate::configuration::CompileOptions;
+use crate::mdast::Node;
+use crate::util::{
+    constant::{SAFE_PROTOCOL_HREF, SAFE_PROTOCOL_SRC},
+    encode::encode,
+    normalize_identifier::normalize_identifier,
+};
+use alloc::{collections::BTreeMap, format, string::String, vec::Vec};
 
 /// Make a value safe for injection as a URL.
 ///
@@ -54,6 +60,126 @@ pub fn sanitize(value: &str) -> String {
 pub fn sanitize_with_protocols(value: &str, protocols: &[&str]) -> String {
     let value = sanitize(value);
 
+    if allowed(&value, protocols) {
+        value
+    } else {
+        String::new()
+    }
+}
+
+/// Make a value safe for injection as a URL, and check protocols based on
+/// `options`, for `img[src]` (if `image`) or `a[href]`.
+pub fn sanitize_with_options(value: &str, options: &CompileOptions, image: bool) -> String {
+    if options.allow_dangerous_protocol {
+        sanitize(value)
+    } else {
+        sanitize_with_protocols(value, &protocols(options, image))
+    }
+}
+
+/// Drop URLs in links, images, and definitions in `tree` that
+/// [`sanitize_with_options`][sanitize_with_options] would drop.
+///
+/// Definitions are checked for how they are used: against the protocols for
+/// `img[src]` when used by image references, for `a[href]` when used by link
+/// references (or not used), and against both when used by both.
+pub fn sanitize_tree(tree: &mut Node, options: &CompileOptions) {
+    if !options.allow_dangerous_protocol {
+        let mut uses = BTreeMap::new();
+        collect_uses(tree, &mut uses);
+        sanitize_node(tree, options, &uses);
+    }
+}
+
+/// Collect whether each identifier is used by link references and by image
+/// references.
+fn collect_uses(node: &Node, uses: &mut BTreeMap<String, (bool, bool)>) {
+    let reference = match node {
+        Node::LinkReference(node) => Some((&node.identifier, false)),
+        Node::ImageReference(node) => Some((&node.identifier, true)),
+        _ => None,
+    };
+
+    if let Some((identifier, image)) = reference {
+        let entry = uses
+            .entry(normalize_identifier(identifier).to_lowercase())
+            .or_insert((false, false));
+
+        if image {
+            entry.1 = true;
+        } else {
+            entry.0 = true;
+        }
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            collect_uses(child, uses);
+        }
+    }
+}
+
+/// Drop URLs in `node` and its descendants, with `uses` of definitions.
+fn sanitize_node(node: &mut Node, options: &CompileOptions, uses: &BTreeMap<String, (bool, bool)>) {
+    match node {
+        Node::Link(node) => sanitize_url(&mut node.url, options, false),
+        Node::Image(node) => sanitize_url(&mut node.url, options, true),
+        Node::Definition(node) => {
+            let (link, image) = uses
+                .get(&normalize_identifier(&node.identifier).to_lowercase())
+                .copied()
+                .unwrap_or((true, false));
+
+            if link || !image {
+                sanitize_url(&mut node.url, options, false);
+            }
+
+            if image {
+                sanitize_url(&mut node.url, options, true);
+            }
+        }
+        _ => {}
+    }
+
+    if let Some(children) = node.children_mut() {
+        for child in children {
+            sanitize_node(child, options, uses);
+        }
+    }
+}
+
+/// Clear `url` if its protocol is not allowed for `img[src]` (if `image`) or
+/// `a[href]`.
+fn sanitize_url(url: &mut String, options: &CompileOptions, image: bool) {
+    if !allowed(url, &protocols(options, image)) {
+        url.clear();
+    }
+}
+
+/// Get the protocols allowed by `options` for `img[src]` (if `image`) or
+/// `a[href]`.
+fn protocols(options: &CompileOptions, image: bool) -> Vec<&str> {
+    let custom = if image {
+        &options.protocol_src
+    } else {
+        &options.protocol_href
+    };
+
+    if let Some(custom) = custom {
+        custom.iter().map(String::as_str).collect()
+    } else if image {
+        SAFE_PROTOCOL_SRC.to_vec()
+    } else {
+        SAFE_PROTOCOL_HREF.to_vec()
+    }
+}
+
+/// Check whether the protocol of `value` is in `protocols`.
+///
+/// Values without protocol (relative URLs) are allowed.
+/// Protocols in `protocols` with a colon (such as `data:image/png`) match
+/// the start of `value` instead.
+fn allowed(value: &str, protocols: &[&str]) -> bool {
     let end = value.find(|c| matches!(c, '?' | '#' | '/'));
     let mut colon = value.find(|c| matches!(c, ':'));
 
@@ -69,13 +195,19 @@ pub fn sanitize_with_protocols(value: &str, protocols: &[&str]) -> String {
     // If there is no protocol, it’s relative, and fine.
     if let Some(colon) = colon {
         // If it is a protocol, it should be allowed.
-        let protocol = value[0..colon].to_lowercase();
-        if !protocols.contains(&protocol.as_str()) {
-            return String::new();
-        }
+        let protocol = &value[0..colon];
+        protocols.iter().any(|allowed| {
+            if allowed.contains(':') {
+                value
+                    .get(0..allowed.len())
+                    .map_or(false, |start| start.eq_ignore_ascii_case(allowed))
+            } else {
+                protocol.eq_ignore_ascii_case(allowed)
+            }
+        })
+    } else {
+        true
     }
-
-    value
 }
 
 /// Normalize a URL (such as used in [definitions][definition],
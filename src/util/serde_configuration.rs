@@ -0,0 +1,418 @@
+//! Deserialize configuration, with presets and partial overrides.
+//!
+//! Each of [`Options`][], [`ParseOptions`][], [`CompileOptions`][], and
+//! [`Constructs`][] can be deserialized from:
+//!
+//! *   the name of a preset (`"commonmark"`, `"gfm"`, or `"mdx"`)
+//! *   a map of fields, where missing fields are taken from a `preset` field
+//!     (`"commonmark"` when there is none); nested options (`parse`,
+//!     `compile`, `constructs`) are applied on top of what the preset gives
+//!     them, so `{ preset: "gfm", parse: { math_text_single_dollar: false } }`
+//!     keeps GFM constructs on
+//!
+//! Unknown fields are an error, as are the fields with functions
+//! (`mdx_expression_parse`, `mdx_esm_parse`, and `cancel`), which cannot be
+//! deserialized.
+
+use crate::configuration::{
+    CompileOptions, Constructs, Diagram, HtmlSchema, HtmlSyntax, Matter, Options, ParseOptions,
+    PositionEncoding,
+};
+use crate::util::line_ending::LineEnding;
+use alloc::{string::String, vec::Vec};
+use core::{fmt, marker::PhantomData};
+use serde::{
+    de::{self, value::MapAccessDeserializer, IntoDeserializer, MapAccess, Visitor},
+    Deserialize, Deserializer,
+};
+
+/// Name of a preset.
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum Preset {
+    /// `CommonMark`, the default.
+    CommonMark,
+    /// GFM.
+    Gfm,
+    /// MDX.
+    Mdx,
+}
+
+/// Something that can be created from a preset.
+trait FromPreset {
+    /// Create from `preset`.
+    fn from_preset(preset: Preset) -> Self;
+}
+
+impl FromPreset for Constructs {
+    fn from_preset(preset: Preset) -> Self {
+        match preset {
+            Preset::CommonMark => Constructs::default(),
+            Preset::Gfm => Constructs::gfm(),
+            Preset::Mdx => Constructs::mdx(),
+        }
+    }
+}
+
+impl FromPreset for ParseOptions {
+    fn from_preset(preset: Preset) -> Self {
+        match preset {
+            Preset::CommonMark => ParseOptions::default(),
+            Preset::Gfm => ParseOptions::gfm(),
+            Preset::Mdx => ParseOptions::mdx(),
+        }
+    }
+}
+
+impl FromPreset for CompileOptions {
+    fn from_preset(preset: Preset) -> Self {
+        match preset {
+            // MDX does not change how HTML is compiled.
+            Preset::CommonMark | Preset::Mdx => CompileOptions::default(),
+            Preset::Gfm => CompileOptions::gfm(),
+        }
+    }
+}
+
+impl FromPreset for Options {
+    fn from_preset(preset: Preset) -> Self {
+        Options {
+            parse: ParseOptions::from_preset(preset),
+            compile: CompileOptions::from_preset(preset),
+        }
+    }
+}
+
+/// Fields that can be applied to a value.
+trait Apply<T> {
+    /// Set the fields that are defined on `base`.
+    fn apply(self, base: &mut T);
+}
+
+/// A preset name, or fields.
+enum Patch<F> {
+    /// Preset name.
+    Preset(Preset),
+    /// Fields.
+    Fields(F),
+}
+
+impl<F> Patch<F> {
+    /// Apply to `base`.
+    fn apply<T: FromPreset>(self, base: &mut T)
+    where
+        F: Apply<T>,
+    {
+        match self {
+            Patch::Preset(preset) => *base = T::from_preset(preset),
+            Patch::Fields(fields) => fields.apply(base),
+        }
+    }
+
+    /// Apply to what `T` is by default.
+    fn into_value<T: FromPreset>(self) -> T
+    where
+        F: Apply<T>,
+    {
+        let mut value = T::from_preset(Preset::CommonMark);
+        self.apply(&mut value);
+        value
+    }
+}
+
+impl<'de, F: Deserialize<'de>> Deserialize<'de> for Patch<F> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        /// Visitor of a string or a map.
+        struct PatchVisitor<F>(PhantomData<F>);
+
+        impl<'de, F: Deserialize<'de>> Visitor<'de> for PatchVisitor<F> {
+            type Value = Patch<F>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a preset (`commonmark`, `gfm`, or `mdx`) or a map")
+            }
+
+            fn visit_str<E: de::Error>(self, value: &str) -> Result<Self::Value, E> {
+                Preset::deserialize(value.into_deserializer()).map(Patch::Preset)
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<Self::Value, A::Error> {
+                F::deserialize(MapAccessDeserializer::new(map)).map(Patch::Fields)
+            }
+        }
+
+        deserializer.deserialize_any(PatchVisitor(PhantomData))
+    }
+}
+
+/// Fields of [`Constructs`][].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConstructsFields {
+    preset: Option<Preset>,
+    attention: Option<bool>,
+    autolink: Option<bool>,
+    block_quote: Option<bool>,
+    character_escape: Option<bool>,
+    character_reference: Option<bool>,
+    code_indented: Option<bool>,
+    code_fenced: Option<bool>,
+    code_text: Option<bool>,
+    definition: Option<bool>,
+    frontmatter: Option<bool>,
+    gfm_autolink_literal: Option<bool>,
+    gfm_footnote_definition: Option<bool>,
+    gfm_label_start_footnote: Option<bool>,
+    gfm_strikethrough: Option<bool>,
+    gfm_table: Option<bool>,
+    gfm_task_list_item: Option<bool>,
+    hard_break_escape: Option<bool>,
+    hard_break_trailing: Option<bool>,
+    heading_atx: Option<bool>,
+    heading_setext: Option<bool>,
+    html_flow: Option<bool>,
+    html_text: Option<bool>,
+    label_start_image: Option<bool>,
+    label_start_link: Option<bool>,
+    label_end: Option<bool>,
+    list_item: Option<bool>,
+    math_flow: Option<bool>,
+    math_text: Option<bool>,
+    mdx_esm: Option<bool>,
+    mdx_expression_flow: Option<bool>,
+    mdx_expression_text: Option<bool>,
+    mdx_jsx_flow: Option<bool>,
+    mdx_jsx_text: Option<bool>,
+    thematic_break: Option<bool>,
+}
+
+impl Apply<Constructs> for ConstructsFields {
+    fn apply(self, base: &mut Constructs) {
+        if let Some(preset) = self.preset {
+            *base = Constructs::from_preset(preset);
+        }
+
+        set(&mut base.attention, self.attention);
+        set(&mut base.autolink, self.autolink);
+        set(&mut base.block_quote, self.block_quote);
+        set(&mut base.character_escape, self.character_escape);
+        set(&mut base.character_reference, self.character_reference);
+        set(&mut base.code_indented, self.code_indented);
+        set(&mut base.code_fenced, self.code_fenced);
+        set(&mut base.code_text, self.code_text);
+        set(&mut base.definition, self.definition);
+        set(&mut base.frontmatter, self.frontmatter);
+        set(&mut base.gfm_autolink_literal, self.gfm_autolink_literal);
+        set(
+            &mut base.gfm_footnote_definition,
+            self.gfm_footnote_definition,
+        );
+        set(
+            &mut base.gfm_label_start_footnote,
+            self.gfm_label_start_footnote,
+        );
+        set(&mut base.gfm_strikethrough, self.gfm_strikethrough);
+        set(&mut base.gfm_table, self.gfm_table);
+        set(&mut base.gfm_task_list_item, self.gfm_task_list_item);
+        set(&mut base.hard_break_escape, self.hard_break_escape);
+        set(&mut base.hard_break_trailing, self.hard_break_trailing);
+        set(&mut base.heading_atx, self.heading_atx);
+        set(&mut base.heading_setext, self.heading_setext);
+        set(&mut base.html_flow, self.html_flow);
+        set(&mut base.html_text, self.html_text);
+        set(&mut base.label_start_image, self.label_start_image);
+        set(&mut base.label_start_link, self.label_start_link);
+        set(&mut base.label_end, self.label_end);
+        set(&mut base.list_item, self.list_item);
+        set(&mut base.math_flow, self.math_flow);
+        set(&mut base.math_text, self.math_text);
+        set(&mut base.mdx_esm, self.mdx_esm);
+        set(&mut base.mdx_expression_flow, self.mdx_expression_flow);
+        set(&mut base.mdx_expression_text, self.mdx_expression_text);
+        set(&mut base.mdx_jsx_flow, self.mdx_jsx_flow);
+        set(&mut base.mdx_jsx_text, self.mdx_jsx_text);
+        set(&mut base.thematic_break, self.thematic_break);
+    }
+}
+
+/// Fields of [`ParseOptions`][].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ParseOptionsFields {
+    preset: Option<Preset>,
+    constructs: Option<Patch<ConstructsFields>>,
+    frontmatter_matters: Option<Vec<Matter>>,
+    gfm_strikethrough_single_tilde: Option<bool>,
+    math_text_single_dollar: Option<bool>,
+    size_max: Option<usize>,
+    container_depth_max: Option<usize>,
+    events_max: Option<usize>,
+    label_size_max: Option<usize>,
+    position_encoding: Option<PositionEncoding>,
+}
+
+impl Apply<ParseOptions> for ParseOptionsFields {
+    fn apply(self, base: &mut ParseOptions) {
+        if let Some(preset) = self.preset {
+            *base = ParseOptions::from_preset(preset);
+        }
+
+        if let Some(constructs) = self.constructs {
+            constructs.apply(&mut base.constructs);
+        }
+
+        set(&mut base.frontmatter_matters, self.frontmatter_matters);
+        set(
+            &mut base.gfm_strikethrough_single_tilde,
+            self.gfm_strikethrough_single_tilde,
+        );
+        set(
+            &mut base.math_text_single_dollar,
+            self.math_text_single_dollar,
+        );
+        set_some(&mut base.size_max, self.size_max);
+        set_some(&mut base.container_depth_max, self.container_depth_max);
+        set_some(&mut base.events_max, self.events_max);
+        set_some(&mut base.label_size_max, self.label_size_max);
+        set(&mut base.position_encoding, self.position_encoding);
+    }
+}
+
+/// Fields of [`CompileOptions`][].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct CompileOptionsFields {
+    preset: Option<Preset>,
+    allow_dangerous_html: Option<bool>,
+    allow_dangerous_protocol: Option<bool>,
+    protocol_href: Option<Vec<String>>,
+    protocol_src: Option<Vec<String>>,
+    sanitize_html: Option<HtmlSchema>,
+    default_line_ending: Option<LineEnding>,
+    html_syntax: Option<HtmlSyntax>,
+    gfm_footnote_label: Option<String>,
+    gfm_footnote_label_tag_name: Option<String>,
+    gfm_footnote_label_attributes: Option<String>,
+    gfm_footnote_back_label: Option<String>,
+    gfm_footnote_clobber_prefix: Option<String>,
+    gfm_tagfilter: Option<bool>,
+    sourcepos: Option<bool>,
+    link_external_attributes: Option<String>,
+    link_internal_hosts: Option<Vec<String>>,
+    image_attributes: Option<String>,
+    image_figure: Option<bool>,
+    code_line_numbers: Option<bool>,
+    code_highlight_lines: Option<bool>,
+    diagrams: Option<Vec<Diagram>>,
+}
+
+impl Apply<CompileOptions> for CompileOptionsFields {
+    fn apply(self, base: &mut CompileOptions) {
+        if let Some(preset) = self.preset {
+            *base = CompileOptions::from_preset(preset);
+        }
+
+        set(&mut base.allow_dangerous_html, self.allow_dangerous_html);
+        set(
+            &mut base.allow_dangerous_protocol,
+            self.allow_dangerous_protocol,
+        );
+        set_some(&mut base.protocol_href, self.protocol_href);
+        set_some(&mut base.protocol_src, self.protocol_src);
+        set_some(&mut base.sanitize_html, self.sanitize_html);
+        set(&mut base.default_line_ending, self.default_line_ending);
+        set(&mut base.html_syntax, self.html_syntax);
+        set_some(&mut base.gfm_footnote_label, self.gfm_footnote_label);
+        set_some(
+            &mut base.gfm_footnote_label_tag_name,
+            self.gfm_footnote_label_tag_name,
+        );
+        set_some(
+            &mut base.gfm_footnote_label_attributes,
+            self.gfm_footnote_label_attributes,
+        );
+        set_some(
+            &mut base.gfm_footnote_back_label,
+            self.gfm_footnote_back_label,
+        );
+        set_some(
+            &mut base.gfm_footnote_clobber_prefix,
+            self.gfm_footnote_clobber_prefix,
+        );
+        set(&mut base.gfm_tagfilter, self.gfm_tagfilter);
+        set(&mut base.sourcepos, self.sourcepos);
+        set_some(
+            &mut base.link_external_attributes,
+            self.link_external_attributes,
+        );
+        set_some(&mut base.link_internal_hosts, self.link_internal_hosts);
+        set_some(&mut base.image_attributes, self.image_attributes);
+        set(&mut base.image_figure, self.image_figure);
+        set(&mut base.code_line_numbers, self.code_line_numbers);
+        set(&mut base.code_highlight_lines, self.code_highlight_lines);
+        set(&mut base.diagrams, self.diagrams);
+    }
+}
+
+/// Fields of [`Options`][].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct OptionsFields {
+    preset: Option<Preset>,
+    parse: Option<Patch<ParseOptionsFields>>,
+    compile: Option<Patch<CompileOptionsFields>>,
+}
+
+impl Apply<Options> for OptionsFields {
+    fn apply(self, base: &mut Options) {
+        if let Some(preset) = self.preset {
+            *base = Options::from_preset(preset);
+        }
+
+        if let Some(parse) = self.parse {
+            parse.apply(&mut base.parse);
+        }
+
+        if let Some(compile) = self.compile {
+            compile.apply(&mut base.compile);
+        }
+    }
+}
+
+/// Set `field` to `value`, if there is a value.
+fn set<T>(field: &mut T, value: Option<T>) {
+    if let Some(value) = value {
+        *field = value;
+    }
+}
+
+/// Set an optional `field` to `value`, if there is a value.
+fn set_some<T>(field: &mut Option<T>, value: Option<T>) {
+    if value.is_some() {
+        *field = value;
+    }
+}
+
+impl<'de> Deserialize<'de> for Constructs {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Patch::<ConstructsFields>::deserialize(deserializer).map(Patch::into_value)
+    }
+}
+
+impl<'de> Deserialize<'de> for ParseOptions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Patch::<ParseOptionsFields>::deserialize(deserializer).map(Patch::into_value)
+    }
+}
+
+impl<'de> Deserialize<'de> for CompileOptions {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Patch::<CompileOptionsFields>::deserialize(deserializer).map(Patch::into_value)
+    }
+}
+
+impl<'de> Deserialize<'de> for Options {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Patch::<OptionsFields>::deserialize(deserializer).map(Patch::into_value)
+    }
+}
@@ -0,0 +1,298 @@
+//! Turn raw HTML into XHTML.
+
+use crate::util::{
+    character_reference::decode_named,
+    constant::{
+        CHARACTER_REFERENCE_DECIMAL_SIZE_MAX, CHARACTER_REFERENCE_HEXADECIMAL_SIZE_MAX,
+        CHARACTER_REFERENCE_NAMED_SIZE_MAX, HTML_BOOLEAN_NAMES, HTML_VOID_NAMES,
+    },
+};
+use alloc::string::String;
+use core::fmt::Write;
+
+/// Turn raw HTML into XHTML.
+///
+/// Tag and attribute names are lowercased (`<BR>` to `<br>`), void elements
+/// (such as `<br>`) are closed with a slash (`<br />`), boolean attributes
+/// without a value (such as `disabled`) get their name as value
+/// (`disabled="disabled"`), other attributes without a value get an empty
+/// value (`alt=""`), and unquoted values are quoted, with quotes (`"`) and
+/// less than signs (`<`) in them encoded.
+/// Named character references other than the five XML knows (such as
+/// `&copy;`) are turned into numeric ones (`&#xA9;`), and other ampersands
+/// that do not start a character reference are encoded (`&amp;`).
+/// Everything else, such as comments, is kept as it is.
+///
+/// ## Examples
+///
+/// ```rust ignore
+/// use markdown::util::xhtml::to_xhtml;
+///
+/// assert_eq!(to_xhtml("<br>a&nbsp;&amp;b&c"), "<br />a&#xA0;&amp;b&amp;c");
+/// ```
+pub fn to_xhtml(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut result = String::with_capacity(value.len());
+    let mut index = 0;
+    let mut start = 0;
+
+    while index < bytes.len() {
+        match bytes[index] {
+            b'&' => {
+                result.push_str(&value[start..index]);
+                index = reference(value, index, &mut result);
+                start = index;
+            }
+            b'<' if bytes.get(index + 1).map_or(false, u8::is_ascii_alphabetic) => {
+                result.push_str(&value[start..index]);
+                index = tag(value, index, &mut result);
+                start = index;
+            }
+            b'<' if bytes.get(index + 1) == Some(&b'/')
+                && bytes.get(index + 2).map_or(false, u8::is_ascii_alphabetic) =>
+            {
+                result.push_str(&value[start..index]);
+                result.push_str("</");
+                index += 2;
+                start = index;
+
+                while index < bytes.len()
+                    && (bytes[index].is_ascii_alphanumeric() || bytes[index] == b'-')
+                {
+                    index += 1;
+                }
+
+                result.push_str(&value[start..index].to_ascii_lowercase());
+                start = index;
+            }
+            b'<' if value[index..].starts_with("<!--") => {
+                // Keep comments as they are.
+                index = value[index + 4..]
+                    .find("-->")
+                    .map_or(bytes.len(), |end| index + 4 + end + 3);
+            }
+            _ => index += 1,
+        }
+    }
+
+    result.push_str(&value[start..]);
+
+    result
+}
+
+/// Turn a character reference or ampersand at `index` (at its `&`) into
+/// XML, returning the index after it.
+fn reference(value: &str, index: usize, result: &mut String) -> usize {
+    let bytes = value.as_bytes();
+    let mut end = index + 1;
+
+    if bytes.get(end) == Some(&b'#') {
+        end += 1;
+        let hexadecimal = matches!(bytes.get(end), Some(b'x' | b'X'));
+
+        if hexadecimal {
+            end += 1;
+        }
+
+        let digits = end;
+        let max = if hexadecimal {
+            CHARACTER_REFERENCE_HEXADECIMAL_SIZE_MAX
+        } else {
+            CHARACTER_REFERENCE_DECIMAL_SIZE_MAX
+        };
+
+        while end < bytes.len()
+            && end - digits < max
+            && (if hexadecimal {
+                bytes[end].is_ascii_hexdigit()
+            } else {
+                bytes[end].is_ascii_digit()
+            })
+        {
+            end += 1;
+        }
+
+        if end > digits && bytes.get(end) == Some(&b';') {
+            result.push_str(&value[index..=end]);
+            return end + 1;
+        }
+    } else {
+        while end < bytes.len()
+            && end - index <= CHARACTER_REFERENCE_NAMED_SIZE_MAX
+            && bytes[end].is_ascii_alphanumeric()
+        {
+            end += 1;
+        }
+
+        if end > index + 1 && bytes.get(end) == Some(&b';') {
+            let name = &value[index + 1..end];
+
+            if matches!(name, "amp" | "apos" | "gt" | "lt" | "quot") {
+                result.push_str(&value[index..=end]);
+                return end + 1;
+            }
+
+            if let Some(decoded) = decode_named(name, true) {
+                for char in decoded.chars() {
+                    // Writing to a string does not fail.
+                    let _ = write!(result, "&#x{:X};", char as u32);
+                }
+
+                return end + 1;
+            }
+        }
+    }
+
+    result.push_str("&amp;");
+    index + 1
+}
+
+/// Turn an opening tag at `index` (at its `<`) into XHTML, returning the
+/// index after it.
+///
+/// The tag is kept as it is if it does not end.
+fn tag(value: &str, index: usize, result: &mut String) -> usize {
+    let bytes = value.as_bytes();
+    let mut tag = String::new();
+    let mut end = index + 1;
+
+    while end < bytes.len() && (bytes[end].is_ascii_alphanumeric() || bytes[end] == b'-') {
+        end += 1;
+    }
+
+    let name = value[index + 1..end].to_ascii_lowercase();
+    let void = HTML_VOID_NAMES.contains(&name.as_str());
+    tag.push('<');
+    tag.push_str(&name);
+
+    loop {
+        let whitespace = end;
+
+        while end < bytes.len() && bytes[end].is_ascii_whitespace() {
+            end += 1;
+        }
+
+        tag.push_str(&value[whitespace..end]);
+
+        match bytes.get(end) {
+            None => {
+                result.push_str(&value[index..]);
+                return bytes.len();
+            }
+            Some(b'>') => {
+                if void {
+                    tag.push_str(if end > whitespace { "/>" } else { " />" });
+                } else {
+                    tag.push('>');
+                }
+
+                break;
+            }
+            Some(b'/') if bytes.get(end + 1) == Some(&b'>') => {
+                tag.push_str("/>");
+                end += 1;
+                break;
+            }
+            _ => {}
+        }
+
+        // Attribute name.
+        let name_start = end;
+
+        while end < bytes.len()
+            && !bytes[end].is_ascii_whitespace()
+            && !matches!(bytes[end], b'/' | b'>' | b'=')
+        {
+            end += 1;
+        }
+
+        // A stray slash or equals sign.
+        if end == name_start {
+            tag.push(char::from(bytes[end]));
+            end += 1;
+            continue;
+        }
+
+        let name = value[name_start..end].to_ascii_lowercase();
+        let mut after_name = end;
+
+        while after_name < bytes.len() && bytes[after_name].is_ascii_whitespace() {
+            after_name += 1;
+        }
+
+        tag.push_str(&name);
+        tag.push('=');
+        let mut quote = '"';
+
+        if bytes.get(after_name) == Some(&b'=') {
+            end = after_name + 1;
+
+            while end < bytes.len() && bytes[end].is_ascii_whitespace() {
+                end += 1;
+            }
+
+            let (value_start, value_end, after) =
+                if let Some(marker @ (b'"' | b'\'')) = bytes.get(end) {
+                    quote = char::from(*marker);
+                    let value_start = end + 1;
+                    let value_end = value[value_start..]
+                        .find(quote)
+                        .map_or(bytes.len(), |d| value_start + d);
+                    (value_start, value_end, (value_end + 1).min(bytes.len()))
+                } else {
+                    let mut value_end = end;
+
+                    while value_end < bytes.len()
+                        && !bytes[value_end].is_ascii_whitespace()
+                        && bytes[value_end] != b'>'
+                    {
+                        value_end += 1;
+                    }
+
+                    (end, value_end, value_end)
+                };
+
+            let mut position = value_start;
+            let mut start = value_start;
+            tag.push(quote);
+
+            while position < value_end {
+                match bytes[position] {
+                    b'&' => {
+                        tag.push_str(&value[start..position]);
+                        position = reference(value, position, &mut tag);
+                        start = position;
+                    }
+                    b'<' => {
+                        tag.push_str(&value[start..position]);
+                        tag.push_str("&lt;");
+                        position += 1;
+                        start = position;
+                    }
+                    b'"' if quote == '"' => {
+                        tag.push_str(&value[start..position]);
+                        tag.push_str("&quot;");
+                        position += 1;
+                        start = position;
+                    }
+                    _ => position += 1,
+                }
+            }
+
+            tag.push_str(&value[start..value_end]);
+            end = after;
+        } else {
+            // Bare attribute, such as `disabled` or `alt`.
+            tag.push(quote);
+
+            if HTML_BOOLEAN_NAMES.contains(&name.as_str()) {
+                tag.push_str(&name);
+            }
+        }
+
+        tag.push(quote);
+    }
+
+    result.push_str(&tag);
+    end + 1
+}
@@ -0,0 +1,415 @@
+//! WebAssembly API of `markdown-rs`.
+//!
+//! Needs the `wasm` feature.
+//! Build a package that JavaScript (in browsers, Node.js, and the like) can
+//! import with [`wasm-bindgen`](https://rustwasm.github.io/wasm-bindgen/):
+//!
+//! ```sh
+//! cargo rustc --release --target wasm32-unknown-unknown --features wasm --crate-type cdylib
+//! wasm-bindgen --target web --out-dir pkg target/wasm32-unknown-unknown/release/markdown.wasm
+//! ```
+//!
+//! It exposes `toHtml(value, options?)` and `toMdast(value, options?)`.
+//! The trees are plain objects, the same as what `mdast-util-from-markdown`
+//! gives in JavaScript: positions count in UTF-16 code units, like JavaScript
+//! strings.
+//! Errors (such as syntax errors in MDX) are thrown.
+//!
+//! Options are plain objects, with the names from `micromark`,
+//! `mdast-util-from-markdown`, and their extensions:
+//!
+//! *   `extensions` (`Array<string>`)
+//!     — names of extensions to turn on: `'frontmatter'`, `'gfm'`, `'math'`,
+//!     and `'mdx'`
+//! *   `constructs` (`Record<string, boolean>`)
+//!     — constructs to turn on and off, such as `{codeIndented: false}`
+//!     (the names of the fields of `Constructs`, in camelcase)
+//! *   `singleTilde` (`boolean`)
+//!     — whether to support strikethrough with one tilde
+//! *   `singleDollarTextMath` (`boolean`)
+//!     — whether to support math (text) with one dollar
+//! *   `mdxExpressionParse` (`(value: string, kind: string) => undefined`)
+//!     — parse expressions, where `kind` is `'expression'`,
+//!     `'attributeExpression'`, or `'attributeValueExpression'`
+//! *   `mdxEsmParse` (`(value: string) => undefined`)
+//!     — parse ESM
+//! *   `allowDangerousHtml` (`boolean`)
+//!     — whether to allow HTML
+//! *   `allowDangerousProtocol` (`boolean`)
+//!     — whether to allow dangerous protocols in links and images
+//! *   `defaultLineEnding` (`'\r' | '\r\n' | '\n'`)
+//!     — line ending to use when there are none in the document
+//! *   `clobberPrefix`, `label`, `labelAttributes`, `labelTagName`,
+//!     `backLabel` (`string`)
+//!     — how to compile GFM footnotes
+//!
+//! The MDX hooks signal syntax errors by throwing, like `acorn` does: an
+//! error with a `pos` (UTF-16 offset into `value`) that is (or a `raisedAt`
+//! that is at or after) the end of `value` means more text is needed.
+//!
+//! ## Examples
+//!
+//! ```js
+//! import * as acorn from 'acorn'
+//! import init, {toHtml, toMdast} from './pkg/markdown.js'
+//!
+//! await init()
+//!
+//! console.log(toHtml('~hi~', {extensions: ['gfm']}))
+//! // => '<p><del>hi</del></p>'
+//!
+//! console.log(
+//!   toMdast('{1 + 1}', {
+//!     extensions: ['mdx'],
+//!     mdxExpressionParse(value) {
+//!       acorn.parseExpressionAt(value, 0, {ecmaVersion: 'latest'})
+//!     }
+//!   })
+//! )
+//! ```
+
+use crate::{
+    to_html_with_options, to_mdast_json, CompileOptions, Constructs, LineEnding, MdxExpressionKind,
+    MdxSignal, Options, ParseOptions, PositionEncoding,
+};
+use alloc::{boxed::Box, format, string::String};
+use js_sys::{Array, Function, Reflect, JSON};
+use wasm_bindgen::{prelude::wasm_bindgen, JsCast, JsError, JsValue};
+
+#[wasm_bindgen(typescript_custom_section)]
+const TYPES: &'static str = r#"
+export interface Options {
+  extensions?: Array<'frontmatter' | 'gfm' | 'math' | 'mdx'> | null | undefined
+  constructs?: Record<string, boolean> | null | undefined
+  singleTilde?: boolean | null | undefined
+  singleDollarTextMath?: boolean | null | undefined
+  mdxExpressionParse?: ((value: string, kind: 'expression' | 'attributeExpression' | 'attributeValueExpression') => undefined) | null | undefined
+  mdxEsmParse?: ((value: string) => undefined) | null | undefined
+  allowDangerousHtml?: boolean | null | undefined
+  allowDangerousProtocol?: boolean | null | undefined
+  defaultLineEnding?: '\r' | '\r\n' | '\n' | null | undefined
+  clobberPrefix?: string | null | undefined
+  label?: string | null | undefined
+  labelAttributes?: string | null | undefined
+  labelTagName?: string | null | undefined
+  backLabel?: string | null | undefined
+}
+"#;
+
+#[wasm_bindgen]
+extern "C" {
+    /// Configuration, as a plain object.
+    #[wasm_bindgen(typescript_type = "Options")]
+    pub type JsOptions;
+}
+
+/// Turn markdown into HTML.
+///
+/// ## Errors
+///
+/// Throws for syntax errors in MDX, and for invalid options.
+// `wasm-bindgen` passes options by value.
+#[allow(clippy::needless_pass_by_value)]
+#[wasm_bindgen(js_name = toHtml)]
+pub fn to_html(value: &str, options: Option<JsOptions>) -> Result<String, JsError> {
+    let options = to_options(options.as_deref())?;
+    to_html_with_options(value, &options).map_err(|message| JsError::new(&message))
+}
+
+/// Turn markdown into a syntax tree (mdast).
+///
+/// ## Errors
+///
+/// Throws for syntax errors in MDX, and for invalid options.
+// `wasm-bindgen` passes options by value.
+#[allow(clippy::needless_pass_by_value)]
+#[wasm_bindgen(js_name = toMdast)]
+pub fn to_mdast(value: &str, options: Option<JsOptions>) -> Result<JsValue, JsError> {
+    let options = to_options(options.as_deref())?;
+    let json = to_mdast_json(value, &options.parse).map_err(|message| JsError::new(&message))?;
+    JSON::parse(&json).map_err(|_| JsError::new("Unexpected invalid JSON for tree"))
+}
+
+/// Turn JS options into options.
+fn to_options(object: Option<&JsValue>) -> Result<Options, JsError> {
+    let mut options = Options {
+        parse: ParseOptions {
+            position_encoding: PositionEncoding::Utf16,
+            ..ParseOptions::default()
+        },
+        ..Options::default()
+    };
+
+    let object = match object {
+        Some(object) if !object.is_null() && !object.is_undefined() => object,
+        _ => return Ok(options),
+    };
+
+    if let Some(extensions) = get(object, "extensions")? {
+        let extensions = extensions
+            .dyn_into::<Array>()
+            .map_err(|_| unexpected("extensions", "an array"))?;
+
+        for extension in extensions.iter() {
+            let constructs = &mut options.parse.constructs;
+
+            match extension.as_string().as_deref() {
+                Some("frontmatter") => constructs.frontmatter = true,
+                Some("gfm") => {
+                    let gfm = Constructs::gfm();
+                    constructs.gfm_autolink_literal = gfm.gfm_autolink_literal;
+                    constructs.gfm_footnote_definition = gfm.gfm_footnote_definition;
+                    constructs.gfm_label_start_footnote = gfm.gfm_label_start_footnote;
+                    constructs.gfm_strikethrough = gfm.gfm_strikethrough;
+                    constructs.gfm_table = gfm.gfm_table;
+                    constructs.gfm_task_list_item = gfm.gfm_task_list_item;
+                    options.compile.gfm_tagfilter = CompileOptions::gfm().gfm_tagfilter;
+                }
+                Some("math") => {
+                    constructs.math_flow = true;
+                    constructs.math_text = true;
+                }
+                Some("mdx") => {
+                    let mdx = Constructs::mdx();
+                    constructs.autolink = mdx.autolink;
+                    constructs.code_indented = mdx.code_indented;
+                    constructs.html_flow = mdx.html_flow;
+                    constructs.html_text = mdx.html_text;
+                    constructs.mdx_esm = mdx.mdx_esm;
+                    constructs.mdx_expression_flow = mdx.mdx_expression_flow;
+                    constructs.mdx_expression_text = mdx.mdx_expression_text;
+                    constructs.mdx_jsx_flow = mdx.mdx_jsx_flow;
+                    constructs.mdx_jsx_text = mdx.mdx_jsx_text;
+                }
+                _ => {
+                    return Err(JsError::new(&format!(
+                        "Unexpected extension `{}`, expected `frontmatter`, `gfm`, `math`, or `mdx`",
+                        extension.as_string().unwrap_or_else(|| "?".into())
+                    )));
+                }
+            }
+        }
+    }
+
+    if let Some(constructs) = get(object, "constructs")? {
+        to_constructs(&constructs, &mut options.parse.constructs)?;
+    }
+
+    let parse = &mut options.parse;
+    let compile = &mut options.compile;
+
+    if let Some(value) = get_bool(object, "singleTilde")? {
+        parse.gfm_strikethrough_single_tilde = value;
+    }
+
+    if let Some(value) = get_bool(object, "singleDollarTextMath")? {
+        parse.math_text_single_dollar = value;
+    }
+
+    if let Some(func) = get_function(object, "mdxExpressionParse")? {
+        parse.mdx_expression_parse = Some(Box::new(move |value, kind| {
+            let kind = match kind {
+                MdxExpressionKind::Expression => "expression",
+                MdxExpressionKind::AttributeExpression => "attributeExpression",
+                MdxExpressionKind::AttributeValueExpression => "attributeValueExpression",
+            };
+            to_signal(
+                value,
+                func.call2(&JsValue::NULL, &value.into(), &kind.into()),
+            )
+        }));
+    }
+
+    if let Some(func) = get_function(object, "mdxEsmParse")? {
+        parse.mdx_esm_parse = Some(Box::new(move |value| {
+            to_signal(value, func.call1(&JsValue::NULL, &value.into()))
+        }));
+    }
+
+    if let Some(value) = get_bool(object, "allowDangerousHtml")? {
+        compile.allow_dangerous_html = value;
+    }
+
+    if let Some(value) = get_bool(object, "allowDangerousProtocol")? {
+        compile.allow_dangerous_protocol = value;
+    }
+
+    if let Some(value) = get_string(object, "defaultLineEnding")? {
+        compile.default_line_ending = match value.as_str() {
+            "\r\n" => LineEnding::CarriageReturnLineFeed,
+            "\r" => LineEnding::CarriageReturn,
+            "\n" => LineEnding::LineFeed,
+            _ => {
+                return Err(unexpected(
+                    "defaultLineEnding",
+                    "`'\\r'`, `'\\r\\n'`, or `'\\n'`",
+                ))
+            }
+        };
+    }
+
+    compile.gfm_footnote_clobber_prefix = get_string(object, "clobberPrefix")?;
+    compile.gfm_footnote_label = get_string(object, "label")?;
+    compile.gfm_footnote_label_attributes = get_string(object, "labelAttributes")?;
+    compile.gfm_footnote_label_tag_name = get_string(object, "labelTagName")?;
+    compile.gfm_footnote_back_label = get_string(object, "backLabel")?;
+
+    Ok(options)
+}
+
+/// Turn a JS object of constructs into `constructs`.
+fn to_constructs(object: &JsValue, constructs: &mut Constructs) -> Result<(), JsError> {
+    let fields = [
+        ("attention", &mut constructs.attention),
+        ("autolink", &mut constructs.autolink),
+        ("blockQuote", &mut constructs.block_quote),
+        ("characterEscape", &mut constructs.character_escape),
+        ("characterReference", &mut constructs.character_reference),
+        ("codeIndented", &mut constructs.code_indented),
+        ("codeFenced", &mut constructs.code_fenced),
+        ("codeText", &mut constructs.code_text),
+        ("definition", &mut constructs.definition),
+        ("frontmatter", &mut constructs.frontmatter),
+        ("gfmAutolinkLiteral", &mut constructs.gfm_autolink_literal),
+        (
+            "gfmFootnoteDefinition",
+            &mut constructs.gfm_footnote_definition,
+        ),
+        (
+            "gfmLabelStartFootnote",
+            &mut constructs.gfm_label_start_footnote,
+        ),
+        ("gfmStrikethrough", &mut constructs.gfm_strikethrough),
+        ("gfmTable", &mut constructs.gfm_table),
+        ("gfmTaskListItem", &mut constructs.gfm_task_list_item),
+        ("hardBreakEscape", &mut constructs.hard_break_escape),
+        ("hardBreakTrailing", &mut constructs.hard_break_trailing),
+        ("headingAtx", &mut constructs.heading_atx),
+        ("headingSetext", &mut constructs.heading_setext),
+        ("htmlFlow", &mut constructs.html_flow),
+        ("htmlText", &mut constructs.html_text),
+        ("labelStartImage", &mut constructs.label_start_image),
+        ("labelStartLink", &mut constructs.label_start_link),
+        ("labelEnd", &mut constructs.label_end),
+        ("listItem", &mut constructs.list_item),
+        ("mathFlow", &mut constructs.math_flow),
+        ("mathText", &mut constructs.math_text),
+        ("mdxEsm", &mut constructs.mdx_esm),
+        ("mdxExpressionFlow", &mut constructs.mdx_expression_flow),
+        ("mdxExpressionText", &mut constructs.mdx_expression_text),
+        ("mdxJsxFlow", &mut constructs.mdx_jsx_flow),
+        ("mdxJsxText", &mut constructs.mdx_jsx_text),
+        ("thematicBreak", &mut constructs.thematic_break),
+    ];
+
+    for (name, field) in fields {
+        if let Some(value) = get_bool(object, name)? {
+            *field = value;
+        }
+    }
+
+    Ok(())
+}
+
+/// Turn what a JS MDX hook did into a signal.
+///
+/// Returning means OK, throwing means an error, at the end of `value` (EOF)
+/// or at its `pos` (UTF-16 offset into `value`).
+fn to_signal(value: &str, result: Result<JsValue, JsValue>) -> MdxSignal {
+    let error = match result {
+        Ok(_) => return MdxSignal::Ok,
+        Err(error) => error,
+    };
+
+    let message = error
+        .dyn_ref::<js_sys::Error>()
+        .map_or_else(|| error.as_string(), |d| d.message().as_string())
+        .unwrap_or_else(|| "Unexpected error in MDX hook".into());
+    let pos = number(&error, "pos");
+    let raised_at = number(&error, "raisedAt").or(pos);
+    let size = value.encode_utf16().count();
+
+    match (pos, raised_at) {
+        (Some(pos), Some(raised_at)) if pos < size && raised_at < size => {
+            MdxSignal::Error(message, byte_offset(value, pos))
+        }
+        (Some(_), _) => MdxSignal::Eof(message),
+        _ => MdxSignal::Error(message, 0),
+    }
+}
+
+/// Turn a UTF-16 `offset` into `value` into a byte offset.
+fn byte_offset(value: &str, offset: usize) -> usize {
+    let mut units = 0;
+
+    for (index, char) in value.char_indices() {
+        if units >= offset {
+            return index;
+        }
+
+        units += char.len_utf16();
+    }
+
+    value.len()
+}
+
+/// Get a field `name` from a JS object, as a whole number.
+fn number(object: &JsValue, name: &str) -> Option<usize> {
+    let value = Reflect::get(object, &name.into()).ok()?.as_f64()?;
+
+    if value >= 0.0 && value.fract() == 0.0 {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Some(value as usize)
+    } else {
+        None
+    }
+}
+
+/// Get a field `name` from a JS object, if it’s not `null` or `undefined`.
+fn get(object: &JsValue, name: &str) -> Result<Option<JsValue>, JsError> {
+    let value = Reflect::get(object, &name.into())
+        .map_err(|_| JsError::new(&format!("Cannot read `{}` in options", name)))?;
+
+    if value.is_null() || value.is_undefined() {
+        Ok(None)
+    } else {
+        Ok(Some(value))
+    }
+}
+
+/// Get a boolean field `name` from a JS object.
+fn get_bool(object: &JsValue, name: &str) -> Result<Option<bool>, JsError> {
+    get(object, name)?
+        .map(|value| value.as_bool().ok_or_else(|| unexpected(name, "a boolean")))
+        .transpose()
+}
+
+/// Get a string field `name` from a JS object.
+fn get_string(object: &JsValue, name: &str) -> Result<Option<String>, JsError> {
+    get(object, name)?
+        .map(|value| {
+            value
+                .as_string()
+                .ok_or_else(|| unexpected(name, "a string"))
+        })
+        .transpose()
+}
+
+/// Get a function field `name` from a JS object.
+fn get_function(object: &JsValue, name: &str) -> Result<Option<Function>, JsError> {
+    get(object, name)?
+        .map(|value| {
+            value
+                .dyn_into::<Function>()
+                .map_err(|_| unexpected(name, "a function"))
+        })
+        .transpose()
+}
+
+/// Error for an unexpected value of field `name` in options.
+fn unexpected(name: &str, expected: &str) -> JsError {
+    JsError::new(&format!(
+        "Unexpected value for `{}` in options, expected {}",
+        name, expected
+    ))
+}
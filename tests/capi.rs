@@ -0,0 +1,193 @@
+#![cfg(feature = "capi")]
+
+use markdown::{
+    capi::{
+        markdown_options_default, markdown_options_gfm, markdown_options_mdx, markdown_string_free,
+        markdown_to_html, markdown_to_html_with_options, markdown_to_mdast_json,
+        MarkdownLineEnding, MarkdownOptions,
+    },
+    to_mdast_json, ParseOptions,
+};
+use pretty_assertions::assert_eq;
+use std::{
+    ffi::{CStr, CString},
+    os::raw::c_char,
+    ptr,
+};
+
+/// Take an owned string from the C API, freeing it.
+fn take(value: *mut c_char) -> Option<String> {
+    if value.is_null() {
+        None
+    } else {
+        let result = unsafe { CStr::from_ptr(value) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { markdown_string_free(value) };
+        Some(result)
+    }
+}
+
+/// Turn `value` into HTML with `options`, returning the result or the
+/// error.
+fn html(value: &str, options: Option<&MarkdownOptions>) -> Result<String, String> {
+    let value = CString::new(value).unwrap();
+    let mut error = ptr::null_mut();
+    let result = unsafe {
+        markdown_to_html_with_options(
+            value.as_ptr(),
+            options.map_or(ptr::null(), |d| d as *const MarkdownOptions),
+            &mut error,
+        )
+    };
+
+    match take(result) {
+        Some(result) => {
+            assert!(error.is_null(), "expected no error w/ a result");
+            Ok(result)
+        }
+        None => Err(take(error).unwrap()),
+    }
+}
+
+#[test]
+fn capi() {
+    let value = CString::new("# hi\n\n<i>a</i>").unwrap();
+    assert_eq!(
+        take(unsafe { markdown_to_html(value.as_ptr()) }),
+        Some("<h1>hi</h1>\n<p>&lt;i&gt;a&lt;/i&gt;</p>".into()),
+        "should support `markdown_to_html`"
+    );
+
+    assert_eq!(
+        take(unsafe { markdown_to_html(ptr::null()) }),
+        None,
+        "should return `NULL` for `NULL` in `markdown_to_html`"
+    );
+
+    let value = [0xFF_u8, 0];
+    assert_eq!(
+        take(unsafe { markdown_to_html(value.as_ptr().cast()) }),
+        None,
+        "should return `NULL` for invalid UTF-8 in `markdown_to_html`"
+    );
+
+    assert_eq!(
+        html("~a~", None),
+        Ok("<p>~a~</p>".into()),
+        "should support `NULL` options in `markdown_to_html_with_options`"
+    );
+
+    assert_eq!(
+        html("~a~", Some(&markdown_options_default())),
+        Ok("<p>~a~</p>".into()),
+        "should support `markdown_options_default`"
+    );
+
+    assert_eq!(
+        html("~a~", Some(&markdown_options_gfm())),
+        Ok("<p><del>a</del></p>".into()),
+        "should support `markdown_options_gfm`"
+    );
+
+    assert_eq!(
+        html("<a />", Some(&markdown_options_mdx())),
+        Ok("".into()),
+        "should support `markdown_options_mdx`"
+    );
+
+    assert_eq!(
+        html("a {b", Some(&markdown_options_mdx())),
+        Err("1:5: Unexpected end of file in expression, expected a corresponding closing brace for `{`".into()),
+        "should support errors in `markdown_to_html_with_options`"
+    );
+
+    let value = CString::new("a {b").unwrap();
+    let options = markdown_options_mdx();
+    assert_eq!(
+        take(unsafe { markdown_to_html_with_options(value.as_ptr(), &options, ptr::null_mut()) }),
+        None,
+        "should support `NULL` as `error`"
+    );
+
+    let mut options = markdown_options_default();
+    options.parse.constructs.heading_atx = false;
+    options.compile.allow_dangerous_html = true;
+    options.compile.default_line_ending = MarkdownLineEnding::CarriageReturnLineFeed;
+    assert_eq!(
+        html("# a\n<i>b</i>\n\n> c", Some(&options)),
+        Ok("<p># a\n<i>b</i></p>\n<blockquote>\n<p>c</p>\n</blockquote>".into()),
+        "should support parse and compile options"
+    );
+
+    let label = CString::new("Notes").unwrap();
+    let mut options = markdown_options_gfm();
+    options.compile.gfm_footnote_label = label.as_ptr();
+    assert!(
+        html("[^a]\n\n[^a]: b", Some(&options))
+            .unwrap()
+            .contains(">Notes</h2>"),
+        "should support strings in compile options"
+    );
+
+    let protocols = CString::new("https, tel").unwrap();
+    let mut options = markdown_options_default();
+    options.compile.protocol_href = protocols.as_ptr();
+    assert_eq!(
+        html("[a](tel:1) [b](sms:2)", Some(&options)),
+        Ok("<p><a href=\"tel:1\">a</a> <a href=\"\">b</a></p>".into()),
+        "should support lists of protocols in compile options"
+    );
+
+    let mut options = markdown_options_default();
+    options.compile.sanitize_html = true;
+    assert_eq!(
+        html("<kbd onclick=\"a\">b</kbd><script>", Some(&options)),
+        Ok("<p><kbd>b</kbd></p>".into()),
+        "should support sanitizing HTML"
+    );
+
+    let attributes = CString::new("target=\"_blank\"").unwrap();
+    let hosts = CString::new("a.b").unwrap();
+    let mut options = markdown_options_default();
+    options.compile.link_external_attributes = attributes.as_ptr();
+    options.compile.link_internal_hosts = hosts.as_ptr();
+    options.compile.image_figure = true;
+    assert_eq!(
+        html("[a](https://a.b) [c](https://d.e)\n\n![f](g)", Some(&options)),
+        Ok("<p><a href=\"https://a.b\">a</a> <a href=\"https://d.e\" target=\"_blank\">c</a></p>\n<figure><img src=\"g\" alt=\"f\" /></figure>".into()),
+        "should support link and image options"
+    );
+
+    let label = [0xFF_u8, 0];
+    options.compile.gfm_footnote_label = label.as_ptr().cast();
+    assert_eq!(
+        html("a", Some(&options)),
+        Err("Unexpected invalid UTF-8 in `gfm_footnote_label`".into()),
+        "should error on invalid UTF-8 in strings in options"
+    );
+
+    let value = CString::new("*a*").unwrap();
+    let mut error = ptr::null_mut();
+    assert_eq!(
+        take(unsafe { markdown_to_mdast_json(value.as_ptr(), ptr::null(), &mut error) }),
+        Some(to_mdast_json("*a*", &ParseOptions::default()).unwrap()),
+        "should support `markdown_to_mdast_json`"
+    );
+
+    let value = CString::new("a {b").unwrap();
+    let options = markdown_options_mdx().parse;
+    assert_eq!(
+        take(unsafe { markdown_to_mdast_json(value.as_ptr(), &options, &mut error) }),
+        None,
+        "should return `NULL` for errors in `markdown_to_mdast_json`"
+    );
+    assert_eq!(
+        take(error),
+        Some("1:5: Unexpected end of file in expression, expected a corresponding closing brace for `{`".into()),
+        "should support errors in `markdown_to_mdast_json`"
+    );
+
+    unsafe { markdown_string_free(ptr::null_mut()) };
+}
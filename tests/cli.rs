@@ -0,0 +1,259 @@
+#![cfg(feature = "cli")]
+
+use pretty_assertions::assert_eq;
+use std::{
+    env, fs,
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Run the binary with `args` and `stdin`, returning its exit code, stdout,
+/// and stderr.
+fn run(args: &[&str], stdin: &str) -> (i32, String, String) {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_markdown"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(stdin.as_bytes())
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    (
+        output.status.code().unwrap(),
+        String::from_utf8(output.stdout).unwrap(),
+        String::from_utf8(output.stderr).unwrap(),
+    )
+}
+
+#[test]
+fn cli() {
+    assert_eq!(
+        run(&[], "# a *b*"),
+        (0, "<h1>a <em>b</em></h1>".into(), String::new()),
+        "should turn stdin into HTML"
+    );
+
+    assert_eq!(
+        run(&["--gfm"], "a | ~b~\n--|--"),
+        (
+            0,
+            "<table>\n<thead>\n<tr>\n<th>a</th>\n<th><del>b</del></th>\n</tr>\n</thead>\n</table>"
+                .into(),
+            String::new()
+        ),
+        "should support `--gfm`"
+    );
+
+    assert_eq!(
+        run(&["--math", "--allow-dangerous-html"], "$a$ <b>"),
+        (
+            0,
+            "<p><code class=\"language-math math-inline\">a</code> <b></p>".into(),
+            String::new()
+        ),
+        "should support `--math` and `--allow-dangerous-html`"
+    );
+
+    assert_eq!(
+        run(&["--sourcepos"], "> a"),
+        (
+            0,
+            "<blockquote data-sourcepos=\"1:1-1:3\">\n<p data-sourcepos=\"1:3-1:3\">a</p>\n</blockquote>"
+                .into(),
+            String::new()
+        ),
+        "should support `--sourcepos`"
+    );
+
+    assert_eq!(
+        run(&["--line-ending", "crlf"], "> a"),
+        (
+            0,
+            "<blockquote>\r\n<p>a</p>\r\n</blockquote>".into(),
+            String::new()
+        ),
+        "should support `--line-ending`"
+    );
+
+    assert_eq!(
+        run(&["--frontmatter", "-t", "mdast"], "---\na: b\n---"),
+        (
+            0,
+            "{\"type\":\"root\",\"children\":[{\"type\":\"yaml\",\"value\":\"a: b\",\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":3,\"column\":4,\"offset\":12}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":3,\"column\":4,\"offset\":12}}}\n".into(),
+            String::new()
+        ),
+        "should support `--to mdast` and `--frontmatter`"
+    );
+
+    assert_eq!(
+        run(&["--gfm", "--to", "markdown"], "* a\n* *b*\n\n|a|\n|-|"),
+        (0, "- a\n- _b_\n\n| a   |\n| --- |\n".into(), String::new()),
+        "should support `--to markdown`"
+    );
+
+    assert_eq!(
+        run(&["--mdx"], "a {b"),
+        (
+            1,
+            String::new(),
+            "<stdin>:1:5: Unexpected end of file in expression, expected a corresponding closing brace for `{`\n".into()
+        ),
+        "should exit with `1` for MDX errors"
+    );
+
+    assert_eq!(
+        run(&["--unknown"], ""),
+        (
+            2,
+            String::new(),
+            "markdown: unknown option `--unknown`\nTry `markdown --help` for more info.\n".into()
+        ),
+        "should exit with `2` for unknown options"
+    );
+
+    assert_eq!(
+        run(&["--to", "pdf"], "").0,
+        2,
+        "should exit with `2` for unknown formats"
+    );
+
+    assert_eq!(
+        run(&["--version"], ""),
+        (
+            0,
+            format!("markdown {}\n", env!("CARGO_PKG_VERSION")),
+            String::new()
+        ),
+        "should support `--version`"
+    );
+
+    assert!(
+        run(&["--help"], "").1.starts_with("Usage: markdown"),
+        "should support `--help`"
+    );
+}
+
+#[test]
+fn cli_batch() {
+    let root = env::temp_dir().join(format!("markdown-rs-cli-{}", std::process::id()));
+    let input = root.join("input");
+    let output = root.join("output");
+    fs::create_dir_all(input.join("b")).unwrap();
+    fs::write(input.join("a.md"), "# a").unwrap();
+    fs::write(input.join("b").join("c.markdown"), "*c*").unwrap();
+    fs::write(input.join("b").join("d.mdx"), "{d").unwrap();
+    fs::write(input.join("e.txt"), "e").unwrap();
+
+    let (code, stdout, stderr) = run(
+        &[
+            "--mdx",
+            "-o",
+            output.to_str().unwrap(),
+            input.to_str().unwrap(),
+        ],
+        "",
+    );
+
+    assert_eq!(code, 1, "should exit with `1` if a file fails");
+    assert_eq!(stdout, "", "should not write to stdout in batch mode");
+    assert!(
+        stderr.contains("d.mdx:1:3: Unexpected end of file in expression"),
+        "should report errors with the file path"
+    );
+    assert_eq!(
+        fs::read_to_string(output.join("a.html")).unwrap(),
+        "<h1>a</h1>",
+        "should write files into `--output`"
+    );
+    assert_eq!(
+        fs::read_to_string(output.join("b").join("c.html")).unwrap(),
+        "<p><em>c</em></p>",
+        "should write files in nested directories"
+    );
+    assert!(
+        !output.join("e.html").exists(),
+        "should ignore files that are not markdown"
+    );
+
+    let (code, _, _) = run(
+        &[
+            input.join("a.md").to_str().unwrap(),
+            input.join("b").join("c.markdown").to_str().unwrap(),
+        ],
+        "",
+    );
+    assert_eq!(code, 0, "should support several files");
+    assert_eq!(
+        fs::read_to_string(input.join("b").join("c.html")).unwrap(),
+        "<p><em>c</em></p>",
+        "should write files next to inputs without `--output`"
+    );
+
+    let (code, _, _) = run(
+        &[
+            "--to",
+            "markdown",
+            input.join("a.md").to_str().unwrap(),
+            input.join("b").join("c.markdown").to_str().unwrap(),
+        ],
+        "",
+    );
+    assert_eq!(code, 0, "should support formatting several files");
+    assert_eq!(
+        fs::read_to_string(input.join("b").join("c.markdown")).unwrap(),
+        "_c_\n",
+        "should format files in place with `--to markdown`"
+    );
+
+    fs::write(input.join("a.md"), "# a\n").unwrap();
+    fs::write(input.join("f.mdx"), "<f {").unwrap();
+
+    let (code, _, stderr) = run(&["--mdx", "--to", "markdown", input.to_str().unwrap()], "");
+    assert_eq!(code, 1, "should exit with `1` if formatting a file fails");
+    assert!(
+        stderr.contains("f.mdx:"),
+        "should report errors when formatting"
+    );
+    assert_eq!(
+        fs::read_to_string(input.join("f.mdx")).unwrap(),
+        "<f {",
+        "should keep the contents of files that fail to format"
+    );
+    assert_eq!(
+        fs::read_to_string(input.join("a.md")).unwrap(),
+        "# a\n",
+        "should still format other files"
+    );
+    assert!(
+        fs::read_dir(&input).unwrap().all(|entry| !entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .ends_with(".tmp")),
+        "should not leave temporary files"
+    );
+
+    let html = output.join("g.html");
+    fs::write(&html, "<p>g</p>").unwrap();
+
+    let (code, _, _) = run(&["--mdx", "-o", html.to_str().unwrap()], "<g {");
+    assert_eq!(
+        code, 1,
+        "should exit with `1` if converting to a file fails"
+    );
+    assert_eq!(
+        fs::read_to_string(&html).unwrap(),
+        "<p>g</p>",
+        "should keep an earlier output file if converting fails"
+    );
+
+    fs::remove_dir_all(&root).unwrap();
+}
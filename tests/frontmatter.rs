@@ -1,8 +1,8 @@
 use markdown::{
-    mdast::{Node, Root, Toml, Yaml},
-    to_html, to_html_with_options, to_mdast,
+    mdast::{BlockQuote, Frontmatter, Heading, Json, Node, Root, Text, Toml, Yaml},
+    to_frontmatter, to_html, to_html_with_options, to_mdast,
     unist::Position,
-    Constructs, Options, ParseOptions,
+    Constructs, FrontmatterValue, Matter, Options, ParseOptions, PositionEncoding,
 };
 use pretty_assertions::assert_eq;
 
@@ -14,6 +14,8 @@ fn frontmatter() -> Result<(), String> {
                 frontmatter: true,
                 ..Default::default()
             },
+            frontmatter_matters: [ParseOptions::default().frontmatter_matters, Matter::json()]
+                .concat(),
             ..Default::default()
         },
         ..Default::default()
@@ -151,5 +153,389 @@ fn frontmatter() -> Result<(), String> {
         "should support toml as `Toml`s in mdast"
     );
 
+    assert_eq!(
+        to_html_with_options(
+            ";;;\n{\"title\": \"Jupyter\"}\n;;;\n\n{\n}",
+            &Options {
+                parse: ParseOptions {
+                    constructs: Constructs {
+                        frontmatter: true,
+                        ..Default::default()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        )?,
+        "<p>;;;\n{&quot;title&quot;: &quot;Jupyter&quot;}\n;;;</p>\n<p>{\n}</p>",
+        "should not support json frontmatter by default"
+    );
+
+    assert_eq!(
+        to_html_with_options(";;;\n{\"title\": \"Jupyter\"}\n;;;", &frontmatter)?,
+        "",
+        "should support frontmatter (json, semicolons)"
+    );
+
+    assert_eq!(
+        to_html_with_options("{\n\"title\": \"Jupyter\"\n}", &frontmatter)?,
+        "",
+        "should support frontmatter (json, braces)"
+    );
+
+    assert_eq!(
+        to_html_with_options("{\n\"title\": \"Jupyter\"\n{", &frontmatter)?,
+        "<p>{\n&quot;title&quot;: &quot;Jupyter&quot;\n{</p>",
+        "should not support an opening brace as a closing fence"
+    );
+
+    assert_eq!(
+        to_html_with_options("{{\n}}", &frontmatter)?,
+        "<p>{{\n}}</p>",
+        "should not support 2 braces in a fence"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "{\n}",
+            &Options {
+                parse: ParseOptions {
+                    constructs: Constructs {
+                        frontmatter: true,
+                        ..Constructs::mdx()
+                    },
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        )?,
+        "",
+        "should not support braces as fences in MDX (where it is an expression)"
+    );
+
+    assert_eq!(
+        to_mdast(";;;\n{\"a\": \"b\"}\n;;;", &frontmatter.parse)?,
+        Node::Root(Root {
+            children: vec![Node::Json(Json {
+                value: "{\"a\": \"b\"}".into(),
+                position: Some(Position::new(1, 1, 0, 3, 4, 18))
+            })],
+            position: Some(Position::new(1, 1, 0, 3, 4, 18))
+        }),
+        "should support json as `Json`s in mdast"
+    );
+
+    assert_eq!(
+        to_mdast("{\n  \"a\": \"b\"\n} \t", &frontmatter.parse)?,
+        Node::Root(Root {
+            children: vec![Node::Json(Json {
+                value: "{\n  \"a\": \"b\"\n}".into(),
+                position: Some(Position::new(1, 1, 0, 3, 5, 16))
+            })],
+            position: Some(Position::new(1, 1, 0, 3, 5, 16))
+        }),
+        "should include braces in the value of `Json`s"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn frontmatter_matters() -> Result<(), String> {
+    let constructs = Constructs {
+        frontmatter: true,
+        ..Default::default()
+    };
+    let hugo = Options {
+        parse: ParseOptions {
+            constructs: constructs.clone(),
+            frontmatter_matters: vec![Matter::new("toml", "+++")],
+            ..Default::default()
+        },
+        ..Default::default()
+    };
+    let meta = ParseOptions {
+        constructs,
+        frontmatter_matters: vec![Matter {
+            kind: "meta".into(),
+            open: "~~~meta".into(),
+            close: "~~~".into(),
+            anywhere: true,
+        }],
+        ..Default::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("+++\ntitle = \"Jupyter\"\n+++", &hugo)?,
+        "",
+        "should support custom matters"
+    );
+
+    assert_eq!(
+        to_html_with_options("---\ntitle: Jupyter\n---", &hugo)?,
+        "<hr />\n<h2>title: Jupyter</h2>",
+        "should not support matters that are not configured"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "---\n---",
+            &Options {
+                parse: ParseOptions {
+                    constructs: Constructs {
+                        frontmatter: true,
+                        ..Default::default()
+                    },
+                    frontmatter_matters: vec![Matter::new("yaml", "--")],
+                    ..Default::default()
+                },
+                ..Default::default()
+            }
+        )?,
+        "<hr />\n<hr />",
+        "should not support longer fences"
+    );
+
+    assert_eq!(
+        to_mdast("~~~meta\na: b\n~~~", &meta)?,
+        Node::Root(Root {
+            children: vec![Node::Frontmatter(Frontmatter {
+                value: "a: b".into(),
+                position: Some(Position::new(1, 1, 0, 3, 4, 16)),
+                kind: "meta".into()
+            })],
+            position: Some(Position::new(1, 1, 0, 3, 4, 16))
+        }),
+        "should support other kinds as `Frontmatter`s in mdast"
+    );
+
+    assert_eq!(
+        to_mdast("# a\n\n~~~meta\nb\n~~~\n> ~~~meta\n> c\n> ~~~", &meta)?,
+        Node::Root(Root {
+            children: vec![
+                Node::Heading(Heading {
+                    children: vec![Node::Text(Text {
+                        value: "a".into(),
+                        position: Some(Position::new(1, 3, 2, 1, 4, 3))
+                    })],
+                    position: Some(Position::new(1, 1, 0, 1, 4, 3)),
+                    depth: 1
+                }),
+                Node::Frontmatter(Frontmatter {
+                    value: "b".into(),
+                    position: Some(Position::new(3, 1, 5, 5, 4, 18)),
+                    kind: "meta".into()
+                }),
+                Node::BlockQuote(BlockQuote {
+                    children: vec![Node::Frontmatter(Frontmatter {
+                        value: "c".into(),
+                        position: Some(Position::new(6, 3, 21, 8, 6, 38)),
+                        kind: "meta".into()
+                    })],
+                    position: Some(Position::new(6, 1, 19, 8, 6, 38))
+                })
+            ],
+            position: Some(Position::new(1, 1, 0, 8, 6, 38))
+        }),
+        "should support matters anywhere, including in containers"
+    );
+
+    let meta = Options {
+        parse: meta,
+        ..Default::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("a\n~~~meta\nb\n~~~", &meta)?,
+        "<p>a</p>\n<pre><code class=\"language-meta\">b\n</code></pre>",
+        "should not support matters anywhere interrupting a paragraph (but code can)"
+    );
+
+    assert_eq!(
+        to_html_with_options("* a\n\n  ~~~meta\n  x\n ~~~\n", &meta)?,
+        to_html("* a\n\n  ~~~meta\n  x\n ~~~\n"),
+        "should parse matters anywhere that are not closed in their list item like without them"
+    );
+
+    assert_eq!(
+        to_html_with_options("> ~~~meta\n> x\n~~~\n", &meta)?,
+        to_html("> ~~~meta\n> x\n~~~\n"),
+        "should parse matters anywhere that are not closed in their block quote like without them"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn frontmatter_to_data() -> Result<(), String> {
+    let options = ParseOptions {
+        constructs: Constructs {
+            frontmatter: true,
+            ..Default::default()
+        },
+        frontmatter_matters: [ParseOptions::default().frontmatter_matters, Matter::json()].concat(),
+        ..Default::default()
+    };
+
+    assert_eq!(
+        to_frontmatter("# Venus", &options)?,
+        None,
+        "should support no frontmatter"
+    );
+
+    assert_eq!(
+        to_frontmatter(";;;\n;;;", &options)?,
+        Some(FrontmatterValue::Null),
+        "should support empty frontmatter"
+    );
+
+    assert_eq!(
+        to_frontmatter(
+            ";;;\n{\"a\": [1, -2.5e1, true, null, \"\\u0062\\n\\ud83d\\ude00\"], \"c\": {}}\n;;;",
+            &options
+        )?,
+        Some(FrontmatterValue::Object(vec![
+            (
+                "a".into(),
+                FrontmatterValue::Array(vec![
+                    FrontmatterValue::Integer(1),
+                    FrontmatterValue::Float(-25.0),
+                    FrontmatterValue::Bool(true),
+                    FrontmatterValue::Null,
+                    FrontmatterValue::String("b\n😀".into())
+                ])
+            ),
+            ("c".into(), FrontmatterValue::Object(vec![]))
+        ])),
+        "should support json"
+    );
+
+    assert_eq!(
+        to_frontmatter("{\n  \"b\": 1,\n  \"a\": 2\n}", &options)?,
+        Some(FrontmatterValue::Object(vec![
+            ("b".into(), FrontmatterValue::Integer(1)),
+            ("a".into(), FrontmatterValue::Integer(2))
+        ])),
+        "should support json in braces, and keep keys in order"
+    );
+
+    assert_eq!(
+        to_frontmatter(";;;\n\"\\ud800x \\udc00 \\ud800\\u0061\"\n;;;", &options)?,
+        Some(FrontmatterValue::String(
+            "\u{FFFD}x \u{FFFD} \u{FFFD}a".into()
+        )),
+        "should turn unpaired surrogates in json into replacement characters"
+    );
+
+    assert_eq!(
+        to_frontmatter("{\n  \"a\": 1,\n}", &options),
+        Err(
+            "3:1: Unexpected character `}` before key, expected a string (frontmatter-json:parse)"
+                .into()
+        ),
+        "should crash on invalid json, with a position in the document"
+    );
+
+    assert_eq!(
+        to_frontmatter(";;;\n[1, 2\n;;;", &options),
+        Err(
+            "2:6: Unexpected end of input after value in array, expected `,` or `]` (frontmatter-json:parse)"
+                .into()
+        ),
+        "should crash on json at the end"
+    );
+
+    assert_eq!(
+        to_frontmatter(";;;\n\"a\" \"b\"\n;;;", &options),
+        Err(
+            "2:5: Unexpected character `\"` after value, expected end of input (frontmatter-json:parse)"
+                .into()
+        ),
+        "should crash on json w/ multiple values"
+    );
+
+    assert_eq!(
+        to_frontmatter(
+            ";;;\n\"😀\" \"b\"\n;;;",
+            &ParseOptions {
+                constructs: Constructs {
+                    frontmatter: true,
+                    ..Default::default()
+                },
+                frontmatter_matters: Matter::json(),
+                position_encoding: PositionEncoding::Utf16,
+                ..Default::default()
+            }
+        ),
+        Err(
+            "2:6: Unexpected character `\"` after value, expected end of input (frontmatter-json:parse)"
+                .into()
+        ),
+        "should encode positions in errors (`position_encoding`)"
+    );
+
+    #[cfg(feature = "yaml")]
+    {
+        assert_eq!(
+            to_frontmatter("---\ntitle: Venus\ntags: [a, b]\ncount: 2\n---", &options)?,
+            Some(FrontmatterValue::Object(vec![
+                ("title".into(), FrontmatterValue::String("Venus".into())),
+                (
+                    "tags".into(),
+                    FrontmatterValue::Array(vec![
+                        FrontmatterValue::String("a".into()),
+                        FrontmatterValue::String("b".into())
+                    ])
+                ),
+                ("count".into(), FrontmatterValue::Integer(2))
+            ])),
+            "should support yaml"
+        );
+
+        assert_eq!(
+            to_frontmatter("---\na: b\nc: [d\n---", &options),
+            Err(
+                "3:6: while parsing a flow sequence, expected ',' or ']' (frontmatter-yaml:parse)"
+                    .into()
+            ),
+            "should crash on invalid yaml, with a position in the document"
+        );
+    }
+
+    #[cfg(not(feature = "yaml"))]
+    assert_eq!(
+        to_frontmatter("---\na: b\n---", &options),
+        Err("2:1: Cannot parse YAML frontmatter, expected the `yaml` feature to be enabled (frontmatter-yaml:parse)".into()),
+        "should crash on yaml w/o the `yaml` feature"
+    );
+
+    #[cfg(feature = "toml")]
+    {
+        assert_eq!(
+            to_frontmatter("+++\ntitle = \"Venus\"\n[a]\nb = 1.5\n+++", &options)?,
+            Some(FrontmatterValue::Object(vec![
+                ("title".into(), FrontmatterValue::String("Venus".into())),
+                (
+                    "a".into(),
+                    FrontmatterValue::Object(vec![("b".into(), FrontmatterValue::Float(1.5))])
+                )
+            ])),
+            "should support toml"
+        );
+
+        assert_eq!(
+            to_frontmatter("+++\na = 1\nb = \n+++", &options),
+            Err("3:5: invalid string, expected `\"`, `'` (frontmatter-toml:parse)".into()),
+            "should crash on invalid toml, with a position in the document"
+        );
+    }
+
+    #[cfg(not(feature = "toml"))]
+    assert_eq!(
+        to_frontmatter("+++\na = 1\n+++", &options),
+        Err("2:1: Cannot parse TOML frontmatter, expected the `toml` feature to be enabled (frontmatter-toml:parse)".into()),
+        "should crash on toml w/o the `toml` feature"
+    );
+
     Ok(())
 }
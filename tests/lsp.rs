@@ -0,0 +1,297 @@
+#![cfg(feature = "lsp")]
+
+use markdown::{language_server, Constructs, ParseOptions};
+use pretty_assertions::assert_eq;
+use std::{
+    io::Write,
+    process::{Command, Stdio},
+};
+
+/// Frame `bodies` as messages.
+fn frame(bodies: &[&str]) -> String {
+    bodies
+        .iter()
+        .map(|body| format!("Content-Length: {}\r\n\r\n{}", body.len(), body))
+        .collect()
+}
+
+/// Run the server with `bodies` as messages, returning the bodies of the
+/// messages it writes.
+fn run(bodies: &[&str], options: &ParseOptions) -> Vec<String> {
+    let input = frame(bodies);
+    let mut output = vec![];
+    language_server(&mut input.as_bytes(), &mut output, options).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    output
+        .split("Content-Length: ")
+        .skip(1)
+        .map(|message| {
+            let (length, body) = message.split_once("\r\n\r\n").unwrap();
+            assert_eq!(
+                length.parse::<usize>().unwrap(),
+                body.len(),
+                "should write the length of the body"
+            );
+            body.to_string()
+        })
+        .collect()
+}
+
+/// Open a document at `file:///a.md` with `text`.
+fn open(text: &str) -> String {
+    format!(
+        "{{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/didOpen\",\"params\":{{\"textDocument\":{{\"uri\":\"file:///a.md\",\"languageId\":\"markdown\",\"version\":1,\"text\":\"{}\"}}}}}}",
+        text
+    )
+}
+
+/// Make a request for `method` on `file:///a.md` at `line` and `character`.
+fn request(method: &str, line: usize, character: usize) -> String {
+    format!(
+        "{{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"{}\",\"params\":{{\"textDocument\":{{\"uri\":\"file:///a.md\"}},\"position\":{{\"line\":{},\"character\":{}}}}}}}",
+        method, line, character
+    )
+}
+
+#[test]
+fn lsp() {
+    let gfm = ParseOptions::gfm();
+    let no_diagnostics = "{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{\"uri\":\"file:///a.md\",\"diagnostics\":[]}}";
+
+    assert_eq!(
+        run(&["{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"initialize\",\"params\":{}}"], &gfm),
+        vec!["{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":{\"capabilities\":{\"textDocumentSync\":1,\"definitionProvider\":true,\"hoverProvider\":true,\"documentSymbolProvider\":true,\"completionProvider\":{\"triggerCharacters\":[\"[\"]}},\"serverInfo\":{\"name\":\"markdown-rs\",\"version\":\"1.0.0-alpha.10\"}}}"],
+        "should support `initialize`"
+    );
+
+    assert_eq!(
+        run(
+            &[
+                &open("[a] [b][]\\n\\n> [B]: c"),
+                &request("textDocument/definition", 0, 6)
+            ],
+            &gfm
+        ),
+        vec![
+            no_diagnostics,
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"uri\":\"file:///a.md\",\"range\":{\"start\":{\"line\":2,\"character\":2},\"end\":{\"line\":2,\"character\":8}}}}"
+        ],
+        "should support `textDocument/definition` for link references"
+    );
+
+    assert_eq!(
+        run(
+            &[
+                &open("😀 [^a]\\n\\n[^a]: b"),
+                &request("textDocument/definition", 0, 4)
+            ],
+            &gfm
+        ),
+        vec![
+            no_diagnostics,
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"uri\":\"file:///a.md\",\"range\":{\"start\":{\"line\":2,\"character\":0},\"end\":{\"line\":2,\"character\":7}}}}"
+        ],
+        "should support `textDocument/definition` for footnote references, with UTF-16 characters"
+    );
+
+    assert_eq!(
+        run(
+            &[
+                &open("[a] [b][]\\n\\n[b]: c"),
+                &request("textDocument/definition", 0, 1)
+            ],
+            &gfm
+        ),
+        vec![
+            no_diagnostics,
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":null}"
+        ],
+        "should support `textDocument/definition` when there is no reference"
+    );
+
+    assert_eq!(
+        run(
+            &[
+                &open("a[^b]\\n\\n[^b]: *c*\\n    d."),
+                &request("textDocument/hover", 0, 2)
+            ],
+            &gfm
+        ),
+        vec![
+            no_diagnostics,
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"contents\":{\"kind\":\"markdown\",\"value\":\"_c_\\nd.\"},\"range\":{\"start\":{\"line\":0,\"character\":1},\"end\":{\"line\":0,\"character\":5}}}}"
+        ],
+        "should support `textDocument/hover` for footnote references"
+    );
+
+    assert_eq!(
+        run(
+            &[
+                &open("# a\\n\\n## b\\n\\n# c"),
+                &request("textDocument/documentSymbol", 0, 0)
+            ],
+            &gfm
+        ),
+        vec![
+            no_diagnostics,
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":[{\"name\":\"a\",\"kind\":15,\"range\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":4,\"character\":0}},\"selectionRange\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":0,\"character\":3}},\"children\":[{\"name\":\"b\",\"kind\":15,\"range\":{\"start\":{\"line\":2,\"character\":0},\"end\":{\"line\":4,\"character\":0}},\"selectionRange\":{\"start\":{\"line\":2,\"character\":0},\"end\":{\"line\":2,\"character\":4}},\"children\":[]}]},{\"name\":\"c\",\"kind\":15,\"range\":{\"start\":{\"line\":4,\"character\":0},\"end\":{\"line\":4,\"character\":3}},\"selectionRange\":{\"start\":{\"line\":4,\"character\":0},\"end\":{\"line\":4,\"character\":3}},\"children\":[]}]}"
+        ],
+        "should support `textDocument/documentSymbol` for headings, nested in sections"
+    );
+
+    assert_eq!(
+        run(
+            &[
+                &open("[Alpha]: a\\n[alpha]: b\\n[^Bravo]: c"),
+                &request("textDocument/completion", 0, 0)
+            ],
+            &gfm
+        ),
+        vec![
+            no_diagnostics,
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":[{\"label\":\"Alpha\",\"kind\":18,\"detail\":\"a\"},{\"label\":\"^Bravo\",\"kind\":18}]}"
+        ],
+        "should support `textDocument/completion` for definition labels"
+    );
+
+    assert_eq!(
+        run(
+            &[&open("a {b")],
+            &ParseOptions {
+                constructs: Constructs::mdx(),
+                ..ParseOptions::default()
+            }
+        ),
+        vec![
+            "{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{\"uri\":\"file:///a.md\",\"diagnostics\":[{\"range\":{\"start\":{\"line\":0,\"character\":4},\"end\":{\"line\":0,\"character\":4}},\"severity\":1,\"source\":\"markdown-rs\",\"message\":\"Unexpected end of file in expression, expected a corresponding closing brace for `{`\"}]}}"
+        ],
+        "should publish diagnostics for syntax errors in MDX"
+    );
+
+    assert_eq!(
+        run(
+            &[&open("abc")],
+            &ParseOptions {
+                size_max: Some(2),
+                ..ParseOptions::default()
+            }
+        ),
+        vec![
+            "{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{\"uri\":\"file:///a.md\",\"diagnostics\":[{\"range\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":0,\"character\":0}},\"severity\":1,\"source\":\"markdown-rs\",\"message\":\"Unexpected document of 3 bytes, expected at most 2 bytes\",\"code\":\"size-max\"}]}}"
+        ],
+        "should publish diagnostics with a source and code"
+    );
+
+    assert_eq!(
+        run(
+            &[
+                &open("# a"),
+                "{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/didChange\",\"params\":{\"textDocument\":{\"uri\":\"file:///a.md\",\"version\":2},\"contentChanges\":[{\"text\":\"# b\"}]}}",
+                &request("textDocument/documentSymbol", 0, 0),
+                "{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/didClose\",\"params\":{\"textDocument\":{\"uri\":\"file:///a.md\"}}}",
+                &request("textDocument/documentSymbol", 0, 0),
+            ],
+            &gfm
+        ),
+        vec![
+            no_diagnostics,
+            no_diagnostics,
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":[{\"name\":\"b\",\"kind\":15,\"range\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":0,\"character\":3}},\"selectionRange\":{\"start\":{\"line\":0,\"character\":0},\"end\":{\"line\":0,\"character\":3}},\"children\":[]}]}",
+            no_diagnostics,
+            "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":null}"
+        ],
+        "should support `textDocument/didChange` and `textDocument/didClose`"
+    );
+
+    assert_eq!(
+        run(
+            &[
+                "{\"jsonrpc\":\"2.0\",\"id\":\"x\",\"method\":\"a\"}",
+                "{\"jsonrpc\":\"2.0\",\"id\":3,\"method\":\"textDocument/hover\",\"params\":{}}",
+                "{",
+            ],
+            &gfm
+        ),
+        vec![
+            "{\"jsonrpc\":\"2.0\",\"id\":\"x\",\"error\":{\"code\":-32601,\"message\":\"Cannot handle unknown method `a`\"}}",
+            "{\"jsonrpc\":\"2.0\",\"id\":3,\"error\":{\"code\":-32602,\"message\":\"Expected `params.textDocument.uri`\"}}",
+            "{\"jsonrpc\":\"2.0\",\"id\":null,\"error\":{\"code\":-32700,\"message\":\"1:2: Unexpected end of input before key, expected a string\"}}"
+        ],
+        "should respond with errors"
+    );
+
+    assert_eq!(
+        run(
+            &[
+                "{\"jsonrpc\":\"2.0\",\"id\":1,\"method\":\"shutdown\"}",
+                "{\"jsonrpc\":\"2.0\",\"method\":\"exit\"}",
+                "{\"jsonrpc\":\"2.0\",\"id\":2,\"method\":\"shutdown\"}",
+            ],
+            &gfm
+        ),
+        vec!["{\"jsonrpc\":\"2.0\",\"id\":1,\"result\":null}"],
+        "should support `shutdown` and `exit`"
+    );
+
+    assert_eq!(
+        language_server(
+            &mut "Content-Length: 67108865\r\n\r\n{}".as_bytes(),
+            &mut vec![],
+            &gfm
+        )
+        .unwrap_err()
+        .to_string(),
+        "`Content-Length` of 67108865 is larger than 67108864 bytes",
+        "should crash on messages that are too large"
+    );
+
+    assert_eq!(
+        language_server(&mut "a".repeat(5000).as_bytes(), &mut vec![], &gfm)
+            .unwrap_err()
+            .to_string(),
+        "Header is longer than 4096 bytes",
+        "should crash on headers that are too long"
+    );
+}
+
+#[test]
+fn lsp_binary() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_markdown-lsp"))
+        .arg("--gfm")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            frame(&[
+                &open("a[^b]\\n\\n[^b]: c"),
+                &request("textDocument/hover", 0, 2),
+                "{\"jsonrpc\":\"2.0\",\"method\":\"exit\"}",
+            ])
+            .as_bytes(),
+        )
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    assert_eq!(
+        (
+            output.status.code().unwrap(),
+            String::from_utf8(output.stdout).unwrap(),
+        ),
+        (
+            0,
+            frame(&[
+                "{\"jsonrpc\":\"2.0\",\"method\":\"textDocument/publishDiagnostics\",\"params\":{\"uri\":\"file:///a.md\",\"diagnostics\":[]}}",
+                "{\"jsonrpc\":\"2.0\",\"id\":2,\"result\":{\"contents\":{\"kind\":\"markdown\",\"value\":\"c\"},\"range\":{\"start\":{\"line\":0,\"character\":1},\"end\":{\"line\":0,\"character\":5}}}}"
+            ])
+        ),
+        "should run over stdio"
+    );
+}
@@ -1,4 +1,7 @@
-use markdown::to_html;
+use markdown::{
+    mdast::Node, sanitize_mdast, to_html, to_html_with_options, to_mdast, CompileOptions,
+    Constructs, Options, ParseOptions,
+};
 use pretty_assertions::assert_eq;
 
 #[test]
@@ -195,3 +198,141 @@ fn dangerous_protocol_link() {
         "should allow a colon in a path"
     );
 }
+
+#[test]
+fn dangerous_protocol_allowlist() -> Result<(), String> {
+    let options = Options {
+        parse: ParseOptions {
+            constructs: Constructs::gfm(),
+            ..ParseOptions::default()
+        },
+        compile: CompileOptions {
+            protocol_href: Some(vec!["https".into(), "tel".into(), "app".into()]),
+            protocol_src: Some(vec!["https".into(), "data:image/png".into()]),
+            ..CompileOptions::default()
+        },
+    };
+
+    assert_eq!(
+        to_html_with_options("[a](tel:123) [b](app://c)", &options)?,
+        "<p><a href=\"tel:123\">a</a> <a href=\"app://c\">b</a></p>",
+        "should support `protocol_href` in links"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a](TEL:123)", &options)?,
+        "<p><a href=\"TEL:123\">a</a></p>",
+        "should compare protocols case-insensitively"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a](mailto:b@c.d)", &options)?,
+        "<p><a href=\"\">a</a></p>",
+        "should replace the default protocols with `protocol_href`"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a](./b) [c](#d)", &options)?,
+        "<p><a href=\"./b\">a</a> <a href=\"#d\">c</a></p>",
+        "should always allow relative URLs"
+    );
+
+    assert_eq!(
+        to_html_with_options("<tel:123> <sms:456>", &options)?,
+        "<p><a href=\"tel:123\">tel:123</a> <a href=\"\">sms:456</a></p>",
+        "should support `protocol_href` in autolinks"
+    );
+
+    assert_eq!(
+        to_html_with_options("a https://b.c and www.d.e", &options)?,
+        "<p>a <a href=\"https://b.c\">https://b.c</a> and <a href=\"\">www.d.e</a></p>",
+        "should support `protocol_href` in GFM autolink literals"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a]\n\n[a]: tel:123", &options)?,
+        "<p><a href=\"tel:123\">a</a></p>\n",
+        "should support `protocol_href` in definitions"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "![a](data:image/png;base64,b) ![c](data:image/svg+xml;base64,d) ![e](tel:123)",
+            &options
+        )?,
+        "<p><img src=\"data:image/png;base64,b\" alt=\"a\" /> <img src=\"\" alt=\"c\" /> <img src=\"\" alt=\"e\" /></p>",
+        "should support `protocol_src` in images, w/ prefixes"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "[a](javascript:b) ![c](javascript:d)",
+            &Options {
+                compile: CompileOptions {
+                    allow_dangerous_protocol: true,
+                    ..options.compile.clone()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<p><a href=\"javascript:b\">a</a> <img src=\"javascript:d\" alt=\"c\" /></p>",
+        "should allow everything w/ `allow_dangerous_protocol`"
+    );
+
+    let mut tree = to_mdast(
+        "[a](javascript:b) [c](tel:1) ![d](data:image/png;e) ![f](http://g)\n\n[h]: sms:2",
+        &ParseOptions::default(),
+    )?;
+    sanitize_mdast(&mut tree, &options.compile);
+    let mut urls = vec![];
+    collect_urls(&tree, &mut urls);
+
+    assert_eq!(
+        urls,
+        vec!["", "tel:1", "data:image/png;e", "", ""],
+        "should support `sanitize_mdast`"
+    );
+
+    let mut tree = to_mdast("[a](javascript:b)", &ParseOptions::default())?;
+    sanitize_mdast(&mut tree, &CompileOptions::default());
+    let mut urls = vec![];
+    collect_urls(&tree, &mut urls);
+
+    assert_eq!(
+        urls,
+        vec![""],
+        "should support `sanitize_mdast` w/ the default protocols"
+    );
+
+    let mut tree = to_mdast(
+        "![a][b] [c][d] ![e][f] [e][f]\n\n[b]: data:image/png;g\n[d]: data:image/png;h\n[f]: data:image/png;i\n[j]: tel:3",
+        &ParseOptions::default(),
+    )?;
+    sanitize_mdast(&mut tree, &options.compile);
+    let mut urls = vec![];
+    collect_urls(&tree, &mut urls);
+
+    assert_eq!(
+        urls,
+        vec!["data:image/png;g", "", "", "tel:3"],
+        "should check definitions against the protocols for how they are used in `sanitize_mdast`"
+    );
+
+    Ok(())
+}
+
+/// Collect URLs of links, images, and definitions in `node`.
+fn collect_urls(node: &Node, urls: &mut Vec<String>) {
+    match node {
+        Node::Link(d) => urls.push(d.url.clone()),
+        Node::Image(d) => urls.push(d.url.clone()),
+        Node::Definition(d) => urls.push(d.url.clone()),
+        _ => {}
+    }
+
+    if let Some(children) = node.children() {
+        for child in children {
+            collect_urls(child, urls);
+        }
+    }
+}
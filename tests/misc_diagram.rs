@@ -0,0 +1,86 @@
+use markdown::{to_html_with_options, CompileOptions, Diagram, Options};
+use pretty_assertions::assert_eq;
+use std::sync::Arc;
+
+#[test]
+fn diagram() -> Result<(), String> {
+    let options = Options {
+        compile: CompileOptions {
+            diagrams: Diagram::defaults(),
+            ..CompileOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("```mermaid\na --> b\n```", &options)?,
+        "<pre class=\"mermaid\">a --&gt; b\n</pre>",
+        "should turn mermaid into `pre.mermaid`"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "```dot\na -> b\n```\n\n```PlantUML title\nc\n```",
+            &options
+        )?,
+        "<div class=\"diagram language-dot\">a -&gt; b\n</div>\n<div class=\"diagram language-plantuml\">c\n</div>",
+        "should turn other diagrams into `div.diagram` (case-insensitive, w/ meta)"
+    );
+
+    assert_eq!(
+        to_html_with_options("```js\na\n```\n\n    mermaid", &options)?,
+        "<pre><code class=\"language-js\">a\n</code></pre>\n<pre><code>mermaid\n</code></pre>",
+        "should not turn other code into diagrams"
+    );
+
+    assert_eq!(
+        to_html_with_options("```mermaid\na\n```", &Options::default())?,
+        "<pre><code class=\"language-mermaid\">a\n</code></pre>",
+        "should not recognize diagrams by default"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "> ```mermaid\n> a\n\nb",
+            &Options {
+                compile: CompileOptions {
+                    diagrams: vec![Diagram {
+                        lang: "mermaid".into(),
+                        tag_name: "x-diagram".into(),
+                        class_name: "a\"b".into(),
+                    }],
+                    sourcepos: true,
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<blockquote data-sourcepos=\"1:1-2:3\">\n<x-diagram class=\"a&quot;b\" data-sourcepos=\"1:3-2:3\">a\n</x-diagram>\n</blockquote>\n<p data-sourcepos=\"4:1-4:1\">b</p>",
+        "should support custom diagrams, w/ `sourcepos`"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "```mermaid\na & b\n```\n\n```dot\nc\n```",
+            &Options {
+                compile: CompileOptions {
+                    diagrams: Diagram::defaults(),
+                    render_diagram: Some(Arc::new(|lang, code| {
+                        if lang == "mermaid" {
+                            Some(format!("<svg>{}</svg>", code.len()))
+                        } else {
+                            None
+                        }
+                    })),
+                    highlight: Some(Arc::new(|_, _, _| Some("x".into()))),
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<svg>6</svg>\n<div class=\"diagram language-dot\">c\n</div>",
+        "should use the HTML from `render_diagram`, and not highlight diagrams"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,382 @@
+use markdown::{
+    format,
+    mdast::{Node, Root, Text},
+    to_markdown, to_mdast, AttentionMarker, BulletMarker, Constructs, FenceMarker, FormatOptions,
+    HeadingStyle, ListNumbering, ParseOptions,
+};
+use pretty_assertions::assert_eq;
+
+/// Remove positions (and MDX stops, which are positions too).
+fn clean(node: &mut Node) {
+    node.position_set(None);
+
+    match node {
+        Node::MdxFlowExpression(node) => node.stops = vec![],
+        Node::MdxTextExpression(node) => node.stops = vec![],
+        Node::MdxjsEsm(node) => node.stops = vec![],
+        _ => {}
+    }
+
+    if let Some(children) = node.children_mut() {
+        for child in children {
+            clean(child);
+        }
+    }
+}
+
+/// Check that formatting `value` is idempotent and keeps the tree.
+fn check(value: &str, options: &FormatOptions) -> Result<(), String> {
+    let result = format(value, options)?;
+    let mut expected = to_mdast(value, &options.parse)?;
+    let mut actual = to_mdast(&result, &options.parse)?;
+    clean(&mut expected);
+    clean(&mut actual);
+    assert_eq!(actual, expected, "should keep the tree of {:?}", value);
+    assert_eq!(
+        format(&result, options)?,
+        result,
+        "should be idempotent for {:?}",
+        value
+    );
+    Ok(())
+}
+
+#[test]
+fn misc_format() -> Result<(), String> {
+    let gfm = FormatOptions {
+        parse: ParseOptions::gfm(),
+        ..FormatOptions::default()
+    };
+
+    assert_eq!(
+        format("", &FormatOptions::default())?,
+        "",
+        "should support an empty document"
+    );
+
+    assert_eq!(
+        format(
+            "Alpha\n=====\n\nBravo\n---\n\n### Charlie ###",
+            &FormatOptions::default()
+        )?,
+        "# Alpha\n\n## Bravo\n\n### Charlie\n",
+        "should use ATX headings by default"
+    );
+
+    assert_eq!(
+        format("* a\n* b\n\n+ c\n\n3) d\n3) e", &FormatOptions::default())?,
+        "- a\n- b\n\n* c\n\n3. d\n4. e\n",
+        "should normalize bullets and numbering (alternating markers for adjacent lists)"
+    );
+
+    assert_eq!(
+        format("*a* __b__ _c*d*e_", &FormatOptions::default())?,
+        "_a_ **b** _c*d*e_\n",
+        "should normalize emphasis and strong (using `*` in `_`)"
+    );
+
+    assert_eq!(
+        format("a*b*c", &FormatOptions::default())?,
+        "a*b*c\n",
+        "should not use `_` inside words"
+    );
+
+    assert_eq!(
+        format(
+            "~~~js\nconsole.log(1)\n~~~\n\n    indented",
+            &FormatOptions::default()
+        )?,
+        "```js\nconsole.log(1)\n```\n\n```\nindented\n```\n",
+        "should always use fenced code"
+    );
+
+    assert_eq!(
+        format("````\n```\n````\n\n~~~a`b\n~~~", &FormatOptions::default())?,
+        "````\n```\n````\n\n~~~a`b\n~~~\n",
+        "should use longer fences or tildes when needed"
+    );
+
+    assert_eq!(
+        format("| a | b | c |\n|:-|:-:|-:|\n| long | x | y |", &gfm)?,
+        "| a    |  b  |   c |\n| :--- | :-: | --: |\n| long |  x  |   y |\n",
+        "should align table columns"
+    );
+
+    assert_eq!(
+        format("- [x] a\n- [ ] b\n\n~one~ www.example.com", &gfm)?,
+        "- [x] a\n- [ ] b\n\n~~one~~ www.example.com\n",
+        "should support other GFM constructs"
+    );
+
+    assert_eq!(
+        format("* a\n\n  b\n* c\n\n1. d\n\n1. e", &FormatOptions::default())?,
+        "- a\n\n  b\n- c\n\n1. d\n\n2. e\n",
+        "should keep whether lists and items are spread"
+    );
+
+    assert_eq!(
+        format("> a\n> # b\n>\n> c", &FormatOptions::default())?,
+        "> a\n>\n> # b\n>\n> c\n",
+        "should support block quotes"
+    );
+
+    assert_eq!(
+        format(
+            "[a]: <b c> 'd'\n[E]: f\n\n[a] [e][] [x][E] ![g](h (i))",
+            &FormatOptions::default()
+        )?,
+        "[a]: <b c> \"d\"\n[E]: f\n\n[a] [e][] [x][E] ![g](h \"i\")\n",
+        "should support definitions, references, and resources"
+    );
+
+    assert_eq!(
+        format("<https://a.com> [b](b) <c@d.e>", &FormatOptions::default())?,
+        "<https://a.com> [b](b) <c@d.e>\n",
+        "should support autolinks"
+    );
+
+    assert_eq!(
+        format(
+            "\\* a \\_b\\_ \\# c\n\\# d\n\\- e\n1\\. f\n&amp; &copy;",
+            &FormatOptions::default()
+        )?,
+        "\\* a \\_b\\_ # c\n\\# d\n\\- e\n1\\. f\n& ©\n",
+        "should escape what needs escaping (only)"
+    );
+
+    assert_eq!(
+        format("&#32;a&#9;\n\n&#10;b", &FormatOptions::default())?,
+        "&#x20;a&#x9;\n\n&#xA;b\n",
+        "should encode whitespace that would be removed"
+    );
+
+    assert_eq!(
+        format("``a ` b`` `c`", &FormatOptions::default())?,
+        "``a ` b`` `c`\n",
+        "should support code (text)"
+    );
+
+    assert_eq!(
+        format("a\\\nb  \nc", &FormatOptions::default())?,
+        "a\\\nb\\\nc\n",
+        "should use escapes for hard breaks"
+    );
+
+    assert_eq!(
+        format(
+            "<!-- *a* -->\n\n<div>\n*b*\n\n</div>\n\nc <span>*d*</span>",
+            &FormatOptions::default()
+        )?,
+        "<!-- *a* -->\n\n<div>\n*b*\n\n</div>\n\nc <span>*d*</span>\n",
+        "should keep HTML (and comments) verbatim"
+    );
+
+    assert_eq!(
+        format(
+            "---\ntitle:   a\n---\n\n# b",
+            &FormatOptions {
+                parse: ParseOptions {
+                    constructs: Constructs {
+                        frontmatter: true,
+                        ..Constructs::default()
+                    },
+                    ..ParseOptions::default()
+                },
+                ..FormatOptions::default()
+            }
+        )?,
+        "---\ntitle:   a\n---\n\n# b\n",
+        "should keep frontmatter verbatim"
+    );
+
+    assert_eq!(
+        format(
+            "import a from 'b'\n\n<A  b=\"c\" {...d}>\n  *e*\n</A>\n\n{ f }",
+            &FormatOptions {
+                parse: ParseOptions::mdx(),
+                ..FormatOptions::default()
+            }
+        )?,
+        "import a from 'b'\n\n<A  b=\"c\" {...d}>\n  _e_\n</A>\n\n{ f }\n",
+        "should keep MDX ESM and expressions verbatim"
+    );
+
+    assert_eq!(
+        format(
+            "<A   b={1}>\n*c*\n</A  >\n\nd <e f  =\"g\"\n  h>*i*</ e> <j/>",
+            &FormatOptions {
+                parse: ParseOptions::mdx(),
+                ..FormatOptions::default()
+            }
+        )?,
+        "<A   b={1}>\n  _c_\n</A  >\n\nd <e f  =\"g\"\n  h>*i*</ e> <j/>\n",
+        "should keep MDX JSX tags verbatim"
+    );
+
+    assert_eq!(
+        format("* a\r\n* b\r\n", &FormatOptions::default())?,
+        "- a\r\n- b\r\n",
+        "should use the first line ending"
+    );
+
+    let options = FormatOptions {
+        parse: ParseOptions::gfm(),
+        bullet: BulletMarker::Asterisk,
+        emphasis: AttentionMarker::Asterisk,
+        strong: AttentionMarker::Underscore,
+        heading: HeadingStyle::Setext,
+        fence: FenceMarker::Tilde,
+        table_align: false,
+        list_numbering: ListNumbering::Same,
+    };
+
+    assert_eq!(
+        format(
+            "# a\n\n### b\n\n- c\n- ***\n\n2. d\n3. e\n\n_f_ **g**\n\n```\nh\n```\n\n| i | j |\n|:-|-|\n| kkk | l |",
+            &options
+        )?,
+        "a\n===\n\n### b\n\n* c\n* ---\n\n2. d\n2. e\n\n*f* __g__\n\n~~~\nh\n~~~\n\n| i | j |\n| :- | - |\n| kkk | l |\n",
+        "should support other styles"
+    );
+
+    assert_eq!(
+        format(
+            "- a\n- b",
+            &FormatOptions {
+                bullet: BulletMarker::Plus,
+                ..FormatOptions::default()
+            }
+        )?,
+        "+ a\n+ b\n",
+        "should support `BulletMarker::Plus`"
+    );
+
+    assert_eq!(
+        to_markdown(
+            &Node::Root(Root {
+                children: vec![Node::Text(Text {
+                    value: "*a* <b> [c]".into(),
+                    position: None
+                })],
+                position: None
+            }),
+            &FormatOptions::default()
+        ),
+        "\\*a\\* \\<b> \\[c\\]\n",
+        "should support `to_markdown` with trees not from markdown"
+    );
+
+    assert_eq!(
+        format(
+            "{a",
+            &FormatOptions {
+                parse: ParseOptions::mdx(),
+                ..FormatOptions::default()
+            }
+        ),
+        Err("1:3: Unexpected end of file in expression, expected a corresponding closing brace for `{`".into()),
+        "should crash on MDX syntax errors"
+    );
+
+    let corpus = [
+        "# a #\n\n## b ##",
+        "a\nb\n===",
+        "# \\#\n\n# a #\\#",
+        "***\n\n- - -\n\n___",
+        "- a\n  - b\n    - c\n\n      d",
+        "1. a\n1. b\n\n10) c\n\n- d\n\n- e",
+        "- a\n\n\n- b\n- c",
+        "* ***\n* b",
+        "-\n  a\n-\n\n  b",
+        "> a\n>\n> > b\n> c",
+        "a\n\n    b\n\n```\n\n\nc\n```",
+        "*a **b** c* **a *b* c** *__a__* **_a_**",
+        "a _b_c d_e_ f*g*h _*i*_",
+        "*a*b* _a_b_ *(a)* ( *a* )",
+        "**a*b**c* *a**b*c**",
+        "a&b &amp; &#123; \\& \\\\ \\a \\`",
+        "<a> <a b=c> </a> <!-- b --> <?c?>",
+        "<div>\n\n*a*\n\n</div>",
+        "<pre>\na\n\nb",
+        "[a]\n\n[a]: b\n\n[\\[a\\]]\n\n[\\[a\\]]: c",
+        "[a](b) [c](<d e>) [f](g \"h\\\"i\") [j](k 'l') [m](\\(n\\))",
+        "![a *b*](c) ![d](e)",
+        "a  \nb\\\nc",
+        "`` ` `` ``` `` ``` `a`",
+        "1. a\n\n   1) b",
+        "a\n-\n\na\n=",
+        "- a\n+ b\n* c",
+        "# a\n\n    b\n\nc",
+        "\\[a](b)",
+        "a\n# b\n> c\n- d",
+        "a\n1. b\n\na\n2. c",
+        "- # a\n- > b\n- ```\n  c\n  ```",
+        "\t\ta\n\n \tb",
+        "a\t\n\tb",
+    ];
+
+    for value in &corpus {
+        check(value, &FormatOptions::default())?;
+        check(value, &options)?;
+    }
+
+    let gfm_corpus = [
+        "| a |\n| - |\n| b \\| c |\n| `d \\| e` |",
+        "| a | b |\n| - | - |\n| c |\n| d | e | f |",
+        "a | b\n-: | :-:\n",
+        "~a~ ~~b~~ ~~~c~~~",
+        "a@b.c www.d.e https://f.g http://h.i\n\n<a@b.c> <https://f.g>",
+        "www.a.com. (www.b.com) www.c.com/d)",
+        "a\\@b.c www\\.d.e https\\://f.g",
+        "[^a]\n\n[^a]: b\n\n    c",
+        "- [ ] a\n- [x] b\n\n* [ ]\n  c",
+        "[www.a.com](https://b.com)",
+    ];
+
+    for value in &gfm_corpus {
+        check(value, &gfm)?;
+    }
+
+    let math = FormatOptions {
+        parse: ParseOptions {
+            constructs: Constructs {
+                math_flow: true,
+                math_text: true,
+                frontmatter: true,
+                ..Constructs::gfm()
+            },
+            ..ParseOptions::gfm()
+        },
+        ..FormatOptions::default()
+    };
+
+    for value in &[
+        "$$a\nb\n$$\n\n$c$ $$d$$ $$$e $ f$$$",
+        "$$\n$$$\n$$\n\n\\$a$",
+        "+++\na = 1\n+++\n\n# b",
+        "---\n---\n\na",
+        "| a |\n| - |\n| $b \\| c$ |",
+    ] {
+        check(value, &math)?;
+    }
+
+    let mdx = FormatOptions {
+        parse: ParseOptions::mdx(),
+        ..FormatOptions::default()
+    };
+
+    for value in &[
+        "import a from 'b'\nexport const c = {d}\n\n# e",
+        "<A>\n  <B c=\"d &quot;e&quot;\" f='g' h={i} j />\n\n  - k\n</A>",
+        "<>\n  a\n</>\n\n<></>",
+        "a <b>c *d*</b> {e} \\{ f \\<g",
+        "{\n  a\n}\n\n<A\n  b\n/>",
+        "> <A>\n>   b\n> </A>",
+        "> <A\n>   b=\"c\n> d\"\n> />\n\n* <A\n    e  ={f}>\n  g\n  </A>",
+        "a <b\nc='d'>e</b>",
+    ] {
+        check(value, &mdx)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,184 @@
+use markdown::{to_html_with_options, CompileOptions, Constructs, Options, ParseOptions};
+use pretty_assertions::assert_eq;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn highlight() -> Result<(), String> {
+    let calls = Arc::new(Mutex::new(vec![]));
+    let calls_inner = calls.clone();
+    let options = Options {
+        compile: CompileOptions {
+            highlight: Some(Arc::new(move |lang, meta, code| {
+                calls_inner.lock().unwrap().push((
+                    lang.map(String::from),
+                    meta.map(String::from),
+                    code.to_string(),
+                ));
+
+                if lang == Some("x") {
+                    Some(format!("<b>{}</b>", code.len()))
+                } else {
+                    None
+                }
+            })),
+            ..CompileOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("```x &amp; {1}\na < \"b\" &amp;\n```", &options)?,
+        "<pre><code class=\"language-x\"><b>14</b></code></pre>",
+        "should use the HTML from `highlight`"
+    );
+
+    assert_eq!(
+        calls.lock().unwrap().pop(),
+        Some((
+            Some("x".into()),
+            Some("& {1}".into()),
+            "a < \"b\" &amp;\n".into()
+        )),
+        "should pass the language, meta, and code (decoded) to `highlight`"
+    );
+
+    assert_eq!(
+        to_html_with_options("```y\na < b\n```\n\n    c", &options)?,
+        "<pre><code class=\"language-y\">a &lt; b\n</code></pre>\n<pre><code>c\n</code></pre>",
+        "should encode code if `highlight` returns `None`"
+    );
+
+    assert_eq!(
+        calls.lock().unwrap().pop(),
+        Some((None, None, "c\n".into())),
+        "should pass indented code to `highlight`"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "$$\na\n$$",
+            &Options {
+                parse: ParseOptions {
+                    constructs: Constructs {
+                        math_flow: true,
+                        ..Constructs::default()
+                    },
+                    ..ParseOptions::default()
+                },
+                compile: options.compile.clone(),
+            }
+        )?,
+        "<pre><code class=\"language-math math-display\">a\n</code></pre>",
+        "should not highlight math (flow)"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn code_lines() -> Result<(), String> {
+    let options = Options {
+        compile: CompileOptions {
+            code_line_numbers: true,
+            code_highlight_lines: true,
+            ..CompileOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("```js title=\"a\" {1, 3-4,x}\nb\nc\nd\r\ne\n\nf\n```", &options)?,
+        "<pre><code class=\"language-js\"><span class=\"line highlighted\" data-line-number=\"1\">b</span>\n<span class=\"line\" data-line-number=\"2\">c</span>\n<span class=\"line highlighted\" data-line-number=\"3\">d</span>\r\n<span class=\"line highlighted\" data-line-number=\"4\">e</span>\n<span class=\"line\" data-line-number=\"5\"></span>\n<span class=\"line\" data-line-number=\"6\">f</span>\n</code></pre>",
+        "should number and highlight lines"
+    );
+
+    assert_eq!(
+        to_html_with_options("    a\n    b", &options)?,
+        "<pre><code><span class=\"line\" data-line-number=\"1\">a</span>\n<span class=\"line\" data-line-number=\"2\">b</span>\n</code></pre>",
+        "should number lines of indented code"
+    );
+
+    assert_eq!(
+        to_html_with_options("```\n```", &options)?,
+        "<pre><code></code></pre>",
+        "should not add lines to empty code"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "```js {1}\na\n```",
+            &Options {
+                compile: CompileOptions {
+                    code_line_numbers: true,
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<pre><code class=\"language-js\"><span class=\"line\" data-line-number=\"1\">a</span>\n</code></pre>",
+        "should not highlight lines w/o `code_highlight_lines`"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "```js {1}\na\n```",
+            &Options {
+                compile: CompileOptions {
+                    code_highlight_lines: true,
+                    highlight: Some(Arc::new(|_, _, code| Some(code.replace('a', "<i>a</i>")))),
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<pre><code class=\"language-js\"><span class=\"line highlighted\"><i>a</i></span>\n</code></pre>",
+        "should highlight lines of highlighted code"
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "highlight")]
+#[test]
+fn highlight_builtin() -> Result<(), String> {
+    let options = Options {
+        compile: CompileOptions {
+            highlight: Some(Arc::new(markdown::highlight)),
+            code_line_numbers: true,
+            ..CompileOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("```ts\nconst a = `b\nc` /* d\ne */ 1.5\n```", &options)?,
+        "<pre><code class=\"language-ts\"><span class=\"line\" data-line-number=\"1\"><span class=\"hl-keyword\">const</span> a = <span class=\"hl-string\">`b</span></span>\n<span class=\"line\" data-line-number=\"2\"><span class=\"hl-string\">c`</span> <span class=\"hl-comment\">/* d</span></span>\n<span class=\"line\" data-line-number=\"3\"><span class=\"hl-comment\">e */</span> <span class=\"hl-number\">1.5</span></span>\n</code></pre>",
+        "should highlight, w/o elements spanning lines"
+    );
+
+    assert_eq!(
+        to_html_with_options("```sh\n# a\necho \"b & c\" x2\n```", &options)?,
+        "<pre><code class=\"language-sh\"><span class=\"line\" data-line-number=\"1\"><span class=\"hl-comment\"># a</span></span>\n<span class=\"line\" data-line-number=\"2\">echo <span class=\"hl-string\">&quot;b &amp; c&quot;</span> x2</span>\n</code></pre>",
+        "should encode highlighted code"
+    );
+
+    assert_eq!(
+        to_html_with_options("```txt\nif a\n```\n\n    if b", &options)?,
+        "<pre><code class=\"language-txt\"><span class=\"line\" data-line-number=\"1\">if a</span>\n</code></pre>\n<pre><code><span class=\"line\" data-line-number=\"1\">if b</span>\n</code></pre>",
+        "should not highlight unknown languages"
+    );
+
+    assert_eq!(
+        to_html_with_options("```js\nlet café = \"é\" // ü\n```", &options)?,
+        "<pre><code class=\"language-js\"><span class=\"line\" data-line-number=\"1\"><span class=\"hl-keyword\">let</span> café = <span class=\"hl-string\">&quot;é&quot;</span> <span class=\"hl-comment\">// ü</span></span>\n</code></pre>",
+        "should support non-ASCII characters"
+    );
+
+    assert_eq!(
+        markdown::highlight(Some("py"), None, "é😀 in 1"),
+        Some("é😀 <span class=\"hl-keyword\">in</span> <span class=\"hl-number\">1</span>".into()),
+        "should support non-ASCII characters outside words"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,194 @@
+use markdown::{to_html_with_options, CompileOptions, Options, ParseOptions};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn link_external_attributes() -> Result<(), String> {
+    let options = Options {
+        parse: ParseOptions::gfm(),
+        compile: CompileOptions {
+            link_external_attributes: Some("rel=\"nofollow noopener\" target=\"_blank\"".into()),
+            link_internal_hosts: Some(vec!["Example.com".into()]),
+            ..CompileOptions::default()
+        },
+    };
+
+    assert_eq!(
+        to_html_with_options("[a](https://a.b/c)", &options)?,
+        "<p><a href=\"https://a.b/c\" rel=\"nofollow noopener\" target=\"_blank\">a</a></p>",
+        "should add attributes to external links"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a](https://a.b \"c\")", &options)?,
+        "<p><a href=\"https://a.b\" title=\"c\" rel=\"nofollow noopener\" target=\"_blank\">a</a></p>",
+        "should add attributes after titles"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a](/b) [c](d) [e](#f) [g](mailto:h@i.j)", &options)?,
+        "<p><a href=\"/b\">a</a> <a href=\"d\">c</a> <a href=\"#f\">e</a> <a href=\"mailto:h@i.j\">g</a></p>",
+        "should not add attributes to links w/o host"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "[a](https://example.com) [b](http://www.EXAMPLE.com:80/c) [d](https://user@example.com.)",
+            &options
+        )?,
+        "<p><a href=\"https://example.com\">a</a> <a href=\"http://www.EXAMPLE.com:80/c\">b</a> <a href=\"https://user@example.com.\">d</a></p>",
+        "should not add attributes to links to internal hosts (and subdomains)"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a](https://notexample.com) [b](//c.d)", &options)?,
+        "<p><a href=\"https://notexample.com\" rel=\"nofollow noopener\" target=\"_blank\">a</a> <a href=\"//c.d\" rel=\"nofollow noopener\" target=\"_blank\">b</a></p>",
+        "should add attributes to links to other hosts"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "[a](https:b.c) [d](HTTP:/e.f) [g](https:///h.i) [j](https:example.com)",
+            &options
+        )?,
+        "<p><a href=\"https:b.c\" rel=\"nofollow noopener\" target=\"_blank\">a</a> <a href=\"HTTP:/e.f\" rel=\"nofollow noopener\" target=\"_blank\">d</a> <a href=\"https:///h.i\" rel=\"nofollow noopener\" target=\"_blank\">g</a> <a href=\"https:example.com\">j</a></p>",
+        "should treat hosts after a special scheme w/o two slashes as browsers do"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a]\n\n[a]: https://b.c", &options)?,
+        "<p><a href=\"https://b.c\" rel=\"nofollow noopener\" target=\"_blank\">a</a></p>\n",
+        "should support references"
+    );
+
+    assert_eq!(
+        to_html_with_options("<https://a.b> https://c.d <https://example.com>", &options)?,
+        "<p><a href=\"https://a.b\" rel=\"nofollow noopener\" target=\"_blank\">https://a.b</a> <a href=\"https://c.d\" rel=\"nofollow noopener\" target=\"_blank\">https://c.d</a> <a href=\"https://example.com\">https://example.com</a></p>",
+        "should support autolinks and GFM autolink literals"
+    );
+
+    assert_eq!(
+        to_html_with_options("![a](https://b.c)", &options)?,
+        "<p><img src=\"https://b.c\" alt=\"a\" /></p>",
+        "should not add link attributes to images"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "[a](https://b.c)",
+            &Options {
+                compile: CompileOptions {
+                    link_external_attributes: Some("target=\"_blank\"".into()),
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<p><a href=\"https://b.c\" target=\"_blank\">a</a></p>",
+        "should treat all hosts as external w/o `link_internal_hosts`"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn image_attributes() -> Result<(), String> {
+    let options = Options {
+        compile: CompileOptions {
+            image_attributes: Some("loading=\"lazy\" decoding=\"async\"".into()),
+            ..CompileOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("![a](b.png \"c\")", &options)?,
+        "<p><img src=\"b.png\" alt=\"a\" title=\"c\" loading=\"lazy\" decoding=\"async\" /></p>",
+        "should add attributes to images"
+    );
+
+    assert_eq!(
+        to_html_with_options("![a ![b](c)](d)", &options)?,
+        "<p><img src=\"d\" alt=\"a b\" loading=\"lazy\" decoding=\"async\" /></p>",
+        "should add attributes once for images in images"
+    );
+
+    Ok(())
+}
+
+#[test]
+fn image_figure() -> Result<(), String> {
+    let options = Options {
+        compile: CompileOptions {
+            image_figure: true,
+            ..CompileOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("![a](b.png \"c &amp; d\")", &options)?,
+        "<figure><img src=\"b.png\" alt=\"a\" title=\"c &amp; d\" /><figcaption>c &amp; d</figcaption></figure>",
+        "should turn a paragraph w/ only an image into a figure"
+    );
+
+    assert_eq!(
+        to_html_with_options("![a](b.png)", &options)?,
+        "<figure><img src=\"b.png\" alt=\"a\" /></figure>",
+        "should not add a caption w/o title"
+    );
+
+    assert_eq!(
+        to_html_with_options("![a]\n\n[a]: b.png \"c\"", &options)?,
+        "<figure><img src=\"b.png\" alt=\"a\" title=\"c\" /><figcaption>c</figcaption></figure>\n",
+        "should support references"
+    );
+
+    assert_eq!(
+        to_html_with_options("![a ![b](c \"d\")](e)", &options)?,
+        "<figure><img src=\"e\" alt=\"a b\" /></figure>",
+        "should not use titles of images in images"
+    );
+
+    assert_eq!(
+        to_html_with_options("a ![b](c \"d\")\n\n![e](f) g\n\n![h](i) ![j](k)", &options)?,
+        "<p>a <img src=\"c\" alt=\"b\" title=\"d\" /></p>\n<p><img src=\"f\" alt=\"e\" /> g</p>\n<p><img src=\"i\" alt=\"h\" /> <img src=\"k\" alt=\"j\" /></p>",
+        "should not turn paragraphs w/ other content into figures"
+    );
+
+    assert_eq!(
+        to_html_with_options("[![a](b \"c\")](d)", &options)?,
+        "<p><a href=\"d\"><img src=\"b\" alt=\"a\" title=\"c\" /></a></p>",
+        "should not turn linked images into figures"
+    );
+
+    assert_eq!(
+        to_html_with_options("* ![a](b \"c\")\n* d", &options)?,
+        "<ul>\n<li><figure><img src=\"b\" alt=\"a\" title=\"c\" /><figcaption>c</figcaption></figure></li>\n<li>d</li>\n</ul>",
+        "should support figures in tight lists"
+    );
+
+    assert_eq!(
+        to_html_with_options("> ![a](b)\n>\n> c", &options)?,
+        "<blockquote>\n<figure><img src=\"b\" alt=\"a\" /></figure>\n<p>c</p>\n</blockquote>",
+        "should support figures in containers"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "![a](b \"c\")",
+            &Options {
+                compile: CompileOptions {
+                    image_figure: true,
+                    image_attributes: Some("loading=\"lazy\"".into()),
+                    sourcepos: true,
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<figure data-sourcepos=\"1:1-1:11\"><img src=\"b\" alt=\"a\" title=\"c\" loading=\"lazy\" /><figcaption>c</figcaption></figure>",
+        "should support `sourcepos` and `image_attributes` on figures"
+    );
+
+    Ok(())
+}
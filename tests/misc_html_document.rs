@@ -0,0 +1,141 @@
+use markdown::{
+    to_html_document, CompileOptions, Constructs, DocumentOptions, HtmlSyntax, Matter, ParseOptions,
+};
+use pretty_assertions::assert_eq;
+
+fn frontmatter() -> ParseOptions {
+    ParseOptions {
+        constructs: Constructs {
+            frontmatter: true,
+            ..Constructs::default()
+        },
+        frontmatter_matters: [ParseOptions::default().frontmatter_matters, Matter::json()].concat(),
+        ..ParseOptions::default()
+    }
+}
+
+#[test]
+fn html_document() -> Result<(), String> {
+    assert_eq!(
+        to_html_document("a\n\n> b\n> ===\n\n# c", &DocumentOptions::default())?,
+        "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n<title>b</title>\n</head>\n<body>\n<p>a</p>\n<blockquote>\n<h1>b</h1>\n</blockquote>\n<h1>c</h1>\n</body>\n</html>\n",
+        "should use the first heading as the title"
+    );
+
+    assert_eq!(
+        to_html_document("a", &DocumentOptions::default())?,
+        "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n</head>\n<body>\n<p>a</p>\n</body>\n</html>\n",
+        "should not add a title w/o heading"
+    );
+
+    assert_eq!(
+        to_html_document("", &DocumentOptions::default())?,
+        "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n</head>\n<body>\n</body>\n</html>\n",
+        "should support an empty document"
+    );
+
+    assert_eq!(
+        to_html_document("a *b* &amp; `<c>`\nd\n===", &DocumentOptions::default())?,
+        "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n<title>a b &amp; &lt;c&gt; d</title>\n</head>\n<body>\n<h1>a <em>b</em> &amp; <code>&lt;c&gt;</code>\nd</h1>\n</body>\n</html>\n",
+        "should use the text of the heading as the title, on one line"
+    );
+
+    assert_eq!(
+        to_html_document(
+            ";;;\n{\"title\": \"a & b\"}\n;;;\n# c",
+            &DocumentOptions {
+                parse: frontmatter(),
+                ..DocumentOptions::default()
+            }
+        )?,
+        "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n<title>a &amp; b</title>\n</head>\n<body>\n<h1>c</h1>\n</body>\n</html>\n",
+        "should use the title from frontmatter"
+    );
+
+    assert_eq!(
+        to_html_document(
+            ";;;\n{\"title\": 1}\n;;;\n# c",
+            &DocumentOptions {
+                parse: frontmatter(),
+                ..DocumentOptions::default()
+            }
+        )?,
+        "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n<title>c</title>\n</head>\n<body>\n<h1>c</h1>\n</body>\n</html>\n",
+        "should use the first heading if the title in frontmatter is not a string"
+    );
+
+    assert_eq!(
+        to_html_document(
+            ";;;\n{\"title\"\n;;;\n# c",
+            &DocumentOptions {
+                parse: frontmatter(),
+                ..DocumentOptions::default()
+            }
+        )?,
+        "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n<title>c</title>\n</head>\n<body>\n<h1>c</h1>\n</body>\n</html>\n",
+        "should use the first heading if frontmatter cannot be parsed"
+    );
+
+    assert_eq!(
+        to_html_document(
+            "+++\ntitle = \"a\"\n+++\n# b",
+            &DocumentOptions {
+                parse: frontmatter(),
+                ..DocumentOptions::default()
+            }
+        )?,
+        if cfg!(feature = "toml") {
+            "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n<title>a</title>\n</head>\n<body>\n<h1>b</h1>\n</body>\n</html>\n"
+        } else {
+            "<!doctype html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n<title>b</title>\n</head>\n<body>\n<h1>b</h1>\n</body>\n</html>\n"
+        },
+        "should use the first heading if the feature for frontmatter is not enabled"
+    );
+
+    assert_eq!(
+        to_html_document(
+            "# a\r\n\r\nb",
+            &DocumentOptions {
+                lang: Some("nl".into()),
+                stylesheets: vec!["a.css".into(), "b&c.css".into()],
+                ..DocumentOptions::default()
+            }
+        )?,
+        "<!doctype html>\r\n<html lang=\"nl\">\r\n<head>\r\n<meta charset=\"utf-8\" />\r\n<title>a</title>\r\n<link rel=\"stylesheet\" href=\"a.css\" />\r\n<link rel=\"stylesheet\" href=\"b&amp;c.css\" />\r\n</head>\r\n<body>\r\n<h1>a</h1>\r\n<p>b</p>\r\n</body>\r\n</html>\r\n",
+        "should support `lang`, `stylesheets`, and the line ending of the document"
+    );
+
+    assert_eq!(
+        to_html_document(
+            "a",
+            &DocumentOptions {
+                lang: None,
+                compile: CompileOptions {
+                    html_syntax: HtmlSyntax::Html5,
+                    ..CompileOptions::default()
+                },
+                stylesheets: vec!["b.css".into()],
+                ..DocumentOptions::default()
+            }
+        )?,
+        "<!doctype html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n<link rel=\"stylesheet\" href=\"b.css\">\n</head>\n<body>\n<p>a</p>\n</body>\n</html>\n",
+        "should support HTML5 syntax, and no `lang`"
+    );
+
+    assert_eq!(
+        to_html_document(
+            "a",
+            &DocumentOptions {
+                compile: CompileOptions {
+                    html_syntax: HtmlSyntax::Xhtml,
+                    ..CompileOptions::default()
+                },
+                ..DocumentOptions::default()
+            }
+        )?,
+        "<!DOCTYPE html>\n<html xmlns=\"http://www.w3.org/1999/xhtml\" lang=\"en\" xml:lang=\"en\">\n<head>\n<meta charset=\"utf-8\" />\n</head>\n<body>\n<p>a</p>\n</body>\n</html>\n",
+        "should support XHTML syntax"
+    );
+
+    Ok(())
+}
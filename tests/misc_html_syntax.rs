@@ -0,0 +1,129 @@
+use markdown::{
+    to_html_with_options, CompileOptions, DefaultRender, HtmlSchema, HtmlSyntax, Options,
+    ParseOptions,
+};
+use pretty_assertions::assert_eq;
+use std::sync::Arc;
+
+fn options(html_syntax: HtmlSyntax) -> Options {
+    Options {
+        parse: ParseOptions::gfm(),
+        compile: CompileOptions {
+            html_syntax,
+            ..CompileOptions::default()
+        },
+    }
+}
+
+#[test]
+fn html_syntax() -> Result<(), String> {
+    let markdown = "a\\\nb ![c](d)\n\n***\n\n* [ ] e\n* [x] f\n\ng[^h]\n\n[^h]: i";
+
+    assert_eq!(
+        to_html_with_options(markdown, &options(HtmlSyntax::Default))?,
+        "<p>a<br />\nb <img src=\"d\" alt=\"c\" /></p>\n<hr />\n<ul>\n<li><input type=\"checkbox\" disabled=\"\" /> e</li>\n<li><input type=\"checkbox\" disabled=\"\" checked=\"\" /> f</li>\n</ul>\n<p>g<sup><a href=\"#user-content-fn-h\" id=\"user-content-fnref-h\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup></p>\n<section data-footnotes=\"\" class=\"footnotes\"><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"user-content-fn-h\">\n<p>i <a href=\"#user-content-fnref-h\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>\n</li>\n</ol>\n</section>\n",
+        "should close void elements and use empty values by default"
+    );
+
+    assert_eq!(
+        to_html_with_options(markdown, &options(HtmlSyntax::Html5))?,
+        "<p>a<br>\nb <img src=\"d\" alt=\"c\"></p>\n<hr>\n<ul>\n<li><input type=\"checkbox\" disabled> e</li>\n<li><input type=\"checkbox\" disabled checked> f</li>\n</ul>\n<p>g<sup><a href=\"#user-content-fn-h\" id=\"user-content-fnref-h\" data-footnote-ref aria-describedby=\"footnote-label\">1</a></sup></p>\n<section data-footnotes class=\"footnotes\"><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"user-content-fn-h\">\n<p>i <a href=\"#user-content-fnref-h\" data-footnote-backref aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>\n</li>\n</ol>\n</section>\n",
+        "should not close void elements and use bare attributes in HTML5"
+    );
+
+    let mut rendered = options(HtmlSyntax::Html5);
+    rendered.compile.render = Some(Arc::new(DefaultRender));
+
+    assert_eq!(
+        to_html_with_options(markdown, &rendered)?,
+        to_html_with_options(markdown, &options(HtmlSyntax::Html5))?,
+        "should use HTML5 with `DefaultRender` as `render`"
+    );
+
+    assert_eq!(
+        to_html_with_options(markdown, &options(HtmlSyntax::Xhtml))?,
+        "<p>a<br />\nb <img src=\"d\" alt=\"c\" /></p>\n<hr />\n<ul>\n<li><input type=\"checkbox\" disabled=\"disabled\" /> e</li>\n<li><input type=\"checkbox\" disabled=\"disabled\" checked=\"checked\" /> f</li>\n</ul>\n<p>g<sup><a href=\"#user-content-fn-h\" id=\"user-content-fnref-h\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup></p>\n<section data-footnotes=\"\" class=\"footnotes\"><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"user-content-fn-h\">\n<p>i <a href=\"#user-content-fnref-h\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>\n</li>\n</ol>\n</section>\n",
+        "should repeat names of boolean attributes in XHTML"
+    );
+
+    assert_eq!(
+        to_html_with_options("a &copy; &amp; b", &options(HtmlSyntax::Xhtml))?,
+        "<p>a © &amp; b</p>",
+        "should decode character references in markdown in XHTML"
+    );
+
+    let mut dangerous = options(HtmlSyntax::Xhtml);
+    dangerous.compile.allow_dangerous_html = true;
+
+    assert_eq!(
+        to_html_with_options(
+            "<div>\n<img src=\"a>b\" alt='&copy;'><br/>\n</div>\n\nc <br\nclass=\"d\"> <hr title=\"&nbsp;&lt;&ngE;\">",
+            &dangerous
+        )?,
+        "<div>\n<img src=\"a>b\" alt='&#xA9;' /><br/>\n</div>\n<p>c <br\nclass=\"d\" /> <hr title=\"&#xA0;&lt;&#x2267;&#x338;\" /></p>",
+        "should close void elements and use numeric character references in raw HTML in XHTML"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "a <b title=\"&copy;\"></b><wbrx title=\"&unknown;\">",
+            &dangerous
+        )?,
+        "<p>a <b title=\"&#xA9;\"></b><wbrx title=\"&amp;unknown;\"></p>",
+        "should not close other elements, and encode unknown character references, in XHTML"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<div>\n<input disabled type=checkbox checked><details open class='a\"b'>\n</div>",
+            &dangerous
+        )?,
+        "<div>\n<input disabled=\"disabled\" type=\"checkbox\" checked=\"checked\" /><details open=\"open\" class='a\"b'>\n</div>",
+        "should give bare attributes a value, and quote values, in raw HTML in XHTML"
+    );
+
+    assert_eq!(
+        to_html_with_options("a <img src=x alt> <video CONTROLS muted=''>", &dangerous)?,
+        "<p>a <img src=\"x\" alt=\"\" /> <video controls=\"controls\" muted=''></p>",
+        "should give bare attributes that are not boolean an empty value in XHTML"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<DIV CLASS=a>
+<BR><Hr/>
+</Div>
+
+b <SPAN Id=c>d</SPAN>",
+            &dangerous
+        )?,
+        "<div class=\"a\">\n<br /><hr/>\n</div>\n<p>b <span id=\"c\">d</span></p>",
+        "should lowercase tag and attribute names in XHTML"
+    );
+
+    assert_eq!(
+        to_html_with_options("<div title=c\"d e=f<g h='i<j'>\n</div>", &dangerous)?,
+        "<div title=\"c&quot;d\" e=\"f&lt;g\" h='i&lt;j'>\n</div>",
+        "should encode quotes and less than signs in values in XHTML"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<div title=\"a & b\">\nc & d &NotANamed; &#x41; &#65; &#; <!-- & -->\n</div>",
+            &dangerous
+        )?,
+        "<div title=\"a &amp; b\">\nc &amp; d &amp;NotANamed; &#x41; &#65; &amp;#; <!-- & -->\n</div>",
+        "should encode ampersands that do not start character references in XHTML"
+    );
+
+    let mut sanitized = options(HtmlSyntax::Html5);
+    sanitized.compile.sanitize_html = Some(HtmlSchema::github());
+
+    assert_eq!(
+        to_html_with_options("a <br/> <img src=\"b\"> <hr />", &sanitized)?,
+        "<p>a <br> <img src=\"b\"> <hr></p>",
+        "should not close void elements in sanitized HTML in HTML5"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,137 @@
+#![cfg(feature = "json")]
+
+use markdown::{
+    mdast::{Node, Paragraph, Root, Text},
+    to_mdast, to_mdast_json,
+    unist::Position,
+    Constructs, Matter, ParseOptions,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn misc_json() -> Result<(), String> {
+    let gfm = ParseOptions {
+        constructs: Constructs {
+            frontmatter: true,
+            math_flow: true,
+            math_text: true,
+            ..Constructs::gfm()
+        },
+        frontmatter_matters: vec![
+            Matter::new("yaml", "---"),
+            Matter::new("toml", "+++"),
+            Matter::new("json", ";;;"),
+            Matter::new("meta", "~~~"),
+        ],
+        ..ParseOptions::gfm()
+    };
+
+    assert_eq!(
+        to_mdast_json("# a", &ParseOptions::default())?,
+        "{\"type\":\"root\",\"children\":[{\"type\":\"heading\",\"depth\":1,\"children\":[{\"type\":\"text\",\"value\":\"a\",\"position\":{\"start\":{\"line\":1,\"column\":3,\"offset\":2},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}",
+        "should serialize fields in the order of JavaScript"
+    );
+
+    assert_eq!(
+        to_mdast_json("- a", &gfm)?,
+        "{\"type\":\"root\",\"children\":[{\"type\":\"list\",\"ordered\":false,\"start\":null,\"spread\":false,\"children\":[{\"type\":\"listItem\",\"spread\":false,\"checked\":null,\"children\":[{\"type\":\"paragraph\",\"children\":[{\"type\":\"text\",\"value\":\"a\",\"position\":{\"start\":{\"line\":1,\"column\":3,\"offset\":2},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":3,\"offset\":2},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}",
+        "should serialize missing values as `null` (`start`, `checked`)"
+    );
+
+    assert_eq!(
+        to_mdast_json("[a][]", &ParseOptions::default())?,
+        "{\"type\":\"root\",\"children\":[{\"type\":\"paragraph\",\"children\":[{\"type\":\"text\",\"value\":\"[a][]\",\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":6,\"offset\":5}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":6,\"offset\":5}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":6,\"offset\":5}}}",
+        "should serialize text"
+    );
+
+    assert_eq!(
+        to_mdast_json("[a]: b\n\n![a][]", &ParseOptions::default())?,
+        "{\"type\":\"root\",\"children\":[{\"type\":\"definition\",\"identifier\":\"a\",\"label\":\"a\",\"title\":null,\"url\":\"b\",\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":7,\"offset\":6}}},{\"type\":\"paragraph\",\"children\":[{\"type\":\"imageReference\",\"alt\":\"a\",\"position\":{\"start\":{\"line\":3,\"column\":1,\"offset\":8},\"end\":{\"line\":3,\"column\":7,\"offset\":14}},\"label\":\"a\",\"identifier\":\"a\",\"referenceType\":\"collapsed\"}],\"position\":{\"start\":{\"line\":3,\"column\":1,\"offset\":8},\"end\":{\"line\":3,\"column\":7,\"offset\":14}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":3,\"column\":7,\"offset\":14}}}",
+        "should serialize references with the fields added after `position`"
+    );
+
+    assert_eq!(
+        to_mdast_json("$a$", &gfm)?,
+        "{\"type\":\"root\",\"children\":[{\"type\":\"paragraph\",\"children\":[{\"type\":\"inlineMath\",\"value\":\"a\",\"data\":{\"hName\":\"code\",\"hProperties\":{\"className\":[\"language-math\",\"math-inline\"]},\"hChildren\":[{\"type\":\"text\",\"value\":\"a\"}]},\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":4,\"offset\":3}}}",
+        "should serialize `data` of math"
+    );
+
+    assert_eq!(
+        to_mdast_json("`\"\\\u{1}`", &ParseOptions::default())?,
+        "{\"type\":\"root\",\"children\":[{\"type\":\"paragraph\",\"children\":[{\"type\":\"inlineCode\",\"value\":\"\\\"\\\\\\u0001\",\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":6,\"offset\":5}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":6,\"offset\":5}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":6,\"offset\":5}}}",
+        "should escape strings like `JSON.stringify`"
+    );
+
+    assert_eq!(
+        to_mdast_json("<a b c=\"d\" e={f} {...g} />", &ParseOptions::mdx())?,
+        "{\"type\":\"root\",\"children\":[{\"type\":\"mdxJsxFlowElement\",\"name\":\"a\",\"attributes\":[{\"type\":\"mdxJsxAttribute\",\"name\":\"b\",\"value\":null},{\"type\":\"mdxJsxAttribute\",\"name\":\"c\",\"value\":\"d\"},{\"type\":\"mdxJsxAttribute\",\"name\":\"e\",\"value\":{\"type\":\"mdxJsxAttributeValueExpression\",\"value\":\"f\"}},{\"type\":\"mdxJsxExpressionAttribute\",\"value\":\"...g\"}],\"children\":[],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":27,\"offset\":26}}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":27,\"offset\":26}}}",
+        "should serialize JSX attributes"
+    );
+
+    assert_eq!(
+        Node::from_json("{\"type\":\"root\",\"children\":[{\"type\":\"paragraph\",\"children\":[{\"type\":\"text\",\"value\":\"a\",\"data\":{\"b\":1}}],\"position\":{\"start\":{\"line\":1,\"column\":1,\"offset\":0},\"end\":{\"line\":1,\"column\":2,\"offset\":1}},\"c\":true}]}")?,
+        Node::Root(Root {
+            children: vec![Node::Paragraph(Paragraph {
+                children: vec![Node::Text(Text {
+                    value: "a".into(),
+                    position: None
+                })],
+                position: Some(Position::new(1, 1, 0, 1, 2, 1))
+            })],
+            position: None
+        }),
+        "should parse JSON, ignoring `data` and unknown fields"
+    );
+
+    assert_eq!(
+        Node::from_json("{\"type\":\"root\",\"children\":[{\"type\":\"heading\",\"depth\":7,\"children\":[]}]}"),
+        Err("Cannot turn JSON into mdast, expected `depth` to be a number from 1 to 6 at `tree.children[0]`".into()),
+        "should error for invalid fields, with where"
+    );
+
+    assert_eq!(
+        Node::from_json("{\"type\":\"root\",\"children\":[{\"type\":\"blockquote\",\"children\":[{\"type\":\"unknown\"}]}]}"),
+        Err("Cannot turn JSON into mdast, expected `type` to be a known node type at `tree.children[0].children[0]`".into()),
+        "should error for unknown nodes"
+    );
+
+    assert_eq!(
+        Node::from_json("{\"type\":\"root\",\"children\":[}"),
+        Err(
+            "1:28: Unexpected character `}` before value, expected a value (mdast-json:parse)"
+                .into()
+        ),
+        "should error for invalid JSON"
+    );
+
+    let values = [
+        "---\na: b\n---\n\n# a *b* **c** ~d~ `e` $f$\n\ng\n=\n\n> h\n\n1. [ ] i\n2. [x] j\n\n   k\n\n- l\n\n***\n\n```m n\no\n```\n\n    p\n\n$$q\nr\n$$\n\n<div>\n\ns\\\nt  \nu [v](w \"x\") ![y](z) [y] [y][] [y][v] ![y] https://example.com\n\n[y]: a 'b'\n\n| a | b | c | d |\n| :- | -: | :-: | - |\n| e | f |\n\nc[^d]\n\n[^d]: e\n\n&amp; &#123; <!-- f -->",
+        "+++\na = \"b\"\n+++",
+        ";;;\n{\"a\": \"\\u0001\\\\\"}\n;;;",
+        "~~~\na\n~~~",
+    ];
+
+    for value in values {
+        let tree = to_mdast(value, &gfm)?;
+        let json = tree.to_json();
+
+        assert_eq!(
+            Node::from_json(&json)?,
+            tree,
+            "should turn JSON back into the same tree ({:?})",
+            value
+        );
+    }
+
+    let value =
+        "import a from 'b'\n\n{c}\n\n<D e=\"f\" {...g} h={i}>\n  j <K /> {l}\n</D>\n\n<>m</>";
+    let json = to_mdast_json(value, &ParseOptions::mdx())?;
+
+    assert_eq!(
+        Node::from_json(&json)?.to_json(),
+        json,
+        "should turn JSON of MDX back into the same JSON"
+    );
+
+    Ok(())
+}
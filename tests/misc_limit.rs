@@ -0,0 +1,221 @@
+use markdown::{to_html_with_options, to_mdast, Options, ParseOptions};
+use pretty_assertions::assert_eq;
+use std::{cell::Cell, rc::Rc};
+
+#[test]
+fn misc_limit() -> Result<(), String> {
+    let size = Options {
+        parse: ParseOptions {
+            size_max: Some(3),
+            ..ParseOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("*a*", &size)?,
+        "<p><em>a</em></p>",
+        "should support documents as big as `size_max`"
+    );
+
+    assert_eq!(
+        to_html_with_options("*ab*", &size),
+        Err(
+            "1:1: Unexpected document of 4 bytes, expected at most 3 bytes (markdown-rs:size-max)"
+                .into()
+        ),
+        "should error for documents bigger than `size_max`"
+    );
+
+    assert_eq!(
+        to_mdast("*ab*", &size.parse),
+        Err(
+            "1:1: Unexpected document of 4 bytes, expected at most 3 bytes (markdown-rs:size-max)"
+                .into()
+        ),
+        "should error for documents bigger than `size_max` (mdast)"
+    );
+
+    let depth = Options {
+        parse: ParseOptions {
+            container_depth_max: Some(2),
+            ..ParseOptions::gfm()
+        },
+        ..Options::gfm()
+    };
+
+    assert_eq!(
+        to_html_with_options("> > a\n\n* * b\n\n> c", &depth)?,
+        "<blockquote>\n<blockquote>\n<p>a</p>\n</blockquote>\n</blockquote>\n<ul>\n<li>\n<ul>\n<li>b</li>\n</ul>\n</li>\n</ul>\n<blockquote>\n<p>c</p>\n</blockquote>",
+        "should support containers as deep as `container_depth_max`"
+    );
+
+    assert_eq!(
+        to_html_with_options("> > > a", &depth),
+        Err("1:7: Unexpected container nested 3 deep, expected at most 2 nested containers (markdown-rs:container-depth-max)".into()),
+        "should error for block quotes deeper than `container_depth_max`"
+    );
+
+    assert_eq!(
+        to_html_with_options("* a\n  * b\n    * c", &depth),
+        Err("3:7: Unexpected container nested 3 deep, expected at most 2 nested containers (markdown-rs:container-depth-max)".into()),
+        "should error for list items deeper than `container_depth_max`"
+    );
+
+    assert_eq!(
+        to_html_with_options("[^a]: > * b", &depth),
+        Err("1:11: Unexpected container nested 3 deep, expected at most 2 nested containers (markdown-rs:container-depth-max)".into()),
+        "should error for footnote definitions deeper than `container_depth_max`"
+    );
+
+    assert!(
+        to_html_with_options(&"> ".repeat(100_000), &depth).is_err(),
+        "should error for very deep containers"
+    );
+
+    let events = Options {
+        parse: ParseOptions {
+            events_max: Some(100),
+            ..ParseOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("# a\n\n*b*", &events)?,
+        "<h1>a</h1>\n<p><em>b</em></p>",
+        "should support documents with fewer events than `events_max`"
+    );
+
+    assert_eq!(
+        to_html_with_options(&"*a* ".repeat(100), &events),
+        Err("1:52: Unexpected event, expected at most 100 events (markdown-rs:events-max)".into()),
+        "should error for documents with more events than `events_max`"
+    );
+
+    let label = Options {
+        parse: ParseOptions {
+            label_size_max: Some(3),
+            ..ParseOptions::gfm()
+        },
+        ..Options::gfm()
+    };
+
+    assert_eq!(
+        to_html_with_options("[abc](d) ![efg](h) [i]\n\n[i]: j", &label)?,
+        "<p><a href=\"d\">abc</a> <img src=\"h\" alt=\"efg\" /> <a href=\"j\">i</a></p>\n",
+        "should support labels as big as `label_size_max`"
+    );
+
+    assert_eq!(
+        to_html_with_options("[abcd](e) ![fghi](j) [k l m]\n\n[k l m]: n", &label)?,
+        "<p>[abcd](e) ![fghi](j) [k l m]</p>\n",
+        "should not support labels bigger than `label_size_max`"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            &format!("{}a{}", "[".repeat(10_000), "]".repeat(10_000)),
+            &label
+        )?,
+        format!("<p>{}a{}</p>", "[".repeat(10_000), "]".repeat(10_000)),
+        "should support lots of brackets with `label_size_max`"
+    );
+
+    let calls = Rc::new(Cell::new(0));
+    let calls_in_cancel = Rc::clone(&calls);
+    let value = "a *b* c\n\n".repeat(1000);
+
+    assert_eq!(
+        to_html_with_options(
+            &value,
+            &Options {
+                parse: ParseOptions {
+                    cancel: Some(Box::new(move || {
+                        calls_in_cancel.set(calls_in_cancel.get() + 1);
+                        false
+                    })),
+                    ..ParseOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        to_html_with_options(&value, &Options::default())?,
+        "should support `cancel` returning `false`"
+    );
+
+    assert!(calls.get() > 0, "should call `cancel`");
+
+    assert_eq!(
+        to_html_with_options(
+            &value,
+            &Options {
+                parse: ParseOptions {
+                    cancel: Some(Box::new(|| true)),
+                    ..ParseOptions::default()
+                },
+                ..Options::default()
+            }
+        ),
+        Err(
+            "215:6: Unexpected cancel, parsing was stopped by `cancel` (markdown-rs:cancel)".into()
+        ),
+        "should error when `cancel` returns `true`"
+    );
+
+    let value = "> ".repeat(25000);
+    let calls = Rc::new(Cell::new(0));
+    let calls_in_cancel = Rc::clone(&calls);
+
+    assert!(
+        to_html_with_options(
+            &value,
+            &Options {
+                parse: ParseOptions {
+                    cancel: Some(Box::new(move || {
+                        calls_in_cancel.set(calls_in_cancel.get() + 1);
+                        calls_in_cancel.get() > 10
+                    })),
+                    ..ParseOptions::default()
+                },
+                ..Options::default()
+            },
+        )
+        .unwrap_err()
+        .ends_with("(markdown-rs:cancel)"),
+        "should stop deeply nested containers when canceled"
+    );
+
+    assert_eq!(
+        calls.get(),
+        11,
+        "should stop as soon as `cancel` returns `true`"
+    );
+
+    let calls = Rc::new(Cell::new(0));
+    let calls_in_cancel = Rc::clone(&calls);
+
+    assert!(
+        to_mdast(
+            &value,
+            &ParseOptions {
+                cancel: Some(Box::new(move || {
+                    calls_in_cancel.set(calls_in_cancel.get() + 1);
+                    calls_in_cancel.get() > 10
+                })),
+                ..ParseOptions::default()
+            },
+        )
+        .unwrap_err()
+        .ends_with("(markdown-rs:cancel)"),
+        "should stop deeply nested containers when canceled (mdast)"
+    );
+
+    assert_eq!(
+        calls.get(),
+        11,
+        "should stop as soon as `cancel` returns `true` (mdast)"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,283 @@
+use markdown::{
+    lint, unist::Position, EmphasisMarker, LintMessage, LintOptions, LintRules, ListItemIndent,
+    ParseOptions, PositionEncoding,
+};
+use pretty_assertions::assert_eq;
+
+/// Lint `value` with only the rule(s) in `rules` on, and get the messages
+/// as strings.
+fn check(value: &str, rules: LintRules) -> Result<Vec<String>, String> {
+    Ok(lint(
+        value,
+        &LintOptions {
+            parse: ParseOptions::gfm(),
+            rules,
+        },
+    )?
+    .iter()
+    .map(ToString::to_string)
+    .collect())
+}
+
+/// No rules.
+fn none() -> LintRules {
+    LintRules {
+        emphasis_marker: None,
+        heading_increment: false,
+        no_duplicate_definitions: false,
+        no_unused_definitions: false,
+        list_item_indent: None,
+        fenced_code_flag: false,
+        maximum_line_length: None,
+    }
+}
+
+#[test]
+fn misc_lint() -> Result<(), String> {
+    assert_eq!(
+        lint("# a\n\n*b* _c_", &LintOptions::default())?,
+        vec![LintMessage {
+            rule: "emphasis-marker",
+            reason: "Unexpected emphasis marker `_`, expected `*`".into(),
+            position: Position::new(3, 5, 9, 3, 6, 10)
+        }],
+        "should support positioned messages"
+    );
+
+    assert_eq!(
+        lint(
+            "# a\n\n### b\n\n[c]: d\n[C]: e\n\n```\nf\n```",
+            &LintOptions::default()
+        )?
+        .iter()
+        .map(|d| d.rule)
+        .collect::<Vec<_>>(),
+        vec![
+            "heading-increment",
+            "no-unused-definitions",
+            "no-duplicate-definitions",
+            "no-unused-definitions",
+            "fenced-code-flag"
+        ],
+        "should sort messages by place"
+    );
+
+    assert_eq!(
+        check("*a* __b__ *c*", none())?,
+        Vec::<String>::new(),
+        "should support turning rules off"
+    );
+
+    assert_eq!(
+        check(
+            "_a_ *b* __c__ _d_",
+            LintRules {
+                emphasis_marker: Some(EmphasisMarker::Consistent),
+                ..none()
+            }
+        )?,
+        vec!["1:5: Unexpected emphasis marker `*`, expected `_` (markdown-lint:emphasis-marker)"],
+        "should support `emphasis-marker: Consistent` (ignoring strong)"
+    );
+
+    assert_eq!(
+        check(
+            "*a* _b_",
+            LintRules {
+                emphasis_marker: Some(EmphasisMarker::Underscore),
+                ..none()
+            }
+        )?,
+        vec!["1:1: Unexpected emphasis marker `*`, expected `_` (markdown-lint:emphasis-marker)"],
+        "should support `emphasis-marker: Underscore`"
+    );
+
+    assert_eq!(
+        check(
+            "## a\n\n> #### b\n\nc\n===\n\n# d\n\n### e",
+            LintRules {
+                heading_increment: true,
+                ..none()
+            }
+        )?,
+        vec![
+            "3:3: Unexpected heading rank `4`, expected rank `3` (markdown-lint:heading-increment)",
+            "10:1: Unexpected heading rank `3`, expected rank `2` (markdown-lint:heading-increment)"
+        ],
+        "should support `heading-increment` (including nested and setext headings)"
+    );
+
+    assert_eq!(
+        check(
+            "[a]: b\n\n> [A]: c\n\n[d]: e\n\n[a] ![d][]",
+            LintRules {
+                no_duplicate_definitions: true,
+                ..none()
+            }
+        )?,
+        vec!["3:3: Unexpected definition with an already defined identifier (`A`), expected unique identifiers (first defined at 1:1) (markdown-lint:no-duplicate-definitions)"],
+        "should support `no-duplicate-definitions`"
+    );
+
+    assert_eq!(
+        check(
+            "[a]: b\n[c]: d\n[e]: f\n\n[A] ![c][] [e](g)",
+            LintRules {
+                no_unused_definitions: true,
+                ..none()
+            }
+        )?,
+        vec!["3:1: Unexpected unused definition (`e`), expected a reference to it (markdown-lint:no-unused-definitions)"],
+        "should support `no-unused-definitions`"
+    );
+
+    assert_eq!(
+        check(
+            "- a\n-   b\n10.  c\n-\n  d\n*     e",
+            LintRules {
+                list_item_indent: Some(ListItemIndent::One),
+                ..none()
+            }
+        )?,
+        vec![
+            "2:2: Unexpected `3` spaces between list item marker and content, expected `1` space (markdown-lint:list-item-indent)",
+            "3:4: Unexpected `2` spaces between list item marker and content, expected `1` space (markdown-lint:list-item-indent)"
+        ],
+        "should support `list-item-indent: One` (ignoring blank starts and code)"
+    );
+
+    assert_eq!(
+        check(
+            "-   a\n- b\n\n1.  c\n10.  d",
+            LintRules {
+                list_item_indent: Some(ListItemIndent::Tab),
+                ..none()
+            }
+        )?,
+        vec![
+            "2:2: Unexpected `1` space between list item marker and content, expected `3` spaces (markdown-lint:list-item-indent)",
+            "5:4: Unexpected `2` spaces between list item marker and content, expected `1` space (markdown-lint:list-item-indent)"
+        ],
+        "should support `list-item-indent: Tab`"
+    );
+
+    assert_eq!(
+        check(
+            "- a\n-   b\n- c\n  d\n-   e\n\n    f\n\n",
+            LintRules {
+                list_item_indent: Some(ListItemIndent::Mixed),
+                ..none()
+            }
+        )?,
+        vec![
+            "2:2: Unexpected `3` spaces between list item marker and content, expected `1` space (markdown-lint:list-item-indent)",
+            "3:2: Unexpected `1` space between list item marker and content, expected `3` spaces (markdown-lint:list-item-indent)"
+        ],
+        "should support `list-item-indent: Mixed`"
+    );
+
+    assert_eq!(
+        check(
+            "```\na\n```\n\n~~~rust\nb\n~~~\n\n> ```\n\n    c",
+            LintRules {
+                fenced_code_flag: true,
+                ..none()
+            }
+        )?,
+        vec![
+            "1:1: Unexpected missing fenced code language flag in info string, expected keyword (markdown-lint:fenced-code-flag)",
+            "9:3: Unexpected missing fenced code language flag in info string, expected keyword (markdown-lint:fenced-code-flag)"
+        ],
+        "should support `fenced-code-flag` (ignoring indented code)"
+    );
+
+    assert_eq!(
+        check(
+            "aaaaa\r\naaaaaa\r\naaaaaaaaaa\n# aaaaaa\n\n```\naaaaaa\n```\n\n| aaaaaa |\n| - |\n\n[a]: aaaaaa\n\naa [b](c)\naa [b](c) d",
+            LintRules {
+                maximum_line_length: Some(5),
+                ..none()
+            }
+        )?,
+        vec![
+            "2:6: Unexpected `6` character line, expected at most `5` characters, remove `1` character (markdown-lint:maximum-line-length)",
+            "3:6: Unexpected `10` character line, expected at most `5` characters, remove `5` characters (markdown-lint:maximum-line-length)",
+            "16:6: Unexpected `11` character line, expected at most `5` characters, remove `6` characters (markdown-lint:maximum-line-length)"
+        ],
+        "should support `maximum-line-length` (ignoring some things)"
+    );
+
+    assert_eq!(
+        check(
+            "ääääää",
+            LintRules {
+                maximum_line_length: Some(6),
+                ..none()
+            }
+        )?,
+        Vec::<String>::new(),
+        "should count characters for `maximum-line-length`"
+    );
+
+    assert_eq!(
+        check(
+            "aaaaaaaaaaaaaaa\rbbbbbbbbbbbbbbbb\r",
+            LintRules {
+                maximum_line_length: Some(10),
+                ..none()
+            }
+        )?,
+        vec![
+            "1:11: Unexpected `15` character line, expected at most `10` characters, remove `5` characters (markdown-lint:maximum-line-length)",
+            "2:11: Unexpected `16` character line, expected at most `10` characters, remove `6` characters (markdown-lint:maximum-line-length)"
+        ],
+        "should support carriage returns as line endings in `maximum-line-length`"
+    );
+
+    assert_eq!(
+        lint(
+            "{a",
+            &LintOptions {
+                parse: ParseOptions::mdx(),
+                ..LintOptions::default()
+            }
+        ),
+        Err("1:3: Unexpected end of file in expression, expected a corresponding closing brace for `{`".into()),
+        "should crash on MDX syntax errors"
+    );
+
+    assert_eq!(
+        lint(
+            "😀😀 *a* _b_",
+            &LintOptions {
+                parse: ParseOptions {
+                    position_encoding: PositionEncoding::Utf16,
+                    ..ParseOptions::default()
+                },
+                ..LintOptions::default()
+            }
+        )?
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>(),
+        vec!["1:10: Unexpected emphasis marker `_`, expected `*` (markdown-lint:emphasis-marker)"],
+        "should encode positions (`position_encoding`)"
+    );
+
+    assert_eq!(
+        lint(
+            "😀 {a",
+            &LintOptions {
+                parse: ParseOptions {
+                    position_encoding: PositionEncoding::Utf16,
+                    ..ParseOptions::mdx()
+                },
+                ..LintOptions::default()
+            }
+        ),
+        Err("1:6: Unexpected end of file in expression, expected a corresponding closing brace for `{`".into()),
+        "should encode positions in errors (`position_encoding`)"
+    );
+
+    Ok(())
+}
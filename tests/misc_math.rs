@@ -0,0 +1,227 @@
+use markdown::{to_html_with_options, CompileOptions, Constructs, Options, ParseOptions};
+use pretty_assertions::assert_eq;
+use std::sync::Arc;
+
+fn parse() -> ParseOptions {
+    ParseOptions {
+        constructs: Constructs {
+            math_flow: true,
+            math_text: true,
+            ..Constructs::default()
+        },
+        ..ParseOptions::default()
+    }
+}
+
+#[test]
+fn math() -> Result<(), String> {
+    let options = Options {
+        parse: parse(),
+        compile: CompileOptions {
+            math: Some(Arc::new(|value, display| {
+                if value.contains('!') {
+                    None
+                } else {
+                    Some(format!(
+                        "<x-math display=\"{}\">{}</x-math>",
+                        display, value
+                    ))
+                }
+            })),
+            ..CompileOptions::default()
+        },
+    };
+
+    assert_eq!(
+        to_html_with_options("a $b < c$ d", &options)?,
+        "<p>a <x-math display=\"false\">b < c</x-math> d</p>",
+        "should use the HTML from `math` for math (text), w/ decoded value"
+    );
+
+    assert_eq!(
+        to_html_with_options("$$\na & b\n$$\n\nc", &options)?,
+        "<x-math display=\"true\">a & b\n</x-math>\n<p>c</p>",
+        "should use the HTML from `math` for math (flow)"
+    );
+
+    assert_eq!(
+        to_html_with_options("$a!$\n\n$$\nb!\n$$", &options)?,
+        "<p><code class=\"language-math math-inline\">a!</code></p>\n<pre><code class=\"language-math math-display\">b!\n</code></pre>",
+        "should use the default if `math` returns `None`"
+    );
+
+    assert_eq!(
+        to_html_with_options("`a` ![b $c$](d)", &options)?,
+        "<p><code>a</code> <img src=\"d\" alt=\"b c\" /></p>",
+        "should not use `math` for code or in image alt text"
+    );
+
+    assert_eq!(
+        to_html_with_options("$a$\n\n```math\nb\n```", &Options {
+            parse: parse(),
+            ..Options::default()
+        })?,
+        "<p><code class=\"language-math math-inline\">a</code></p>\n<pre><code class=\"language-math\">b\n</code></pre>",
+        "should output `code` elements w/o `math`"
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "mathml")]
+#[test]
+fn mathml() {
+    use markdown::to_mathml;
+
+    assert_eq!(
+        to_mathml("a + 12.5 - x", false),
+        Some("<math><mi>a</mi><mo>+</mo><mn>12.5</mn><mo>−</mo><mi>x</mi></math>".into()),
+        "should support identifiers, numbers, and operators"
+    );
+
+    assert_eq!(
+        to_mathml("a", true),
+        Some("<math display=\"block\"><mi>a</mi></math>".into()),
+        "should support display math"
+    );
+
+    assert_eq!(
+        to_mathml("x_i^{2n} + y_1 + f'(x)", false),
+        Some("<math><msubsup><mi>x</mi><mi>i</mi><mrow><mn>2</mn><mi>n</mi></mrow></msubsup><mo>+</mo><msub><mi>y</mi><mn>1</mn></msub><mo>+</mo><msup><mi>f</mi><mo>′</mo></msup><mo>(</mo><mi>x</mi><mo>)</mo></math>".into()),
+        "should support sub- and superscripts, and primes"
+    );
+
+    assert_eq!(
+        to_mathml("\\frac{a}{b+1} \\sqrt{2} \\sqrt[3]{x}", false),
+        Some("<math><mfrac><mi>a</mi><mrow><mi>b</mi><mo>+</mo><mn>1</mn></mrow></mfrac><msqrt><mn>2</mn></msqrt><mroot><mi>x</mi><mn>3</mn></mroot></math>".into()),
+        "should support fractions and roots"
+    );
+
+    assert_eq!(
+        to_mathml("\\alpha \\Omega \\infty", false),
+        Some("<math><mi>α</mi><mi mathvariant=\"normal\">Ω</mi><mi>∞</mi></math>".into()),
+        "should support Greek letters and other identifiers"
+    );
+
+    assert_eq!(
+        to_mathml("a \\leq b \\times c \\neq d < e \\to f", false),
+        Some("<math><mi>a</mi><mo>≤</mo><mi>b</mi><mo>×</mo><mi>c</mi><mo>≠</mo><mi>d</mi><mo>&lt;</mo><mi>e</mi><mo>→</mo><mi>f</mi></math>".into()),
+        "should support (and encode) operators"
+    );
+
+    assert_eq!(
+        to_mathml("\\sum_{i=0}^n i \\int_0^1 x \\lim_{x \\to 0} \\sin x", false),
+        Some("<math><munderover><mo>∑</mo><mrow><mi>i</mi><mo>=</mo><mn>0</mn></mrow><mi>n</mi></munderover><mi>i</mi><msubsup><mo>∫</mo><mn>0</mn><mn>1</mn></msubsup><mi>x</mi><munder><mo movablelimits=\"true\" form=\"prefix\">lim</mo><mrow><mi>x</mi><mo>→</mo><mn>0</mn></mrow></munder><mi>sin</mi><mi>x</mi></math>".into()),
+        "should support large operators, limits, and functions"
+    );
+
+    assert_eq!(
+        to_mathml("\\hat{a} \\text{if } b \\mathbf{C} \\, \\quad", false),
+        Some("<math><mover accent=\"true\"><mi>a</mi><mo>^</mo></mover><mtext>if </mtext><mi>b</mi><mi mathvariant=\"bold\">C</mi><mspace width=\"0.167em\"></mspace><mspace width=\"1em\"></mspace></math>".into()),
+        "should support accents, text, and spaces"
+    );
+
+    assert_eq!(
+        to_mathml("\\left( \\frac{a}{b} \\right] \\left. c \\right\\}", false),
+        Some("<math><mrow><mo>(</mo><mfrac><mi>a</mi><mi>b</mi></mfrac><mo>]</mo></mrow><mrow><mi>c</mi><mo>}</mo></mrow></math>".into()),
+        "should support delimiters"
+    );
+
+    assert_eq!(
+        to_mathml("\\begin{pmatrix} a & b \\\\ c & d \\\\ \\end{pmatrix}", false),
+        Some("<math><mrow><mo>(</mo><mtable><mtr><mtd><mi>a</mi></mtd><mtd><mi>b</mi></mtd></mtr><mtr><mtd><mi>c</mi></mtd><mtd><mi>d</mi></mtd></mtr></mtable><mo>)</mo></mrow></math>".into()),
+        "should support matrices"
+    );
+
+    assert_eq!(
+        to_mathml("f(x) = \\begin{cases} 1 & x > 0 \\\\ 0 & \\text{else} \\end{cases}", false),
+        Some("<math><mi>f</mi><mo>(</mo><mi>x</mi><mo>)</mo><mo>=</mo><mrow><mo>{</mo><mtable columnalign=\"left left\"><mtr><mtd><mn>1</mn></mtd><mtd><mi>x</mi><mo>&gt;</mo><mn>0</mn></mtd></mtr><mtr><mtd><mn>0</mn></mtd><mtd><mtext>else</mtext></mtd></mtr></mtable></mrow></math>".into()),
+        "should support cases"
+    );
+
+    assert_eq!(
+        to_mathml("\\unknown", false),
+        None,
+        "should not support unknown commands"
+    );
+    assert_eq!(
+        to_mathml("{a", false),
+        None,
+        "should not support unclosed groups"
+    );
+    assert_eq!(
+        to_mathml("a}", false),
+        None,
+        "should not support unopened groups"
+    );
+    assert_eq!(
+        to_mathml("\\frac{a}", false),
+        None,
+        "should not support missing arguments"
+    );
+    assert_eq!(
+        to_mathml("\\left( a", false),
+        None,
+        "should not support `\\left` w/o `\\right`"
+    );
+    assert_eq!(
+        to_mathml("\\begin{matrix} a \\end{pmatrix}", false),
+        None,
+        "should not support mismatched environments"
+    );
+    assert_eq!(
+        to_mathml("a & b", false),
+        None,
+        "should not support `&` outside environments"
+    );
+
+    assert_eq!(
+        to_mathml(&format!("{}x{}", "{".repeat(100), "}".repeat(100)), false),
+        Some("<math><mi>x</mi></math>".into()),
+        "should support some nesting"
+    );
+
+    assert_eq!(
+        to_mathml(
+            &format!("{}x{}", "{".repeat(20000), "}".repeat(20000)),
+            false
+        ),
+        None,
+        "should not support too much nesting (groups)"
+    );
+
+    assert_eq!(
+        to_mathml(&"\\sqrt".repeat(20000), false),
+        None,
+        "should not support too much nesting (commands)"
+    );
+}
+
+#[cfg(feature = "mathml")]
+#[test]
+fn mathml_nesting() -> Result<(), String> {
+    use markdown::to_mathml;
+
+    let value = format!("${}x{}$", "{".repeat(20000), "}".repeat(20000));
+
+    assert_eq!(
+        to_html_with_options(
+            &value,
+            &Options {
+                parse: parse(),
+                compile: CompileOptions {
+                    math: Some(Arc::new(to_mathml)),
+                    ..CompileOptions::default()
+                },
+            }
+        )?,
+        format!(
+            "<p><code class=\"language-math math-inline\">{}x{}</code></p>",
+            "{".repeat(20000),
+            "}".repeat(20000)
+        ),
+        "should use the default for too much nesting in documents"
+    );
+
+    Ok(())
+}
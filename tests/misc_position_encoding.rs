@@ -0,0 +1,150 @@
+use markdown::{
+    mdast::{Emphasis, InlineCode, Node, Paragraph, Root, Text},
+    to_html_with_options, to_mdast, to_mdast_reparse,
+    unist::Position,
+    Edit, Options, ParseOptions, PositionEncoding,
+};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn misc_position_encoding() -> Result<(), String> {
+    let value = "ä\n😀 *b*\r\n`c`";
+    let tree = |text: Position, emphasis: Position, b: Position, code: Position, end: Position| {
+        let line_ending = Position::new(
+            2,
+            emphasis.end.column,
+            emphasis.end.offset,
+            3,
+            1,
+            code.start.offset,
+        );
+        Node::Root(Root {
+            children: vec![Node::Paragraph(Paragraph {
+                children: vec![
+                    Node::Text(Text {
+                        value: "ä\n😀 ".into(),
+                        position: Some(text),
+                    }),
+                    Node::Emphasis(Emphasis {
+                        children: vec![Node::Text(Text {
+                            value: "b".into(),
+                            position: Some(b),
+                        })],
+                        position: Some(emphasis),
+                    }),
+                    Node::Text(Text {
+                        value: "\r\n".into(),
+                        position: Some(line_ending),
+                    }),
+                    Node::InlineCode(InlineCode {
+                        value: "c".into(),
+                        position: Some(code),
+                    }),
+                ],
+                position: Some(end.clone()),
+            })],
+            position: Some(end),
+        })
+    };
+
+    assert_eq!(
+        to_mdast(value, &ParseOptions::default())?,
+        tree(
+            Position::new(1, 1, 0, 2, 6, 8),
+            Position::new(2, 6, 8, 2, 9, 11),
+            Position::new(2, 7, 9, 2, 8, 10),
+            Position::new(3, 1, 13, 3, 4, 16),
+            Position::new(1, 1, 0, 3, 4, 16)
+        ),
+        "should use bytes by default"
+    );
+
+    assert_eq!(
+        to_mdast(
+            value,
+            &ParseOptions {
+                position_encoding: PositionEncoding::Utf16,
+                ..ParseOptions::default()
+            }
+        )?,
+        tree(
+            Position::new(1, 1, 0, 2, 4, 5),
+            Position::new(2, 4, 5, 2, 7, 8),
+            Position::new(2, 5, 6, 2, 6, 7),
+            Position::new(3, 1, 10, 3, 4, 13),
+            Position::new(1, 1, 0, 3, 4, 13)
+        ),
+        "should support `PositionEncoding::Utf16`"
+    );
+
+    assert_eq!(
+        to_mdast(
+            value,
+            &ParseOptions {
+                position_encoding: PositionEncoding::Chars,
+                ..ParseOptions::default()
+            }
+        )?,
+        tree(
+            Position::new(1, 1, 0, 2, 3, 4),
+            Position::new(2, 3, 4, 2, 6, 7),
+            Position::new(2, 4, 5, 2, 5, 6),
+            Position::new(3, 1, 9, 3, 4, 12),
+            Position::new(1, 1, 0, 3, 4, 12)
+        ),
+        "should support `PositionEncoding::Chars`"
+    );
+
+    let utf16 = ParseOptions {
+        position_encoding: PositionEncoding::Utf16,
+        ..ParseOptions::mdx()
+    };
+
+    assert_eq!(
+        to_mdast("😀 {a", &utf16),
+        Err("1:6: Unexpected end of file in expression, expected a corresponding closing brace for `{`".into()),
+        "should encode the place at the start of error messages"
+    );
+
+    assert_eq!(
+        to_mdast("ä <a>\n\n😀 *b*", &utf16),
+        Err("1:6: Expected a closing tag for `<a>` (1:3) before the end of `Paragraph` (mdx-jsx:end-tag-mismatch)".into()),
+        "should encode places in parens in error messages"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "😀 {a",
+            &Options {
+                parse: utf16,
+                ..Options::default()
+            }
+        ),
+        Err("1:6: Unexpected end of file in expression, expected a corresponding closing brace for `{`".into()),
+        "should encode places in error messages when compiling to HTML"
+    );
+
+    let utf16 = ParseOptions {
+        position_encoding: PositionEncoding::Utf16,
+        ..ParseOptions::default()
+    };
+    let previous = to_mdast("# 😀\n\na\n\nb\n\nc", &utf16)?;
+    let result = to_mdast_reparse(
+        "# 😀\n\nxa\n\nb\n\nc",
+        &previous,
+        &Edit {
+            start: 9,
+            old_end: 9,
+            new_end: 10,
+        },
+        &utf16,
+    )?;
+
+    assert_eq!(
+        (result.tree, result.children, result.range),
+        (to_mdast("# 😀\n\nxa\n\nb\n\nc", &utf16)?, 0..4, 0..16),
+        "should parse everything again when reparsing with other encodings"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,169 @@
+use markdown::{
+    to_html_with_options, CompileOptions, DefaultRender, HtmlSyntax, Options, ParseOptions, Render,
+};
+use pretty_assertions::assert_eq;
+use std::sync::Arc;
+
+struct Custom;
+
+impl Render for Custom {
+    fn heading(&self, rank: u8, attributes: &str, children: &str) -> String {
+        format!(
+            "<h{} class=\"title\"{}>{}</h{}>",
+            rank, attributes, children, rank
+        )
+    }
+
+    fn code(
+        &self,
+        lang: Option<&str>,
+        meta: Option<&str>,
+        attributes: &str,
+        value: &str,
+    ) -> String {
+        format!(
+            "<code-block{} lang=\"{}\" meta=\"{}\">{}</code-block>",
+            attributes,
+            lang.unwrap_or_default(),
+            meta.unwrap_or_default(),
+            value
+        )
+    }
+
+    fn link(&self, url: &str, title: Option<&str>, attributes: &str, children: &str) -> String {
+        format!(
+            "<x-link to=\"{}\" label=\"{}\"{}>{}</x-link>",
+            url,
+            title.unwrap_or_default(),
+            attributes,
+            children
+        )
+    }
+
+    fn image(
+        &self,
+        syntax: HtmlSyntax,
+        url: &str,
+        alt: &str,
+        title: Option<&str>,
+        attributes: &str,
+    ) -> String {
+        format!(
+            "<picture>{}</picture>",
+            DefaultRender.image(syntax, url, alt, title, attributes)
+        )
+    }
+
+    fn table(&self, attributes: &str, children: &str) -> String {
+        format!(
+            "<div class=\"scroll\">{}</div>",
+            DefaultRender.table(attributes, children)
+        )
+    }
+
+    fn list_item(&self, attributes: &str, children: &str) -> String {
+        format!("<li class=\"item\"{}>{}</li>", attributes, children)
+    }
+
+    fn footnote_section(&self, _syntax: HtmlSyntax, children: &str) -> String {
+        format!("<aside>{}</aside>", children)
+    }
+}
+
+#[test]
+fn render() -> Result<(), String> {
+    let options = Options {
+        parse: ParseOptions::gfm(),
+        compile: CompileOptions {
+            render: Some(Arc::new(Custom)),
+            ..CompileOptions::default()
+        },
+    };
+
+    assert_eq!(
+        to_html_with_options("# a *b*\n\nc\n-", &options)?,
+        "<h1 class=\"title\">a <em>b</em></h1>\n<h2 class=\"title\">c</h2>",
+        "should support a custom heading (atx, setext)"
+    );
+
+    assert_eq!(
+        to_html_with_options("```js eval\na\n```\n\n    b", &options)?,
+        "<code-block lang=\"js\" meta=\"eval\">a\n</code-block>\n<code-block lang=\"\" meta=\"\">b\n</code-block>",
+        "should support custom code (fenced, indented)"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a *b*](c \"d\") [e] <f@g.h> https://i.j\n\n[e]: k", &options)?,
+        "<p><x-link to=\"c\" label=\"d\">a <em>b</em></x-link> <x-link to=\"k\" label=\"\">e</x-link> <x-link to=\"mailto:f@g.h\" label=\"\">f@g.h</x-link> <x-link to=\"https://i.j\" label=\"\">https://i.j</x-link></p>\n",
+        "should support custom links (resource, reference, autolinks)"
+    );
+
+    assert_eq!(
+        to_html_with_options("![a *b*](c \"d\")", &options)?,
+        "<p><picture><img src=\"c\" alt=\"a b\" title=\"d\" /></picture></p>",
+        "should support custom images"
+    );
+
+    assert_eq!(
+        to_html_with_options("| a |\n| - |\n| b |", &options)?,
+        "<div class=\"scroll\"><table>\n<thead>\n<tr>\n<th>a</th>\n</tr>\n</thead>\n<tbody>\n<tr>\n<td>b</td>\n</tr>\n</tbody>\n</table></div>",
+        "should support custom tables"
+    );
+
+    assert_eq!(
+        to_html_with_options("* a\n* [x] b\n\n1. c\n\n   d", &options)?,
+        "<ul>\n<li class=\"item\">a</li>\n<li class=\"item\"><input type=\"checkbox\" disabled=\"\" checked=\"\" /> b</li>\n</ul>\n<ol>\n<li class=\"item\">\n<p>c</p>\n<p>d</p>\n</li>\n</ol>",
+        "should support custom list items (tight, loose)"
+    );
+
+    assert_eq!(
+        to_html_with_options("a[^b]\n\n[^b]: c", &options)?,
+        "<p>a<sup><a href=\"#user-content-fn-b\" id=\"user-content-fnref-b\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup></p>\n<aside><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"user-content-fn-b\">\n<p>c <a href=\"#user-content-fnref-b\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>\n</li>\n</ol>\n</aside>\n",
+        "should support a custom footnote section"
+    );
+
+    assert_eq!(
+        to_html_with_options("* a\n  * b\n\n    c[^d]\n\n[^d]: e\n\n    * f\n\n      g", &options)?,
+        "<ul>\n<li class=\"item\">a\n<ul>\n<li class=\"item\">\n<p>b</p>\n<p>c<sup><a href=\"#user-content-fn-d\" id=\"user-content-fnref-d\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup></p>\n</li>\n</ul>\n</li>\n</ul>\n<aside><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"user-content-fn-d\">\n<p>e</p>\n<ul>\n<li class=\"item\">\n<p>f</p>\n<p>g</p>\n</li>\n</ul>\n<a href=\"#user-content-fnref-d\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a>\n</li>\n</ol>\n</aside>\n",
+        "should support custom list items and footnote sections, nested"
+    );
+
+    assert_eq!(
+        to_html_with_options("* a\n* b\n\nc[^d]\n\n[^d]: e", &options)?,
+        "<ul>\n<li class=\"item\">a</li>\n<li class=\"item\">b</li>\n</ul>\n<p>c<sup><a href=\"#user-content-fn-d\" id=\"user-content-fnref-d\" data-footnote-ref=\"\" aria-describedby=\"footnote-label\">1</a></sup></p>\n<aside><h2 id=\"footnote-label\" class=\"sr-only\">Footnotes</h2>\n<ol>\n<li id=\"user-content-fn-d\">\n<p>e <a href=\"#user-content-fnref-d\" data-footnote-backref=\"\" aria-label=\"Back to content\" class=\"data-footnote-backref\">↩</a></p>\n</li>\n</ol>\n</aside>\n",
+        "should support custom list items before a footnote section"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "# a\n\n```js\nb\n```",
+            &Options {
+                compile: CompileOptions {
+                    render: Some(Arc::new(Custom)),
+                    sourcepos: true,
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<h1 class=\"title\" data-sourcepos=\"1:1-1:3\">a</h1>\n<code-block data-sourcepos=\"3:1-5:3\" lang=\"js\" meta=\"\">b\n</code-block>",
+        "should pass `sourcepos` as attributes"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "# a\n\n* [b](c)",
+            &Options {
+                compile: CompileOptions {
+                    render: Some(Arc::new(DefaultRender)),
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        to_html_with_options("# a\n\n* [b](c)", &Options::default())?,
+        "should produce the same output w/ `DefaultRender`"
+    );
+
+    Ok(())
+}
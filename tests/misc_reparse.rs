@@ -0,0 +1,251 @@
+use markdown::{
+    mdast::{Node, Paragraph, Root, Text},
+    to_mdast, to_mdast_reparse,
+    unist::Position,
+    Constructs, Edit, ParseOptions,
+};
+use pretty_assertions::assert_eq;
+
+/// Replace `start..end` in `value` with `replacement`, then check that
+/// reparsing gives the same tree as parsing the result.
+fn check(value: &str, start: usize, end: usize, replacement: &str, options: &ParseOptions) {
+    let previous = to_mdast(value, options).unwrap();
+    let next = format!("{}{}{}", &value[..start], replacement, &value[end..]);
+    let edit = Edit {
+        start,
+        old_end: end,
+        new_end: start + replacement.len(),
+    };
+
+    assert_eq!(
+        to_mdast_reparse(&next, &previous, &edit, options)
+            .unwrap()
+            .tree,
+        to_mdast(&next, options).unwrap(),
+        "should reparse {:?} to {:?} like a parse",
+        value,
+        next
+    );
+}
+
+#[test]
+fn misc_reparse() -> Result<(), String> {
+    let options = ParseOptions::default();
+    let value = "# a\n\nb\n\nc\n\nd";
+    let tree = to_mdast(value, &options)?;
+
+    let result = to_mdast_reparse(
+        "# a\n\nb\n\ncd\n\nd",
+        &tree,
+        &Edit {
+            start: 9,
+            old_end: 9,
+            new_end: 10,
+        },
+        &options,
+    )?;
+
+    assert_eq!(result.children, 1..4, "should reparse some children");
+    assert_eq!(result.range, 5..13, "should reparse some of the value");
+    assert_eq!(
+        result.tree.children().unwrap()[2],
+        Node::Paragraph(Paragraph {
+            children: vec![Node::Text(Text {
+                value: "cd".into(),
+                position: Some(Position::new(5, 1, 8, 5, 3, 10))
+            })],
+            position: Some(Position::new(5, 1, 8, 5, 3, 10))
+        }),
+        "should reparse the edited node"
+    );
+    assert_eq!(
+        result.tree.children().unwrap()[3],
+        Node::Paragraph(Paragraph {
+            children: vec![Node::Text(Text {
+                value: "d".into(),
+                position: Some(Position::new(7, 1, 12, 7, 2, 13))
+            })],
+            position: Some(Position::new(7, 1, 12, 7, 2, 13))
+        }),
+        "should move nodes after the edit"
+    );
+
+    let result = to_mdast_reparse(
+        "# a\n\n```\nb\n\nc\n\nd",
+        &tree,
+        &Edit {
+            start: 5,
+            old_end: 5,
+            new_end: 9,
+        },
+        &options,
+    )?;
+
+    assert_eq!(
+        (result.children, result.range),
+        (0..2, 0..16),
+        "should reparse further when an edit affects what follows"
+    );
+
+    let result = to_mdast_reparse(
+        "# a\n\n[b]\n\n[b]: c\n\nd",
+        &tree,
+        &Edit {
+            start: 8,
+            old_end: 9,
+            new_end: 16,
+        },
+        &options,
+    )?;
+
+    assert_eq!(
+        result.range,
+        0..19,
+        "should parse everything when adding a definition"
+    );
+
+    let value = "# a\n\n[b]: c\n\nd [b]\n\ne\n\nf";
+    let result = to_mdast_reparse(
+        "# a\n\n[b]: c\n\ndd [b]\n\ne\n\nf",
+        &to_mdast(value, &options)?,
+        &Edit {
+            start: 13,
+            old_end: 13,
+            new_end: 14,
+        },
+        &options,
+    )?;
+
+    assert_eq!(
+        (result.children, result.range),
+        (1..4, 5..22),
+        "should reparse part of the document with a definition that stays"
+    );
+
+    check(value, 11, 12, "x", &options);
+    check(value, 6, 7, "x", &options);
+    check(value, 5, 12, "", &options);
+
+    assert_eq!(
+        to_mdast_reparse(
+            "x",
+            &Node::Root(Root {
+                children: vec![],
+                position: None
+            }),
+            &Edit {
+                start: 0,
+                old_end: 0,
+                new_end: 1
+            },
+            &options
+        ),
+        Err("Cannot reparse, expected `edit` to fit the previous tree and the new value".into()),
+        "should error if `previous` has no position"
+    );
+
+    assert_eq!(
+        to_mdast_reparse(
+            "# a",
+            &tree,
+            &Edit {
+                start: 0,
+                old_end: 1,
+                new_end: 1
+            },
+            &options
+        ),
+        Err("Cannot reparse, expected `edit` to fit the previous tree and the new value".into()),
+        "should error if `edit` does not fit"
+    );
+
+    let gfm = ParseOptions::gfm();
+    let mdx = ParseOptions {
+        constructs: Constructs {
+            frontmatter: true,
+            ..Constructs::mdx()
+        },
+        ..ParseOptions::default()
+    };
+
+    let values = [
+        "# a\n\nb\n\n> c\n> d\n\n* e\n\n  f\n\ng\n===\n\n```h\ni\n```\n\n    j\n\nk",
+        "a\n\n- b\n- c\n\n+ d\n\n<div>\ne\n</div>\n\nf *g* [h][]\n\n[h]: i\n\n***\n\nj",
+        "---\na: b\n---\n\n| a | b |\n| - | - |\n| c | d |\n\ne[^f]\n\n[^f]: g\n\n- [x] h\n\ni",
+        "a\tb\n\n\t> c\n\n  d\r\ne\r\n\r\nf\n\n\n\ng",
+    ];
+    let replacements = [
+        "", "\n", "\n\n", "`", "```", "*", ">", "  ", "#", "[", "]:", "-", "=", "|", "<",
+    ];
+
+    for value in values {
+        for options in [&options, &gfm] {
+            for start in 0..=value.len() {
+                for replacement in replacements {
+                    check(value, start, start, replacement, options);
+                }
+
+                if start < value.len() {
+                    check(value, start, start + 1, "", options);
+                    check(value, start, (start + 4).min(value.len()), "x", options);
+                }
+            }
+        }
+    }
+
+    let value = "import a from 'b'\n\n# c\n\n{d}\n\n<E f={g} />\n\nh {i}\n\nj";
+    let previous = to_mdast(value, &mdx)?;
+
+    for start in 0..=value.len() {
+        for replacement in ["", "\n\n", "{", "}", "<", "/>", "x"] {
+            let next = format!("{}{}{}", &value[..start], replacement, &value[start..]);
+            let edit = Edit {
+                start,
+                old_end: start,
+                new_end: start + replacement.len(),
+            };
+
+            assert_eq!(
+                to_mdast_reparse(&next, &previous, &edit, &mdx).map(|d| d.tree),
+                to_mdast(&next, &mdx),
+                "should reparse MDX like a parse ({:?})",
+                next
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn misc_reparse_random() {
+    let gfm = ParseOptions::gfm();
+    let values = [
+        "a\n\n* b\n\n  c\n\nd\n\n1. e\n\nf\n===\n\n    code\n\nz",
+        "> a\n\n> b\nc\n\n- d\n\n  - e\n\n    f\n\n<!--\ng\n-->\n\nh\n---\n\n1) i\n2) j\n\nk",
+        "# a\n\n[b]: c\n\n* d\n* e\n\n    f\n\ng[^h]\n\n[^h]: i\n\n    j\n\n| k |\n| - |\n\nl",
+    ];
+    let replacements = [
+        "", " ", "  ", "    ", "\n", "\n\n", "x", "*", "-", "=", "1.", ">", "#", "`", "|", "[b]:",
+        "[^h]:", "<div>",
+    ];
+    // Xorshift, so that the edits are the same each run.
+    let mut seed: u64 = 0x2545_F491_4F6C_DD1D;
+    let mut random = |max: usize| {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        (seed % (max as u64 + 1)) as usize
+    };
+
+    for value in values {
+        for options in [&ParseOptions::default(), &gfm] {
+            for _ in 0..1000 {
+                let start = random(value.len());
+                let end = (start + random(8)).min(value.len());
+                let replacement = replacements[random(replacements.len() - 1)];
+                check(value, start, end, replacement, options);
+            }
+        }
+    }
+}
@@ -0,0 +1,139 @@
+use markdown::{
+    resolve_url, to_html_with_options, unist::Position, CompileOptions, Options, ParseOptions,
+    UrlKind,
+};
+use pretty_assertions::assert_eq;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn rewrite_url() -> Result<(), String> {
+    let calls = Arc::new(Mutex::new(vec![]));
+    let record = calls.clone();
+    let options = Options {
+        parse: ParseOptions::gfm(),
+        compile: CompileOptions {
+            rewrite_url: Some(Arc::new(move |url, kind, position| {
+                record
+                    .lock()
+                    .unwrap()
+                    .push((url.to_string(), *kind, position.clone()));
+                Some(format!("/x/{}", url))
+            })),
+            ..CompileOptions::default()
+        },
+    };
+
+    assert_eq!(
+        to_html_with_options("[a](b) ![c](d)", &options)?,
+        "<p><a href=\"/x/b\">a</a> <img src=\"/x/d\" alt=\"c\" /></p>",
+        "should rewrite links and images"
+    );
+
+    assert_eq!(
+        calls.lock().unwrap().clone(),
+        vec![
+            ("b".into(), UrlKind::Link, Position::new(1, 1, 0, 1, 7, 6)),
+            (
+                "d".into(),
+                UrlKind::Image,
+                Position::new(1, 8, 7, 1, 15, 14)
+            ),
+        ],
+        "should pass the url, kind, and position"
+    );
+
+    calls.lock().unwrap().clear();
+
+    assert_eq!(
+        to_html_with_options("[a] ![a] [a][]\n\n[a]: b", &options)?,
+        "<p><a href=\"/x/b\">a</a> <img src=\"/x/b\" alt=\"a\" /> <a href=\"/x/b\">a</a></p>\n",
+        "should rewrite definitions where references use them"
+    );
+
+    assert_eq!(
+        calls.lock().unwrap().clone(),
+        vec![
+            ("b".into(), UrlKind::Link, Position::new(1, 1, 0, 1, 4, 3)),
+            ("b".into(), UrlKind::Image, Position::new(1, 5, 4, 1, 9, 8)),
+            (
+                "b".into(),
+                UrlKind::Link,
+                Position::new(1, 10, 9, 1, 15, 14)
+            )
+        ],
+        "should pass the kind and position of references to definitions"
+    );
+
+    calls.lock().unwrap().clear();
+
+    assert_eq!(
+        to_html_with_options("<https://a.b> <c@d.e> www.f.g", &options)?,
+        "<p><a href=\"/x/https://a.b\">https://a.b</a> <a href=\"/x/mailto:c@d.e\">c@d.e</a> <a href=\"/x/http://www.f.g\">www.f.g</a></p>",
+        "should rewrite autolinks and GFM autolink literals"
+    );
+
+    assert_eq!(
+        calls
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|d| d.1)
+            .collect::<Vec<_>>(),
+        vec![UrlKind::Autolink, UrlKind::Autolink, UrlKind::Autolink],
+        "should pass `Autolink` as kind for autolinks"
+    );
+
+    let options = Options {
+        compile: CompileOptions {
+            rewrite_url: Some(Arc::new(|url, _, _| {
+                if url.starts_with("drop") {
+                    None
+                } else if url == "evil" {
+                    Some("javascript:alert(1)".into())
+                } else {
+                    Some(url.into())
+                }
+            })),
+            ..CompileOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("[a](drop) <drop:b> [c]\n\n[c]: drop", &options)?,
+        "<p><a href=\"\">a</a> <a href=\"\">drop:b</a> <a href=\"\">c</a></p>\n",
+        "should drop URLs when `None` is returned"
+    );
+
+    assert_eq!(
+        to_html_with_options("[a](evil)", &options)?,
+        "<p><a href=\"\">a</a></p>",
+        "should check rewritten URLs for dangerous protocols"
+    );
+
+    let options = Options {
+        compile: CompileOptions {
+            rewrite_url: Some(Arc::new(|url, _, _| {
+                Some(resolve_url("/docs/guide/", &url.replace(".md", "/")))
+            })),
+            ..CompileOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options(
+            "[a](../api.md#foo) [b](./c.md) [d](https://e.f/g.md) [h](#i)",
+            &options
+        )?,
+        "<p><a href=\"/docs/api/#foo\">a</a> <a href=\"/docs/guide/c/\">b</a> <a href=\"https://e.f/g/\">d</a> <a href=\"/docs/guide/#i\">h</a></p>",
+        "should support `resolve_url` for base paths"
+    );
+
+    assert!(
+        format!("{:?}", options.compile).contains("rewrite_url: Some(\"[Function]\")"),
+        "should support `Debug` trait on `rewrite_url`"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use markdown::{to_html_with_options, CompileOptions, HtmlSchema, Options, ParseOptions};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn sanitize_html() -> Result<(), String> {
+    let options = Options {
+        parse: ParseOptions::gfm(),
+        compile: CompileOptions {
+            sanitize_html: Some(HtmlSchema::github()),
+            ..CompileOptions::default()
+        },
+    };
+
+    assert_eq!(
+        to_html_with_options(
+            "<details>\n<summary>a</summary>\n\n*b*\n\n</details>",
+            &options
+        )?,
+        "<details>\n<summary>a</summary>\n<p><em>b</em></p>\n</details>",
+        "should keep allowed elements in HTML (flow)"
+    );
+
+    assert_eq!(
+        to_html_with_options("a <kbd>b</kbd> <sup>c</sup> <sub>d</sub>", &options)?,
+        "<p>a <kbd>b</kbd> <sup>c</sup> <sub>d</sub></p>",
+        "should keep allowed elements in HTML (text)"
+    );
+
+    assert_eq!(
+        to_html_with_options("<iframe src=\"a\">\n\nb <marquee>c</marquee>", &options)?,
+        "<p>b c</p>",
+        "should drop other elements, but keep their content"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<script>\nalert(1)\n</script>\n\n<STYLE>a {}</Style>b",
+            &options
+        )?,
+        "b",
+        "should drop `script` and `style` w/ their content"
+    );
+
+    assert_eq!(
+        to_html_with_options("text <script>alert(1)</script> more", &options)?,
+        "<p>text  more</p>",
+        "should drop the content of `script` between HTML (text)"
+    );
+
+    assert_eq!(
+        to_html_with_options("a <style>*b* `c`</style> d\n\ne", &options)?,
+        "<p>a  d</p>\n<p>e</p>",
+        "should drop constructs in `style` between HTML (text)"
+    );
+
+    assert_eq!(
+        to_html_with_options("a <script>*b*\nc\n\nd", &options)?,
+        "<p>a </p>\n<p>d</p>",
+        "should stop dropping content of an unclosed `script` at the end of the paragraph"
+    );
+
+    assert_eq!(
+        to_html_with_options("<style>\n\n*a*\n\n</style>\n\nb", &options)?,
+        "<p>b</p>",
+        "should drop the content of `style` between HTML (flow)"
+    );
+
+    assert_eq!(
+        to_html_with_options("<!-- a -->\n\nb <!-- c --> <?d?> <![CDATA[e]]>", &options)?,
+        "<p>b   </p>",
+        "should drop comments, instructions, and cdata"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<kbd onclick=\"a\" ONMOUSEOVER=b title=c style=\"d\" title=\"e\">f</kbd>",
+            &options
+        )?,
+        "<p><kbd title=\"c\">f</kbd></p>",
+        "should drop event handlers and other attributes, and keep the first of duplicates"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<a href=\"javascript:alert(1)\">a</a> <a href='java&#x73;cript&colon;alert(1)'>b</a> <img src=\"data:image/png;base64,c\" alt=d>",
+            &options
+        )?,
+        "<p><a>a</a> <a>b</a> <img alt=\"d\" /></p>",
+        "should drop dangerous URLs (also w/ character references)"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<a href=\"https://a.b?c=d&amp;e=f\" title='\"g\" &lt;h&gt;'>i</a> <a href=/j>k</a>",
+            &options
+        )?,
+        "<p><a href=\"https://a.b?c=d&amp;e=f\" title=\"&quot;g&quot; &lt;h&gt;\">i</a> <a href=\"/j\">k</a></p>",
+        "should encode attribute values"
+    );
+
+    assert_eq!(
+        to_html_with_options("<h2 id=\"a\" name=b>c</h2>", &options)?,
+        "<h2 id=\"user-content-a\" name=\"user-content-b\">c</h2>",
+        "should prefix `id` and `name`"
+    );
+
+    assert_eq!(
+        to_html_with_options("<b>a &copy; &amp; &b; < c > d</b>", &options)?,
+        "<p><b>a © &amp; &amp;b; &lt; c &gt; d</b></p>",
+        "should encode text"
+    );
+
+    assert_eq!(
+        to_html_with_options("a<br>b<br/>c<BR />d <hr> <div/>e", &options)?,
+        "<p>a<br />b<br />c<br />d <hr /> <div></div>e</p>",
+        "should support void and self-closing elements"
+    );
+
+    assert_eq!(
+        to_html_with_options("a <b>c</i> d</b> e</sup>", &options)?,
+        "<p>a <b>c d</b> e</p>",
+        "should drop closing tags w/o opening tag"
+    );
+
+    assert_eq!(
+        to_html_with_options("a <b><i>c</b> d", &options)?,
+        "<p>a <b><i>c</i></b> d</p>",
+        "should close elements opened inside closed elements"
+    );
+
+    assert_eq!(
+        to_html_with_options("a <kbd>b\n\nc", &options)?,
+        "<p>a <kbd>b</kbd></p>\n<p>c</p>",
+        "should close elements from HTML (text) at the end of the paragraph"
+    );
+
+    assert_eq!(
+        to_html_with_options("# <sup>a\n\nb\n=\n<sub>c\n\n| <b>d |\n| - |", &options)?,
+        "<h1><sup>a</sup></h1>\n<h1>b</h1>\n<p><sub>c</sub></p>\n<table>\n<thead>\n<tr>\n<th><b>d</b></th>\n</tr>\n</thead>\n</table>",
+        "should close elements from HTML (text) at the end of headings and cells"
+    );
+
+    assert_eq!(
+        to_html_with_options("<div>\n<span>a\n</div>\n\nb", &options)?,
+        "<div>\n<span>a\n</span></div>\n<p>b</p>",
+        "should balance HTML (flow)"
+    );
+
+    assert_eq!(
+        to_html_with_options("<div>\n\na", &options)?,
+        "<div>\n<p>a</p>\n</div>",
+        "should close elements from HTML (flow) at the end of the document"
+    );
+
+    assert_eq!(
+        to_html_with_options("> <div>\n> a\n\nb\n\n* <div>\n* c", &options)?,
+        "<blockquote>\n<div>\na\n</div>\n</blockquote>\n<p>b</p>\n<ul>\n<li>\n<div>\n</div>\n</li>\n<li>c</li>\n</ul>",
+        "should close elements from HTML (flow) at the end of containers"
+    );
+
+    assert_eq!(
+        to_html_with_options("<div>\n\na\n\n</p></div>", &options)?,
+        "<div>\n<p>a</p>\n</div>",
+        "should support elements from HTML (flow) around markdown"
+    );
+
+    assert_eq!(
+        to_html_with_options("![a <kbd>b</kbd>](c)", &options)?,
+        "<p><img src=\"c\" alt=\"a b\" /></p>",
+        "should drop tags in image alt text"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<mark class=\"a\">b</mark> <span class=\"c\">d</span>",
+            &Options {
+                compile: CompileOptions {
+                    sanitize_html: Some(HtmlSchema {
+                        tag_names: vec!["mark".into()],
+                        ..HtmlSchema::default()
+                    }),
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<p><mark>b</mark> d</p>",
+        "should support a custom schema"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<span class=\"a\" id=\"b\">c</span>",
+            &Options {
+                compile: CompileOptions {
+                    sanitize_html: Some(HtmlSchema {
+                        tag_names: vec!["span".into()],
+                        attributes: vec![("SPAN".into(), vec!["class".into(), "id".into()])],
+                        clobber: vec![],
+                        ..HtmlSchema::default()
+                    }),
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<p><span class=\"a\" id=\"b\">c</span></p>",
+        "should support custom attributes"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "<kbd>a</kbd>",
+            &Options {
+                compile: CompileOptions {
+                    allow_dangerous_html: true,
+                    sanitize_html: Some(HtmlSchema {
+                        tag_names: vec![],
+                        ..HtmlSchema::default()
+                    }),
+                    ..CompileOptions::default()
+                },
+                ..Options::default()
+            }
+        )?,
+        "<p>a</p>",
+        "should take precedence over `allow_dangerous_html`"
+    );
+
+    Ok(())
+}
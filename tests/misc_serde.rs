@@ -0,0 +1,227 @@
+#![cfg(all(feature = "serde", feature = "toml"))]
+
+use markdown::{
+    to_html_with_options, CompileOptions, Constructs, Diagram, HtmlSyntax, LineEnding, Matter,
+    Options, ParseOptions, PositionEncoding,
+};
+use pretty_assertions::assert_eq;
+
+#[derive(Debug, serde::Deserialize)]
+struct Site {
+    markdown: Options,
+}
+
+#[test]
+fn misc_serde() -> Result<(), String> {
+    let site: Site = toml::from_str("markdown = \"gfm\"").map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        format!("{:?}", site.markdown),
+        format!("{:?}", Options::gfm()),
+        "should support a preset name"
+    );
+
+    let site: Site = toml::from_str("[markdown]").map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        format!("{:?}", site.markdown),
+        format!("{:?}", Options::default()),
+        "should default to `CommonMark`"
+    );
+
+    let options: Options = toml::from_str(
+        "preset = \"gfm\"\n\n[parse]\nmath_text_single_dollar = false\nsize_max = 1024\n\n[parse.constructs]\nmath_text = true\n\n[compile]\nallow_dangerous_html = true\ndefault_line_ending = \"crlf\"",
+    )
+    .map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        options.parse.constructs,
+        Constructs {
+            math_text: true,
+            ..Constructs::gfm()
+        },
+        "should support overriding constructs of a preset"
+    );
+
+    assert_eq!(
+        (
+            options.parse.math_text_single_dollar,
+            options.parse.gfm_strikethrough_single_tilde,
+            options.parse.size_max
+        ),
+        (false, true, Some(1024)),
+        "should support overriding parse options of a preset"
+    );
+
+    assert_eq!(
+        (
+            options.compile.allow_dangerous_html,
+            options.compile.gfm_tagfilter,
+            options.compile.default_line_ending.clone()
+        ),
+        (true, true, LineEnding::CarriageReturnLineFeed),
+        "should support overriding compile options of a preset"
+    );
+
+    assert_eq!(
+        to_html_with_options("<b>$a$ $$b$$</b> ~~c~~", &options)?,
+        "<p><b>$a$ <code class=\"language-math math-inline\">b</code></b> <del>c</del></p>",
+        "should support using deserialized options"
+    );
+
+    let options: Options =
+        toml::from_str("parse = \"mdx\"\ncompile = \"gfm\"").map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        (
+            options.parse.constructs,
+            options.compile.gfm_tagfilter,
+            options.parse.mdx_expression_parse.is_none()
+        ),
+        (Constructs::mdx(), true, true),
+        "should support presets for nested options"
+    );
+
+    let parse: ParseOptions = toml::from_str(
+        "constructs = { preset = \"gfm\", gfm_table = false }\n\n[[frontmatter_matters]]\nkind = \"toml\"\nopen = \"+++\"\nclose = \"+++\"",
+    )
+    .map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        (parse.constructs, parse.frontmatter_matters),
+        (
+            Constructs {
+                gfm_table: false,
+                ..Constructs::gfm()
+            },
+            vec![Matter::new("toml", "+++")]
+        ),
+        "should support inline tables and matters (with `anywhere` optional)"
+    );
+
+    let compile: CompileOptions =
+        toml::from_str("default_line_ending = \"\\r\"").map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        compile.default_line_ending,
+        LineEnding::CarriageReturn,
+        "should support line endings as characters"
+    );
+
+    let compile: CompileOptions = toml::from_str("protocol_src = [\"https\", \"data:image/png\"]")
+        .map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        (compile.protocol_href, compile.protocol_src),
+        (None, Some(vec!["https".into(), "data:image/png".into()])),
+        "should support protocol lists"
+    );
+
+    let compile: CompileOptions =
+        toml::from_str("[sanitize_html]\ntag_names = [\"kbd\"]").map_err(|d| d.to_string())?;
+    let schema = compile.sanitize_html.unwrap();
+
+    assert_eq!(
+        (schema.tag_names, schema.clobber_prefix),
+        (vec!["kbd".into()], "user-content-".into()),
+        "should support HTML schemas, w/ defaults for missing fields"
+    );
+
+    let compile: CompileOptions =
+        toml::from_str("[[diagrams]]\nlang = \"d2\"\ntag_name = \"div\"\nclass_name = \"d2\"")
+            .map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        compile.diagrams,
+        vec![Diagram {
+            lang: "d2".into(),
+            tag_name: "div".into(),
+            class_name: "d2".into(),
+        }],
+        "should support diagrams"
+    );
+
+    let compile: CompileOptions =
+        toml::from_str("html_syntax = \"xhtml\"").map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        compile.html_syntax,
+        HtmlSyntax::Xhtml,
+        "should support HTML syntaxes"
+    );
+
+    let parse: ParseOptions =
+        toml::from_str("position_encoding = \"chars\"").map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        parse.position_encoding,
+        PositionEncoding::Chars,
+        "should support position encodings"
+    );
+
+    assert!(
+        toml::from_str::<Options>("preset = \"gfn\"")
+            .unwrap_err()
+            .to_string()
+            .contains("unknown variant `gfn`, expected one of `commonmark`, `gfm`, `mdx`"),
+        "should error for unknown presets"
+    );
+
+    assert!(
+        toml::from_str::<Options>("[parse]\nconstruct = {}")
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field `construct`"),
+        "should error for unknown fields"
+    );
+
+    assert!(
+        toml::from_str::<ParseOptions>("cancel = true")
+            .unwrap_err()
+            .to_string()
+            .contains("unknown field `cancel`"),
+        "should error for functions"
+    );
+
+    let options = Options {
+        parse: ParseOptions {
+            cancel: Some(Box::new(|| false)),
+            label_size_max: Some(4),
+            position_encoding: PositionEncoding::Utf16,
+            ..ParseOptions::mdx()
+        },
+        compile: CompileOptions {
+            gfm_footnote_label: Some("Notes".into()),
+            ..CompileOptions::gfm()
+        },
+    };
+    let value = toml::to_string(&options).map_err(|d| d.to_string())?;
+
+    assert!(
+        !value.contains("cancel"),
+        "should skip functions when serializing"
+    );
+
+    let back: Options = toml::from_str(&value).map_err(|d| d.to_string())?;
+
+    assert_eq!(
+        format!("{:?}", back),
+        format!(
+            "{:?}",
+            Options {
+                parse: ParseOptions {
+                    label_size_max: Some(4),
+                    position_encoding: PositionEncoding::Utf16,
+                    ..ParseOptions::mdx()
+                },
+                compile: CompileOptions {
+                    gfm_footnote_label: Some("Notes".into()),
+                    ..CompileOptions::gfm()
+                },
+            }
+        ),
+        "should round trip (except for functions)"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,63 @@
+use markdown::{to_html_with_options, CompileOptions, Constructs, Options, ParseOptions};
+use pretty_assertions::assert_eq;
+
+#[test]
+fn misc_sourcepos() -> Result<(), String> {
+    let sourcepos = Options {
+        compile: CompileOptions {
+            sourcepos: true,
+            ..CompileOptions::default()
+        },
+        ..Options::default()
+    };
+
+    assert_eq!(
+        to_html_with_options("# a\n\nb", &Options::default())?,
+        "<h1>a</h1>\n<p>b</p>",
+        "should not add `data-sourcepos` by default"
+    );
+
+    assert_eq!(
+        to_html_with_options("# a #\n\nb\nc\n===\n\n***", &sourcepos)?,
+        "<h1 data-sourcepos=\"1:1-1:5\">a</h1>\n<h1 data-sourcepos=\"3:1-5:3\">b\nc</h1>\n<hr data-sourcepos=\"7:1-7:3\" />",
+        "should support headings and thematic breaks"
+    );
+
+    assert_eq!(
+        to_html_with_options("a\nb  \n\nc", &sourcepos)?,
+        "<p data-sourcepos=\"1:1-2:1\">a\nb</p>\n<p data-sourcepos=\"4:1-4:1\">c</p>",
+        "should support paragraphs"
+    );
+
+    assert_eq!(
+        to_html_with_options("> a\n> - b\n>\n>   c\n\n3. d\n4. e\n\n* f", &sourcepos)?,
+        "<blockquote data-sourcepos=\"1:1-4:5\">\n<p data-sourcepos=\"1:3-1:3\">a</p>\n<ul data-sourcepos=\"2:3-4:5\">\n<li data-sourcepos=\"2:3-4:5\">\n<p data-sourcepos=\"2:5-2:5\">b</p>\n<p data-sourcepos=\"4:5-4:5\">c</p>\n</li>\n</ul>\n</blockquote>\n<ol data-sourcepos=\"6:1-7:4\" start=\"3\">\n<li data-sourcepos=\"6:1-6:4\">d</li>\n<li data-sourcepos=\"7:1-7:4\">e</li>\n</ol>\n<ul data-sourcepos=\"9:1-9:3\">\n<li data-sourcepos=\"9:1-9:3\">f</li>\n</ul>",
+        "should support block quotes, lists, and list items (without trailing blank lines)"
+    );
+
+    assert_eq!(
+        to_html_with_options("```js\na\n```\n\n    b", &sourcepos)?,
+        "<pre data-sourcepos=\"1:1-3:3\"><code class=\"language-js\">a\n</code></pre>\n<pre data-sourcepos=\"5:1-5:5\"><code>b\n</code></pre>",
+        "should support code (fenced, indented)"
+    );
+
+    assert_eq!(
+        to_html_with_options(
+            "| a | b |\n| - | - |\n| c | d |\n\n$$\ne\n$$\n\n- [x] f",
+            &Options {
+                parse: ParseOptions {
+                    constructs: Constructs {
+                        math_flow: true,
+                        ..Constructs::gfm()
+                    },
+                    ..ParseOptions::default()
+                },
+                ..sourcepos
+            }
+        )?,
+        "<table data-sourcepos=\"1:1-3:9\">\n<thead>\n<tr data-sourcepos=\"1:1-1:9\">\n<th data-sourcepos=\"1:1-1:3\">a</th>\n<th data-sourcepos=\"1:5-1:9\">b</th>\n</tr>\n</thead>\n<tbody>\n<tr data-sourcepos=\"3:1-3:9\">\n<td data-sourcepos=\"3:1-3:3\">c</td>\n<td data-sourcepos=\"3:5-3:9\">d</td>\n</tr>\n</tbody>\n</table>\n<pre data-sourcepos=\"5:1-7:2\"><code class=\"language-math math-display\">e\n</code></pre>\n<ul data-sourcepos=\"9:1-9:7\">\n<li data-sourcepos=\"9:1-9:7\"><input type=\"checkbox\" disabled=\"\" checked=\"\" /> f</li>\n</ul>",
+        "should support GFM tables and math (flow)"
+    );
+
+    Ok(())
+}
@@ -0,0 +1,99 @@
+use core::fmt;
+use markdown::{to_html_with_options, to_html_write, Options};
+use pretty_assertions::assert_eq;
+
+/// Output that keeps each write separately.
+#[derive(Default)]
+struct Chunks(Vec<String>);
+
+impl fmt::Write for Chunks {
+    fn write_str(&mut self, value: &str) -> fmt::Result {
+        self.0.push(value.into());
+        Ok(())
+    }
+}
+
+/// Output that always errors.
+struct Broken;
+
+impl fmt::Write for Broken {
+    fn write_str(&mut self, _: &str) -> fmt::Result {
+        Err(fmt::Error)
+    }
+}
+
+#[test]
+fn misc_write() -> Result<(), String> {
+    let gfm = Options::gfm();
+
+    let mut result = String::new();
+    to_html_write("# a\n\n*b*", &Options::default(), &mut result)?;
+    assert_eq!(result, "<h1>a</h1>\n<p><em>b</em></p>", "should write HTML");
+
+    for value in [
+        "[a]\n\n> * b\n>\n>   c\n\n[a]: d",
+        "a\n===\n\n    b\n\n```c\nd\n```",
+        "a\r\n\r\n* b\r\n* c\r\n",
+        "A call.[^a]\n\n[^a]: whatevs\n\n| a |\n| - |\n| b |",
+        "- [x] a\n- [ ] b\n\n~c~ www.d.com",
+        "",
+    ] {
+        let mut result = String::new();
+        to_html_write(value, &gfm, &mut result)?;
+        assert_eq!(
+            result,
+            to_html_with_options(value, &gfm)?,
+            "should write the same HTML as `to_html_with_options` ({:?})",
+            value
+        );
+    }
+
+    let mut chunks = Chunks::default();
+    to_html_write("# a\n\nb\n\n> c\n\nd", &Options::default(), &mut chunks)?;
+    assert_eq!(
+        chunks.0,
+        vec![
+            "<h1>a</h1>",
+            "\n",
+            "<p>b</p>",
+            "\n",
+            "<blockquote>\n<p>c</p>\n</blockquote>",
+            "\n",
+            "<p>d</p>"
+        ],
+        "should write HTML block by block"
+    );
+
+    assert_eq!(
+        to_html_write("a", &Options::default(), &mut Broken),
+        Err("Cannot write HTML, the output errored".into()),
+        "should error if the output errors"
+    );
+
+    Ok(())
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn misc_write_io() -> Result<(), String> {
+    use markdown::to_html_write_io;
+    use std::io;
+
+    let mut result = vec![];
+    to_html_write_io("# a\n\n*b*", &Options::default(), &mut result)?;
+    assert_eq!(
+        String::from_utf8(result).unwrap(),
+        "<h1>a</h1>\n<p><em>b</em></p>",
+        "should write HTML to `io::Write`"
+    );
+
+    let mut buffer = [0; 4];
+    let mut cursor = io::Cursor::new(&mut buffer[..]);
+    assert_eq!(
+        to_html_write_io("# a", &Options::default(), &mut cursor),
+        Err("Cannot write HTML: failed to write whole buffer".into()),
+        "should error with the message of the `io::Write` error"
+    );
+
+    Ok(())
+}